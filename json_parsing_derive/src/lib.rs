@@ -0,0 +1,312 @@
+//! Derive macros for the `FromJson` and `ToJson` traits of `json_parsing`
+//!
+//! Structs with named fields map to objects, tuple structs to arrays (or to their single
+//! field), and unit structs to `null`. Enums are externally tagged: unit variants are
+//! strings, others are single-key objects like `{"Variant": ...}`.
+//!
+//! Supported attributes:
+//! - `#[json(rename = "name")]` on fields and variants
+//! - `#[json(default)]` or `#[json(default = "path::to::fn")]` on fields
+//! - `#[json(skip)]` on fields, which are filled in with their default value
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    Attribute, Data, DeriveInput, Error, Fields, GenericParam, Generics, Ident, LitStr, Path, Type,
+    parse_macro_input, parse_quote,
+};
+
+#[proc_macro_derive(FromJson, attributes(json))]
+pub fn derive_from_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_json(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(ToJson, attributes(json))]
+pub fn derive_to_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    to_json(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Options collected from `#[json(...)]` attributes
+#[derive(Default)]
+struct Options {
+    rename: Option<String>,
+    default: Option<Option<Path>>,
+    skip: bool,
+}
+
+impl Options {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut options = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("json")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    let name: LitStr = meta.value()?.parse()?;
+                    options.rename = Some(name.value());
+                } else if meta.path.is_ident("default") {
+                    let function = match meta.value() {
+                        Ok(value) => Some(value.parse::<LitStr>()?.parse()?),
+                        Err(_) => None,
+                    };
+                    options.default = Some(function);
+                } else if meta.path.is_ident("skip") {
+                    options.skip = true;
+                } else {
+                    return Err(meta.error("expected `rename`, `default` or `skip`"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(options)
+    }
+
+    /// The JSON key for a field or variant named `ident`
+    fn key(&self, ident: &Ident) -> String {
+        self.rename.clone().unwrap_or_else(|| ident.to_string())
+    }
+
+    fn default_expr(&self) -> TokenStream2 {
+        match &self.default {
+            Some(Some(function)) => quote!(#function()),
+            _ => quote!(::core::default::Default::default()),
+        }
+    }
+}
+
+/// Adds `bound` to every type parameter
+fn add_bounds(generics: &Generics, bound: Path) -> Generics {
+    let mut generics = generics.clone();
+    for param in &mut generics.params {
+        if let GenericParam::Type(param) = param {
+            param.bounds.push(parse_quote!(#bound));
+        }
+    }
+    generics
+}
+
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+fn from_json(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let generics = add_bounds(&input.generics, parse_quote!(::json_parsing::FromJson));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => fields_from_json(&quote!(Self), &data.fields, &quote!(value))?,
+        Data::Enum(data) => {
+            let mut unit_arms = Vec::new();
+            let mut tagged_arms = Vec::new();
+            for variant in &data.variants {
+                let ident = &variant.ident;
+                let key = Options::parse(&variant.attrs)?.key(ident);
+                if let Fields::Unit = variant.fields {
+                    unit_arms.push(quote!(#key => ::core::result::Result::Ok(Self::#ident),));
+                } else {
+                    let build =
+                        fields_from_json(&quote!(Self::#ident), &variant.fields, &quote!(inner))?;
+                    tagged_arms.push(quote! {
+                        #key => (|| #build)().map_err(|err: ::json_parsing::FromJsonError| err.within(tag.as_str())),
+                    });
+                }
+            }
+            quote! {
+                match value {
+                    ::json_parsing::Value::String(tag) => match tag.as_str() {
+                        #(#unit_arms)*
+                        _ => ::core::result::Result::Err(::json_parsing::FromJsonError::custom(
+                            ::std::format!("unknown variant `{}`", tag),
+                        )),
+                    },
+                    ::json_parsing::Value::Object(map) if map.len() == 1 => {
+                        let (tag, inner) = map.iter().next().unwrap();
+                        match tag.as_str() {
+                            #(#tagged_arms)*
+                            _ => ::core::result::Result::Err(::json_parsing::FromJsonError::custom(
+                                ::std::format!("unknown variant `{}`", tag),
+                            )),
+                        }
+                    }
+                    other => ::core::result::Result::Err(
+                        ::json_parsing::FromJsonError::wrong_type("string or single-key object", other),
+                    ),
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new(Span::call_site(), "unions are not supported"));
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::json_parsing::FromJson for #name #ty_generics #where_clause {
+            fn from_json(value: &::json_parsing::Value) -> ::core::result::Result<Self, ::json_parsing::FromJsonError> {
+                #body
+            }
+        }
+    })
+}
+
+/// An expression building `constructor` from the value in `source`
+fn fields_from_json(
+    constructor: &TokenStream2,
+    fields: &Fields,
+    source: &TokenStream2,
+) -> syn::Result<TokenStream2> {
+    match fields {
+        Fields::Named(fields) => {
+            let mut inits = Vec::new();
+            for field in &fields.named {
+                let ident = field.ident.as_ref().unwrap();
+                let options = Options::parse(&field.attrs)?;
+                let key = options.key(ident);
+                let init = if options.skip {
+                    options.default_expr()
+                } else if options.default.is_some() {
+                    let default = options.default_expr();
+                    quote!(::json_parsing::optional_field(#source, #key)?.unwrap_or_else(|| #default))
+                } else if is_option(&field.ty) {
+                    quote!(::json_parsing::optional_field(#source, #key)?)
+                } else {
+                    quote!(::json_parsing::field(#source, #key)?)
+                };
+                inits.push(quote!(#ident: #init));
+            }
+            Ok(quote!(::core::result::Result::Ok(#constructor { #(#inits),* })))
+        }
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => Ok(quote! {
+            ::core::result::Result::Ok(#constructor(::json_parsing::FromJson::from_json(#source)?))
+        }),
+        Fields::Unnamed(fields) => {
+            let len = fields.unnamed.len();
+            let elements = (0..len).map(|i| {
+                let index = i.to_string();
+                quote! {
+                    ::json_parsing::FromJson::from_json(&array[#i]).map_err(|err| err.within(#index))?
+                }
+            });
+            Ok(quote! {{
+                let array = match #source {
+                    ::json_parsing::Value::Array(array) if array.len() == #len => array,
+                    other => return ::core::result::Result::Err(
+                        ::json_parsing::FromJsonError::wrong_type(::core::concat!("array of length ", #len), other),
+                    ),
+                };
+                ::core::result::Result::Ok(#constructor(#(#elements),*))
+            }})
+        }
+        Fields::Unit => Ok(quote!(::core::result::Result::Ok(#constructor))),
+    }
+}
+
+fn to_json(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let generics = add_bounds(&input.generics, parse_quote!(::json_parsing::ToJson));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (pattern, build) = fields_to_json(&data.fields)?;
+            quote! {
+                let Self #pattern = self;
+                #build
+            }
+        }
+        Data::Enum(data) => {
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                let ident = &variant.ident;
+                let key = Options::parse(&variant.attrs)?.key(ident);
+                let (pattern, build) = fields_to_json(&variant.fields)?;
+                let arm = if let Fields::Unit = variant.fields {
+                    quote!(Self::#ident => ::json_parsing::Value::String(::std::string::String::from(#key)),)
+                } else {
+                    quote! {
+                        Self::#ident #pattern => {
+                            let inner = #build;
+                            ::json_parsing::Value::Object(::json_parsing::Map::from([
+                                (::std::string::String::from(#key), inner),
+                            ]))
+                        }
+                    }
+                };
+                arms.push(arm);
+            }
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new(Span::call_site(), "unions are not supported"));
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::json_parsing::ToJson for #name #ty_generics #where_clause {
+            fn to_json(&self) -> ::json_parsing::Value {
+                #body
+            }
+        }
+    })
+}
+
+/// A pattern binding the fields by reference, and an expression building their value
+fn fields_to_json(fields: &Fields) -> syn::Result<(TokenStream2, TokenStream2)> {
+    match fields {
+        Fields::Named(fields) => {
+            let mut bindings = Vec::new();
+            let mut inserts = Vec::new();
+            for field in &fields.named {
+                let ident = field.ident.as_ref().unwrap();
+                let options = Options::parse(&field.attrs)?;
+                if options.skip {
+                    bindings.push(quote!(#ident: _));
+                    continue;
+                }
+                let key = options.key(ident);
+                bindings.push(quote!(#ident));
+                inserts.push(quote! {
+                    map.insert(::std::string::String::from(#key), ::json_parsing::ToJson::to_json(#ident));
+                });
+            }
+            let pattern = quote!({ #(#bindings),* });
+            let build = quote! {{
+                let mut map = ::json_parsing::Map::new();
+                #(#inserts)*
+                ::json_parsing::Value::Object(map)
+            }};
+            Ok((pattern, build))
+        }
+        Fields::Unnamed(fields) => {
+            let bindings: Vec<_> = (0..fields.unnamed.len())
+                .map(|i| format_ident!("field{}", i))
+                .collect();
+            let pattern = quote!((#(#bindings),*));
+            let build = if bindings.len() == 1 {
+                quote!(::json_parsing::ToJson::to_json(field0))
+            } else {
+                quote! {
+                    ::json_parsing::Value::Array(::std::vec![#(::json_parsing::ToJson::to_json(#bindings)),*])
+                }
+            };
+            Ok((pattern, build))
+        }
+        Fields::Unit => Ok((quote!(), quote!(::json_parsing::Value::Null))),
+    }
+}
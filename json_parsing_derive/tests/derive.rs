@@ -0,0 +1,105 @@
+use json_parsing::{FromJson as _, FromJsonErrorKind, Parser, ToJson as _, Value};
+use json_parsing_derive::{FromJson, ToJson};
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+struct User {
+    #[json(rename = "userName")]
+    name: String,
+    nickname: Option<String>,
+    #[json(default)]
+    tags: Vec<String>,
+    #[json(default = "default_level")]
+    level: u8,
+    #[json(skip)]
+    cache: Vec<u32>,
+    role: Role,
+}
+
+fn default_level() -> u8 {
+    1
+}
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+enum Role {
+    Admin,
+    #[json(rename = "guest")]
+    Guest(u32),
+    Pair(String, bool),
+    Member {
+        team: String,
+    },
+}
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+struct Point(f64, f64);
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+struct Wrapper<T>(T);
+
+fn value(input: &str) -> Value {
+    Parser::new().parse_str(input).unwrap()
+}
+
+#[test]
+fn struct_with_attributes() {
+    let user = User::from_json(&value(r#"{"userName": "ken", "role": "Admin"}"#)).unwrap();
+    let expected = User {
+        name: String::from("ken"),
+        nickname: None,
+        tags: vec![],
+        level: 1,
+        cache: vec![],
+        role: Role::Admin,
+    };
+    assert_eq!(user, expected);
+
+    let json =
+        value(r#"{"userName": "ken", "nickname": null, "tags": [], "level": 1, "role": "Admin"}"#);
+    assert_eq!(user.to_json(), json);
+}
+
+#[test]
+fn enum_variants_round_trip() {
+    let roles = [
+        Role::Admin,
+        Role::Guest(7),
+        Role::Pair(String::from("a"), true),
+        Role::Member {
+            team: String::from("core"),
+        },
+    ];
+    let json =
+        value(r#"["Admin", {"guest": 7}, {"Pair": ["a", true]}, {"Member": {"team": "core"}}]"#);
+    assert_eq!(roles.to_json(), json);
+    assert_eq!(Vec::<Role>::from_json(&json).unwrap(), roles);
+}
+
+#[test]
+fn tuple_and_generic_structs() {
+    let point = Point(1.0, 2.5);
+    assert_eq!(point.to_json(), value("[1, 2.5]"));
+    assert_eq!(Point::from_json(&value("[1, 2.5]")).unwrap(), point);
+    assert!(Point::from_json(&value("[1]")).is_err());
+
+    let wrapped = Wrapper(String::from("x"));
+    assert_eq!(wrapped.to_json(), value(r#""x""#));
+    assert_eq!(
+        Wrapper::<String>::from_json(&value(r#""x""#)).unwrap(),
+        wrapped
+    );
+}
+
+#[test]
+fn errors_point_into_the_value() {
+    let err = User::from_json(&value(
+        r#"{"userName": "ken", "role": {"Member": {"team": 3}}}"#,
+    ))
+    .unwrap_err();
+    assert_eq!(err.pointer.to_string(), "/role/Member/team");
+
+    let err = Role::from_json(&value(r#""Owner""#)).unwrap_err();
+    assert_eq!(
+        err.kind,
+        FromJsonErrorKind::Custom(String::from("unknown variant `Owner`"))
+    );
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::Value;
+
+/// A required value is missing or has the wrong type, see [`Value::require_str`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessError {
+    /// The JSON Pointer as it was requested
+    pub pointer: String,
+
+    /// The type that was asked for, ex. `"string"` or `"integer"`
+    pub expected: &'static str,
+
+    /// The type of the value found there, or `None` when there is no value
+    pub found: Option<&'static str>,
+}
+
+/// The kind of value [`Value::require_all`] asks for at a pointer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expect {
+    /// Any value at all
+    Any,
+    String,
+    Number,
+    /// A whole number that fits in an `i64`
+    Integer,
+    Boolean,
+    Array,
+    Object,
+}
+
+impl Expect {
+    /// The name used in [`AccessError::expected`]
+    fn name(self) -> &'static str {
+        match self {
+            Expect::Any => "value",
+            Expect::String => "string",
+            Expect::Number => "number",
+            Expect::Integer => "integer",
+            Expect::Boolean => "boolean",
+            Expect::Array => "array",
+            Expect::Object => "object",
+        }
+    }
+
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            Expect::Any => true,
+            Expect::Integer => value.as_i64().is_some(),
+            expect => value.type_name() == expect.name(),
+        }
+    }
+}
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.found {
+            Some(found) => write!(
+                f,
+                "expected {} at `{}`, found {found}",
+                self.expected, self.pointer
+            ),
+            None => write!(f, "missing {} at `{}`", self.expected, self.pointer),
+        }
+    }
+}
+
+impl std::error::Error for AccessError {}
+
+impl Value {
+    /// The value at `pointer`, or an error naming the pointer if there is none
+    ///
+    /// Like the typed `require_*` methods this takes a JSON Pointer, ex. `/user/email`,
+    /// where one that is not valid syntax finds no value.
+    pub fn require(&self, pointer: &str) -> Result<&Value, AccessError> {
+        self.require_as(pointer, "value", Some)
+    }
+
+    pub fn require_str(&self, pointer: &str) -> Result<&str, AccessError> {
+        self.require_as(pointer, "string", Value::as_str)
+    }
+
+    pub fn require_f64(&self, pointer: &str) -> Result<f64, AccessError> {
+        self.require_as(pointer, "number", Value::as_f64)
+    }
+
+    /// The number at `pointer` if it is a whole number that fits in an `i64`, see
+    /// [`Value::as_i64`]
+    pub fn require_i64(&self, pointer: &str) -> Result<i64, AccessError> {
+        self.require_as(pointer, "integer", Value::as_i64)
+    }
+
+    pub fn require_bool(&self, pointer: &str) -> Result<bool, AccessError> {
+        self.require_as(pointer, "boolean", Value::as_bool)
+    }
+
+    pub fn require_array(&self, pointer: &str) -> Result<&Vec<Value>, AccessError> {
+        self.require_as(pointer, "array", Value::as_array)
+    }
+
+    pub fn require_object(&self, pointer: &str) -> Result<&HashMap<String, Value>, AccessError> {
+        self.require_as(pointer, "object", Value::as_object)
+    }
+
+    /// The values at each of the `requests`, in order, or an error for every one that is
+    /// missing or has the wrong type
+    ///
+    /// Unlike chaining the `require_*` methods, every pointer is checked, so a form can
+    /// report all of its problems at once.
+    pub fn require_all(
+        &self,
+        requests: &[(&str, Expect)],
+    ) -> Result<Vec<&Value>, Vec<AccessError>> {
+        let mut values = Vec::new();
+        let mut errors = Vec::new();
+        for &(pointer, expect) in requests {
+            let found = self.require_as(pointer, expect.name(), |value| {
+                expect.matches(value).then_some(value)
+            });
+            match found {
+                Ok(value) => values.push(value),
+                Err(err) => errors.push(err),
+            }
+        }
+        if errors.is_empty() {
+            Ok(values)
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn require_as<'a, T>(
+        &'a self,
+        pointer: &str,
+        expected: &'static str,
+        get: impl FnOnce(&'a Value) -> Option<T>,
+    ) -> Result<T, AccessError> {
+        let value = self.pointer(pointer);
+        value.and_then(get).ok_or_else(|| AccessError {
+            pointer: String::from(pointer),
+            expected,
+            found: value.map(Value::type_name),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::{AccessError, Expect};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    #[test]
+    fn typed_values() {
+        let document =
+            value(r#"{"user": {"email": "a@x", "age": 36, "admin": false, "tags": ["a"]}}"#);
+        assert_eq!(document.require_str("/user/email"), Ok("a@x"));
+        assert_eq!(document.require_i64("/user/age"), Ok(36));
+        assert_eq!(document.require_f64("/user/age"), Ok(36.0));
+        assert_eq!(document.require_bool("/user/admin"), Ok(false));
+        assert_eq!(document.require_array("/user/tags").map(Vec::len), Ok(1));
+        assert_eq!(document.require_object("/user").map(|map| map.len()), Ok(4));
+        assert_eq!(document.require("/user/tags/0"), Ok(&Value::string("a")));
+    }
+
+    #[test]
+    fn errors_name_the_pointer() {
+        let document = Value::object([("ratio", Value::Number(0.5)), ("name", Value::Null)]);
+        let err = document.require_str("/name").unwrap_err();
+        assert_eq!(
+            err,
+            AccessError {
+                pointer: String::from("/name"),
+                expected: "string",
+                found: Some("null"),
+            }
+        );
+        assert_eq!(err.to_string(), "expected string at `/name`, found null");
+
+        let err = document.require_i64("/ratio").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "expected integer at `/ratio`, found number"
+        );
+
+        let err = document.require_bool("/user/admin").unwrap_err();
+        assert_eq!(err.found, None);
+        assert_eq!(err.to_string(), "missing boolean at `/user/admin`");
+        assert_eq!(document.require("name").unwrap_err().found, None);
+    }
+
+    #[test]
+    fn collects_every_problem() {
+        let form = value(r#"{"name": "ada", "age": "36", "tags": [], "zip": 2100.5}"#);
+        let found = form.require_all(&[("/name", Expect::String), ("/tags", Expect::Any)]);
+        assert_eq!(
+            found,
+            Ok(vec![&Value::string("ada"), &Value::Array(Vec::new())])
+        );
+
+        let errors = form
+            .require_all(&[
+                ("/name", Expect::String),
+                ("/age", Expect::Integer),
+                ("/email", Expect::String),
+                ("/tags", Expect::Object),
+                ("/zip", Expect::Integer),
+                ("/zip", Expect::Number),
+            ])
+            .unwrap_err();
+        let messages: Vec<_> = errors.iter().map(AccessError::to_string).collect();
+        assert_eq!(
+            messages,
+            [
+                "expected integer at `/age`, found string",
+                "missing string at `/email`",
+                "expected object at `/tags`, found array",
+                "expected integer at `/zip`, found number",
+            ]
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use crate::Value;
+
+impl Value {
+    /// Compares two trees, treating numbers within `epsilon` of each other as equal
+    ///
+    /// Everything else must match exactly, as with `==`.
+    pub fn approx_eq(&self, other: &Value, epsilon: f64) -> bool {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => a == b || (a - b).abs() <= epsilon,
+            (Value::Array(a), Value::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.approx_eq(b, epsilon))
+            }
+            (Value::Object(a), Value::Object(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(key, a)| b.get(key).is_some_and(|b| a.approx_eq(b, epsilon)))
+            }
+            (a, b) => a == b,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    #[test]
+    fn numbers_within_tolerance() {
+        assert!(Value::Number(0.1 + 0.2).approx_eq(&Value::Number(0.3), 1e-9));
+        assert!(!Value::Number(0.1).approx_eq(&Value::Number(0.2), 1e-9));
+        assert!(!Value::Number(f64::NAN).approx_eq(&Value::Number(f64::NAN), 1.0));
+    }
+
+    #[test]
+    fn nested_trees() {
+        let a = value(r#"{"points": [{"x": 1.0000001, "y": 2}], "name": "a"}"#);
+        let b = value(r#"{"points": [{"x": 1, "y": 2.0000001}], "name": "a"}"#);
+        assert!(a.approx_eq(&b, 1e-6));
+        assert!(!a.approx_eq(&b, 1e-9));
+    }
+
+    #[test]
+    fn structure_must_match() {
+        assert!(!value("[1, 2]").approx_eq(&value("[1]"), 1.0));
+        assert!(!value(r#"{"a": 1}"#).approx_eq(&value(r#"{"b": 1}"#), 1.0));
+        assert!(!value(r#""1""#).approx_eq(&value("1"), 1.0));
+    }
+}
@@ -0,0 +1,232 @@
+/// Limits on the documents generated for property tests
+///
+/// Used as the parameters of the `proptest::arbitrary::Arbitrary` implementation, and
+/// by [`ArbitraryOptions::generate`] for `quickcheck`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArbitraryOptions {
+    /// How many arrays and objects can be nested, `0` only generates scalars
+    pub max_depth: u32,
+
+    /// The most elements in an array or members in an object
+    pub max_len: usize,
+
+    /// The most characters in a string or object key
+    pub max_string_len: usize,
+}
+
+impl Default for ArbitraryOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 3,
+            max_len: 8,
+            max_string_len: 16,
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod proptest_impl {
+    use proptest::arbitrary::{Arbitrary, any};
+    use proptest::collection::{hash_map, vec};
+    use proptest::num::f64;
+    use proptest::prelude::*;
+
+    use super::ArbitraryOptions;
+    use crate::Value;
+
+    impl Arbitrary for Value {
+        type Parameters = ArbitraryOptions;
+        type Strategy = BoxedStrategy<Value>;
+
+        fn arbitrary_with(options: ArbitraryOptions) -> Self::Strategy {
+            let string = vec(any::<char>(), 0..=options.max_string_len)
+                .prop_map(String::from_iter)
+                .boxed();
+            // JSON has no NaN or infinities, and most real numbers are integers
+            let number = prop_oneof![
+                any::<i32>().prop_map(f64::from),
+                f64::NORMAL | f64::SUBNORMAL | f64::ZERO,
+            ];
+            let leaf = prop_oneof![
+                Just(Value::Null),
+                any::<bool>().prop_map(Value::Boolean),
+                number.prop_map(Value::Number),
+                string.clone().prop_map(Value::String),
+            ];
+
+            let max_len = options.max_len;
+            leaf.prop_recursive(
+                options.max_depth,
+                256,
+                max_len.max(1) as u32,
+                move |inner| {
+                    prop_oneof![
+                        vec(inner.clone(), 0..=max_len).prop_map(Value::Array),
+                        hash_map(string.clone(), inner, 0..=max_len).prop_map(Value::Object),
+                    ]
+                },
+            )
+            .boxed()
+        }
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+mod quickcheck_impl {
+    use std::iter;
+
+    use quickcheck::{Arbitrary, Gen};
+
+    use super::ArbitraryOptions;
+    use crate::Value;
+
+    impl ArbitraryOptions {
+        /// A random document within these limits, for use in `quickcheck` generators
+        pub fn generate(&self, g: &mut Gen) -> Value {
+            generate(self, g, 0)
+        }
+    }
+
+    fn generate(options: &ArbitraryOptions, g: &mut Gen, depth: u32) -> Value {
+        let kinds = if depth < options.max_depth { 6 } else { 4 };
+        match usize::arbitrary(g) % kinds {
+            0 => Value::Null,
+            1 => Value::Boolean(bool::arbitrary(g)),
+            2 => Value::Number(number(g)),
+            3 => Value::String(string(options, g)),
+            4 => Value::Array(
+                (0..len(options.max_len, g))
+                    .map(|_| generate(options, g, depth + 1))
+                    .collect(),
+            ),
+            _ => Value::Object(
+                (0..len(options.max_len, g))
+                    .map(|_| (string(options, g), generate(options, g, depth + 1)))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn len(max: usize, g: &mut Gen) -> usize {
+        usize::arbitrary(g) % (max + 1)
+    }
+
+    fn string(options: &ArbitraryOptions, g: &mut Gen) -> String {
+        (0..len(options.max_string_len, g))
+            .map(|_| char::arbitrary(g))
+            .collect()
+    }
+
+    /// An integer or a finite float, JSON has no NaN or infinities
+    fn number(g: &mut Gen) -> f64 {
+        let n = f64::arbitrary(g);
+        if bool::arbitrary(g) || !n.is_finite() {
+            f64::from(i32::arbitrary(g))
+        } else {
+            n
+        }
+    }
+
+    impl Arbitrary for Value {
+        /// Generates with the default [`ArbitraryOptions`], except that `g.size()` bounds
+        /// the length of arrays, objects and strings
+        fn arbitrary(g: &mut Gen) -> Self {
+            let options = ArbitraryOptions {
+                max_len: g.size().min(ArbitraryOptions::default().max_len),
+                max_string_len: g.size(),
+                ..ArbitraryOptions::default()
+            };
+            options.generate(g)
+        }
+
+        /// Tries `null` first, then smaller scalars, and containers with fewer or smaller
+        /// children
+        fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+            let smaller: Box<dyn Iterator<Item = Self>> = match self {
+                Value::Null => return Box::new(iter::empty()),
+                Value::Boolean(b) => Box::new(b.shrink().map(Value::Boolean)),
+                Value::Number(n) => Box::new(n.shrink().map(Value::Number)),
+                Value::String(s) => Box::new(s.shrink().map(Value::String)),
+                Value::Array(array) => Box::new(array.shrink().map(Value::Array)),
+                Value::Object(map) => Box::new(map.shrink().map(Value::Object)),
+            };
+            Box::new(iter::once(Value::Null).chain(smaller))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "proptest")]
+    mod proptest_tests {
+        use proptest::prelude::*;
+
+        use crate::reader::read_document;
+        use crate::{ArbitraryOptions, Value};
+
+        fn depth(value: &Value) -> u32 {
+            match value {
+                Value::Array(array) => 1 + array.iter().map(depth).max().unwrap_or(0),
+                Value::Object(map) => 1 + map.values().map(depth).max().unwrap_or(0),
+                _ => 0,
+            }
+        }
+
+        proptest! {
+            #[test]
+            fn round_trips(value: Value) {
+                let text = value.to_json_string();
+                prop_assert_eq!(read_document(text.as_bytes()).unwrap(), value);
+            }
+
+            #[test]
+            fn respects_limits(value in any_with::<Value>(ArbitraryOptions {
+                max_depth: 1,
+                max_len: 2,
+                max_string_len: 3,
+            })) {
+                prop_assert!(depth(&value) <= 1);
+                if let Value::Array(array) = &value {
+                    prop_assert!(array.len() <= 2);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "quickcheck")]
+    mod quickcheck_tests {
+        use quickcheck::{Arbitrary, Gen, QuickCheck};
+
+        use crate::reader::read_document;
+        use crate::{ArbitraryOptions, Value};
+
+        #[test]
+        fn round_trips() {
+            fn property(value: Value) -> bool {
+                read_document(value.to_json_string().as_bytes()).unwrap() == value
+            }
+            QuickCheck::new().quickcheck(property as fn(Value) -> bool);
+        }
+
+        #[test]
+        fn scalars_only_at_depth_zero() {
+            let options = ArbitraryOptions {
+                max_depth: 0,
+                ..ArbitraryOptions::default()
+            };
+            let mut g = Gen::new(10);
+            for _ in 0..100 {
+                let value = options.generate(&mut g);
+                assert!(!matches!(value, Value::Array(_) | Value::Object(_)));
+            }
+        }
+
+        #[test]
+        fn shrinks_towards_null() {
+            let value = Value::Array(vec![Value::Boolean(true)]);
+            let shrunk: Vec<_> = value.shrink().collect();
+            assert_eq!(shrunk[0], Value::Null);
+            assert!(shrunk.contains(&Value::Array(vec![])));
+        }
+    }
+}
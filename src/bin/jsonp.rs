@@ -0,0 +1,422 @@
+//! `jsonp`, a small command-line tool for checking and reshaping JSON files
+//!
+//! Every subcommand reads from standard input when a file is `-` or omitted.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+use std::{env, fs};
+
+use json_parsing::interop::{self, CsvOptions, XmlOptions};
+use json_parsing::{
+    ColorChoice, MergeStrategy, PrettyOptions, ReaderError, Value, diff, minify, parse_with_spans,
+};
+
+const USAGE: &str = "\
+usage: jsonp <command> [options] [files]
+
+commands:
+  validate [FILE...]              check that each file is valid JSON
+  fmt [--minify] [--indent N] [FILE]
+                                  pretty-print or minify a document
+  get POINTER [FILE]              print the value at a JSON Pointer, ex. /items/0
+  diff FILE FILE                  list the differences between two documents
+  convert [--from FORMAT] [--to FORMAT] [FILE]
+                                  convert between json, yaml, toml, csv (input only),
+                                  xml, msgpack, cbor and bson, json by default
+  merge [--deep] FILE...          apply each file as a merge patch to the first one,
+                                  or merge them recursively with --deep";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let stdout = io::stdout();
+    let colored = ColorChoice::Auto.enabled_for(&stdout);
+    match run(&args, &mut stdout.lock(), colored) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(message) => {
+            eprintln!("jsonp: {message}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Runs a command, returning whether it succeeded, ex. `false` when `validate` finds an
+/// invalid file or `diff` finds a difference
+fn run(args: &[String], output: &mut impl Write, colored: bool) -> Result<bool, String> {
+    let (command, args) = args.split_first().ok_or(USAGE)?;
+    let allowed: &[&str] = match command.as_str() {
+        "fmt" => &["--minify", "--indent"],
+        "merge" => &["--deep"],
+        "convert" => &["--from", "--to"],
+        _ => &[],
+    };
+    let (options, args) = split_options(command, args, allowed)?;
+    let mut text = match command.as_str() {
+        "validate" => {
+            let files = if args.is_empty() { vec!["-"] } else { args };
+            let mut valid = true;
+            for file in files {
+                if let Err(message) = load(file) {
+                    eprintln!("{message}");
+                    valid = false;
+                }
+            }
+            return Ok(valid);
+        }
+        "fmt" => {
+            let file = single_file(&args, 0)?;
+            let input = read_input(file)?;
+            let value = parse_text(file, &input)?;
+            if options.contains_key("--minify") {
+                minify(&input).map_err(|err| format!("{file}: {err:?}"))?
+            } else {
+                let indent = match options.get("--indent") {
+                    Some(indent) => indent
+                        .parse()
+                        .map_err(|_| format!("invalid indent `{indent}`"))?,
+                    None => PrettyOptions::default().indent,
+                };
+                value.to_json_string_pretty_with(&PrettyOptions {
+                    indent,
+                    ..PrettyOptions::default()
+                })
+            }
+        }
+        "get" => {
+            let pointer = args.first().ok_or("get needs a pointer")?;
+            let file = single_file(&args, 1)?;
+            let value = load(file)?;
+            let found = value
+                .pointer(pointer)
+                .ok_or_else(|| format!("{file}: nothing at {pointer}"))?;
+            found.to_json_string_pretty_with(&PrettyOptions::default())
+        }
+        "diff" => {
+            let [a, b] = args[..] else {
+                return Err(String::from("diff needs two files"));
+            };
+            let changes = diff::compare(&load(a)?, &load(b)?);
+            write!(output, "{}", diff::format(&changes, colored)).map_err(io_error)?;
+            return Ok(changes.is_empty());
+        }
+        "merge" => {
+            let (first, rest) = args.split_first().ok_or("merge needs at least one file")?;
+            let mut merged = load(first)?;
+            for file in rest {
+                let patch = load(file)?;
+                if options.contains_key("--deep") {
+                    merged
+                        .deep_merge(patch, &MergeStrategy::default())
+                        .map_err(|conflict| format!("{file}: conflict at {}", conflict.pointer))?;
+                } else {
+                    merged.merge_patch(&patch);
+                }
+            }
+            merged.to_json_string_pretty_with(&PrettyOptions::default())
+        }
+        "convert" => {
+            let file = single_file(&args, 0)?;
+            let from = options.get("--from").copied().unwrap_or("json");
+            let to = options.get("--to").copied().unwrap_or("json");
+            let value = decode(from, file)?;
+            let bytes = encode(to, &value)?;
+            output.write_all(&bytes).map_err(io_error)?;
+            return Ok(true);
+        }
+        "help" | "--help" => String::from(USAGE),
+        command => return Err(format!("unknown command `{command}`\n{USAGE}")),
+    };
+    text.push('\n');
+    output.write_all(text.as_bytes()).map_err(io_error)?;
+    Ok(true)
+}
+
+/// Separates `--name` options from the other arguments, options in `VALUE_OPTIONS` take
+/// a value written as `--name value` or `--name=value`
+fn split_options<'a>(
+    command: &str,
+    args: &'a [String],
+    allowed: &[&str],
+) -> Result<(HashMap<&'a str, &'a str>, Vec<&'a str>), String> {
+    const VALUE_OPTIONS: &[&str] = &["--indent", "--from", "--to"];
+
+    let mut options = HashMap::new();
+    let mut rest = Vec::new();
+    let mut args = args.iter().map(String::as_str);
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            rest.push(arg);
+            continue;
+        }
+        let (name, value) = match arg.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (arg, None),
+        };
+        if !allowed.contains(&name) {
+            return Err(format!("unknown option `{name}` for {command}"));
+        }
+        let value = match value {
+            Some(value) => value,
+            None if VALUE_OPTIONS.contains(&name) => args
+                .next()
+                .ok_or_else(|| format!("`{name}` needs a value"))?,
+            None => "",
+        };
+        options.insert(name, value);
+    }
+    Ok((options, rest))
+}
+
+/// The file at `index`, or standard input if there are no more arguments
+fn single_file<'a>(args: &[&'a str], index: usize) -> Result<&'a str, String> {
+    match &args[index.min(args.len())..] {
+        [] => Ok("-"),
+        [file] => Ok(file),
+        [_, extra, ..] => Err(format!("unexpected argument `{extra}`")),
+    }
+}
+
+fn read_input(file: &str) -> Result<String, String> {
+    let name = if file == "-" { "<stdin>" } else { file };
+    String::from_utf8(read_bytes(file)?).map_err(|err| format!("{name}: {err}"))
+}
+
+fn read_bytes(file: &str) -> Result<Vec<u8>, String> {
+    if file == "-" {
+        let mut input = Vec::new();
+        io::stdin()
+            .read_to_end(&mut input)
+            .map_err(|err| format!("<stdin>: {err}"))?;
+        return Ok(input);
+    }
+    fs::read(file).map_err(|err| format!("{file}: {err}"))
+}
+
+/// Reads and parses a file, accepting the full JSON grammar
+fn load(file: &str) -> Result<Value, String> {
+    parse_text(file, &read_input(file)?)
+}
+
+fn parse_text(file: &str, input: &str) -> Result<Value, String> {
+    let name = if file == "-" { "<stdin>" } else { file };
+    let value = parse_with_spans(input).map_err(|err| format!("{name}:{}", describe(&err)))?;
+    Ok(value.to_value())
+}
+
+/// An error message prefixed with its `line:column`, when it has one
+fn describe(err: &ReaderError) -> String {
+    match err {
+        ReaderError::Io(err) => format!(" {err}"),
+        ReaderError::UnexpectedEof => String::from(" unexpected end of input"),
+        ReaderError::UnexpectedByte { position, found } => format!(
+            "{}:{}: unexpected {}",
+            position.line,
+            position.column,
+            char::from(*found).escape_default()
+        ),
+        ReaderError::InvalidNumber { position } => {
+            format!("{}:{}: invalid number", position.line, position.column)
+        }
+        ReaderError::InvalidEscape { position } => {
+            format!("{}:{}: invalid escape", position.line, position.column)
+        }
+        ReaderError::InvalidUtf8 { position } => {
+            format!("{}:{}: invalid UTF-8", position.line, position.column)
+        }
+        ReaderError::UnsafeInteger { position } => {
+            format!("{}:{}: integer too large to be exact", position.line, position.column)
+        }
+        ReaderError::NumberOutOfRange { position } => {
+            format!("{}:{}: number out of range", position.line, position.column)
+        }
+        ReaderError::LimitExceeded { position, limit } => {
+            format!("{}:{}: exceeded the {limit:?} limit", position.line, position.column)
+        }
+        ReaderError::DuplicateKey { position, key } => {
+            format!(
+                "{}:{}: duplicate key {key:?}",
+                position.line, position.column
+            )
+        }
+        ReaderError::TrailingContent { position } => {
+            format!(
+                "{}:{}: unexpected content after the document",
+                position.line, position.column
+            )
+        }
+    }
+}
+
+fn decode(format: &str, file: &str) -> Result<Value, String> {
+    let name = if file == "-" { "<stdin>" } else { file };
+    let error = |err: &dyn Debug| format!("{name}: {err:?}");
+    Ok(match format {
+        "json" => return load(file),
+        #[cfg(feature = "yaml")]
+        "yaml" => interop::from_yaml(&read_input(file)?).map_err(|err| error(&err))?,
+        #[cfg(feature = "toml")]
+        "toml" => interop::from_toml(&read_input(file)?).map_err(|err| error(&err))?,
+        "csv" => interop::from_csv(&read_input(file)?, &CsvOptions::default())
+            .map_err(|err| error(&err))?,
+        "xml" => interop::from_xml(&read_input(file)?, &XmlOptions::default())
+            .map_err(|err| error(&err))?,
+        "msgpack" => interop::from_msgpack(&read_bytes(file)?).map_err(|err| error(&err))?,
+        "cbor" => interop::from_cbor(&read_bytes(file)?).map_err(|err| error(&err))?,
+        "bson" => interop::from_bson(&read_bytes(file)?).map_err(|err| error(&err))?,
+        format => return Err(unknown_format(format)),
+    })
+}
+
+fn encode(format: &str, value: &Value) -> Result<Vec<u8>, String> {
+    let error = |err: &dyn Debug| format!("cannot write {format}: {err:?}");
+    let mut text = match format {
+        "json" => value.to_json_string_pretty_with(&PrettyOptions::default()),
+        #[cfg(feature = "yaml")]
+        "yaml" => interop::to_yaml(value),
+        #[cfg(feature = "toml")]
+        "toml" => interop::to_toml(value).map_err(|err| error(&err))?,
+        "xml" => interop::to_xml(value, &XmlOptions::default()).map_err(|err| error(&err))?,
+        "msgpack" => return Ok(interop::to_msgpack(value)),
+        "cbor" => return Ok(interop::to_cbor(value)),
+        "bson" => return interop::to_bson(value).map_err(|err| error(&err)),
+        format => return Err(unknown_format(format)),
+    };
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    Ok(text.into_bytes())
+}
+
+fn unknown_format(format: &str) -> String {
+    let disabled = ["yaml", "toml"].contains(&format);
+    if disabled {
+        format!("{format} support needs the `{format}` feature")
+    } else {
+        format!("unknown format `{format}`")
+    }
+}
+
+fn io_error(err: io::Error) -> String {
+    format!("<stdout>: {err}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use json_parsing::{Value, parse_with_spans};
+
+    use super::run;
+
+    /// Writes `files` to a fresh directory and runs the command with their paths
+    fn jsonp(args: &[&str], files: &[(&str, &str)]) -> (Result<bool, String>, String) {
+        let (result, output) = jsonp_bytes(args, files);
+        (result, String::from_utf8(output).unwrap())
+    }
+
+    fn jsonp_bytes(args: &[&str], files: &[(&str, &str)]) -> (Result<bool, String>, Vec<u8>) {
+        let dir = std::env::temp_dir().join(format!(
+            "jsonp_{}_{}",
+            std::process::id(),
+            args.join("_").replace(['/', '-', '='], "")
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let mut paths: Vec<PathBuf> = Vec::new();
+        for (name, contents) in files {
+            paths.push(dir.join(name));
+            fs::write(dir.join(name), contents).unwrap();
+        }
+        let mut args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        args.extend(paths.iter().map(|path| path.display().to_string()));
+
+        let mut output = Vec::new();
+        let result = run(&args, &mut output, false);
+        fs::remove_dir_all(&dir).unwrap();
+        (result, output)
+    }
+
+    #[test]
+    fn validate() {
+        let (result, _) = jsonp(&["validate"], &[("a.json", "[-1e3, {\"a\": null}]")]);
+        assert_eq!(result, Ok(true));
+        let (result, _) = jsonp(&["validate"], &[("a.json", "[]"), ("b.json", "[1,]")]);
+        assert_eq!(result, Ok(false));
+        let deep = "[".repeat(200_000) + &"]".repeat(200_000);
+        let (result, _) = jsonp(&["validate"], &[("deep.json", &deep)]);
+        assert_eq!(result, Ok(false));
+    }
+
+    #[test]
+    fn fmt() {
+        let files = [("a.json", "{\"b\": [1, 2],\n \"a\": \"x\"}")];
+        let (result, output) = jsonp(&["fmt", "--minify"], &files);
+        assert_eq!(result, Ok(true));
+        assert_eq!(output, "{\"b\":[1,2],\"a\":\"x\"}\n");
+        let (_, output) = jsonp(&["fmt", "--indent", "4"], &[("a.json", "[[1, 2], {}]")]);
+        assert_eq!(output, "[[1, 2], {}]\n");
+        let (result, _) = jsonp(&["fmt", "--indent=x"], &files);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn get() {
+        let files = [("a.json", "{\"items\": [{\"id\": 7}]}")];
+        let (result, output) = jsonp(&["get", "/items/0/id"], &files);
+        assert_eq!(result, Ok(true));
+        assert_eq!(output, "7\n");
+        let (result, _) = jsonp(&["get", "/missing"], &files);
+        assert!(result.unwrap_err().ends_with("nothing at /missing"));
+    }
+
+    #[test]
+    fn diff() {
+        let files = [("a.json", "{\"a\": 1}"), ("b.json", "{\"a\": 2}")];
+        let (result, output) = jsonp(&["diff"], &files);
+        assert_eq!(result, Ok(false));
+        assert_eq!(output, "@@ /a @@\n- 1\n+ 2\n");
+        let (result, output) = jsonp(&["diff"], &[("a.json", "[1]"), ("b.json", "[1]")]);
+        assert_eq!(result, Ok(true));
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn merge() {
+        let files = [
+            ("a.json", "{\"a\": {\"b\": 1, \"c\": 2}}"),
+            ("b.json", "{\"a\": {\"c\": null, \"d\": [3]}}"),
+        ];
+        let (_, output) = jsonp(&["merge"], &files);
+        assert_eq!(output, "{\"a\": {\"b\": 1, \"d\": [3]}}\n");
+        let (_, output) = jsonp(&["merge", "--deep"], &files);
+        assert_eq!(output, "{\"a\": {\"b\": 1, \"c\": null, \"d\": [3]}}\n");
+    }
+
+    #[test]
+    fn convert() {
+        let files = [("a.csv", "id,name\n1,x\n")];
+        let (result, output) = jsonp(&["convert", "--from", "csv"], &files);
+        assert_eq!(result, Ok(true));
+        let value = parse_with_spans(&output).unwrap().to_value();
+        assert_eq!(
+            value.pointer("/0/name"),
+            Some(&Value::String(String::from("x")))
+        );
+
+        let (result, output) = jsonp_bytes(&["convert", "--to=cbor"], &[("a.json", "[true]")]);
+        assert_eq!(result, Ok(true));
+        assert_eq!(output, [0x81, 0xf5]);
+        let (result, _) = jsonp(&["convert", "--to", "ini"], &[("a.json", "[]")]);
+        assert_eq!(result, Err(String::from("unknown format `ini`")));
+    }
+
+    #[test]
+    fn usage_errors() {
+        assert!(jsonp(&[], &[]).0.is_err());
+        assert!(jsonp(&["frobnicate"], &[]).0.is_err());
+        assert!(jsonp(&["merge", "--shallow"], &[]).0.is_err());
+        assert!(jsonp(&["fmt", "--indent"], &[]).0.is_err());
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+use std::hash::Hasher;
+use std::sync::Arc;
+
+use crate::hash::FnvHasher;
+use crate::reader::{SubtreeHooks, read_subtree_with};
+use crate::{DefaultState, JsonReader, Position, ReaderError, Value};
+
+/// Parsed arrays and objects keyed by their source text, see [`crate::Parser::parse_cached`]
+///
+/// `hash` is a 64-bit hash of `source`, the exact bytes of the subtree from its opening
+/// bracket to its closing one. A cache should compare `source` as well when a hash
+/// collision would matter. One cache should only be used with one parser configuration,
+/// as the same text may be read differently by another.
+pub trait SubtreeCache {
+    /// The subtree read earlier from `source`, if it is cached
+    fn get(&mut self, hash: u64, source: &[u8]) -> Option<Arc<Value>>;
+
+    /// Offers a subtree that was just read from `source`, which the cache may keep
+    fn insert(&mut self, hash: u64, source: &[u8], value: &Value);
+
+    /// Subtrees with shorter source are neither looked up nor offered, which also saves
+    /// hashing them
+    fn min_len(&self) -> usize {
+        0
+    }
+}
+
+type Entry = (Box<[u8]>, Arc<Value>);
+
+/// A [`SubtreeCache`] holding every subtree of at least a minimum size in memory
+#[derive(Debug, Clone, Default)]
+pub struct MemoryCache {
+    min_len: usize,
+    entries: HashMap<u64, Vec<Entry>>,
+}
+
+impl MemoryCache {
+    /// A cache keeping subtrees whose source is at least `min_len` bytes, smaller ones are
+    /// cheaper to parse again than to look up
+    pub fn new(min_len: usize) -> Self {
+        Self {
+            min_len,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// The number of subtrees cached
+    pub fn len(&self) -> usize {
+        self.entries.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl SubtreeCache for MemoryCache {
+    fn get(&mut self, hash: u64, source: &[u8]) -> Option<Arc<Value>> {
+        let entries = self.entries.get(&hash)?;
+        let (_, value) = entries.iter().find(|(text, _)| **text == *source)?;
+        Some(Arc::clone(value))
+    }
+
+    fn insert(&mut self, hash: u64, source: &[u8], value: &Value) {
+        if source.len() < self.min_len {
+            return;
+        }
+        let entries = self.entries.entry(hash).or_default();
+        if !entries.iter().any(|(text, _)| **text == *source) {
+            entries.push((source.into(), Arc::new(value.clone())));
+        }
+    }
+
+    fn min_len(&self) -> usize {
+        self.min_len
+    }
+}
+
+struct CacheHooks<'a, C> {
+    input: &'a [u8],
+    cache: &'a mut C,
+
+    /// The start and end offsets of every array and object, in order of their start
+    ends: Vec<(usize, Option<usize>)>,
+
+    /// The hash of each container being read, when `enter` computed one
+    hashes: Vec<Option<u64>>,
+}
+
+impl<C: SubtreeCache> SubtreeHooks<&[u8]> for CacheHooks<'_, C> {
+    fn enter(
+        &mut self,
+        reader: &mut JsonReader<&[u8]>,
+        start: Position,
+    ) -> Result<Option<Value>, ReaderError> {
+        let end = self
+            .ends
+            .binary_search_by_key(&start.offset, |(start, _)| *start)
+            .ok()
+            .and_then(|i| self.ends[i].1);
+        let source = match end {
+            Some(end) if end - start.offset >= self.cache.min_len() => {
+                &self.input[start.offset..end]
+            }
+            _ => {
+                self.hashes.push(None);
+                return Ok(None);
+            }
+        };
+        let hash = hash(source);
+        let Some(value) = self.cache.get(hash, source) else {
+            self.hashes.push(Some(hash));
+            return Ok(None);
+        };
+        reader.skip_value()?;
+        Ok(Some(Arc::unwrap_or_clone(value)))
+    }
+
+    fn leave(&mut self, reader: &JsonReader<&[u8]>, start: Position, value: &Value) {
+        let known = self.hashes.pop().flatten();
+        let source = &self.input[start.offset..reader.position().offset];
+        if source.len() >= self.cache.min_len() {
+            let hash = known.unwrap_or_else(|| hash(source));
+            self.cache.insert(hash, source, value);
+        }
+    }
+}
+
+/// Reads a whole document from `input`, looking each array and object up in `cache`
+/// before reading it
+pub(crate) fn read_cached<C: SubtreeCache>(
+    mut reader: JsonReader<&[u8]>,
+    input: &[u8],
+    cache: &mut C,
+) -> Result<Value, ReaderError> {
+    let first = reader.next_event()?.ok_or(ReaderError::UnexpectedEof)?;
+    let mut hooks = CacheHooks {
+        input,
+        cache,
+        ends: subtree_ends(input),
+        hashes: Vec::new(),
+    };
+    let value = read_subtree_with(&mut reader, first, &DefaultState::default(), &mut hooks)?;
+    // the reader reports anything after the root value
+    reader.next_event()?;
+    Ok(value)
+}
+
+fn hash(source: &[u8]) -> u64 {
+    let mut hasher = FnvHasher::default();
+    hasher.write(source);
+    hasher.finish()
+}
+
+/// The start and end offsets of every array and object in `source`, found in one pass
+/// by matching brackets outside of strings and comments
+///
+/// The end is `None` for containers that are still open where the scan stops, at the end
+/// of the input or of an unterminated string or comment.
+fn subtree_ends(source: &[u8]) -> Vec<(usize, Option<usize>)> {
+    let mut ends = Vec::new();
+    let mut open = Vec::new();
+    let mut i = 0;
+    while let Some(&byte) = source.get(i) {
+        i += 1;
+        let skip = match byte {
+            b'[' | b'{' => {
+                open.push(ends.len());
+                ends.push((i - 1, None));
+                Some(0)
+            }
+            b']' | b'}' => {
+                if let Some(index) = open.pop() {
+                    ends[index].1 = Some(i);
+                }
+                Some(0)
+            }
+            b'"' => string_len(&source[i..]),
+            b'\'' if source[i..].starts_with(b"''") => {
+                find(&source[i + 2..], b"'''").map(|len| len + 5)
+            }
+            b'`' => find(&source[i..], b"`").map(|len| len + 1),
+            b'/' if source.get(i) == Some(&b'/') => find(&source[i..], b"\n"),
+            b'/' if source.get(i) == Some(&b'*') => {
+                find(&source[i + 1..], b"*/").map(|len| len + 3)
+            }
+            _ => Some(0),
+        };
+        match skip {
+            Some(skip) => i += skip,
+            None => break,
+        }
+    }
+    ends
+}
+
+/// The length of the rest of a string after its opening quote, including the closing one
+fn string_len(rest: &[u8]) -> Option<usize> {
+    let mut i = 0;
+    loop {
+        match rest.get(i)? {
+            b'\\' => i += 2,
+            b'"' => return Some(i + 1),
+            _ => i += 1,
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{Parser, ParserOptions, Value};
+
+    use super::{MemoryCache, SubtreeCache, subtree_ends};
+
+    const RECORD: &str = r#"{"kind": "user", "roles": ["admin", "ops"], "limits": {"cpu": 4}}"#;
+
+    /// Counts the lookups, and those that found a subtree
+    struct Counting {
+        inner: MemoryCache,
+        lookups: usize,
+        hits: usize,
+    }
+
+    impl SubtreeCache for Counting {
+        fn get(&mut self, hash: u64, source: &[u8]) -> Option<Arc<Value>> {
+            let found = self.inner.get(hash, source);
+            self.lookups += 1;
+            self.hits += usize::from(found.is_some());
+            found
+        }
+
+        fn insert(&mut self, hash: u64, source: &[u8], value: &Value) {
+            self.inner.insert(hash, source, value);
+        }
+
+        fn min_len(&self) -> usize {
+            self.inner.min_len()
+        }
+    }
+
+    #[test]
+    fn reuses_cached_subtrees() {
+        let mut cache = Counting {
+            inner: MemoryCache::new(16),
+            lookups: 0,
+            hits: 0,
+        };
+        let parser = Parser::new();
+        let first = format!(r#"{{"a": {RECORD}, "b": [1]}}"#);
+        let value = parser.parse_cached(first.as_bytes(), &mut cache).unwrap();
+        assert_eq!(value, Parser::new().parse_str(&first).unwrap());
+        assert_eq!((cache.lookups, cache.hits), (3, 0));
+        // the root, the record and its roles, but not the short `[1]` or `{"cpu": 4}`
+        assert_eq!(cache.inner.len(), 3);
+
+        let second = format!(r#"[{RECORD},{{"other": true}}, {RECORD}]"#);
+        let value = parser.parse_cached(second.as_bytes(), &mut cache).unwrap();
+        assert_eq!(value, Parser::new().parse_str(&second).unwrap());
+        assert_eq!(cache.hits, 2);
+    }
+
+    #[test]
+    fn substitutes_the_cached_value() {
+        struct Planted;
+
+        impl SubtreeCache for Planted {
+            fn get(&mut self, _: u64, source: &[u8]) -> Option<Arc<Value>> {
+                (source == b"[1, 2]").then(|| Arc::new(Value::string("cached")))
+            }
+
+            fn insert(&mut self, _: u64, _: &[u8], _: &Value) {}
+        }
+
+        let input = br#"{"x": [1, 2], "y": [1,2]}"#;
+        let value = Parser::new().parse_cached(input, &mut Planted).unwrap();
+        let expected = Parser::new()
+            .parse_str(r#"{"x": "cached", "y": [1, 2]}"#)
+            .unwrap();
+        assert_eq!(value, expected);
+
+        let lenient = Parser::new().options(ParserOptions::lenient());
+        let input = b"[[1, 2] /* ] */, [1, 2]]";
+        let value = lenient.parse_cached(input, &mut Planted).unwrap();
+        assert_eq!(value, Value::Array(vec![Value::string("cached"); 2]));
+        let input = b"[[`]`, 1], [`]`, 1]]";
+        let value = lenient.parse_cached(input, &mut MemoryCache::new(0));
+        let element = Value::Array(vec![Value::string("]"), Value::Number(1.0)]);
+        assert_eq!(value.unwrap(), Value::Array(vec![element; 2]));
+        assert!(
+            Parser::new()
+                .parse_cached(b"[[1, 2]] x", &mut Planted)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn finds_the_end_of_subtrees() {
+        let ends = subtree_ends(br#"{"a": "]}", "b\"}": [1]} tail"#);
+        assert_eq!(ends, [(0, Some(24)), (20, Some(23))]);
+        assert_eq!(subtree_ends(b"[1, // ]\n 2] tail"), [(0, Some(12))]);
+        assert_eq!(subtree_ends(b"[''']''', `]`] tail"), [(0, Some(14))]);
+        assert_eq!(subtree_ends(b"[1, [2]"), [(0, None), (4, Some(7))]);
+        assert_eq!(subtree_ends(b"[[1], \"]"), [(0, None), (1, Some(4))]);
+    }
+
+    #[test]
+    fn deep_documents_scan_once() {
+        let depth = crate::DEFAULT_MAX_DEPTH;
+        let input = "[".repeat(depth) + &"]".repeat(depth);
+        let ends = subtree_ends(input.as_bytes());
+        assert_eq!(ends.len(), depth);
+        assert_eq!(ends[1], (1, Some(2 * depth - 1)));
+
+        let mut cache = MemoryCache::new(input.len());
+        let value = Parser::new().parse_cached(input.as_bytes(), &mut cache);
+        assert_eq!(value.unwrap(), Parser::new().parse_str(&input).unwrap());
+        assert_eq!(cache.len(), 1);
+    }
+}
@@ -0,0 +1,162 @@
+use crate::Value;
+
+/// A naming convention for object keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Case {
+    /// `userId`
+    Camel,
+
+    /// `UserId`
+    Pascal,
+
+    /// `user_id`
+    Snake,
+
+    /// `user-id`
+    Kebab,
+}
+
+impl Case {
+    /// Converts a name from any of the supported cases into this one
+    ///
+    /// Words are split at `_`, `-`, spaces and changes of case, keeping acronyms
+    /// together, ex. `HTTPServer` becomes `http_server` in snake case.
+    pub fn convert(self, name: &str) -> String {
+        let words = split_words(name);
+        match self {
+            Case::Snake => join_lowercase(&words, "_"),
+            Case::Kebab => join_lowercase(&words, "-"),
+            Case::Pascal => words.iter().map(|word| capitalize(word)).collect(),
+            Case::Camel => {
+                let mut output = String::new();
+                for (i, word) in words.iter().enumerate() {
+                    if i == 0 {
+                        output.push_str(&word.to_lowercase());
+                    } else {
+                        output.push_str(&capitalize(word));
+                    }
+                }
+                output
+            }
+        }
+    }
+}
+
+impl Value {
+    /// Renames the keys of every object in the document to `case`
+    ///
+    /// When two keys in an object become the same, one of their values is kept.
+    pub fn rename_keys(&mut self, case: Case) {
+        match self {
+            Value::Array(array) => {
+                for item in array {
+                    item.rename_keys(case);
+                }
+            }
+            Value::Object(map) => {
+                *map = std::mem::take(map)
+                    .into_iter()
+                    .map(|(key, mut value)| {
+                        value.rename_keys(case);
+                        (case.convert(&key), value)
+                    })
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn split_words(name: &str) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = name.char_indices().collect();
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, &(offset, ch)) in chars.iter().enumerate() {
+        if matches!(ch, '_' | '-' | ' ') {
+            if let Some(start) = start.take() {
+                words.push(&name[start..offset]);
+            }
+            continue;
+        }
+
+        let previous = i.checked_sub(1).map(|i| chars[i].1);
+        let next = chars.get(i + 1).map(|&(_, ch)| ch);
+        let boundary = ch.is_uppercase()
+            && previous.is_some_and(|previous| {
+                previous.is_lowercase()
+                    || previous.is_ascii_digit()
+                    || (previous.is_uppercase() && next.is_some_and(char::is_lowercase))
+            });
+        match start {
+            Some(word_start) if boundary => {
+                words.push(&name[word_start..offset]);
+                start = Some(offset);
+            }
+            Some(_) => {}
+            None => start = Some(offset),
+        }
+    }
+    if let Some(start) = start {
+        words.push(&name[start..]);
+    }
+    words
+}
+
+fn join_lowercase(words: &[&str], separator: &str) -> String {
+    let words: Vec<_> = words.iter().map(|word| word.to_lowercase()).collect();
+    words.join(separator)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::Case;
+
+    #[test]
+    fn converts_between_cases() {
+        for name in [
+            "userId", "UserId", "user_id", "user-id", "USER_ID", "user id",
+        ] {
+            assert_eq!(Case::Snake.convert(name), "user_id");
+            assert_eq!(Case::Kebab.convert(name), "user-id");
+            assert_eq!(Case::Camel.convert(name), "userId");
+            assert_eq!(Case::Pascal.convert(name), "UserId");
+        }
+    }
+
+    #[test]
+    fn acronyms_and_digits() {
+        assert_eq!(Case::Snake.convert("HTTPServer"), "http_server");
+        assert_eq!(Case::Snake.convert("parseJSON"), "parse_json");
+        assert_eq!(Case::Camel.convert("address_line_2"), "addressLine2");
+        assert_eq!(Case::Snake.convert("line2Name"), "line2_name");
+        assert_eq!(Case::Snake.convert("__private"), "private");
+        assert_eq!(Case::Camel.convert(""), "");
+    }
+
+    #[test]
+    fn renames_nested_keys() {
+        let mut value = Parser::new()
+            .parse_str(r#"{"userId": 1, "homeAddress": [{"zipCode": "x"}]}"#)
+            .unwrap();
+        value.rename_keys(Case::Snake);
+        let expected = r#"{"user_id": 1, "home_address": [{"zip_code": "x"}]}"#;
+        assert_eq!(value, Parser::new().parse_str(expected).unwrap());
+        assert_eq!(
+            value.pointer("/home_address/0/zip_code"),
+            Some(&Value::string("x"))
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use crate::Value;
+use crate::reader::read_document;
+
+/// Which loose conversions [`Value::coerce_schema`] may make, all are on by default
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoerceOptions {
+    /// Numeric text becomes a number, ex. `" 42 "` to `42`
+    pub strings_to_numbers: bool,
+
+    /// `"true"`, `"yes"` and `"1"` become `true`, `"false"`, `"no"` and `"0"` become
+    /// `false`, ignoring case and surrounding whitespace
+    pub strings_to_booleans: bool,
+
+    /// `1` and `0` become `true` and `false`
+    pub numbers_to_booleans: bool,
+
+    /// Numbers and booleans become their JSON text, ex. `42` to `"42"`
+    pub scalars_to_strings: bool,
+
+    /// `""` becomes `null` where the schema does not allow a string
+    pub empty_strings_to_null: bool,
+}
+
+impl Default for CoerceOptions {
+    fn default() -> Self {
+        Self {
+            strings_to_numbers: true,
+            strings_to_booleans: true,
+            numbers_to_booleans: true,
+            scalars_to_strings: true,
+            empty_strings_to_null: true,
+        }
+    }
+}
+
+impl Value {
+    /// The value as a number under loose rules: numbers as they are, numeric text such as
+    /// `"42"` or `" -1.5e3 "`, and `true` and `false` as 1 and 0
+    ///
+    /// Text must follow the JSON number grammar, so `""`, `"0x10"` and `"NaN"` are `None`,
+    /// as are `null`, arrays and objects.
+    pub fn coerce_to_number(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            Value::Boolean(b) => Some(f64::from(u8::from(*b))),
+            Value::String(s) => parse_number(s),
+            _ => None,
+        }
+    }
+
+    /// The value as a boolean under loose rules: booleans as they are, `1` and `0`, and
+    /// the text described in [`CoerceOptions::strings_to_booleans`]
+    pub fn coerce_to_bool(&self) -> Option<bool> {
+        match self {
+            Value::Boolean(b) => Some(*b),
+            Value::Number(n) => number_to_bool(*n),
+            Value::String(s) => parse_bool(s),
+            _ => None,
+        }
+    }
+
+    /// Converts values in place towards the types a JSON Schema expects, ex. `"42"` to
+    /// `42` where `{"type": "integer"}` is declared
+    ///
+    /// The schema is followed through `properties`, `additionalProperties` and `items`,
+    /// and each `type` is tried in order. Values that already have an allowed type, or
+    /// that no enabled conversion fits, are left alone, so the result should still be
+    /// validated if it must conform.
+    pub fn coerce_schema(&mut self, schema: &Value, options: &CoerceOptions) {
+        let Value::Object(schema) = schema else {
+            return;
+        };
+        if let Some(types) = schema.get("type") {
+            let types: Vec<&str> = match types {
+                Value::String(name) => vec![name],
+                Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            if !types.is_empty() && !types.iter().any(|name| has_type(self, name)) {
+                if let Some(coerced) = types.iter().find_map(|name| convert(self, name, options)) {
+                    *self = coerced;
+                } else if self.as_str() == Some("")
+                    && options.empty_strings_to_null
+                    && !types.contains(&"string")
+                {
+                    *self = Value::Null;
+                }
+            }
+        }
+
+        match self {
+            Value::Object(map) => {
+                let properties = schema.get("properties").and_then(Value::as_object);
+                let additional = schema.get("additionalProperties");
+                for (key, item) in map.iter_mut() {
+                    let property = properties.and_then(|properties| properties.get(key));
+                    if let Some(property) = property.or(additional) {
+                        item.coerce_schema(property, options);
+                    }
+                }
+            }
+            Value::Array(array) => match schema.get("items") {
+                Some(Value::Array(tuple)) => {
+                    for (item, schema) in array.iter_mut().zip(tuple) {
+                        item.coerce_schema(schema, options);
+                    }
+                }
+                Some(items) => {
+                    for item in array {
+                        item.coerce_schema(items, options);
+                    }
+                }
+                None => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match (value, name) {
+        (Value::Number(n), "integer") => n.fract() == 0.0,
+        (value, name) => value.type_name() == name,
+    }
+}
+
+/// `value` converted to the JSON Schema type `name`, if an enabled conversion fits
+fn convert(value: &Value, name: &str, options: &CoerceOptions) -> Option<Value> {
+    let converted = match (value, name) {
+        (Value::String(s), "number") if options.strings_to_numbers => parse_number(s)?,
+        (Value::String(s), "integer") if options.strings_to_numbers => {
+            parse_number(s).filter(|n| n.fract() == 0.0)?
+        }
+        (Value::String(s), "boolean") if options.strings_to_booleans => {
+            return parse_bool(s).map(Value::Boolean);
+        }
+        (Value::Number(n), "boolean") if options.numbers_to_booleans => {
+            return number_to_bool(*n).map(Value::Boolean);
+        }
+        (Value::Number(_) | Value::Boolean(_), "string") if options.scalars_to_strings => {
+            return Some(Value::String(value.to_json_string()));
+        }
+        _ => return None,
+    };
+    Some(Value::Number(converted))
+}
+
+fn parse_number(text: &str) -> Option<f64> {
+    match read_document(text.trim().as_bytes()) {
+        Ok(Value::Number(n)) => Some(n),
+        _ => None,
+    }
+}
+
+fn parse_bool(text: &str) -> Option<bool> {
+    match text.trim().to_ascii_lowercase().as_str() {
+        "true" | "yes" | "1" => Some(true),
+        "false" | "no" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn number_to_bool(n: f64) -> Option<bool> {
+    match n {
+        1.0 => Some(true),
+        0.0 => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::CoerceOptions;
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    #[test]
+    fn loose_scalars() {
+        assert_eq!(Value::string(" 42 ").coerce_to_number(), Some(42.0));
+        assert_eq!(Value::string("-1.5e3").coerce_to_number(), Some(-1500.0));
+        assert_eq!(Value::Boolean(true).coerce_to_number(), Some(1.0));
+        assert_eq!(Value::string("").coerce_to_number(), None);
+        assert_eq!(Value::string("0x10").coerce_to_number(), None);
+        assert_eq!(Value::string("NaN").coerce_to_number(), None);
+        assert_eq!(Value::Null.coerce_to_number(), None);
+
+        assert_eq!(Value::string(" TRUE").coerce_to_bool(), Some(true));
+        assert_eq!(Value::string("no").coerce_to_bool(), Some(false));
+        assert_eq!(Value::Number(0.0).coerce_to_bool(), Some(false));
+        assert_eq!(Value::Number(2.0).coerce_to_bool(), None);
+        assert_eq!(Value::string("maybe").coerce_to_bool(), None);
+    }
+
+    const SCHEMA: &str = r#"{
+        "type": "object",
+        "properties": {
+            "age": {"type": "integer"},
+            "score": {"type": ["number", "null"]},
+            "active": {"type": "boolean"},
+            "zip": {"type": "string"},
+            "note": {"type": ["null", "string"]},
+            "tags": {"type": "array", "items": {"type": "boolean"}},
+            "pair": {"items": [{"type": "number"}, {"type": "string"}]}
+        },
+        "additionalProperties": {"type": "number"}
+    }"#;
+
+    #[test]
+    fn follows_the_schema() {
+        let mut record = value(
+            r#"{"age": "42", "score": "", "active": "yes", "zip": 2100, "note": "",
+                "tags": [1, "false", "x"], "pair": ["3", 4], "extra": "7", "nested": []}"#,
+        );
+        record.coerce_schema(&value(SCHEMA), &CoerceOptions::default());
+        let expected = value(
+            r#"{"age": 42, "score": null, "active": true, "zip": "2100", "note": "",
+                "tags": [true, false, "x"], "pair": [3, "4"], "extra": 7, "nested": []}"#,
+        );
+        assert_eq!(record, expected);
+
+        let mut fraction = value(r#"{"age": "4.5"}"#);
+        fraction.coerce_schema(&value(SCHEMA), &CoerceOptions::default());
+        assert_eq!(fraction, value(r#"{"age": "4.5"}"#));
+    }
+
+    #[test]
+    fn options_turn_conversions_off() {
+        let options = CoerceOptions {
+            strings_to_numbers: false,
+            empty_strings_to_null: false,
+            ..CoerceOptions::default()
+        };
+        let mut record = value(r#"{"age": "42", "score": "", "active": "false"}"#);
+        record.coerce_schema(&value(SCHEMA), &options);
+        assert_eq!(
+            record,
+            value(r#"{"age": "42", "score": "", "active": false}"#)
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use std::env;
+use std::io::{self, IsTerminal};
+
+use crate::Value;
+use crate::serialize::{format_number, write_string, write_value_pretty};
+
+/// ANSI SGR parameters for each kind of token, ex. `"1;34"` for bold blue
+///
+/// An empty string leaves that kind of token uncolored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorScheme {
+    pub key: &'static str,
+    pub string: &'static str,
+    pub number: &'static str,
+    pub boolean: &'static str,
+    pub null: &'static str,
+    pub punctuation: &'static str,
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        Self {
+            key: "1;34",
+            string: "32",
+            number: "36",
+            boolean: "33",
+            null: "90",
+            punctuation: "",
+        }
+    }
+}
+
+/// Whether to emit colored output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color only when writing to a terminal, and `NO_COLOR` is not set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether output to `stream` should be colored
+    pub fn enabled_for(self, stream: &impl IsTerminal) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => {
+                let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+                let dumb = env::var_os("TERM").is_some_and(|term| term == "dumb");
+                !no_color && !dumb && stream.is_terminal()
+            }
+        }
+    }
+}
+
+impl Value {
+    /// Serializes to JSON text indented by two spaces, wrapping tokens in ANSI color codes
+    pub fn to_json_string_colored(&self, scheme: &ColorScheme) -> String {
+        let mut output = String::new();
+        write_colored(self, scheme, 0, &mut output);
+        output
+    }
+}
+
+/// Writes indented JSON text, colored when `choice` allows it for `writer`
+///
+/// Without color the output is the same as [`write_value_pretty`].
+pub fn write_value_colored<W: io::Write + IsTerminal>(
+    writer: &mut W,
+    value: &Value,
+    choice: ColorChoice,
+) -> io::Result<()> {
+    if choice.enabled_for(writer) {
+        let colored = value.to_json_string_colored(&ColorScheme::default());
+        writer.write_all(colored.as_bytes())
+    } else {
+        write_value_pretty(writer, value)
+    }
+}
+
+fn paint(code: &str, text: &str, output: &mut String) {
+    if code.is_empty() {
+        output.push_str(text);
+    } else {
+        output.push_str("\x1b[");
+        output.push_str(code);
+        output.push('m');
+        output.push_str(text);
+        output.push_str("\x1b[0m");
+    }
+}
+
+fn paint_string(code: &str, s: &str, output: &mut String) {
+    let mut quoted = String::new();
+    write_string(s, &mut quoted);
+    paint(code, &quoted, output);
+}
+
+fn newline(depth: usize, output: &mut String) {
+    output.push('\n');
+    for _ in 0..depth {
+        output.push_str("  ");
+    }
+}
+
+fn write_colored(value: &Value, scheme: &ColorScheme, depth: usize, output: &mut String) {
+    match value {
+        Value::Array(array) if !array.is_empty() => {
+            paint(scheme.punctuation, "[", output);
+            for (i, item) in array.iter().enumerate() {
+                if i > 0 {
+                    paint(scheme.punctuation, ",", output);
+                }
+                newline(depth + 1, output);
+                write_colored(item, scheme, depth + 1, output);
+            }
+            newline(depth, output);
+            paint(scheme.punctuation, "]", output);
+        }
+        Value::Object(map) if !map.is_empty() => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            paint(scheme.punctuation, "{", output);
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    paint(scheme.punctuation, ",", output);
+                }
+                newline(depth + 1, output);
+                paint_string(scheme.key, key, output);
+                paint(scheme.punctuation, ":", output);
+                output.push(' ');
+                write_colored(item, scheme, depth + 1, output);
+            }
+            newline(depth, output);
+            paint(scheme.punctuation, "}", output);
+        }
+        Value::Array(_) => paint(scheme.punctuation, "[]", output),
+        Value::Object(_) => paint(scheme.punctuation, "{}", output),
+        Value::String(s) => paint_string(scheme.string, s, output),
+        Value::Number(n) => paint(scheme.number, &format_number(*n), output),
+        Value::Boolean(b) => paint(scheme.boolean, if *b { "true" } else { "false" }, output),
+        Value::Null => paint(scheme.null, "null", output),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File};
+
+    use crate::{Parser, Value};
+
+    use super::{ColorChoice, ColorScheme, write_value_colored};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    fn strip_ansi(s: &str) -> String {
+        let mut output = String::new();
+        let mut chars = s.chars();
+        while let Some(ch) = chars.next() {
+            if ch == '\x1b' {
+                chars.by_ref().find(|ch| *ch == 'm');
+            } else {
+                output.push(ch);
+            }
+        }
+        output
+    }
+
+    #[test]
+    fn colors_each_kind_of_token() {
+        let colored =
+            value(r#"{"a": ["x", 1, true, null]}"#).to_json_string_colored(&ColorScheme::default());
+        assert!(colored.contains("\x1b[1;34m\"a\"\x1b[0m"));
+        assert!(colored.contains("\x1b[32m\"x\"\x1b[0m"));
+        assert!(colored.contains("\x1b[36m1\x1b[0m"));
+        assert!(colored.contains("\x1b[33mtrue\x1b[0m"));
+        assert!(colored.contains("\x1b[90mnull\x1b[0m"));
+    }
+
+    #[test]
+    fn same_layout_as_pretty() {
+        let value = value(r#"{"b": {"c": []}, "a": ["x\n", 2.5]}"#);
+        let colored = value.to_json_string_colored(&ColorScheme::default());
+        assert_eq!(strip_ansi(&colored), value.to_json_string_pretty());
+    }
+
+    #[test]
+    fn files_are_not_terminals() {
+        let path = std::env::temp_dir().join("json_parsing_color_test.json");
+        let value = value(r#"{"a": 1}"#);
+        for choice in [ColorChoice::Never, ColorChoice::Auto, ColorChoice::Always] {
+            let mut file = File::create(&path).unwrap();
+            write_value_colored(&mut file, &value, choice).unwrap();
+            let written = fs::read_to_string(&path).unwrap();
+            let has_color = written.contains('\x1b');
+            assert_eq!(has_color, choice == ColorChoice::Always);
+            assert_eq!(strip_ansi(&written), value.to_json_string_pretty());
+        }
+        fs::remove_file(path).unwrap();
+    }
+}
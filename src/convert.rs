@@ -0,0 +1,576 @@
+//! Hand-written conversions between Rust types and [`Value`], for use without serde
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::BuildHasher;
+
+use crate::path::{PathToken, parse_dot_path};
+use crate::{JsonPointer, Value};
+
+/// Types that can be built from a [`Value`]
+pub trait FromJson: Sized {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError>;
+}
+
+/// Types that can be turned into a [`Value`]
+pub trait ToJson {
+    fn to_json(&self) -> Value;
+}
+
+/// A value did not have the shape the target type expected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FromJsonError {
+    /// Location of the offending value, relative to where conversion started
+    pub pointer: JsonPointer,
+    pub kind: FromJsonErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FromJsonErrorKind {
+    /// The value had a different JSON type than expected
+    WrongType {
+        expected: &'static str,
+        found: &'static str,
+    },
+
+    /// A required object key was not present
+    MissingField(String),
+
+    /// The number does not fit in the target integer type
+    NumberOutOfRange { target: &'static str },
+
+    /// An error raised by a hand-written conversion
+    Custom(String),
+}
+
+impl FromJsonError {
+    pub fn new(kind: FromJsonErrorKind) -> Self {
+        Self {
+            pointer: JsonPointer::root(),
+            kind,
+        }
+    }
+
+    /// An error for `value` not being of the `expected` type
+    pub fn wrong_type(expected: &'static str, value: &Value) -> Self {
+        Self::new(FromJsonErrorKind::WrongType {
+            expected,
+            found: value.type_name(),
+        })
+    }
+
+    pub fn custom(message: impl Into<String>) -> Self {
+        Self::new(FromJsonErrorKind::Custom(message.into()))
+    }
+
+    /// Moves the error one level down, under the object key or array index `token`
+    pub fn within(self, token: impl Into<String>) -> Self {
+        let mut pointer = JsonPointer::root().join(token);
+        for token in self.pointer.tokens() {
+            pointer.push(token.clone());
+        }
+        Self {
+            pointer,
+            kind: self.kind,
+        }
+    }
+}
+
+impl fmt::Display for FromJsonErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongType { expected, found } => write!(f, "expected {expected}, found {found}"),
+            Self::MissingField(key) => write!(f, "missing field `{key}`"),
+            Self::NumberOutOfRange { target } => write!(f, "number out of range for {target}"),
+            Self::Custom(message) => f.write_str(message),
+        }
+    }
+}
+
+impl fmt::Display for FromJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.pointer.is_root() {
+            write!(f, "{}", self.kind)
+        } else {
+            write!(f, "{} at `{}`", self.kind, self.pointer)
+        }
+    }
+}
+
+impl std::error::Error for FromJsonError {}
+
+/// Extracts the required field `key` from an object
+///
+/// Errors inside the field are reported relative to the object, ex. `/address/zip`.
+pub fn field<T: FromJson>(object: &Value, key: &str) -> Result<T, FromJsonError> {
+    match optional_field(object, key)? {
+        Some(value) => Ok(value),
+        None => Err(FromJsonError::new(FromJsonErrorKind::MissingField(
+            String::from(key),
+        ))),
+    }
+}
+
+/// Extracts the field `key` from an object, a missing or `null` field is `None`
+pub fn optional_field<T: FromJson>(object: &Value, key: &str) -> Result<Option<T>, FromJsonError> {
+    let Value::Object(map) = object else {
+        return Err(FromJsonError::wrong_type("object", object));
+    };
+    match map.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => T::from_json(value).map(Some).map_err(|err| err.within(key)),
+    }
+}
+
+impl Value {
+    /// Converts this value into `T`, see [`FromJson`]
+    pub fn extract<T: FromJson>(&self) -> Result<T, FromJsonError> {
+        T::from_json(self)
+    }
+
+    /// Converts the value at a dot path into `T`, ex. `value.extract_path::<u32>("user.age")`
+    ///
+    /// Errors carry their location along the path, so a missing `age` is reported at
+    /// `/user`. A missing value converts like `null`, which lets `Option` fields be absent.
+    pub fn extract_path<T: FromJson>(&self, path: &str) -> Result<T, FromJsonError> {
+        let tokens = parse_dot_path(path)
+            .ok_or_else(|| FromJsonError::custom(format!("invalid path `{path}`")))?;
+        let mut value = self;
+        let mut pointer = Vec::new();
+        for token in &tokens {
+            let next = match (value, token) {
+                (Value::Object(map), PathToken::Key(key)) => Ok(map.get(key)),
+                (Value::Array(array), PathToken::Index(index)) => Ok(array.get(*index)),
+                (_, PathToken::Key(_)) => Err("object"),
+                (_, PathToken::Index(_)) => Err("array"),
+            }
+            .map_err(|expected| within(&pointer, FromJsonError::wrong_type(expected, value)))?;
+            let token = match token {
+                PathToken::Key(key) => key.clone(),
+                PathToken::Index(index) => index.to_string(),
+            };
+            let Some(next) = next else {
+                let missing = FromJsonError::new(FromJsonErrorKind::MissingField(token));
+                return T::from_json(&Value::Null).map_err(|_| within(&pointer, missing));
+            };
+            value = next;
+            pointer.push(token);
+        }
+        T::from_json(value).map_err(|err| within(&pointer, err))
+    }
+}
+
+/// Moves `err` under every token of `pointer`
+fn within(pointer: &[String], err: FromJsonError) -> FromJsonError {
+    pointer
+        .iter()
+        .rev()
+        .fold(err, |err, token| err.within(token.clone()))
+}
+
+/// Extracts several values by dot path into a struct with one field per path, ex.
+/// `extract!(&value, name: String = "user.name", age: u32 = "user.age")?.age`
+///
+/// Each field is converted with [`FromJson`] and the first error is returned, naming
+/// where along its path the value was missing or had the wrong type.
+#[macro_export]
+macro_rules! extract {
+    ($value:expr, $($field:ident : $ty:ty = $path:literal),+ $(,)?) => {{
+        #[derive(Debug, Clone, PartialEq)]
+        struct Extracted {
+            $($field: $ty),+
+        }
+
+        fn extract(value: &$crate::Value) -> Result<Extracted, $crate::FromJsonError> {
+            Ok(Extracted {
+                $($field: value.extract_path::<$ty>($path)?),+
+            })
+        }
+
+        extract($value)
+    }};
+}
+
+impl FromJson for Value {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        Ok(value.clone())
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        value
+            .as_bool()
+            .ok_or_else(|| FromJsonError::wrong_type("boolean", value))
+    }
+}
+
+impl FromJson for String {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        value
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| FromJsonError::wrong_type("string", value))
+    }
+}
+
+impl FromJson for f64 {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        value
+            .as_f64()
+            .ok_or_else(|| FromJsonError::wrong_type("number", value))
+    }
+}
+
+impl FromJson for f32 {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        f64::from_json(value).map(|n| n as f32)
+    }
+}
+
+macro_rules! integer_from_json {
+    ($($ty:ty),*) => {
+        $(impl FromJson for $ty {
+            fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+                let n = f64::from_json(value)?;
+                if n.fract() != 0.0 {
+                    return Err(FromJsonError::wrong_type("integer", value));
+                }
+                // `MAX + 1` is a power of two, so unlike `MAX` it converts to `f64` exactly
+                if n < <$ty>::MIN as f64 || n >= <$ty>::MAX as f64 + 1.0 {
+                    let target = stringify!($ty);
+                    return Err(FromJsonError::new(FromJsonErrorKind::NumberOutOfRange { target }));
+                }
+                Ok(n as $ty)
+            }
+        })*
+    };
+}
+
+integer_from_json!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        match value {
+            Value::Null => Ok(None),
+            value => T::from_json(value).map(Some),
+        }
+    }
+}
+
+impl<T: FromJson> FromJson for Box<T> {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        T::from_json(value).map(Box::new)
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        let array = value
+            .as_array()
+            .ok_or_else(|| FromJsonError::wrong_type("array", value))?;
+        array
+            .iter()
+            .enumerate()
+            .map(|(i, item)| T::from_json(item).map_err(|err| err.within(i.to_string())))
+            .collect()
+    }
+}
+
+/// Any hasher can be used, ex. a faster non-keyed one for trusted input
+impl<T: FromJson, S: BuildHasher + Default> FromJson for HashMap<String, T, S> {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        let map = value
+            .as_object()
+            .ok_or_else(|| FromJsonError::wrong_type("object", value))?;
+        map.iter()
+            .map(|(key, item)| match T::from_json(item) {
+                Ok(item) => Ok((key.clone(), item)),
+                Err(err) => Err(err.within(key)),
+            })
+            .collect()
+    }
+}
+
+impl<T: FromJson> FromJson for BTreeMap<String, T> {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        HashMap::<String, T>::from_json(value).map(|map| map.into_iter().collect())
+    }
+}
+
+impl ToJson for Value {
+    fn to_json(&self) -> Value {
+        self.clone()
+    }
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> Value {
+        Value::Boolean(*self)
+    }
+}
+
+impl ToJson for str {
+    fn to_json(&self) -> Value {
+        Value::String(String::from(self))
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> Value {
+        Value::String(self.clone())
+    }
+}
+
+macro_rules! number_to_json {
+    ($($ty:ty),*) => {
+        $(impl ToJson for $ty {
+            fn to_json(&self) -> Value {
+                Value::Number(*self as f64)
+            }
+        })*
+    };
+}
+
+number_to_json!(f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> Value {
+        (**self).to_json()
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for Box<T> {
+    fn to_json(&self) -> Value {
+        (**self).to_json()
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> Value {
+        self.as_ref().map_or(Value::Null, ToJson::to_json)
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> Value {
+        Value::Array(self.iter().map(ToJson::to_json).collect())
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> Value {
+        self.as_slice().to_json()
+    }
+}
+
+impl<T: ToJson, S: BuildHasher> ToJson for HashMap<String, T, S> {
+    fn to_json(&self) -> Value {
+        let map = self.iter().map(|(k, v)| (k.clone(), v.to_json()));
+        Value::Object(map.collect())
+    }
+}
+
+impl<T: ToJson> ToJson for BTreeMap<String, T> {
+    fn to_json(&self) -> Value {
+        let map = self.iter().map(|(k, v)| (k.clone(), v.to_json()));
+        Value::Object(map.collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::hash::BuildHasherDefault;
+
+    use crate::hash::FnvHasher;
+    use crate::{JsonPointer, Parser, Value};
+
+    use super::{FromJson, FromJsonError, FromJsonErrorKind, ToJson, field, optional_field};
+
+    #[derive(Debug, PartialEq)]
+    struct Address {
+        city: String,
+        zip: u32,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct User {
+        name: String,
+        nickname: Option<String>,
+        addresses: Vec<Address>,
+    }
+
+    impl FromJson for Address {
+        fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+            Ok(Self {
+                city: field(value, "city")?,
+                zip: field(value, "zip")?,
+            })
+        }
+    }
+
+    impl FromJson for User {
+        fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+            Ok(Self {
+                name: field(value, "name")?,
+                nickname: optional_field(value, "nickname")?,
+                addresses: field(value, "addresses")?,
+            })
+        }
+    }
+
+    impl ToJson for Address {
+        fn to_json(&self) -> Value {
+            Value::object([("city", self.city.to_json()), ("zip", self.zip.to_json())])
+        }
+    }
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    fn error_at(input: &str) -> (String, FromJsonErrorKind) {
+        let err = value(input).extract::<User>().unwrap_err();
+        (err.pointer.to_string(), err.kind)
+    }
+
+    #[test]
+    fn extracts_nested_struct() {
+        let user: User = value(r#"{"name": "ken", "addresses": [{"city": "Oslo", "zip": 150}]}"#)
+            .extract()
+            .unwrap();
+        let expected = User {
+            name: String::from("ken"),
+            nickname: None,
+            addresses: vec![Address {
+                city: String::from("Oslo"),
+                zip: 150,
+            }],
+        };
+        assert_eq!(user, expected);
+    }
+
+    #[test]
+    fn errors_carry_the_path() {
+        let (pointer, kind) =
+            error_at(r#"{"name": "ken", "addresses": [{"city": "Oslo", "zip": "x"}]}"#);
+        assert_eq!(pointer, "/addresses/0/zip");
+        let expected = FromJsonErrorKind::WrongType {
+            expected: "number",
+            found: "string",
+        };
+        assert_eq!(kind, expected);
+    }
+
+    #[test]
+    fn missing_field() {
+        let (pointer, kind) = error_at(r#"{"name": "ken", "addresses": [{"city": "Oslo"}]}"#);
+        assert_eq!(pointer, "/addresses/0");
+        assert_eq!(kind, FromJsonErrorKind::MissingField(String::from("zip")));
+    }
+
+    #[test]
+    fn extract_path() {
+        let document = value(r#"{"user": {"name": "ken", "tags": ["a", "b"]}}"#);
+        assert_eq!(
+            document.extract_path::<String>("user.tags[1]"),
+            Ok(String::from("b"))
+        );
+        assert_eq!(document.extract_path::<Option<u32>>("user.age"), Ok(None));
+
+        let err = document.extract_path::<u32>("user.age").unwrap_err();
+        assert_eq!(err.to_string(), "missing field `age` at `/user`");
+        let err = document.extract_path::<u32>("user.name").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "expected number, found string at `/user/name`"
+        );
+        let err = document.extract_path::<u32>("user.name.first").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "expected object, found string at `/user/name`"
+        );
+        assert!(document.extract_path::<u32>("user..name").is_err());
+    }
+
+    #[test]
+    fn extract_macro() {
+        let document = value(r#"{"user": {"name": "ken", "age": 40}, "tags": []}"#);
+        let user = crate::extract!(
+            &document,
+            name: String = "user.name",
+            age: u32 = "user.age",
+            tags: Vec<String> = "tags",
+        )
+        .unwrap();
+        assert_eq!(
+            (user.name.as_str(), user.age, user.tags.len()),
+            ("ken", 40, 0)
+        );
+
+        let err = crate::extract!(&document, name: String = "user.name", id: u64 = "user.id")
+            .unwrap_err();
+        assert_eq!(err.pointer.to_string(), "/user");
+        assert_eq!(
+            err.kind,
+            FromJsonErrorKind::MissingField(String::from("id"))
+        );
+    }
+
+    #[test]
+    fn integer_range() {
+        let err = u8::from_json(&Value::Number(256.0)).unwrap_err();
+        assert_eq!(
+            err.kind,
+            FromJsonErrorKind::NumberOutOfRange { target: "u8" }
+        );
+        assert!(i32::from_json(&Value::Number(1.5)).is_err());
+        assert_eq!(i64::from_json(&Value::Number(-3.0)), Ok(-3));
+
+        let two_to_63 = 9_223_372_036_854_775_808.0;
+        let two_to_64 = 18_446_744_073_709_551_616.0;
+        assert!(i64::from_json(&Value::Number(two_to_63)).is_err());
+        assert_eq!(i64::from_json(&Value::Number(-two_to_63)), Ok(i64::MIN));
+        assert!(u64::from_json(&Value::Number(two_to_64)).is_err());
+        assert_eq!(u64::from_json(&Value::Number(two_to_63)), Ok(1 << 63));
+        assert!(i32::from_json(&Value::Number(2_147_483_648.0)).is_err());
+        assert_eq!(
+            i32::from_json(&Value::Number(2_147_483_647.0)),
+            Ok(i32::MAX)
+        );
+        assert!(u32::from_json(&Value::Number(4_294_967_296.0)).is_err());
+        assert!(u8::from_json(&Value::Number(-1.0)).is_err());
+    }
+
+    #[test]
+    fn display_includes_pointer() {
+        let err = FromJsonError::custom("bad").within("a").within("b");
+        assert_eq!(err.pointer, JsonPointer::parse("/b/a").unwrap());
+        assert_eq!(err.to_string(), "bad at `/b/a`");
+    }
+
+    #[test]
+    fn to_json_collections() {
+        let addresses = vec![Address {
+            city: String::from("Oslo"),
+            zip: 150,
+        }];
+        let map = HashMap::from([(String::from("home"), addresses)]);
+        assert_eq!(
+            map.to_json(),
+            value(r#"{"home": [{"city": "Oslo", "zip": 150}]}"#)
+        );
+        assert_eq!(None::<u8>.to_json(), Value::Null);
+    }
+
+    #[test]
+    fn maps_with_other_hashers() {
+        type FnvMap<T> = HashMap<String, T, BuildHasherDefault<FnvHasher>>;
+
+        let document = value(r#"{"a": 1, "b": 2}"#);
+        let map = FnvMap::<u8>::from_json(&document).unwrap();
+        assert_eq!((map["a"], map["b"]), (1, 2));
+        assert_eq!(map.to_json(), document);
+    }
+}
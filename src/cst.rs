@@ -0,0 +1,625 @@
+//! Lossless parsing that keeps whitespace, comments, key order and number text
+//!
+//! A [`Document`] displays as exactly the text it was parsed from, so a program can
+//! change one part of a file and write it back without disturbing the rest.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{Event, JsonReader, Value};
+
+mod comments;
+mod edit;
+
+pub use comments::{Comments, NodeComments, parse_jsonc_with_comments};
+pub use edit::EditError;
+
+/// A parsed JSON document, including the whitespace and comments around its root
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub(crate) before: String,
+    pub(crate) root: Node,
+    pub(crate) after: String,
+}
+
+/// A value in the syntax tree
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Null,
+    Boolean(bool),
+
+    /// The number exactly as written, ex. `1.50` or `1e3`
+    Number(String),
+
+    String(JsonString),
+    Array(Array),
+    Object(Object),
+}
+
+/// A string along with its source text, escapes included
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonString {
+    pub(crate) raw: String,
+    pub(crate) value: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Array {
+    pub(crate) elements: Vec<Element>,
+    pub(crate) trailing_comma: bool,
+
+    /// Whitespace and comments before `]` that do not follow an element
+    pub(crate) end: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Element {
+    pub(crate) before: String,
+    pub(crate) value: Node,
+    pub(crate) after: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Object {
+    pub(crate) members: Vec<Member>,
+    pub(crate) trailing_comma: bool,
+
+    /// Whitespace and comments before `}` that do not follow a member
+    pub(crate) end: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Member {
+    pub(crate) before: String,
+    pub(crate) key: JsonString,
+    pub(crate) before_colon: String,
+    pub(crate) after_colon: String,
+    pub(crate) value: Node,
+    pub(crate) after: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CstError {
+    /// The input ended inside of a value
+    UnexpectedEof,
+
+    /// A character that is not allowed at this position
+    UnexpectedChar { offset: usize, found: char },
+
+    /// A number does not follow the JSON grammar, ex. `01` or `1.`
+    InvalidNumber { offset: usize },
+
+    /// A string contains an invalid escape or control character
+    InvalidString { offset: usize },
+
+    /// A `/* */` comment is never closed
+    UnterminatedComment { offset: usize },
+
+    /// There is more than whitespace and comments after the root value
+    TrailingContent { offset: usize },
+
+    /// Arrays and objects are nested deeper than [`crate::DEFAULT_MAX_DEPTH`]
+    TooDeep { offset: usize },
+}
+
+impl fmt::Display for CstError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEof => f.write_str("unexpected end of input"),
+            Self::UnexpectedChar { offset, found } => {
+                write!(f, "unexpected character {found:?} at offset {offset}")
+            }
+            Self::InvalidNumber { offset } => write!(f, "invalid number at offset {offset}"),
+            Self::InvalidString { offset } => write!(f, "invalid string at offset {offset}"),
+            Self::UnterminatedComment { offset } => {
+                write!(f, "unterminated comment at offset {offset}")
+            }
+            Self::TrailingContent { offset } => {
+                write!(f, "unexpected content after the value at offset {offset}")
+            }
+            Self::TooDeep { offset } => write!(
+                f,
+                "nested deeper than {} levels at offset {offset}",
+                crate::DEFAULT_MAX_DEPTH
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CstError {}
+
+impl Document {
+    /// Parses a document, allowing `//` and `/* */` comments and trailing commas
+    pub fn parse(input: &str) -> Result<Self, CstError> {
+        let mut parser = Parser {
+            input,
+            pos: 0,
+            depth: 0,
+        };
+        let before = parser.trivia()?;
+        let root = parser.value()?;
+        let after = parser.trivia()?;
+        if parser.pos < input.len() {
+            return Err(CstError::TrailingContent { offset: parser.pos });
+        }
+        Ok(Self {
+            before,
+            root,
+            after,
+        })
+    }
+
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    /// The document as a [`Value`], dropping comments and formatting
+    pub fn to_value(&self) -> Value {
+        self.root.to_value()
+    }
+}
+
+impl Node {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Node::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Node::String(s) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Node::Number(raw) => raw.parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Array> {
+        match self {
+            Node::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&Object> {
+        match self {
+            Node::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    /// The node as a [`Value`], dropping comments and formatting
+    ///
+    /// When an object repeats a key, the last member wins.
+    pub fn to_value(&self) -> Value {
+        match self {
+            Node::Null => Value::Null,
+            Node::Boolean(b) => Value::Boolean(*b),
+            Node::Number(raw) => Value::Number(raw.parse().unwrap_or(f64::NAN)),
+            Node::String(s) => Value::String(s.value.clone()),
+            Node::Array(array) => Value::Array(array.iter().map(Node::to_value).collect()),
+            Node::Object(object) => {
+                let map: HashMap<_, _> = object
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_value()))
+                    .collect();
+                Value::Object(map)
+            }
+        }
+    }
+}
+
+impl JsonString {
+    /// The string with its escapes decoded
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    /// The source text, including quotes
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+}
+
+impl Array {
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Node> {
+        self.elements.get(index).map(|element| &element.value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Node> {
+        self.elements.iter().map(|element| &element.value)
+    }
+}
+
+impl Object {
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// The value of the last member named `key`
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        self.members
+            .iter()
+            .rev()
+            .find(|member| member.key.value == key)
+            .map(|member| &member.value)
+    }
+
+    /// The members in source order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Node)> {
+        self.members
+            .iter()
+            .map(|member| (member.key.as_str(), &member.value))
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}", self.before, self.root, self.after)
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Node::Null => f.write_str("null"),
+            Node::Boolean(b) => write!(f, "{b}"),
+            Node::Number(raw) => f.write_str(raw),
+            Node::String(s) => f.write_str(&s.raw),
+            Node::Array(array) => {
+                f.write_str("[")?;
+                for (i, element) in array.elements.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}{}{}", element.before, element.value, element.after)?;
+                }
+                if array.trailing_comma {
+                    f.write_str(",")?;
+                }
+                write!(f, "{}]", array.end)
+            }
+            Node::Object(object) => {
+                f.write_str("{")?;
+                for (i, member) in object.members.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(
+                        f,
+                        "{}{}{}:{}{}{}",
+                        member.before,
+                        member.key.raw,
+                        member.before_colon,
+                        member.after_colon,
+                        member.value,
+                        member.after
+                    )?;
+                }
+                if object.trailing_comma {
+                    f.write_str(",")?;
+                }
+                write!(f, "{}}}", object.end)
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+
+    /// How many arrays and objects enclose the current position
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn unexpected(&self) -> CstError {
+        match self.peek() {
+            Some(found) => CstError::UnexpectedChar {
+                offset: self.pos,
+                found,
+            },
+            None => CstError::UnexpectedEof,
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), CstError> {
+        if self.peek() != Some(expected) {
+            return Err(self.unexpected());
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Reads whitespace and comments
+    fn trivia(&mut self) -> Result<String, CstError> {
+        let start = self.pos;
+        loop {
+            let rest = &self.input[self.pos..];
+            if rest.starts_with([' ', '\t', '\n', '\r']) {
+                self.pos += 1;
+            } else if rest.starts_with("//") {
+                self.pos += rest.find('\n').unwrap_or(rest.len());
+            } else if let Some(comment) = rest.strip_prefix("/*") {
+                let end = comment
+                    .find("*/")
+                    .ok_or(CstError::UnterminatedComment { offset: self.pos })?;
+                self.pos += end + 4;
+            } else {
+                return Ok(self.input[start..self.pos].to_string());
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Node, CstError> {
+        let rest = &self.input[self.pos..];
+        let literals = [
+            ("null", Node::Null),
+            ("true", Node::Boolean(true)),
+            ("false", Node::Boolean(false)),
+        ];
+        for (text, node) in literals {
+            if rest.starts_with(text) {
+                self.pos += text.len();
+                return Ok(node);
+            }
+        }
+        match self.peek() {
+            Some('[') => self.nested(Self::array).map(Node::Array),
+            Some('{') => self.nested(Self::object).map(Node::Object),
+            Some('"') => self.string().map(Node::String),
+            Some('-' | '0'..='9') => self.number(),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    /// Reads a container one level deeper, the builder recurses so the depth is bounded
+    fn nested<T>(&mut self, read: fn(&mut Self) -> Result<T, CstError>) -> Result<T, CstError> {
+        if self.depth == crate::DEFAULT_MAX_DEPTH {
+            return Err(CstError::TooDeep { offset: self.pos });
+        }
+        self.depth += 1;
+        let result = read(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn number(&mut self) -> Result<Node, CstError> {
+        let start = self.pos;
+        let rest = &self.input[start..];
+        let len = rest
+            .find(|ch: char| !matches!(ch, '0'..='9' | '-' | '+' | '.' | 'e' | 'E'))
+            .unwrap_or(rest.len());
+        let raw = &rest[..len];
+        // the streaming reader checks the grammar, it must see exactly one number
+        let mut reader = JsonReader::new(raw.as_bytes());
+        match (reader.next_event(), reader.next_event()) {
+            (Ok(Some((Event::Number(_), _))), Ok(None)) => {}
+            _ => return Err(CstError::InvalidNumber { offset: start }),
+        }
+        self.pos += len;
+        Ok(Node::Number(raw.to_string()))
+    }
+
+    fn string(&mut self) -> Result<JsonString, CstError> {
+        let start = self.pos;
+        let mut chars = self.input[start + 1..].char_indices();
+        let len = loop {
+            match chars.next() {
+                Some((i, '"')) => break i + 2,
+                Some((_, '\\')) => {
+                    chars.next();
+                }
+                Some(_) => {}
+                None => return Err(CstError::UnexpectedEof),
+            }
+        };
+        let raw = &self.input[start..start + len];
+        let value = match JsonReader::new(raw.as_bytes()).next_event() {
+            Ok(Some((Event::String(value), _))) => value,
+            _ => return Err(CstError::InvalidString { offset: start }),
+        };
+        self.pos += len;
+        Ok(JsonString {
+            raw: raw.to_string(),
+            value,
+        })
+    }
+
+    fn array(&mut self) -> Result<Array, CstError> {
+        self.expect('[')?;
+        let mut elements = Vec::new();
+        loop {
+            let before = self.trivia()?;
+            if self.peek() == Some(']') {
+                self.pos += 1;
+                let trailing_comma = !elements.is_empty();
+                return Ok(Array {
+                    elements,
+                    trailing_comma,
+                    end: before,
+                });
+            }
+            let value = self.value()?;
+            let after = self.trivia()?;
+            elements.push(Element {
+                before,
+                value,
+                after,
+            });
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(Array {
+                        elements,
+                        trailing_comma: false,
+                        end: String::new(),
+                    });
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Object, CstError> {
+        self.expect('{')?;
+        let mut members = Vec::new();
+        loop {
+            let before = self.trivia()?;
+            if self.peek() == Some('}') {
+                self.pos += 1;
+                let trailing_comma = !members.is_empty();
+                return Ok(Object {
+                    members,
+                    trailing_comma,
+                    end: before,
+                });
+            }
+            if self.peek() != Some('"') {
+                return Err(self.unexpected());
+            }
+            let key = self.string()?;
+            let before_colon = self.trivia()?;
+            self.expect(':')?;
+            let after_colon = self.trivia()?;
+            let value = self.value()?;
+            let after = self.trivia()?;
+            members.push(Member {
+                before,
+                key,
+                before_colon,
+                after_colon,
+                value,
+                after,
+            });
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(Object {
+                        members,
+                        trailing_comma: false,
+                        end: String::new(),
+                    });
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::{CstError, Document, Node};
+
+    const CONFIG: &str = r#"// server settings
+{
+  "server": {
+    "host" : "localhost", /* default */
+    "port": 8080,
+  },
+  "ratio": 1.50,
+  "limit": -1e3,
+  "tags": [ "ab", "c" ],
+  "empty": [ /* none */ ]
+}
+"#;
+
+    #[test]
+    fn round_trips_byte_for_byte() {
+        let document = Document::parse(CONFIG).unwrap();
+        assert_eq!(document.to_string(), CONFIG);
+
+        for input in ["null", " [ ] ", "{}", "[1,2 ,3]", "\"\\\"\""] {
+            assert_eq!(Document::parse(input).unwrap().to_string(), input);
+        }
+    }
+
+    #[test]
+    fn keeps_key_order_and_number_text() {
+        let document = Document::parse(CONFIG).unwrap();
+        let root = document.root().as_object().unwrap();
+        let keys: Vec<_> = root.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["server", "ratio", "limit", "tags", "empty"]);
+        assert_eq!(root.get("ratio"), Some(&Node::Number(String::from("1.50"))));
+        assert_eq!(root.get("limit").and_then(Node::as_f64), Some(-1000.0));
+
+        let tags = root.get("tags").and_then(Node::as_array).unwrap();
+        assert_eq!(tags.get(0).and_then(Node::as_str), Some("ab"));
+        assert_eq!(tags.len(), 2);
+    }
+
+    #[test]
+    fn converts_to_value() {
+        let input = r#"{"a": [1, "x", null, true], "b": {"c": 2.5}}"#;
+        let document = Document::parse(input).unwrap();
+        assert_eq!(document.to_value(), Parser::new().parse_str(input).unwrap());
+        assert_eq!(
+            Document::parse("[-2]").unwrap().to_value(),
+            Value::Array(vec![Value::Number(-2.0)])
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Document::parse("[1,"), Err(CstError::UnexpectedEof));
+        assert_eq!(
+            Document::parse("[01]"),
+            Err(CstError::InvalidNumber { offset: 1 })
+        );
+        assert_eq!(
+            Document::parse(r#"["\x"]"#),
+            Err(CstError::InvalidString { offset: 1 })
+        );
+        assert_eq!(
+            Document::parse("{a: 1}"),
+            Err(CstError::UnexpectedChar {
+                offset: 1,
+                found: 'a'
+            })
+        );
+        assert_eq!(
+            Document::parse("1 /* x"),
+            Err(CstError::UnterminatedComment { offset: 2 })
+        );
+        assert_eq!(
+            Document::parse("1 2"),
+            Err(CstError::TrailingContent { offset: 2 })
+        );
+        let deep = "[".repeat(200_000) + &"]".repeat(200_000);
+        assert_eq!(
+            Document::parse(&deep),
+            Err(CstError::TooDeep { offset: 128 })
+        );
+        let limit = crate::DEFAULT_MAX_DEPTH;
+        let nested = "[".repeat(limit) + &"]".repeat(limit);
+        assert_eq!(Document::parse(&nested).unwrap().to_string(), nested);
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use super::{CstError, Document, Node};
+use crate::{JsonPointer, Value};
+
+/// Comments attached to the values of a document, looked up by JSON Pointer
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Comments {
+    nodes: HashMap<JsonPointer, NodeComments>,
+}
+
+/// The comments around a single value, with their `//` or `/* */` markers and
+/// surrounding whitespace removed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeComments {
+    /// Comments on the lines before the value, and between a key and its value
+    pub leading: Vec<String>,
+
+    /// Comments after the value, on its line or before the closing bracket
+    pub trailing: Vec<String>,
+}
+
+impl Comments {
+    /// The comments attached to the value at `pointer`, ex. `/server/port`
+    pub fn get(&self, pointer: &str) -> Option<&NodeComments> {
+        let pointer = JsonPointer::parse(pointer).ok()?;
+        self.nodes.get(&pointer)
+    }
+
+    /// Every value with comments, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&JsonPointer, &NodeComments)> {
+        self.nodes.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    fn leading(&mut self, pointer: &JsonPointer, trivia: &str) {
+        let texts = comment_texts(trivia);
+        if !texts.is_empty() {
+            let node = self.nodes.entry(pointer.clone()).or_default();
+            node.leading.extend(texts);
+        }
+    }
+
+    fn trailing(&mut self, pointer: &JsonPointer, trivia: &str) {
+        let texts = comment_texts(trivia);
+        if !texts.is_empty() {
+            let node = self.nodes.entry(pointer.clone()).or_default();
+            node.trailing.extend(texts);
+        }
+    }
+}
+
+impl Document {
+    /// Attaches each comment in the document to its nearest value
+    ///
+    /// A comment on the same line after a value (or its comma) trails that value. Other
+    /// comments lead the value that follows them, or trail the last value of a
+    /// container when nothing follows.
+    pub fn comments(&self) -> Comments {
+        let mut comments = Comments::default();
+        let root = JsonPointer::root();
+        comments.leading(&root, &self.before);
+        collect(&self.root, &root, &mut comments);
+        comments.trailing(&root, &self.after);
+        comments
+    }
+}
+
+/// Parses JSON with comments and trailing commas, returning its comments alongside
+pub fn parse_jsonc_with_comments(input: &str) -> Result<(Value, Comments), CstError> {
+    let document = Document::parse(input)?;
+    Ok((document.to_value(), document.comments()))
+}
+
+fn collect(node: &Node, pointer: &JsonPointer, comments: &mut Comments) {
+    let (entries, trailing_comma, end): (Vec<_>, _, _) = match node {
+        Node::Array(array) => (
+            array
+                .elements
+                .iter()
+                .enumerate()
+                .map(|(i, element)| {
+                    let child = pointer.join(i.to_string());
+                    (child, &element.before, &element.value, &element.after)
+                })
+                .collect(),
+            array.trailing_comma,
+            &array.end,
+        ),
+        Node::Object(object) => (
+            object
+                .members
+                .iter()
+                .map(|member| {
+                    let child = pointer.join(member.key.as_str());
+                    comments.leading(&child, &member.before_colon);
+                    comments.leading(&child, &member.after_colon);
+                    (child, &member.before, &member.value, &member.after)
+                })
+                .collect(),
+            object.trailing_comma,
+            &object.end,
+        ),
+        _ => return,
+    };
+
+    let mut previous: Option<&JsonPointer> = None;
+    for (child, before, value, after) in &entries {
+        match previous {
+            Some(previous) => {
+                let (line, rest) = split_line(before);
+                comments.trailing(previous, line);
+                comments.leading(child, rest);
+            }
+            None => comments.leading(child, before),
+        }
+        collect(value, child, comments);
+        comments.trailing(child, after);
+        previous = Some(child);
+    }
+
+    // comments before the closing bracket go to the last value, or the container itself
+    match previous {
+        Some(last) if trailing_comma => comments.trailing(last, end),
+        _ => comments.trailing(pointer, end),
+    }
+}
+
+fn split_line(trivia: &str) -> (&str, &str) {
+    match trivia.find('\n') {
+        Some(newline) => trivia.split_at(newline),
+        None => (trivia, ""),
+    }
+}
+
+/// The text of each comment in whitespace and comments that have already been parsed
+fn comment_texts(mut trivia: &str) -> Vec<String> {
+    let mut texts = Vec::new();
+    loop {
+        trivia = trivia.trim_start();
+        if let Some(rest) = trivia.strip_prefix("//") {
+            let end = rest.find('\n').unwrap_or(rest.len());
+            texts.push(rest[..end].trim().to_string());
+            trivia = &rest[end..];
+        } else if let Some(rest) = trivia.strip_prefix("/*") {
+            let end = rest.find("*/").unwrap_or(rest.len());
+            texts.push(rest[..end].trim().to_string());
+            trivia = rest.get(end + 2..).unwrap_or("");
+        } else {
+            return texts;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Value;
+
+    use super::{NodeComments, parse_jsonc_with_comments};
+
+    fn comments(leading: &[&str], trailing: &[&str]) -> NodeComments {
+        NodeComments {
+            leading: leading.iter().map(|text| text.to_string()).collect(),
+            trailing: trailing.iter().map(|text| text.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn attaches_to_nearest_value() {
+        let input = r#"// settings
+{
+  /* where to listen */
+  "host": "localhost", // default
+  "port": /* http */ 8080,
+  "tags": [
+    "a", // first
+    "b"
+    // none after b
+  ],
+  "empty": [ /* nothing */ ],
+}
+"#;
+        let (value, found) = parse_jsonc_with_comments(input).unwrap();
+        assert_eq!(value.pointer("/port"), Some(&Value::Number(8080.0)));
+
+        assert_eq!(found.get(""), Some(&comments(&["settings"], &[])));
+        assert_eq!(
+            found.get("/host"),
+            Some(&comments(&["where to listen"], &["default"]))
+        );
+        assert_eq!(found.get("/port"), Some(&comments(&["http"], &[])));
+        assert_eq!(found.get("/tags/0"), Some(&comments(&[], &["first"])));
+        assert_eq!(
+            found.get("/tags/1"),
+            Some(&comments(&[], &["none after b"]))
+        );
+        assert_eq!(found.get("/empty"), Some(&comments(&[], &["nothing"])));
+        assert_eq!(found.len(), 6);
+    }
+
+    #[test]
+    fn plain_json_has_no_comments() {
+        let (_, found) = parse_jsonc_with_comments(r#"{"a": [1, 2]}"#).unwrap();
+        assert!(found.is_empty());
+    }
+}
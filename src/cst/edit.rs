@@ -0,0 +1,601 @@
+use std::collections::HashMap;
+use std::{fmt, mem};
+
+use super::{Array, Document, Element, JsonString, Member, Node, Object};
+use crate::path::{PathToken, parse_dot_path};
+use crate::serialize::write_string;
+use crate::{PrettyOptions, ToJson, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditError {
+    /// The path is not a valid dot path
+    InvalidPath(String),
+
+    /// Nothing exists at the path
+    NotFound(String),
+
+    /// The path steps into a value that is not an object or array, or uses a key on an
+    /// array or an index on an object
+    NotAContainer(String),
+
+    /// An index is past the end of the array
+    InvalidIndex(String),
+
+    /// Inserting would replace an existing member
+    KeyExists(String),
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPath(path) => write!(f, "`{path}` is not a valid dot path"),
+            Self::NotFound(path) => write!(f, "nothing exists at `{path}`"),
+            Self::NotAContainer(path) => write!(f, "`{path}` steps into the wrong kind of value"),
+            Self::InvalidIndex(path) => write!(f, "the index in `{path}` is out of bounds"),
+            Self::KeyExists(path) => write!(f, "a member already exists at `{path}`"),
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+impl Document {
+    /// Looks up a node by a dot path, ex. `server.port` or `users[0].name`
+    ///
+    /// The empty path is the root.
+    pub fn get(&self, path: &str) -> Option<&Node> {
+        parse_dot_path(path)?
+            .iter()
+            .try_fold(&self.root, |node, token| match (node, token) {
+                (Node::Object(object), PathToken::Key(key)) => object.get(key),
+                (Node::Array(array), PathToken::Index(index)) => array.get(*index),
+                _ => None,
+            })
+    }
+
+    /// Sets the value at a dot path, leaving the rest of the text untouched
+    ///
+    /// A missing member is added after the last one, along with any missing objects
+    /// leading to it, and an index equal to the length appends to an array. New values
+    /// follow the indentation of their siblings.
+    pub fn set(&mut self, path: &str, value: impl ToJson) -> Result<(), EditError> {
+        let value = value.to_json();
+        let tokens =
+            parse_dot_path(path).ok_or_else(|| EditError::InvalidPath(path.to_string()))?;
+        let Some((last, parents)) = tokens.split_last() else {
+            self.root = render(&value, Some(""));
+            return Ok(());
+        };
+
+        let mut node = &mut self.root;
+        for (i, token) in parents.iter().enumerate() {
+            node = match (node, token) {
+                (Node::Object(object), PathToken::Key(key)) => match object.position(key) {
+                    Some(position) => &mut object.members[position].value,
+                    None => {
+                        let nested = missing_objects(&tokens[i + 1..], value)
+                            .ok_or_else(|| EditError::NotFound(path.to_string()))?;
+                        object.push(key, &nested);
+                        return Ok(());
+                    }
+                },
+                (Node::Array(array), PathToken::Index(index)) => {
+                    let element = array
+                        .elements
+                        .get_mut(*index)
+                        .ok_or_else(|| EditError::InvalidIndex(path.to_string()))?;
+                    &mut element.value
+                }
+                _ => return Err(EditError::NotAContainer(path.to_string())),
+            };
+        }
+
+        match (node, last) {
+            (Node::Object(object), PathToken::Key(key)) => match object.position(key) {
+                Some(position) => object.members[position].replace(&value),
+                None => object.push(key, &value),
+            },
+            (Node::Array(array), PathToken::Index(index)) => match *index {
+                index if index < array.len() => array.elements[index].replace(&value),
+                index if index == array.len() => array.push(&value),
+                _ => return Err(EditError::InvalidIndex(path.to_string())),
+            },
+            _ => return Err(EditError::NotAContainer(path.to_string())),
+        }
+        Ok(())
+    }
+
+    /// Adds a new member to an object, or inserts into an array shifting the following
+    /// elements
+    ///
+    /// Unlike [`Document::set`], the container must already exist.
+    pub fn insert(&mut self, path: &str, value: impl ToJson) -> Result<(), EditError> {
+        let value = value.to_json();
+        let tokens =
+            parse_dot_path(path).ok_or_else(|| EditError::InvalidPath(path.to_string()))?;
+        let Some((last, parents)) = tokens.split_last() else {
+            return Err(EditError::KeyExists(path.to_string()));
+        };
+
+        match (self.container_mut(parents, path)?, last) {
+            (Node::Object(object), PathToken::Key(key)) => {
+                if object.position(key).is_some() {
+                    return Err(EditError::KeyExists(path.to_string()));
+                }
+                object.push(key, &value);
+            }
+            (Node::Array(array), PathToken::Index(index)) => match *index {
+                index if index < array.len() => array.insert(index, &value),
+                index if index == array.len() => array.push(&value),
+                _ => return Err(EditError::InvalidIndex(path.to_string())),
+            },
+            _ => return Err(EditError::NotAContainer(path.to_string())),
+        }
+        Ok(())
+    }
+
+    /// Removes and returns the node at a dot path, along with the comments before it
+    ///
+    /// A comment trailing the previous entry on its line is kept.
+    pub fn remove(&mut self, path: &str) -> Result<Node, EditError> {
+        let not_found = || EditError::NotFound(path.to_string());
+        let tokens =
+            parse_dot_path(path).ok_or_else(|| EditError::InvalidPath(path.to_string()))?;
+        let (last, parents) = tokens.split_last().ok_or_else(not_found)?;
+
+        match (self.container_mut(parents, path)?, last) {
+            (Node::Object(object), PathToken::Key(key)) => {
+                let index = object.position(key).ok_or_else(not_found)?;
+                let member = remove_entry(
+                    &mut object.members,
+                    &mut object.trailing_comma,
+                    &mut object.end,
+                    index,
+                );
+                Ok(member.value)
+            }
+            (Node::Array(array), PathToken::Index(index)) => {
+                if *index >= array.len() {
+                    return Err(not_found());
+                }
+                let element = remove_entry(
+                    &mut array.elements,
+                    &mut array.trailing_comma,
+                    &mut array.end,
+                    *index,
+                );
+                Ok(element.value)
+            }
+            _ => Err(EditError::NotAContainer(path.to_string())),
+        }
+    }
+
+    fn container_mut(&mut self, tokens: &[PathToken], path: &str) -> Result<&mut Node, EditError> {
+        let not_found = || EditError::NotFound(path.to_string());
+        tokens
+            .iter()
+            .try_fold(&mut self.root, |node, token| match (node, token) {
+                (Node::Object(object), PathToken::Key(key)) => {
+                    let position = object.position(key).ok_or_else(not_found)?;
+                    Ok(&mut object.members[position].value)
+                }
+                (Node::Array(array), PathToken::Index(index)) => array
+                    .elements
+                    .get_mut(*index)
+                    .map(|element| &mut element.value)
+                    .ok_or_else(not_found),
+                _ => Err(EditError::NotAContainer(path.to_string())),
+            })
+    }
+}
+
+/// Wraps `value` in an object for each key, `None` if the tokens include an index
+fn missing_objects(tokens: &[PathToken], value: Value) -> Option<Value> {
+    tokens
+        .iter()
+        .rev()
+        .try_fold(value, |value, token| match token {
+            PathToken::Key(key) => Some(Value::Object(HashMap::from([(key.clone(), value)]))),
+            PathToken::Index(_) => None,
+        })
+}
+
+impl Object {
+    fn position(&self, key: &str) -> Option<usize> {
+        self.members
+            .iter()
+            .rposition(|member| member.key.value == key)
+    }
+
+    fn push(&mut self, key: &str, value: &Value) {
+        let mut raw = String::new();
+        write_string(key, &mut raw);
+        let after_colon = self
+            .members
+            .last()
+            .map_or(" ", |member| &member.after_colon)
+            .to_string();
+        let member = Member {
+            before: String::new(),
+            key: JsonString {
+                raw,
+                value: key.to_string(),
+            },
+            before_colon: String::new(),
+            after_colon,
+            value: render(value, next_indent(&self.members)),
+            after: String::new(),
+        };
+        append_entry(
+            &mut self.members,
+            self.trailing_comma,
+            &mut self.end,
+            member,
+        );
+    }
+}
+
+impl Array {
+    fn push(&mut self, value: &Value) {
+        let element = Element {
+            before: String::new(),
+            value: render(value, next_indent(&self.elements)),
+            after: String::new(),
+        };
+        append_entry(
+            &mut self.elements,
+            self.trailing_comma,
+            &mut self.end,
+            element,
+        );
+    }
+
+    fn insert(&mut self, index: usize, value: &Value) {
+        let indent = line_indent(&self.elements[index].before);
+        let mut element = Element {
+            before: String::new(),
+            value: render(value, indent),
+            after: String::new(),
+        };
+
+        // a new first element pushes the old one along, with the usual separator
+        let separator = self
+            .elements
+            .get(1)
+            .map_or(" ", |element| &element.before)
+            .to_string();
+
+        // the first line of the trivia may hold a comment on the previous element
+        let next_before = &mut self.elements[index].before;
+        match next_before.find('\n') {
+            Some(newline) => {
+                let indent = &next_before[next_before.rfind('\n').unwrap_or(newline)..];
+                element.before = format!("{}{indent}", &next_before[..newline]);
+                next_before.replace_range(..newline, "");
+            }
+            None => {
+                element.before = mem::replace(next_before, separator);
+                if index > 0 {
+                    next_before.clone_from(&element.before);
+                }
+            }
+        }
+        self.elements.insert(index, element);
+    }
+}
+
+/// An array element or object member, with the trivia on either side of it
+trait Entry {
+    fn trivia(&mut self) -> (&mut String, &mut String);
+    fn before(&self) -> &str;
+    fn value_mut(&mut self) -> &mut Node;
+
+    /// Replaces the value, keeping the trivia and indenting to match the entry
+    fn replace(&mut self, value: &Value) {
+        let node = render(value, line_indent(self.before()));
+        *self.value_mut() = node;
+    }
+}
+
+impl Entry for Element {
+    fn trivia(&mut self) -> (&mut String, &mut String) {
+        (&mut self.before, &mut self.after)
+    }
+
+    fn before(&self) -> &str {
+        &self.before
+    }
+
+    fn value_mut(&mut self) -> &mut Node {
+        &mut self.value
+    }
+}
+
+impl Entry for Member {
+    fn trivia(&mut self) -> (&mut String, &mut String) {
+        (&mut self.before, &mut self.after)
+    }
+
+    fn before(&self) -> &str {
+        &self.before
+    }
+
+    fn value_mut(&mut self) -> &mut Node {
+        &mut self.value
+    }
+}
+
+/// The indentation of the last line of `trivia`, if the trivia spans lines
+fn line_indent(trivia: &str) -> Option<&str> {
+    trivia.rfind('\n').map(|newline| &trivia[newline + 1..])
+}
+
+/// The indentation for an entry added after `entries`, or `None` to keep it on one line
+fn next_indent<T: Entry>(entries: &[T]) -> Option<&str> {
+    entries.last().and_then(|last| line_indent(last.before()))
+}
+
+/// Splits trivia into the part on the current line and the rest, starting at the newline
+fn split_line(trivia: &str) -> (&str, &str) {
+    match trivia.find('\n') {
+        Some(newline) => trivia.split_at(newline),
+        None => (trivia, ""),
+    }
+}
+
+/// The part of trivia on the current line, if it holds a comment
+fn line_comment(trivia: &str) -> &str {
+    let (line, _) = split_line(trivia);
+    if line.trim().is_empty() { "" } else { line }
+}
+
+fn append_entry<T: Entry>(
+    entries: &mut Vec<T>,
+    trailing_comma: bool,
+    end: &mut String,
+    mut entry: T,
+) {
+    let (before, after) = entry.trivia();
+    match entries.last_mut() {
+        None => *after = mem::take(end),
+        Some(last) => {
+            let (last_before, last_after) = last.trivia();
+            let indent = match last_before.rfind('\n') {
+                Some(newline) => &last_before[newline..],
+                None => last_before.as_str(),
+            };
+            *before = indent.to_string();
+
+            // the comma goes after the old last entry, ahead of any comment on its line
+            if !trailing_comma {
+                let last_after = mem::take(last_after);
+                match split_line(&last_after) {
+                    (_, "") => *after = last_after,
+                    (line, rest) => {
+                        before.insert_str(0, line);
+                        *after = rest.to_string();
+                    }
+                }
+            }
+        }
+    }
+    entries.push(entry);
+}
+
+/// Removes an entry along with the comments on its lines, fixing up the trivia of its
+/// neighbours so the remaining text stays formatted
+fn remove_entry<T: Entry>(
+    entries: &mut Vec<T>,
+    trailing_comma: &mut bool,
+    end: &mut String,
+    index: usize,
+) -> T {
+    let mut removed = entries.remove(index);
+    let (removed_before, removed_after) = removed.trivia();
+    let kept = line_comment(removed_before).to_string();
+
+    if let Some(next) = entries.get_mut(index) {
+        let (next_before, _) = next.trivia();
+        match next_before.find('\n') {
+            Some(newline) => next_before.replace_range(..newline, &kept),
+            None if index == 0 => *next_before = removed_before.clone(),
+            None => {}
+        }
+    } else if let Some(previous) = entries.last_mut() {
+        // keep the line break before the closing bracket, but not the removed entry's line
+        let closing = mem::take(if *trailing_comma { end } else { removed_after });
+        let closing = match split_line(&closing) {
+            (line, "") => line,
+            (_, rest) => rest,
+        };
+        let target = if *trailing_comma {
+            end
+        } else {
+            previous.trivia().1
+        };
+        target.push_str(&kept);
+        target.push_str(closing);
+    } else {
+        *trailing_comma = false;
+        end.clear();
+    }
+    removed
+}
+
+/// Builds the node for a new value, laid out on one line or indented to `indent`
+fn render(value: &Value, indent: Option<&str>) -> Node {
+    let text = match indent {
+        Some(indent) => {
+            let options = PrettyOptions {
+                max_width: 80usize.saturating_sub(indent.len()),
+                ..PrettyOptions::default()
+            };
+            let text = value.to_json_string_pretty_with(&options);
+            text.replace('\n', &format!("\n{indent}"))
+        }
+        None => {
+            let options = PrettyOptions {
+                max_width: usize::MAX,
+                ..PrettyOptions::default()
+            };
+            value.to_json_string_pretty_with(&options)
+        }
+    };
+    Document::parse(&text)
+        .expect("serialized values parse")
+        .root
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Value;
+
+    use super::super::Document;
+    use super::EditError;
+
+    const CONFIG: &str = r#"{
+  // server settings
+  "server": {
+    "host": "localhost", // default
+    "port": 8080
+  },
+  "tags": ["a", "b"]
+}
+"#;
+
+    fn edit(input: &str, f: impl FnOnce(&mut Document)) -> String {
+        let mut document = Document::parse(input).unwrap();
+        f(&mut document);
+        document.to_string()
+    }
+
+    #[test]
+    fn set_replaces_only_the_value() {
+        let output = edit(CONFIG, |document| {
+            document.set("server.port", 9090).unwrap();
+            document.set("tags[1]", "c").unwrap();
+        });
+        let expected = CONFIG.replace("8080", "9090").replace(r#""b"]"#, r#""c"]"#);
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn set_adds_missing_members() {
+        let output = edit(CONFIG, |document| {
+            document.set("server.tls.enabled", true).unwrap();
+            document.set("tags[2]", "c").unwrap();
+        });
+        let expected = r#"{
+  // server settings
+  "server": {
+    "host": "localhost", // default
+    "port": 8080,
+    "tls": {"enabled": true}
+  },
+  "tags": ["a", "b", "c"]
+}
+"#;
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn appending_moves_the_comma_before_comments() {
+        let input = "{\n  \"a\": 1 // one\n}";
+        let output = edit(input, |document| document.set("b", 2).unwrap());
+        assert_eq!(output, "{\n  \"a\": 1, // one\n  \"b\": 2\n}");
+
+        let input = "[\n  1,\n]";
+        let output = edit(input, |document| document.set("[1]", 2).unwrap());
+        assert_eq!(output, "[\n  1,\n  2,\n]");
+    }
+
+    #[test]
+    fn large_values_are_indented() {
+        let long = Value::String("x".repeat(40));
+        let value = Value::Array(vec![long.clone(), long]);
+        let output = edit("{\n  \"a\": 1\n}", |document| {
+            document.set("b", value).unwrap()
+        });
+        let x = "x".repeat(40);
+        let expected = format!("{{\n  \"a\": 1,\n  \"b\": [\n    \"{x}\",\n    \"{x}\"\n  ]\n}}");
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn remove_keeps_neighbouring_comments() {
+        let output = edit(CONFIG, |document| {
+            document.remove("server.host").unwrap();
+        });
+        let expected = CONFIG.replace("\n    \"host\": \"localhost\", // default", "");
+        assert_eq!(output, expected);
+
+        let output = edit(CONFIG, |document| {
+            document.remove("server.port").unwrap();
+        });
+        let expected = CONFIG.replace(", // default\n    \"port\": 8080", " // default");
+        assert_eq!(output, expected);
+
+        let output = edit(CONFIG, |document| {
+            document.remove("server").unwrap();
+        });
+        assert_eq!(output, "{\n  \"tags\": [\"a\", \"b\"]\n}\n");
+    }
+
+    #[test]
+    fn remove_from_compact_text() {
+        let output = edit("[1, 2, 3]", |document| {
+            document.remove("[0]").unwrap();
+            document.remove("[1]").unwrap();
+        });
+        assert_eq!(output, "[2]");
+        assert_eq!(edit("[1]", |document| drop(document.remove("[0]"))), "[]");
+    }
+
+    #[test]
+    fn insert_shifts_elements() {
+        let output = edit("[1, 2]", |document| {
+            document.insert("[0]", 0).unwrap();
+            document.insert("[2]", 1.5).unwrap();
+        });
+        assert_eq!(output, "[0, 1, 1.5, 2]");
+
+        let input = "[\n  1, // one\n  2\n]";
+        let output = edit(input, |document| document.insert("[1]", 3).unwrap());
+        assert_eq!(output, "[\n  1, // one\n  3,\n  2\n]");
+    }
+
+    #[test]
+    fn errors() {
+        let mut document = Document::parse(CONFIG).unwrap();
+        let error = |path: &str| path.to_string();
+        assert_eq!(
+            document.insert("server.port", 1),
+            Err(EditError::KeyExists(error("server.port")))
+        );
+        assert_eq!(
+            document.insert("missing.port", 1),
+            Err(EditError::NotFound(error("missing.port")))
+        );
+        assert_eq!(
+            document.set("server.port.x", 1),
+            Err(EditError::NotAContainer(error("server.port.x")))
+        );
+        assert_eq!(
+            document.set("tags[5]", 1),
+            Err(EditError::InvalidIndex(error("tags[5]")))
+        );
+        assert_eq!(
+            document.remove("tags[2]"),
+            Err(EditError::NotFound(error("tags[2]")))
+        );
+        assert_eq!(
+            document.set("tags.a", 1),
+            Err(EditError::NotAContainer(error("tags.a")))
+        );
+        assert_eq!(
+            document.set("tags[", 1),
+            Err(EditError::InvalidPath(error("tags[")))
+        );
+        assert_eq!(document.to_string(), CONFIG);
+    }
+}
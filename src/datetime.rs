@@ -0,0 +1,69 @@
+//! Conversions for RFC 3339 timestamps, enabled with the `chrono` feature
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::{FromJson, FromJsonError, ToJson, Value};
+
+impl Value {
+    /// The RFC 3339 timestamp in a string, ex. `"2024-05-01T12:30:00+02:00"`, in UTC
+    pub fn as_datetime(&self) -> Option<DateTime<Utc>> {
+        let datetime = DateTime::parse_from_rfc3339(self.as_str()?).ok()?;
+        Some(datetime.with_timezone(&Utc))
+    }
+}
+
+impl From<DateTime<Utc>> for Value {
+    /// An RFC 3339 string ending in `Z`, with only as many fractional digits as needed
+    fn from(datetime: DateTime<Utc>) -> Self {
+        Value::String(datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+    }
+}
+
+impl FromJson for DateTime<Utc> {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        let Value::String(s) = value else {
+            return Err(FromJsonError::wrong_type("string", value));
+        };
+        value
+            .as_datetime()
+            .ok_or_else(|| FromJsonError::custom(format!("invalid RFC 3339 timestamp `{s}`")))
+    }
+}
+
+impl ToJson for DateTime<Utc> {
+    fn to_json(&self) -> Value {
+        Value::from(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone, Utc};
+
+    use crate::{FromJson, ToJson, Value};
+
+    #[test]
+    fn parses_rfc3339() {
+        let expected = Utc.with_ymd_and_hms(2024, 5, 1, 10, 30, 0).unwrap();
+        let value = Value::string("2024-05-01T12:30:00+02:00");
+        assert_eq!(value.as_datetime(), Some(expected));
+        assert_eq!(Value::string("2024-05-01").as_datetime(), None);
+        assert_eq!(Value::Number(0.0).as_datetime(), None);
+    }
+
+    #[test]
+    fn round_trips() {
+        let datetime = Utc.with_ymd_and_hms(2024, 5, 1, 10, 30, 0).unwrap();
+        assert_eq!(Value::from(datetime), Value::string("2024-05-01T10:30:00Z"));
+        let precise = datetime + chrono::Duration::milliseconds(250);
+        assert_eq!(precise.to_json(), Value::string("2024-05-01T10:30:00.250Z"));
+        assert_eq!(DateTime::<Utc>::from_json(&precise.to_json()), Ok(precise));
+    }
+
+    #[test]
+    fn conversion_errors() {
+        let err = DateTime::<Utc>::from_json(&Value::string("yesterday")).unwrap_err();
+        assert_eq!(err.to_string(), "invalid RFC 3339 timestamp `yesterday`");
+        assert!(DateTime::<Utc>::from_json(&Value::Null).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+//! Conversions to exact decimals, enabled with the `decimal` feature
+
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+
+use crate::{FromJson, FromJsonError, SpannedKind, SpannedValue, ToJson, Value};
+
+impl Value {
+    /// The number as a decimal with the fewest digits that give back the same `f64`, ex.
+    /// `0.1` rather than `0.1000000000000000055511151231257827`
+    ///
+    /// This matches the source text for numbers of up to 15 significant digits. Use
+    /// [`SpannedValue::as_decimal`] to read longer numbers exactly.
+    pub fn as_decimal(&self) -> Option<Decimal> {
+        let n = self.as_f64()?;
+        if !n.is_finite() {
+            return None;
+        }
+        Decimal::from_str_exact(&n.to_string()).ok()
+    }
+}
+
+impl SpannedValue {
+    /// The number exactly as written in `source`, the text this value was parsed from
+    ///
+    /// Returns `None` for other values and for numbers that don't fit in a [`Decimal`],
+    /// which holds up to 28 significant digits.
+    pub fn as_decimal(&self, source: &str) -> Option<Decimal> {
+        let SpannedKind::Number(_) = self.kind else {
+            return None;
+        };
+        let text = source.get(self.span.range())?;
+        if text.contains(['e', 'E']) {
+            Decimal::from_scientific(text).ok()
+        } else {
+            Decimal::from_str_exact(text).ok()
+        }
+    }
+}
+
+impl From<Decimal> for Value {
+    /// The nearest `f64`, which may round decimals with many digits
+    fn from(decimal: Decimal) -> Self {
+        Value::Number(decimal.to_f64().unwrap_or(f64::NAN))
+    }
+}
+
+impl FromJson for Decimal {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        match value {
+            Value::Number(n) => value
+                .as_decimal()
+                .ok_or_else(|| FromJsonError::custom(format!("{n} does not fit in a decimal"))),
+            _ => Err(FromJsonError::wrong_type("number", value)),
+        }
+    }
+}
+
+impl ToJson for Decimal {
+    fn to_json(&self) -> Value {
+        Value::from(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rust_decimal::Decimal;
+
+    use crate::{FromJson, ToJson, Value, parse_with_spans};
+
+    fn decimal(text: &str) -> Decimal {
+        Decimal::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn shortest_decimal() {
+        assert_eq!(Value::Number(0.1).as_decimal(), Some(decimal("0.1")));
+        assert_eq!(
+            Value::Number(0.1 + 0.2).as_decimal(),
+            Some(decimal("0.30000000000000004"))
+        );
+        assert_eq!(Value::Number(-12.5).as_decimal(), Some(decimal("-12.5")));
+        assert_eq!(Value::Number(f64::NAN).as_decimal(), None);
+        assert_eq!(Value::string("1").as_decimal(), None);
+    }
+
+    #[test]
+    fn exact_from_source() {
+        let source = r#"{"total": 12345678901234567.89, "rate": 1.5e-3, "name": "x"}"#;
+        let document = parse_with_spans(source).unwrap();
+        let total = document.pointer("/total").unwrap();
+        assert_eq!(
+            total.as_decimal(source),
+            Some(decimal("12345678901234567.89"))
+        );
+        assert_ne!(
+            total.to_value().as_decimal(),
+            Some(decimal("12345678901234567.89"))
+        );
+        let rate = document.pointer("/rate").unwrap();
+        assert_eq!(rate.as_decimal(source), Some(decimal("0.0015")));
+        assert_eq!(document.pointer("/name").unwrap().as_decimal(source), None);
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(
+            Decimal::from_json(&Value::Number(19.99)),
+            Ok(decimal("19.99"))
+        );
+        assert!(Decimal::from_json(&Value::Null).is_err());
+        assert_eq!(decimal("19.99").to_json(), Value::Number(19.99));
+    }
+}
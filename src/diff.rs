@@ -0,0 +1,227 @@
+//! Structural comparison of two values, ex. for detecting configuration drift
+
+use std::fmt::Write;
+
+use crate::{JsonPointer, Value};
+
+/// One difference between two documents
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// Present only in the second document
+    Added { pointer: JsonPointer, value: Value },
+
+    /// Present only in the first document
+    Removed { pointer: JsonPointer, value: Value },
+
+    /// Present in both with different values, including a different type
+    Changed {
+        pointer: JsonPointer,
+        old: Value,
+        new: Value,
+    },
+}
+
+impl Change {
+    pub fn pointer(&self) -> &JsonPointer {
+        match self {
+            Change::Added { pointer, .. }
+            | Change::Removed { pointer, .. }
+            | Change::Changed { pointer, .. } => pointer,
+        }
+    }
+}
+
+/// Lists every difference from `a` to `b`, objects are compared by key and arrays by index
+///
+/// Changes are reported at the deepest location that differs, in sorted key order.
+pub fn compare(a: &Value, b: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    compare_at(a, b, &mut JsonPointer::root(), &mut changes);
+    changes
+}
+
+fn compare_at(a: &Value, b: &Value, pointer: &mut JsonPointer, changes: &mut Vec<Change>) {
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => {
+            let mut keys: Vec<&String> = a
+                .keys()
+                .chain(b.keys().filter(|key| !a.contains_key(*key)))
+                .collect();
+            keys.sort();
+            for key in keys {
+                pointer.push(key.clone());
+                match (a.get(key), b.get(key)) {
+                    (Some(a), Some(b)) => compare_at(a, b, pointer, changes),
+                    (Some(a), None) => changes.push(Change::Removed {
+                        pointer: pointer.clone(),
+                        value: a.clone(),
+                    }),
+                    (None, Some(b)) => changes.push(Change::Added {
+                        pointer: pointer.clone(),
+                        value: b.clone(),
+                    }),
+                    (None, None) => unreachable!("key came from one of the objects"),
+                }
+                pointer.pop();
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for i in 0..a.len().max(b.len()) {
+                pointer.push(i.to_string());
+                match (a.get(i), b.get(i)) {
+                    (Some(a), Some(b)) => compare_at(a, b, pointer, changes),
+                    (Some(a), None) => changes.push(Change::Removed {
+                        pointer: pointer.clone(),
+                        value: a.clone(),
+                    }),
+                    (None, Some(b)) => changes.push(Change::Added {
+                        pointer: pointer.clone(),
+                        value: b.clone(),
+                    }),
+                    (None, None) => unreachable!("index is within one of the arrays"),
+                }
+                pointer.pop();
+            }
+        }
+        (a, b) if a != b => changes.push(Change::Changed {
+            pointer: pointer.clone(),
+            old: a.clone(),
+            new: b.clone(),
+        }),
+        _ => {}
+    }
+}
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+/// Renders changes as a unified-style diff, one `-` / `+` line per removed / added value
+///
+/// With `colored`, removals are red, additions green and locations cyan using ANSI escapes.
+pub fn format(changes: &[Change], colored: bool) -> String {
+    let paint = |color: &str, text: String| {
+        if colored {
+            format!("{color}{text}{RESET}")
+        } else {
+            text
+        }
+    };
+
+    let mut output = String::new();
+    for change in changes {
+        let pointer = change.pointer();
+        let location = if pointer.is_root() {
+            String::from("/")
+        } else {
+            pointer.to_string()
+        };
+        writeln!(output, "{}", paint(CYAN, format!("@@ {location} @@"))).unwrap();
+        match change {
+            Change::Added { value, .. } => {
+                writeln!(output, "{}", paint(GREEN, format!("+ {value}"))).unwrap();
+            }
+            Change::Removed { value, .. } => {
+                writeln!(output, "{}", paint(RED, format!("- {value}"))).unwrap();
+            }
+            Change::Changed { old, new, .. } => {
+                writeln!(output, "{}", paint(RED, format!("- {old}"))).unwrap();
+                writeln!(output, "{}", paint(GREEN, format!("+ {new}"))).unwrap();
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{JsonPointer, Parser, Value};
+
+    use super::{Change, compare, format};
+
+    fn diff(a: &str, b: &str) -> Vec<Change> {
+        compare(
+            &Parser::new().parse_str(a).unwrap(),
+            &Parser::new().parse_str(b).unwrap(),
+        )
+    }
+
+    fn pointer(s: &str) -> JsonPointer {
+        JsonPointer::parse(s).unwrap()
+    }
+
+    #[test]
+    fn equal_documents() {
+        assert_eq!(
+            diff(r#"{"a": [1, {"b": null}]}"#, r#"{"a": [1, {"b": null}]}"#),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn nested_changes() {
+        let changes = diff(
+            r#"{"server": {"host": "a", "port": 80}, "debug": false}"#,
+            r#"{"server": {"port": 8080, "tls": true}}"#,
+        );
+        let expected = vec![
+            Change::Removed {
+                pointer: pointer("/debug"),
+                value: Value::Boolean(false),
+            },
+            Change::Removed {
+                pointer: pointer("/server/host"),
+                value: Value::String(String::from("a")),
+            },
+            Change::Changed {
+                pointer: pointer("/server/port"),
+                old: Value::Number(80.0),
+                new: Value::Number(8080.0),
+            },
+            Change::Added {
+                pointer: pointer("/server/tls"),
+                value: Value::Boolean(true),
+            },
+        ];
+        assert_eq!(changes, expected);
+    }
+
+    #[test]
+    fn arrays_by_index() {
+        let changes = diff("[1, 2, 3]", "[1, 5]");
+        let expected = vec![
+            Change::Changed {
+                pointer: pointer("/1"),
+                old: Value::Number(2.0),
+                new: Value::Number(5.0),
+            },
+            Change::Removed {
+                pointer: pointer("/2"),
+                value: Value::Number(3.0),
+            },
+        ];
+        assert_eq!(changes, expected);
+    }
+
+    #[test]
+    fn type_change_is_reported_whole() {
+        let changes = diff(r#"{"a": [1]}"#, r#"{"a": {"0": 1}}"#);
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(&changes[0], Change::Changed { pointer: p, .. } if p.to_string() == "/a"));
+    }
+
+    #[test]
+    fn plain_format() {
+        let changes = diff(r#"{"a": 1, "b": [true]}"#, r#"{"a": 2, "c": null}"#);
+        let expected = "@@ /a @@\n- 1\n+ 2\n@@ /b @@\n- [true]\n@@ /c @@\n+ null\n";
+        assert_eq!(format(&changes, false), expected);
+    }
+
+    #[test]
+    fn colored_format() {
+        let changes = diff("1", "2");
+        let expected = "\x1b[36m@@ / @@\x1b[0m\n\x1b[31m- 1\x1b[0m\n\x1b[32m+ 2\x1b[0m\n";
+        assert_eq!(format(&changes, true), expected);
+    }
+}
@@ -0,0 +1,154 @@
+use std::{env, fmt};
+
+use crate::{JsonPointer, Value};
+
+/// A placeholder in a string could not be expanded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpandError {
+    /// Location of the string holding the placeholder
+    pub pointer: JsonPointer,
+    pub kind: ExpandErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpandErrorKind {
+    /// `${NAME}` refers to a variable that is not set and has no default
+    Undefined(String),
+
+    /// `${` without a closing `}`
+    Unclosed,
+}
+
+impl fmt::Display for ExpandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ExpandErrorKind::Undefined(name) => {
+                write!(f, "`${{{name}}}` is not set at `{}`", self.pointer)
+            }
+            ExpandErrorKind::Unclosed => write!(f, "unclosed `${{` at `{}`", self.pointer),
+        }
+    }
+}
+
+impl std::error::Error for ExpandError {}
+
+impl Value {
+    /// Expands `${VAR}` and `${VAR:-default}` placeholders in every string value from the
+    /// process environment, see [`Value::expand_env_with`]
+    pub fn expand_env(&mut self) -> Result<(), ExpandError> {
+        self.expand_env_with(|name| env::var(name).ok())
+    }
+
+    /// Expands `${VAR}` and `${VAR:-default}` placeholders in every string value, looking
+    /// variables up with `lookup`
+    ///
+    /// As in the shell, the default is used when the variable is unset or empty, and `$${`
+    /// stands for a literal `${`. Object keys are left alone. Strings are expanded in
+    /// document order and the first error stops the pass, leaving later strings as they
+    /// were.
+    pub fn expand_env_with(
+        &mut self,
+        mut lookup: impl FnMut(&str) -> Option<String>,
+    ) -> Result<(), ExpandError> {
+        let mut result = Ok(());
+        self.walk_mut(|pointer, value| {
+            if result.is_ok()
+                && let Value::String(s) = value
+            {
+                match expand(s, &mut lookup) {
+                    Ok(expanded) => *s = expanded,
+                    Err(kind) => {
+                        let pointer = pointer.clone();
+                        result = Err(ExpandError { pointer, kind });
+                    }
+                }
+            }
+        });
+        result
+    }
+}
+
+fn expand(
+    input: &str,
+    lookup: &mut impl FnMut(&str) -> Option<String>,
+) -> Result<String, ExpandErrorKind> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(dollar) = rest.find('$') {
+        output.push_str(&rest[..dollar]);
+        rest = &rest[dollar..];
+        if let Some(after) = rest.strip_prefix("$${") {
+            output.push_str("${");
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("${") {
+            let close = after.find('}').ok_or(ExpandErrorKind::Unclosed)?;
+            let placeholder = &after[..close];
+            let value = match placeholder.split_once(":-") {
+                Some((name, default)) => lookup(name)
+                    .filter(|value| !value.is_empty())
+                    .unwrap_or_else(|| String::from(default)),
+                None => lookup(placeholder)
+                    .ok_or_else(|| ExpandErrorKind::Undefined(String::from(placeholder)))?,
+            };
+            output.push_str(&value);
+            rest = &after[close + 1..];
+        } else {
+            output.push('$');
+            rest = &rest[1..];
+        }
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{JsonPointer, Parser, Value};
+
+    use super::{ExpandError, ExpandErrorKind};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOST" => Some(String::from("db.local")),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn expands_placeholders() {
+        let mut config = value(
+            r#"{"url": "pg://${HOST}:${PORT:-5432}/${EMPTY:-app}", "list": ["$HOST", "$${HOST}"],
+                "${HOST}": 1}"#,
+        );
+        config.expand_env_with(lookup).unwrap();
+        let expected = value(
+            r#"{"url": "pg://db.local:5432/app", "list": ["$HOST", "${HOST}"], "${HOST}": 1}"#,
+        );
+        assert_eq!(config, expected);
+    }
+
+    #[test]
+    fn errors() {
+        let mut config = value(r#"{"a": "${HOST}", "b": ["ok", "${MISSING}"], "c": "${HOST}"}"#);
+        let expected = ExpandError {
+            pointer: JsonPointer::parse("/b/1").unwrap(),
+            kind: ExpandErrorKind::Undefined(String::from("MISSING")),
+        };
+        assert_eq!(config.expand_env_with(lookup), Err(expected));
+        assert_eq!(
+            config,
+            value(r#"{"a": "db.local", "b": ["ok", "${MISSING}"], "c": "${HOST}"}"#)
+        );
+
+        let mut config = Value::String(String::from("${HOST"));
+        assert_eq!(
+            config.expand_env_with(lookup).unwrap_err().kind,
+            ExpandErrorKind::Unclosed
+        );
+    }
+}
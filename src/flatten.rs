@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::Value;
+use crate::path::PathToken;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnflattenError {
+    /// A key has an unclosed bracket or an index that is not a number
+    InvalidKey(String),
+
+    /// A key needs a value to be both a scalar and a container, or both an object and an
+    /// array, ex. `a` and `a.b`
+    Conflict(String),
+}
+
+impl fmt::Display for UnflattenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidKey(key) => write!(f, "invalid flattened key {key:?}"),
+            Self::Conflict(key) => write!(f, "key {key:?} conflicts with another key"),
+        }
+    }
+}
+
+impl std::error::Error for UnflattenError {}
+
+/// Flattens nested objects and arrays into a single map of paths to leaf values, ex.
+/// `{"a": {"b": [{"c": 1}]}}` becomes `{"a.b[0].c": 1}` with a `.` separator
+///
+/// Empty objects and arrays are kept as values so that [`unflatten`] can restore them.
+/// A scalar root is stored under the empty key.
+pub fn flatten(value: &Value, separator: &str) -> HashMap<String, Value> {
+    let mut output = HashMap::new();
+    flatten_into(value, separator, &mut String::new(), &mut output);
+    output
+}
+
+fn flatten_into(
+    value: &Value,
+    separator: &str,
+    prefix: &mut String,
+    output: &mut HashMap<String, Value>,
+) {
+    let len = prefix.len();
+    match value {
+        Value::Array(array) if !array.is_empty() => {
+            for (i, item) in array.iter().enumerate() {
+                prefix.push_str(&format!("[{i}]"));
+                flatten_into(item, separator, prefix, output);
+                prefix.truncate(len);
+            }
+        }
+        Value::Object(map) if !map.is_empty() => {
+            for (key, item) in map {
+                if !prefix.is_empty() {
+                    prefix.push_str(separator);
+                }
+                prefix.push_str(key);
+                flatten_into(item, separator, prefix, output);
+                prefix.truncate(len);
+            }
+        }
+        _ => {
+            output.insert(prefix.clone(), value.clone());
+        }
+    }
+}
+
+/// Rebuilds nested objects and arrays from the output of [`flatten`]
+///
+/// Keys are split on `separator`, which must not be empty, and `[n]` suffixes index
+/// into arrays. Array elements missing from the map are filled with `null`.
+pub fn unflatten(map: &HashMap<String, Value>, separator: &str) -> Result<Value, UnflattenError> {
+    // sorting makes the result and any conflict reported the same on every run
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by_key(|(key, _)| *key);
+
+    let mut root = Slot::Empty;
+    for (key, value) in entries {
+        let tokens =
+            parse_key(key, separator).ok_or_else(|| UnflattenError::InvalidKey(key.clone()))?;
+        let conflict = || UnflattenError::Conflict(key.clone());
+
+        let mut slot = &mut root;
+        for token in &tokens {
+            if let Slot::Empty = slot {
+                *slot = match token {
+                    PathToken::Key(_) => Slot::Object(HashMap::new()),
+                    PathToken::Index(_) => Slot::Array(Vec::new()),
+                };
+            }
+            slot = match (slot, token) {
+                (Slot::Object(map), PathToken::Key(key)) => {
+                    map.entry(key.clone()).or_insert(Slot::Empty)
+                }
+                (Slot::Array(array), PathToken::Index(index)) => {
+                    if array.len() <= *index {
+                        array.resize_with(index + 1, || Slot::Empty);
+                    }
+                    &mut array[*index]
+                }
+                _ => return Err(conflict()),
+            };
+        }
+        if !matches!(slot, Slot::Empty) {
+            return Err(conflict());
+        }
+        *slot = Slot::Leaf(value.clone());
+    }
+    Ok(match root {
+        Slot::Empty => Value::Object(HashMap::new()),
+        root => root.into_value(),
+    })
+}
+
+/// A value being rebuilt, which may not have been filled in yet
+enum Slot {
+    Empty,
+    Leaf(Value),
+    Object(HashMap<String, Slot>),
+    Array(Vec<Slot>),
+}
+
+impl Slot {
+    fn into_value(self) -> Value {
+        match self {
+            Slot::Empty => Value::Null,
+            Slot::Leaf(value) => value,
+            Slot::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(key, slot)| (key, slot.into_value()))
+                    .collect(),
+            ),
+            Slot::Array(array) => Value::Array(array.into_iter().map(Slot::into_value).collect()),
+        }
+    }
+}
+
+/// Splits a flattened key into keys and array indexes
+fn parse_key(key: &str, separator: &str) -> Option<Vec<PathToken>> {
+    let mut tokens = Vec::new();
+    if key.is_empty() {
+        return Some(tokens);
+    }
+    for part in key.split(separator) {
+        let (name, mut indexes) = part.split_at(part.find('[').unwrap_or(part.len()));
+        if !name.is_empty() {
+            tokens.push(PathToken::Key(name.to_string()));
+        }
+        while let Some(rest) = indexes.strip_prefix('[') {
+            let close = rest.find(']')?;
+            tokens.push(PathToken::Index(rest[..close].parse().ok()?));
+            indexes = &rest[close + 1..];
+        }
+        if !indexes.is_empty() {
+            return None;
+        }
+    }
+    Some(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{Parser, Value};
+
+    use super::{UnflattenError, flatten, unflatten};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    fn map(pairs: &[(&str, Value)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn flattens_to_paths() {
+        let document = value(r#"{"a": {"b": [{"c": 1}, "x"]}, "d": [], "e": {}}"#);
+        let expected = map(&[
+            ("a.b[0].c", Value::Number(1.0)),
+            ("a.b[1]", Value::string("x")),
+            ("d", Value::Array(vec![])),
+            ("e", Value::object([])),
+        ]);
+        assert_eq!(flatten(&document, "."), expected);
+    }
+
+    #[test]
+    fn round_trips() {
+        let inputs = [
+            r#"{"a": {"b": [{"c": 1}, [true, null]]}, "d": [], "e": {}}"#,
+            "[1, [2]]",
+            "\"scalar\"",
+        ];
+        for input in inputs {
+            let document = value(input);
+            for separator in [".", "__"] {
+                let flat = flatten(&document, separator);
+                assert_eq!(unflatten(&flat, separator), Ok(document.clone()));
+            }
+        }
+    }
+
+    #[test]
+    fn env_style_separator() {
+        let flat = map(&[
+            ("DB__HOST", Value::string("h")),
+            ("DB__PORTS[1]", Value::Number(2.0)),
+        ]);
+        let expected = value(r#"{"DB": {"HOST": "h", "PORTS": [null, 2]}}"#);
+        assert_eq!(unflatten(&flat, "__"), Ok(expected));
+    }
+
+    #[test]
+    fn errors() {
+        let flat = map(&[("a", Value::Null), ("a.b", Value::Null)]);
+        assert_eq!(
+            unflatten(&flat, "."),
+            Err(UnflattenError::Conflict(String::from("a.b")))
+        );
+        let flat = map(&[("a[0]", Value::Null), ("a.b", Value::Null)]);
+        assert_eq!(
+            unflatten(&flat, "."),
+            Err(UnflattenError::Conflict(String::from("a[0]")))
+        );
+        let flat = map(&[("a[x]", Value::Null)]);
+        assert_eq!(
+            unflatten(&flat, "."),
+            Err(UnflattenError::InvalidKey(String::from("a[x]")))
+        );
+    }
+}
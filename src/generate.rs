@@ -0,0 +1,462 @@
+//! Random documents with a given shape, ex. for load testing APIs or seeding databases
+//!
+//! A [`Shape`] describes the documents to produce. It can be built directly, or from a
+//! JSON Schema with [`Shape::from_schema`], and a seeded [`Generator`] turns it into values
+//! so the same seed always produces the same documents.
+
+use std::collections::HashMap;
+
+use crate::schema::SchemaError;
+use crate::{JsonPointer, Value};
+
+/// The kind of value to generate
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    /// Any scalar: null, a boolean, a small integer or a short string
+    Any,
+    Null,
+    Boolean,
+
+    /// A whole number between `min` and `max`, inclusive
+    Integer {
+        min: i64,
+        max: i64,
+    },
+
+    /// A number between `min` and `max`
+    Number {
+        min: f64,
+        max: f64,
+    },
+
+    /// Lowercase ASCII letters, between `min_len` and `max_len` of them
+    String {
+        min_len: usize,
+        max_len: usize,
+    },
+
+    /// Always this value
+    Const(Value),
+
+    /// One of these values
+    Enum(Vec<Value>),
+
+    /// A value of one of these shapes
+    AnyOf(Vec<Shape>),
+
+    /// Between `min_len` and `max_len` elements, inclusive
+    Array {
+        items: Box<Shape>,
+        min_len: usize,
+        max_len: usize,
+    },
+
+    Object(Vec<Field>),
+}
+
+/// A member of an [`Shape::Object`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub shape: Shape,
+
+    /// Whether the member is left out of about half of the generated objects
+    pub optional: bool,
+}
+
+impl Field {
+    pub fn required(name: impl Into<String>, shape: Shape) -> Self {
+        Field {
+            name: name.into(),
+            shape,
+            optional: false,
+        }
+    }
+
+    pub fn optional(name: impl Into<String>, shape: Shape) -> Self {
+        Field {
+            name: name.into(),
+            shape,
+            optional: true,
+        }
+    }
+}
+
+impl Shape {
+    /// The shape of the documents a JSON Schema accepts
+    ///
+    /// Supports `type`, `const`, `enum`, `anyOf`, `oneOf`, `properties`, `required`,
+    /// `items`, `minItems`, `maxItems`, `minimum`, `maximum`, `minLength` and `maxLength`,
+    /// other keywords are ignored so the result may not satisfy them. Without `type`, the
+    /// shape is guessed from `properties` or `items`.
+    pub fn from_schema(schema: &Value) -> Result<Shape, SchemaError> {
+        from_schema_at(schema, &mut JsonPointer::root())
+    }
+}
+
+fn from_schema_at(schema: &Value, pointer: &mut JsonPointer) -> Result<Shape, SchemaError> {
+    let map = match schema {
+        Value::Boolean(true) => return Ok(Shape::Any),
+        Value::Object(map) => map,
+        _ => return Err(SchemaError::NotASchema(pointer.clone())),
+    };
+
+    if let Some(value) = map.get("const") {
+        return Ok(Shape::Const(value.clone()));
+    }
+    if let Some(values) = map.get("enum") {
+        let values = values.as_array().ok_or_else(|| invalid(pointer, "enum"))?;
+        return Ok(Shape::Enum(values.clone()));
+    }
+    for keyword in ["anyOf", "oneOf"] {
+        if let Some(schemas) = map.get(keyword) {
+            let schemas = schemas
+                .as_array()
+                .ok_or_else(|| invalid(pointer, keyword))?;
+            pointer.push(keyword);
+            let shapes = schemas
+                .iter()
+                .enumerate()
+                .map(|(i, schema)| {
+                    pointer.push(i.to_string());
+                    let shape = from_schema_at(schema, pointer);
+                    pointer.pop();
+                    shape
+                })
+                .collect::<Result<_, _>>();
+            pointer.pop();
+            return Ok(Shape::AnyOf(shapes?));
+        }
+    }
+
+    let types = match map.get("type") {
+        Some(Value::String(name)) => vec![name.as_str()],
+        Some(Value::Array(names)) => names
+            .iter()
+            .map(|name| name.as_str().ok_or_else(|| invalid(pointer, "type")))
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err(invalid(pointer, "type")),
+        None if map.contains_key("properties") => vec!["object"],
+        None if map.contains_key("items") => vec!["array"],
+        None => return Ok(Shape::Any),
+    };
+    let mut shapes = Vec::new();
+    for name in types {
+        let shape = match name {
+            "null" => Shape::Null,
+            "boolean" => Shape::Boolean,
+            "integer" => {
+                let min = number(map, "minimum", pointer)?.map_or(0, |n| n.ceil() as i64);
+                let max = number(map, "maximum", pointer)?
+                    .map_or(min.saturating_add(100), |n| n.floor() as i64);
+                Shape::Integer { min, max }
+            }
+            "number" => {
+                let min = number(map, "minimum", pointer)?.unwrap_or(0.0);
+                let max = number(map, "maximum", pointer)?.unwrap_or(min + 100.0);
+                Shape::Number { min, max }
+            }
+            "string" => {
+                let min_len = length(map, "minLength", pointer)?.unwrap_or(1);
+                let max_len = length(map, "maxLength", pointer)?.unwrap_or(min_len.max(12));
+                Shape::String { min_len, max_len }
+            }
+            "array" => {
+                let items = match map.get("items") {
+                    Some(schema) => {
+                        pointer.push("items");
+                        let items = from_schema_at(schema, pointer);
+                        pointer.pop();
+                        items?
+                    }
+                    None => Shape::Any,
+                };
+                let min_len = length(map, "minItems", pointer)?.unwrap_or(0);
+                let max_len = length(map, "maxItems", pointer)?.unwrap_or(min_len + 4);
+                Shape::Array {
+                    items: Box::new(items),
+                    min_len,
+                    max_len,
+                }
+            }
+            "object" => object_shape(map, pointer)?,
+            _ => return Err(invalid(pointer, "type")),
+        };
+        shapes.push(shape);
+    }
+    Ok(if shapes.len() == 1 {
+        shapes.remove(0)
+    } else {
+        Shape::AnyOf(shapes)
+    })
+}
+
+fn object_shape(
+    map: &HashMap<String, Value>,
+    pointer: &mut JsonPointer,
+) -> Result<Shape, SchemaError> {
+    let required: Vec<&str> = match map.get("required") {
+        None => Vec::new(),
+        Some(Value::Array(names)) => names
+            .iter()
+            .map(|name| name.as_str().ok_or_else(|| invalid(pointer, "required")))
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err(invalid(pointer, "required")),
+    };
+    let properties = match map.get("properties") {
+        None => return Ok(Shape::Object(Vec::new())),
+        Some(properties) => properties
+            .as_object()
+            .ok_or_else(|| invalid(pointer, "properties"))?,
+    };
+
+    // sorted so that the same seed generates the same documents on every run
+    let mut properties: Vec<_> = properties.iter().collect();
+    properties.sort_by_key(|(name, _)| *name);
+    let mut fields = Vec::new();
+    pointer.push("properties");
+    for (name, schema) in properties {
+        pointer.push(name.as_str());
+        let shape = from_schema_at(schema, pointer);
+        pointer.pop();
+        fields.push(Field {
+            name: name.clone(),
+            shape: shape?,
+            optional: !required.contains(&name.as_str()),
+        });
+    }
+    pointer.pop();
+    Ok(Shape::Object(fields))
+}
+
+fn number(
+    map: &HashMap<String, Value>,
+    keyword: &'static str,
+    pointer: &JsonPointer,
+) -> Result<Option<f64>, SchemaError> {
+    match map.get(keyword) {
+        None => Ok(None),
+        Some(value) => value
+            .as_f64()
+            .map(Some)
+            .ok_or_else(|| invalid(pointer, keyword)),
+    }
+}
+
+fn length(
+    map: &HashMap<String, Value>,
+    keyword: &'static str,
+    pointer: &JsonPointer,
+) -> Result<Option<usize>, SchemaError> {
+    match map.get(keyword).map(Value::as_i64) {
+        None => Ok(None),
+        Some(Some(n)) if n >= 0 => Ok(Some(n as usize)),
+        Some(_) => Err(invalid(pointer, keyword)),
+    }
+}
+
+fn invalid(pointer: &JsonPointer, keyword: &'static str) -> SchemaError {
+    SchemaError::InvalidKeyword {
+        pointer: pointer.clone(),
+        keyword,
+    }
+}
+
+/// Produces random values from a [`Shape`], using a small seeded PRNG (SplitMix64) so runs
+/// are reproducible
+#[derive(Debug, Clone)]
+pub struct Generator {
+    state: u64,
+}
+
+impl Generator {
+    pub fn new(seed: u64) -> Self {
+        Generator { state: seed }
+    }
+
+    pub fn generate(&mut self, shape: &Shape) -> Value {
+        match shape {
+            Shape::Any => {
+                let shape = match self.below(4) {
+                    0 => Shape::Null,
+                    1 => Shape::Boolean,
+                    2 => Shape::Integer { min: 0, max: 100 },
+                    _ => Shape::String {
+                        min_len: 1,
+                        max_len: 12,
+                    },
+                };
+                self.generate(&shape)
+            }
+            Shape::Null => Value::Null,
+            Shape::Boolean => Value::Boolean(self.below(2) == 1),
+            Shape::Integer { min, max } => {
+                let span = (i128::from(*max) - i128::from(*min)).max(0) as u128 + 1;
+                let offset = u128::from(self.next_u64()) % span;
+                Value::Number((i128::from(*min) + offset as i128) as f64)
+            }
+            Shape::Number { min, max } => {
+                // the top 53 bits give every representable fraction in [0, 1)
+                let unit = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+                Value::Number(min + unit * (max - min).max(0.0))
+            }
+            Shape::String { min_len, max_len } => {
+                let len = self.between(*min_len, *max_len);
+                let string = (0..len)
+                    .map(|_| char::from(b'a' + self.below(26) as u8))
+                    .collect();
+                Value::String(string)
+            }
+            Shape::Const(value) => value.clone(),
+            Shape::Enum(values) if values.is_empty() => Value::Null,
+            Shape::Enum(values) => values[self.below(values.len())].clone(),
+            Shape::AnyOf(shapes) if shapes.is_empty() => Value::Null,
+            Shape::AnyOf(shapes) => {
+                let index = self.below(shapes.len());
+                self.generate(&shapes[index])
+            }
+            Shape::Array {
+                items,
+                min_len,
+                max_len,
+            } => {
+                let len = self.between(*min_len, *max_len);
+                Value::Array((0..len).map(|_| self.generate(items)).collect())
+            }
+            Shape::Object(fields) => {
+                let mut map = HashMap::new();
+                for field in fields {
+                    if field.optional && self.below(2) == 0 {
+                        continue;
+                    }
+                    map.insert(field.name.clone(), self.generate(&field.shape));
+                }
+                Value::Object(map)
+            }
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`, `n` must not be zero
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// A number in `min..=max`, or `min` if `max` is smaller
+    fn between(&mut self, min: usize, max: usize) -> usize {
+        min + self.below(max.saturating_sub(min) + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::{Schema, SchemaError};
+    use crate::{JsonPointer, Parser, Value};
+
+    use super::{Field, Generator, Shape};
+
+    fn user_shape() -> Shape {
+        Shape::Object(vec![
+            Field::required("id", Shape::Integer { min: 1, max: 1000 }),
+            Field::required(
+                "name",
+                Shape::String {
+                    min_len: 3,
+                    max_len: 8,
+                },
+            ),
+            Field::optional(
+                "tags",
+                Shape::Array {
+                    items: Box::new(Shape::Enum(vec![Value::string("a"), Value::string("b")])),
+                    min_len: 1,
+                    max_len: 3,
+                },
+            ),
+        ])
+    }
+
+    #[test]
+    fn follows_the_shape() {
+        let mut generator = Generator::new(7);
+        let mut saw_tags = [false, false];
+        for _ in 0..100 {
+            let user = generator.generate(&user_shape());
+            let id = user.pointer("/id").and_then(Value::as_f64).unwrap();
+            assert!((1.0..=1000.0).contains(&id) && id.fract() == 0.0);
+            let name = user.pointer("/name").and_then(Value::as_str).unwrap();
+            assert!((3..=8).contains(&name.len()));
+            match user.pointer("/tags").and_then(Value::as_array) {
+                Some(tags) => {
+                    assert!((1..=3).contains(&tags.len()));
+                    saw_tags[1] = true;
+                }
+                None => saw_tags[0] = true,
+            }
+        }
+        assert_eq!(saw_tags, [true, true]);
+    }
+
+    #[test]
+    fn same_seed_same_documents() {
+        let documents = |seed| {
+            let mut generator = Generator::new(seed);
+            (0..10)
+                .map(|_| generator.generate(&user_shape()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(documents(1), documents(1));
+        assert_ne!(documents(1), documents(2));
+    }
+
+    #[test]
+    fn from_schema_generates_valid_documents() {
+        let schema = Parser::new()
+            .parse_str(
+                r#"{
+                "type": "object",
+                "required": ["id", "status", "scores"],
+                "properties": {
+                    "id": {"type": "integer", "minimum": 10, "maximum": 20},
+                    "status": {"enum": ["active", "banned"]},
+                    "scores": {"type": "array", "items": {"type": "number"}, "maxItems": 2},
+                    "note": {"type": ["string", "null"], "maxLength": 4}
+                }
+            }"#,
+            )
+            .unwrap();
+        let shape = Shape::from_schema(&schema).unwrap();
+        let validator = Schema::compile(&schema).unwrap();
+        let mut generator = Generator::new(42);
+        for _ in 0..100 {
+            let document = generator.generate(&shape);
+            assert!(validator.is_valid(&document), "{document:?}");
+        }
+    }
+
+    #[test]
+    fn invalid_schemas() {
+        let schema = Parser::new()
+            .parse_str(r#"{"items": {"type": "array", "minItems": "2"}}"#)
+            .unwrap();
+        assert_eq!(
+            Shape::from_schema(&schema),
+            Err(SchemaError::InvalidKeyword {
+                pointer: JsonPointer::parse("/items").unwrap(),
+                keyword: "minItems",
+            })
+        );
+        let schema = Value::Array(vec![]);
+        assert_eq!(
+            Shape::from_schema(&schema),
+            Err(SchemaError::NotASchema(JsonPointer::root()))
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use crate::Value;
+
+impl Value {
+    /// Groups the objects in an array by their `key` member, ex. `[{"k": "a"}, ...]` into
+    /// `{"a": [{"k": "a"}, ...]}`
+    ///
+    /// Groups are named by the member's string value, or its compact JSON for other types,
+    /// ex. `"1"` or `"null"`. Elements keep their order within a group. Elements that are
+    /// not objects or lack `key` are left out. Returns `None` when `self` is not an array.
+    pub fn group_by(&self, key: &str) -> Option<Value> {
+        aggregate(self, key, |group| {
+            Value::Array(group.into_iter().cloned().collect())
+        })
+    }
+
+    /// The number of elements in each [`Value::group_by`] group
+    pub fn count_by(&self, key: &str) -> Option<Value> {
+        aggregate(self, key, |group| Value::Number(group.len() as f64))
+    }
+
+    /// The total of the numeric `field` members in each [`Value::group_by`] group,
+    /// ignoring elements where `field` is missing or not a number
+    pub fn sum_by(&self, key: &str, field: &str) -> Option<Value> {
+        aggregate(self, key, |group| {
+            Value::Number(numbers(&group, field).sum())
+        })
+    }
+
+    /// The smallest numeric `field` member in each [`Value::group_by`] group, `null` for
+    /// a group without one
+    pub fn min_by(&self, key: &str, field: &str) -> Option<Value> {
+        aggregate(self, key, |group| {
+            numbers(&group, field)
+                .reduce(f64::min)
+                .map_or(Value::Null, Value::Number)
+        })
+    }
+
+    /// The largest numeric `field` member in each [`Value::group_by`] group, `null` for
+    /// a group without one
+    pub fn max_by(&self, key: &str, field: &str) -> Option<Value> {
+        aggregate(self, key, |group| {
+            numbers(&group, field)
+                .reduce(f64::max)
+                .map_or(Value::Null, Value::Number)
+        })
+    }
+}
+
+/// Groups the elements of `array` by `key` and reduces each group with `f`
+fn aggregate(array: &Value, key: &str, mut f: impl FnMut(Vec<&Value>) -> Value) -> Option<Value> {
+    let mut groups: HashMap<String, Vec<&Value>> = HashMap::new();
+    for item in array.as_array()? {
+        let Some(member) = item.as_object().and_then(|map| map.get(key)) else {
+            continue;
+        };
+        let name = match member {
+            Value::String(s) => s.clone(),
+            other => other.to_json_string(),
+        };
+        groups.entry(name).or_default().push(item);
+    }
+    let groups = groups
+        .into_iter()
+        .map(|(name, group)| (name, f(group)))
+        .collect();
+    Some(Value::Object(groups))
+}
+
+fn numbers<'a>(group: &'a [&Value], field: &'a str) -> impl Iterator<Item = f64> + 'a {
+    group
+        .iter()
+        .filter_map(move |item| item.as_object()?.get(field)?.as_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    const ORDERS: &str = r#"[
+        {"region": "eu", "total": 10},
+        {"region": "us", "total": 4},
+        {"region": "eu", "total": 2.5},
+        {"region": "us", "total": "n/a"},
+        {"region": 7, "total": 1},
+        {"total": 100},
+        "not an order"
+    ]"#;
+
+    #[test]
+    fn groups() {
+        let grouped = value(ORDERS).group_by("region").unwrap();
+        let expected = value(
+            r#"{"eu": [{"region": "eu", "total": 10}, {"region": "eu", "total": 2.5}],
+                "us": [{"region": "us", "total": 4}, {"region": "us", "total": "n/a"}],
+                "7": [{"region": 7, "total": 1}]}"#,
+        );
+        assert_eq!(grouped, expected);
+        assert_eq!(value("[]").group_by("region"), Some(value("{}")));
+        assert_eq!(value("{}").group_by("region"), None);
+    }
+
+    #[test]
+    fn aggregations() {
+        let orders = value(ORDERS);
+        assert_eq!(
+            orders.count_by("region"),
+            Some(value(r#"{"eu": 2, "us": 2, "7": 1}"#))
+        );
+        assert_eq!(
+            orders.sum_by("region", "total"),
+            Some(value(r#"{"eu": 12.5, "us": 4, "7": 1}"#))
+        );
+        assert_eq!(
+            orders.min_by("region", "total"),
+            Some(value(r#"{"eu": 2.5, "us": 4, "7": 1}"#))
+        );
+        assert_eq!(
+            orders.max_by("region", "missing"),
+            Some(value(r#"{"eu": null, "us": null, "7": null}"#))
+        );
+        assert_eq!(Value::Null.sum_by("region", "total"), None);
+    }
+}
@@ -0,0 +1,141 @@
+use alloc::vec::Vec;
+use core::hash::Hasher;
+
+use crate::Value;
+
+/// 64-bit FNV-1a, chosen because its output is stable across processes and platforms
+pub(crate) struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Feeds a value into `hasher` so that equal values produce equal hashes
+///
+/// Everything is written as explicit little-endian bytes, and object keys are visited in
+/// sorted order, so the result doesn't depend on the platform or on `HashMap` iteration order.
+pub(crate) fn hash_value<H: Hasher>(value: &Value, hasher: &mut H) {
+    match value {
+        Value::Null => hasher.write(&[0]),
+        Value::Boolean(b) => hasher.write(&[1, u8::from(*b)]),
+        Value::Number(n) => {
+            // `-0.0 == 0.0` and all NaNs are equal, so they must hash the same
+            let n = if *n == 0.0 {
+                0.0
+            } else if n.is_nan() {
+                f64::NAN
+            } else {
+                *n
+            };
+            hasher.write(&[2]);
+            hasher.write(&n.to_bits().to_le_bytes());
+        }
+        Value::String(s) => {
+            hasher.write(&[3]);
+            hash_str(s, hasher);
+        }
+        Value::Array(array) => {
+            hasher.write(&[4]);
+            hasher.write(&(array.len() as u64).to_le_bytes());
+            for item in array {
+                hash_value(item, hasher);
+            }
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            hasher.write(&[5]);
+            hasher.write(&(entries.len() as u64).to_le_bytes());
+            for (key, value) in entries {
+                hash_str(key, hasher);
+                hash_value(value, hasher);
+            }
+        }
+    }
+}
+
+fn hash_str<H: Hasher>(s: &str, hasher: &mut H) {
+    hasher.write(&(s.len() as u64).to_le_bytes());
+    hasher.write(s.as_bytes());
+}
+
+impl Value {
+    /// A 64-bit hash of the value's structure and contents, stable across processes
+    ///
+    /// Structurally equal values always hash the same regardless of object key order, so
+    /// the hash can be persisted and used for deduplication or cache keys.
+    pub fn structural_hash(&self) -> u64 {
+        let mut hasher = FnvHasher::default();
+        hash_value(self, &mut hasher);
+        hasher.finish()
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use crate::{Value, parse};
+
+    fn hash(input: &str) -> u64 {
+        parse(String::from(input)).unwrap().structural_hash()
+    }
+
+    #[test]
+    fn ignores_key_order_and_whitespace() {
+        assert_eq!(
+            hash(r#"{"a": 1, "b": [true]}"#),
+            hash(r#"{ "b":[true],"a":1 }"#)
+        );
+    }
+
+    #[test]
+    fn distinguishes_values() {
+        let inputs = [
+            "null",
+            "false",
+            "true",
+            "0",
+            "1",
+            r#""""#,
+            r#""0""#,
+            "[]",
+            "{}",
+            "[[]]",
+            r#"["a", "b"]"#,
+            r#"["ab"]"#,
+        ];
+        let mut hashes: Vec<u64> = inputs.iter().map(|input| hash(input)).collect();
+        hashes.sort();
+        hashes.dedup();
+        assert_eq!(hashes.len(), inputs.len());
+    }
+
+    #[test]
+    fn negative_zero_matches_zero() {
+        assert_eq!(
+            Value::Number(-0.0).structural_hash(),
+            Value::Number(0.0).structural_hash()
+        );
+    }
+
+    #[test]
+    fn stable_across_runs() {
+        // pinned so accidental changes to the encoding are caught
+        assert_eq!(Value::Null.structural_hash(), 0xaf63bd4c8601b7df);
+    }
+}
@@ -0,0 +1,480 @@
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::io::{self, Read};
+
+use crate::reader::{dedent, read_value};
+use crate::{DuplicateKeys, JsonReader, Limit, Limits, Position, ReaderError, Value};
+
+/// Reads a whole Hjson document, see [`crate::Dialect::Hjson`]
+///
+/// The depth and size limits and the duplicate key policy apply as with the other
+/// dialects, string and number lengths are not limited.
+pub(crate) fn read_hjson<R: io::Read, S: BuildHasher + Clone>(
+    reader: R,
+    duplicate_keys: DuplicateKeys,
+    limits: &Limits,
+    hasher: &S,
+) -> Result<Value<S>, ReaderError> {
+    let mut input = Vec::new();
+    // one byte past the limit is enough to tell that the input is too large
+    let max_read = limits.max_bytes.map_or(u64::MAX, |max| max as u64 + 1);
+    reader.take(max_read).read_to_end(&mut input)?;
+    if let Some(max_bytes) = limits.max_bytes
+        && input.len() > max_bytes
+    {
+        let (position, limit) = (position_at(&input, max_bytes), Limit::Bytes);
+        return Err(ReaderError::LimitExceeded { position, limit });
+    }
+    let input = match String::from_utf8(input) {
+        Ok(input) => input,
+        Err(err) => {
+            let position = position_at(err.as_bytes(), err.utf8_error().valid_up_to());
+            return Err(ReaderError::InvalidUtf8 { position });
+        }
+    };
+    let mut parser = Hjson {
+        input: &input,
+        offset: 0,
+        depth: 0,
+        max_depth: limits.max_depth,
+        duplicate_keys,
+        hasher,
+    };
+    parser.skip_whitespace()?;
+    let value = match parser.peek() {
+        None => return Err(ReaderError::UnexpectedEof),
+        Some(b'{' | b'[') => parser.value()?,
+        // the root object may leave out its braces
+        Some(_) if parser.at_member() => parser.nested(|parser| parser.members(None))?,
+        Some(_) => parser.value()?,
+    };
+    parser.skip_whitespace()?;
+    if parser.peek().is_some() {
+        let position = parser.position();
+        return Err(ReaderError::TrailingContent { position });
+    }
+    Ok(value)
+}
+
+/// Where `offset` is in `input`, counting lines and columns from 1
+fn position_at(input: &[u8], offset: usize) -> Position {
+    let before = &input[..offset];
+    let line_start = before
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map_or(0, |i| i + 1);
+    Position {
+        offset,
+        line: before.iter().filter(|b| **b == b'\n').count() + 1,
+        column: offset - line_start + 1,
+    }
+}
+
+struct Hjson<'a, S> {
+    input: &'a str,
+    offset: usize,
+
+    /// How many arrays and objects enclose the current offset
+    depth: usize,
+    max_depth: Option<usize>,
+    duplicate_keys: DuplicateKeys,
+
+    /// Cloned into every object
+    hasher: &'a S,
+}
+
+impl<'a, S: BuildHasher + Clone> Hjson<'a, S> {
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.offset).copied()
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.offset..]
+    }
+
+    fn position(&self) -> Position {
+        position_at(self.input.as_bytes(), self.offset)
+    }
+
+    fn unexpected(&self) -> ReaderError {
+        match self.peek() {
+            Some(found) => ReaderError::UnexpectedByte {
+                position: self.position(),
+                found,
+            },
+            None => ReaderError::UnexpectedEof,
+        }
+    }
+
+    /// Skips whitespace, line breaks and `#`, `//` and `/* */` comments
+    fn skip_whitespace(&mut self) -> Result<(), ReaderError> {
+        loop {
+            let rest = self.rest();
+            if rest.starts_with([' ', '\t', '\n', '\r']) {
+                self.offset += 1;
+            } else if rest.starts_with('#') || rest.starts_with("//") {
+                self.offset += rest.find('\n').unwrap_or(rest.len());
+            } else if let Some(comment) = rest.strip_prefix("/*") {
+                let end = comment.find("*/").ok_or(ReaderError::UnexpectedEof)?;
+                self.offset += end + 4;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Whether a key followed by `:` starts here
+    fn at_member(&mut self) -> bool {
+        let start = self.offset;
+        let found = self.key().is_ok() && {
+            self.skip_whitespace().ok();
+            self.peek() == Some(b':')
+        };
+        self.offset = start;
+        found
+    }
+
+    fn value(&mut self) -> Result<Value<S>, ReaderError> {
+        match self.peek() {
+            Some(b'{') => self.nested(|parser| {
+                parser.offset += 1;
+                parser.members(Some(b'}'))
+            }),
+            Some(b'[') => self.nested(Self::elements),
+            Some(b'"') => self.quoted(b'"').map(Value::String),
+            Some(b'\'') if self.rest().starts_with("'''") => self.multiline().map(Value::String),
+            Some(b'\'') => self.quoted(b'\'').map(Value::String),
+            Some(b'}' | b']' | b',' | b':') | None => Err(self.unexpected()),
+            Some(_) => Ok(self.quoteless()),
+        }
+    }
+
+    /// Reads an array or object one level deeper, failing past the depth limit
+    fn nested(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> Result<Value<S>, ReaderError>,
+    ) -> Result<Value<S>, ReaderError> {
+        if Some(self.depth) == self.max_depth {
+            let position = self.position();
+            let limit = Limit::Depth;
+            return Err(ReaderError::LimitExceeded { position, limit });
+        }
+        self.depth += 1;
+        let value = read(self);
+        self.depth -= 1;
+        value
+    }
+
+    /// Reads members up to `close`, or to the end of the input when it is `None`
+    fn members(&mut self, close: Option<u8>) -> Result<Value<S>, ReaderError> {
+        let mut map = HashMap::with_hasher(self.hasher.clone());
+        loop {
+            self.skip_whitespace()?;
+            if self.peek() == close {
+                self.offset += usize::from(close.is_some());
+                return Ok(Value::Object(map));
+            }
+            let start = self.offset;
+            let key = self.key()?;
+            self.skip_whitespace()?;
+            if self.peek() != Some(b':') {
+                return Err(self.unexpected());
+            }
+            self.offset += 1;
+            self.skip_whitespace()?;
+            let value = self.value()?;
+            match self.duplicate_keys {
+                DuplicateKeys::Error if map.contains_key(&key) => {
+                    let position = position_at(self.input.as_bytes(), start);
+                    return Err(ReaderError::DuplicateKey { position, key });
+                }
+                DuplicateKeys::First if map.contains_key(&key) => {}
+                _ => {
+                    map.insert(key, value);
+                }
+            }
+            self.separator()?;
+        }
+    }
+
+    fn elements(&mut self) -> Result<Value<S>, ReaderError> {
+        self.offset += 1;
+        let mut array = Vec::new();
+        loop {
+            self.skip_whitespace()?;
+            if self.peek() == Some(b']') {
+                self.offset += 1;
+                return Ok(Value::Array(array));
+            }
+            array.push(self.value()?);
+            self.separator()?;
+        }
+    }
+
+    /// Skips to the next member or element, past a comma if there is one
+    fn separator(&mut self) -> Result<(), ReaderError> {
+        self.skip_whitespace()?;
+        if self.peek() == Some(b',') {
+            self.offset += 1;
+        }
+        Ok(())
+    }
+
+    fn key(&mut self) -> Result<String, ReaderError> {
+        match self.peek() {
+            Some(quote @ (b'"' | b'\'')) => self.quoted(quote),
+            _ => {
+                let start = self.offset;
+                while let Some(byte) = self.peek()
+                    && !matches!(byte, b':' | b',' | b'{' | b'}' | b'[' | b']')
+                    && !byte.is_ascii_whitespace()
+                {
+                    self.offset += 1;
+                }
+                if self.offset == start {
+                    return Err(self.unexpected());
+                }
+                Ok(String::from(&self.input[start..self.offset]))
+            }
+        }
+    }
+
+    /// Reads a value without quotes, which runs to the end of the line unless it starts
+    /// with a number, `true`, `false` or `null` followed by a separator or comment
+    fn quoteless(&mut self) -> Value<S> {
+        let rest = self.rest();
+        let line = &rest[..rest.find(['\n', '\r']).unwrap_or(rest.len())];
+        let end = line
+            .find([',', ']', '}', '#', '/', ' ', '\t'])
+            .unwrap_or(line.len());
+        let after = line[end..].trim_start_matches([' ', '\t']);
+        let separated = after.is_empty()
+            || after.starts_with([',', ']', '}', '#'])
+            || after.starts_with("//")
+            || after.starts_with("/*");
+        if separated
+            && let Ok(value @ (Value::Null | Value::Boolean(_) | Value::Number(_))) =
+                read_value(JsonReader::new(&line.as_bytes()[..end]), self.hasher)
+        {
+            self.offset += end;
+            return value;
+        }
+        self.offset += line.len();
+        Value::String(String::from(line.trim_end()))
+    }
+
+    /// Reads a `"` or `'` string with JSON escapes, starting at its opening quote
+    fn quoted(&mut self, quote: u8) -> Result<String, ReaderError> {
+        self.offset += 1;
+        let mut output = String::new();
+        loop {
+            let rest = self.rest();
+            let end = rest
+                .find([char::from(quote), '\\', '\n'])
+                .ok_or(ReaderError::UnexpectedEof)?;
+            output.push_str(&rest[..end]);
+            self.offset += end;
+            match self.peek() {
+                Some(b'\\') => output.push(self.escape()?),
+                Some(b'\n') => return Err(self.unexpected()),
+                _ => {
+                    self.offset += 1;
+                    return Ok(output);
+                }
+            }
+        }
+    }
+
+    /// Reads an escape starting at its backslash
+    fn escape(&mut self) -> Result<char, ReaderError> {
+        let position = self.position();
+        let invalid = ReaderError::InvalidEscape { position };
+        self.offset += 1;
+        let byte = self.peek().ok_or(ReaderError::UnexpectedEof)?;
+        self.offset += 1;
+        let ch = match byte {
+            b'"' => '"',
+            b'\'' => '\'',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                let high = self.hex4().ok_or(ReaderError::InvalidEscape { position })?;
+                let code = if (0xd800..0xdc00).contains(&high) {
+                    let low = self
+                        .rest()
+                        .starts_with("\\u")
+                        .then(|| {
+                            self.offset += 2;
+                            self.hex4()
+                        })
+                        .flatten()
+                        .filter(|low| (0xdc00..0xe000).contains(low))
+                        .ok_or(ReaderError::InvalidEscape { position })?;
+                    0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                } else {
+                    high
+                };
+                return char::from_u32(code).ok_or(invalid);
+            }
+            _ => return Err(invalid),
+        };
+        Ok(ch)
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        let digits = self.rest().get(..4)?;
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        self.offset += 4;
+        u32::from_str_radix(digits, 16).ok()
+    }
+
+    /// Reads a `'''` string, removing the indentation of the opening quotes from each line
+    fn multiline(&mut self) -> Result<String, ReaderError> {
+        let indent = self.position().column - 1;
+        self.offset += 3;
+        let rest = self.rest();
+        let end = rest.find("'''").ok_or(ReaderError::UnexpectedEof)?;
+        let text = dedent(&rest[..end], indent);
+        self.offset += end + 3;
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DEFAULT_MAX_DEPTH, Dialect, Limit, Parser, Position, ReaderError, Value};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    #[test]
+    fn human_edited_config() {
+        let input = "
+            # server settings
+            name: my app, with commas
+            port: 8080 // inline comment
+            debug: false
+            ratio: 1.5e2
+            path: /usr/local # not a comment
+            'quoted key': \"tab\\there\"
+            servers: [
+              alpha
+              { host: \"beta\", weight: 2 }
+              null, 3
+            ]
+            /* block
+               comment */
+            motd:
+              '''
+              Hello
+                world
+              '''
+            empty: {}
+        ";
+        let expected = value(
+            r#"{"name": "my app, with commas", "port": 8080, "debug": false, "ratio": 150,
+                "path": "/usr/local # not a comment", "quoted key": "tab\there",
+                "servers": ["alpha", {"host": "beta", "weight": 2}, null, 3],
+                "motd": "Hello\n  world", "empty": {}}"#,
+        );
+        assert_eq!(Dialect::Hjson.parse(input).unwrap(), expected);
+    }
+
+    #[test]
+    fn quoteless_values() {
+        let input = "{
+            a: true story
+            b: 12 monkeys
+            c: nullable
+            d: -1e3
+            e: 5 # five
+            f: [1, 2]
+        }";
+        let expected = value(
+            r#"{"a": "true story", "b": "12 monkeys", "c": "nullable", "f": [1, 2], "e": 5}"#,
+        );
+        let mut parsed = Dialect::Hjson.parse(input).unwrap();
+        assert_eq!(parsed.remove("d"), Some(Value::Number(-1000.0)));
+        assert_eq!(parsed, expected);
+    }
+
+    #[test]
+    fn roots() {
+        let text = |s: &str| Value::string(s);
+        assert_eq!(
+            Dialect::Hjson.parse("[a, b]\n]").unwrap(),
+            Value::Array(vec![text("a, b]")])
+        );
+        assert_eq!(
+            Dialect::Hjson.parse("just text").unwrap(),
+            text("just text")
+        );
+        assert_eq!(Dialect::Hjson.parse(" 42 ").unwrap(), Value::Number(42.0));
+        assert_eq!(Dialect::Hjson.parse("# empty\n{}").unwrap(), value("{}"));
+        assert_eq!(
+            Dialect::Json.parse(r#"{"a": 1}"#).unwrap(),
+            value(r#"{"a": 1}"#)
+        );
+        assert!(Dialect::Json.parse("{a: 1}").is_err());
+        assert!(Dialect::Lenient.parse("[1, /* two */ 2,]").is_ok());
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            Dialect::Hjson.parse(""),
+            Err(ReaderError::UnexpectedEof)
+        ));
+        assert!(matches!(
+            Dialect::Hjson.parse("{a: 1"),
+            Err(ReaderError::UnexpectedEof)
+        ));
+        assert!(matches!(
+            Dialect::Hjson.parse("{\n  a 1\n}"),
+            Err(ReaderError::UnexpectedByte {
+                position: Position {
+                    offset: 6,
+                    line: 2,
+                    column: 5
+                },
+                found: b'1',
+            })
+        ));
+        assert!(matches!(
+            Dialect::Hjson.parse(r#"a: "\q""#),
+            Err(ReaderError::InvalidEscape { .. })
+        ));
+        assert!(matches!(
+            Dialect::Hjson.parse("[1]\n]"),
+            Err(ReaderError::TrailingContent { .. })
+        ));
+        assert!(matches!(
+            Dialect::Hjson.parse("a: '''x"),
+            Err(ReaderError::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn depth_limit() {
+        let deep = "[".repeat(200_000) + &"]".repeat(200_000);
+        assert!(matches!(
+            Dialect::Hjson.parse(&deep),
+            Err(ReaderError::LimitExceeded {
+                limit: Limit::Depth,
+                ..
+            })
+        ));
+        // the braceless root object counts as a level
+        let nested = "[".repeat(DEFAULT_MAX_DEPTH - 1) + &"]".repeat(DEFAULT_MAX_DEPTH - 1);
+        assert!(Dialect::Hjson.parse(&format!("a: {nested}")).is_ok());
+        assert!(Dialect::Hjson.parse(&format!("a: [{nested}]")).is_err());
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+use std::hash::Hasher;
+use std::io;
+use std::sync::Arc;
+
+use crate::hash::FnvHasher;
+use crate::{DuplicateKeys, Event, JsonReader, Position, ReaderError, SharedValue};
+
+/// A container that is still being read, with the running hash of its children
+enum Partial {
+    Array(Vec<SharedValue>, FnvHasher),
+    Object(
+        HashMap<String, SharedValue>,
+        Option<(String, Position)>,
+        u64,
+    ),
+}
+
+/// Every string and container read so far, by hash, so repeats can share one allocation
+#[derive(Default)]
+struct Interner {
+    seen: HashMap<u64, Vec<SharedValue>>,
+}
+
+impl Interner {
+    fn intern(&mut self, value: SharedValue, hash: u64) -> SharedValue {
+        let candidates = self.seen.entry(hash).or_default();
+        if let Some(existing) = candidates.iter().find(|seen| same(seen, &value)) {
+            return existing.clone();
+        }
+        candidates.push(value.clone());
+        value
+    }
+}
+
+/// Whether two values are equal, given that their children were already interned so
+/// equal children are the same allocation
+fn same(a: &SharedValue, b: &SharedValue) -> bool {
+    match (a, b) {
+        (SharedValue::String(a), SharedValue::String(b)) => a == b,
+        (SharedValue::Array(a), SharedValue::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| same_child(a, b))
+        }
+        (SharedValue::Object(a), SharedValue::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| same_child(a, b)))
+        }
+        _ => false,
+    }
+}
+
+fn same_child(a: &SharedValue, b: &SharedValue) -> bool {
+    match (a, b) {
+        (SharedValue::String(a), SharedValue::String(b)) => Arc::ptr_eq(a, b),
+        (SharedValue::Array(a), SharedValue::Array(b)) => Arc::ptr_eq(a, b),
+        (SharedValue::Object(a), SharedValue::Object(b)) => Arc::ptr_eq(a, b),
+        (SharedValue::Number(a), SharedValue::Number(b)) => a.to_bits() == b.to_bits(),
+        (a, b) => a == b,
+    }
+}
+
+fn scalar_hash(value: &SharedValue) -> u64 {
+    let mut hasher = FnvHasher::default();
+    match value {
+        SharedValue::Null => hasher.write(&[0]),
+        SharedValue::Boolean(b) => hasher.write(&[1, u8::from(*b)]),
+        SharedValue::Number(n) => {
+            hasher.write(&[2]);
+            hasher.write(&n.to_bits().to_le_bytes());
+        }
+        SharedValue::String(s) => {
+            hasher.write(&[3]);
+            hasher.write(s.as_bytes());
+        }
+        SharedValue::Array(_) | SharedValue::Object(_) => {
+            unreachable!("containers are hashed as they are read")
+        }
+    }
+    hasher.finish()
+}
+
+/// Reads a whole document, sharing one allocation between all equal strings, arrays and
+/// objects
+pub(crate) fn read_interned<R: io::Read>(
+    mut reader: JsonReader<R>,
+    duplicate_keys: DuplicateKeys,
+) -> Result<SharedValue, ReaderError> {
+    let mut interner = Interner::default();
+    let mut stack = Vec::new();
+    loop {
+        let (event, position) = reader.next_event()?.ok_or(ReaderError::UnexpectedEof)?;
+        let (value, hash) = match event {
+            Event::StartArray => {
+                let mut hasher = FnvHasher::default();
+                hasher.write(&[4]);
+                stack.push(Partial::Array(Vec::new(), hasher));
+                continue;
+            }
+            Event::StartObject => {
+                stack.push(Partial::Object(HashMap::new(), None, 5));
+                continue;
+            }
+            Event::Key(key) => {
+                if let Some(Partial::Object(_, pending, _)) = stack.last_mut() {
+                    *pending = Some((key, position));
+                }
+                continue;
+            }
+            Event::EndArray | Event::EndObject => {
+                let (value, hash) = match stack.pop() {
+                    Some(Partial::Array(array, hasher)) => {
+                        (SharedValue::Array(Arc::new(array)), hasher.finish())
+                    }
+                    Some(Partial::Object(map, _, hash)) => {
+                        (SharedValue::Object(Arc::new(map)), hash)
+                    }
+                    None => unreachable!("reader balances containers"),
+                };
+                (interner.intern(value, hash), hash)
+            }
+            Event::String(s) => {
+                let value = SharedValue::String(s.into());
+                let hash = scalar_hash(&value);
+                (interner.intern(value, hash), hash)
+            }
+            Event::Number(n) => {
+                let value = SharedValue::Number(n);
+                (value.clone(), scalar_hash(&value))
+            }
+            Event::Boolean(b) => {
+                let value = SharedValue::Boolean(b);
+                (value.clone(), scalar_hash(&value))
+            }
+            Event::Null => (SharedValue::Null, scalar_hash(&SharedValue::Null)),
+        };
+        match stack.last_mut() {
+            None => {
+                // the reader reports anything after the root value
+                reader.next_event()?;
+                return Ok(value);
+            }
+            Some(Partial::Array(array, hasher)) => {
+                hasher.write(&hash.to_le_bytes());
+                array.push(value);
+            }
+            Some(Partial::Object(map, pending, object_hash)) => {
+                let (key, position) = pending
+                    .take()
+                    .expect("reader emits a key before each value");
+                let replaced = match map.get(&key) {
+                    None => true,
+                    Some(_) if duplicate_keys == DuplicateKeys::Error => {
+                        return Err(ReaderError::DuplicateKey { position, key });
+                    }
+                    Some(_) => duplicate_keys == DuplicateKeys::Last,
+                };
+                if replaced {
+                    let mut hasher = FnvHasher::default();
+                    hasher.write(key.as_bytes());
+                    hasher.write(&hash.to_le_bytes());
+                    // members are unordered, so their hashes are combined by addition, a
+                    // replaced duplicate only makes sharing less likely
+                    *object_hash = object_hash.wrapping_add(hasher.finish());
+                    map.insert(key, value);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{DuplicateKeys, Parser, ParserOptions, ReaderError, SharedValue, Value};
+
+    #[test]
+    fn shares_repeated_subtrees() {
+        let input = r#"{
+            "a": {"flags": ["x", "y"], "on": true},
+            "b": [{"on": true, "flags": ["x", "y"]}, "x"]
+        }"#;
+        let parser = Parser::new();
+        let shared = parser.parse_interned(input.as_bytes()).unwrap();
+        assert_eq!(shared.to_value(), parser.parse_str(input).unwrap());
+
+        let (Some(SharedValue::Object(a)), Some(SharedValue::Object(b))) =
+            (shared.pointer("/a"), shared.pointer("/b/0"))
+        else {
+            panic!("expected objects");
+        };
+        assert!(Arc::ptr_eq(a, b));
+        let (Some(SharedValue::String(x)), Some(SharedValue::String(other))) =
+            (shared.pointer("/a/flags/0"), shared.pointer("/b/1"))
+        else {
+            panic!("expected strings");
+        };
+        assert!(Arc::ptr_eq(x, other));
+    }
+
+    #[test]
+    fn keeps_different_values_apart() {
+        let input = r#"[[1, 2], [2, 1], {"a": 1}, {"a": 1.5}, [0], [-0]]"#;
+        let shared = Parser::new().parse_interned(input.as_bytes()).unwrap();
+        let expected = Parser::new().parse_str(input).unwrap();
+        assert_eq!(shared.to_value(), expected);
+        let Some(SharedValue::Number(n)) = shared.pointer("/5/0") else {
+            panic!("expected a number");
+        };
+        assert!(n.is_sign_negative());
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let input = r#"{"a": 1, "a": 2}"#.as_bytes();
+        let parser = Parser::new().duplicate_keys(DuplicateKeys::First);
+        let shared = parser.parse_interned(input).unwrap();
+        assert_eq!(
+            shared.to_value(),
+            Value::object([("a", Value::Number(1.0))])
+        );
+        let parser = Parser::new().options(ParserOptions::strict());
+        assert!(matches!(
+            parser.parse_interned(input),
+            Err(ReaderError::DuplicateKey { .. })
+        ));
+    }
+}
@@ -0,0 +1,80 @@
+//! Conversions between [`Value`](crate::Value) and other data formats
+
+mod bson;
+mod cbor;
+mod csv;
+mod msgpack;
+mod query;
+#[cfg(feature = "toml")]
+mod toml;
+mod xml;
+#[cfg(feature = "yaml")]
+mod yaml;
+
+pub use bson::{BsonError, from_bson, to_bson};
+pub use cbor::{CborError, from_cbor, to_cbor};
+pub use csv::{CsvError, CsvOptions, from_csv};
+pub use msgpack::{MsgpackError, from_msgpack, to_msgpack};
+pub use query::{QueryError, from_query_string, to_query_string};
+#[cfg(feature = "toml")]
+pub use toml::{TomlError, from_toml, to_toml};
+pub use xml::{XmlError, XmlOptions, from_xml, to_xml};
+#[cfg(feature = "yaml")]
+pub use yaml::{YamlError, from_yaml, to_yaml};
+
+const BASE64URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64(bytes: &[u8], alphabet: &[u8; 64], pad: bool) -> String {
+    let mut output = String::new();
+    for chunk in bytes.chunks(3) {
+        let buffer = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, b)| acc | (*b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            let index = (buffer >> (18 - 6 * i)) & 0x3f;
+            output.push(alphabet[index as usize] as char);
+        }
+        if pad {
+            for _ in chunk.len()..3 {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+fn base16(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Decodes base64 or base64url text, padding is optional
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut output = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in text.trim_end_matches('=').bytes() {
+        let index = BASE64
+            .iter()
+            .position(|b| *b == byte)
+            .or_else(|| BASE64URL.iter().position(|b| *b == byte))?;
+        buffer = buffer << 6 | index as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+    Some(output)
+}
+
+fn decode_base16(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) || !text.is_ascii() {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok())
+        .collect()
+}
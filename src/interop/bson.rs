@@ -0,0 +1,441 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use super::{BASE64, base16, base64, decode_base16, decode_base64};
+use crate::{DEFAULT_MAX_DEPTH, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BsonError {
+    /// The top-level value must be an object, BSON has no bare scalars or arrays
+    NotADocument,
+
+    /// An object key contained a NUL byte, which BSON keys cannot hold
+    NulInKey,
+
+    /// The input ended in the middle of a document
+    UnexpectedEnd,
+
+    /// A length prefix disagrees with the data, or a terminator is missing
+    InvalidLength,
+
+    /// A string was not valid UTF-8
+    InvalidUtf8,
+
+    /// The element type has no JSON mapping, ex. `0x13` (decimal128)
+    UnsupportedType(u8),
+
+    /// There were bytes left over after the document
+    TrailingBytes,
+
+    /// Documents and arrays are nested deeper than [`DEFAULT_MAX_DEPTH`]
+    TooDeep,
+}
+
+impl fmt::Display for BsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotADocument => f.write_str("the top-level value is not an object"),
+            Self::NulInKey => f.write_str("an object key contains a NUL byte"),
+            Self::UnexpectedEnd => f.write_str("unexpected end of input"),
+            Self::InvalidLength => f.write_str("a length prefix does not match the data"),
+            Self::InvalidUtf8 => f.write_str("a string is not valid UTF-8"),
+            Self::UnsupportedType(tag) => write!(f, "unsupported element type 0x{tag:02x}"),
+            Self::TrailingBytes => f.write_str("unexpected bytes after the document"),
+            Self::TooDeep => write!(f, "nested deeper than {DEFAULT_MAX_DEPTH} levels"),
+        }
+    }
+}
+
+impl std::error::Error for BsonError {}
+
+/// Encodes an object as a BSON document
+///
+/// Integral numbers become int32 or int64 when they fit, and other numbers doubles.
+/// Objects shaped like the tagged forms described on [`from_bson`] are written as the
+/// corresponding BSON type.
+pub fn to_bson(value: &Value) -> Result<Vec<u8>, BsonError> {
+    let Value::Object(map) = value else {
+        return Err(BsonError::NotADocument);
+    };
+    let mut output = Vec::new();
+    encode_document(map.iter().map(|(k, v)| (k.as_str(), v)), &mut output)?;
+    Ok(output)
+}
+
+/// Decodes a BSON document into an object
+///
+/// BSON-only types become single-key objects, following MongoDB's extended JSON:
+///
+/// | BSON type          | JSON                                                  |
+/// |--------------------|-------------------------------------------------------|
+/// | ObjectId           | `{"$oid": "<24 hex digits>"}`                         |
+/// | UTC datetime       | `{"$date": <milliseconds since the epoch>}`           |
+/// | binary             | `{"$binary": {"base64": "...", "subType": "<hex>"}}`  |
+/// | regular expression | `{"$regularExpression": {"pattern": "...", "options": "..."}}` |
+/// | timestamp          | `{"$timestamp": {"t": <seconds>, "i": <increment>}}`  |
+/// | JavaScript code    | `{"$code": "..."}`                                    |
+/// | min/max key        | `{"$minKey": 1}` and `{"$maxKey": 1}`                 |
+///
+/// Undefined becomes `null` and symbols become strings. Decimal128, DBPointer and code
+/// with scope are not supported.
+pub fn from_bson(bytes: &[u8]) -> Result<Value, BsonError> {
+    let mut decoder = Decoder {
+        bytes,
+        pos: 0,
+        depth: 0,
+    };
+    let map = decoder.document()?;
+    if decoder.pos != bytes.len() {
+        return Err(BsonError::TrailingBytes);
+    }
+    Ok(Value::Object(map.into_iter().collect()))
+}
+
+fn encode_document<'a>(
+    entries: impl Iterator<Item = (&'a str, &'a Value)>,
+    output: &mut Vec<u8>,
+) -> Result<(), BsonError> {
+    let start = output.len();
+    output.extend([0; 4]);
+    for (key, value) in entries {
+        let kind_index = output.len();
+        output.push(0);
+        encode_cstring(key, output)?;
+        output[kind_index] = encode_element(value, output)?;
+    }
+    output.push(0);
+    let len = (output.len() - start) as i32;
+    output[start..start + 4].copy_from_slice(&len.to_le_bytes());
+    Ok(())
+}
+
+fn encode_cstring(s: &str, output: &mut Vec<u8>) -> Result<(), BsonError> {
+    if s.contains('\0') {
+        return Err(BsonError::NulInKey);
+    }
+    output.extend_from_slice(s.as_bytes());
+    output.push(0);
+    Ok(())
+}
+
+fn encode_string(s: &str, output: &mut Vec<u8>) {
+    output.extend((s.len() as i32 + 1).to_le_bytes());
+    output.extend_from_slice(s.as_bytes());
+    output.push(0);
+}
+
+/// Writes the element's contents and returns its type byte
+fn encode_element(value: &Value, output: &mut Vec<u8>) -> Result<u8, BsonError> {
+    let kind = match value {
+        Value::Null => 0x0a,
+        Value::Boolean(b) => {
+            output.push(*b as u8);
+            0x08
+        }
+        Value::Number(n) if n.fract() == 0.0 && (i32::MIN as f64..=i32::MAX as f64).contains(n) => {
+            output.extend((*n as i32).to_le_bytes());
+            0x10
+        }
+        // 2^63 is the first value outside of the i64 range
+        Value::Number(n)
+            if n.fract() == 0.0 && (i64::MIN as f64..9.223372036854776e18).contains(n) =>
+        {
+            output.extend((*n as i64).to_le_bytes());
+            0x12
+        }
+        Value::Number(n) => {
+            output.extend(n.to_le_bytes());
+            0x01
+        }
+        Value::String(s) => {
+            encode_string(s, output);
+            0x02
+        }
+        Value::Array(array) => {
+            let keys: Vec<String> = (0..array.len()).map(|i| i.to_string()).collect();
+            encode_document(keys.iter().map(String::as_str).zip(array), output)?;
+            0x04
+        }
+        Value::Object(map) => match encode_tagged(map, output) {
+            Some(kind) => kind,
+            None => {
+                encode_document(map.iter().map(|(k, v)| (k.as_str(), v)), output)?;
+                0x03
+            }
+        },
+    };
+    Ok(kind)
+}
+
+/// Writes an extended JSON object as its BSON type, `None` if it isn't one
+fn encode_tagged(map: &HashMap<String, Value>, output: &mut Vec<u8>) -> Option<u8> {
+    if map.len() != 1 {
+        return None;
+    }
+    let (key, value) = map.iter().next()?;
+    let kind = match (key.as_str(), value) {
+        ("$oid", Value::String(hex)) => {
+            let bytes = decode_base16(hex).filter(|bytes| bytes.len() == 12)?;
+            output.extend(bytes);
+            0x07
+        }
+        ("$date", Value::Number(ms)) if ms.fract() == 0.0 => {
+            output.extend((*ms as i64).to_le_bytes());
+            0x09
+        }
+        ("$binary", Value::Object(binary)) => {
+            let data = decode_base64(binary.get("base64")?.as_str()?)?;
+            let subtype = decode_base16(binary.get("subType")?.as_str()?)?;
+            let [subtype] = subtype[..] else {
+                return None;
+            };
+            output.extend((data.len() as i32).to_le_bytes());
+            output.push(subtype);
+            output.extend(data);
+            0x05
+        }
+        ("$regularExpression", Value::Object(regex)) => {
+            let pattern = regex.get("pattern")?.as_str()?;
+            let options = regex.get("options")?.as_str()?;
+            if pattern.contains('\0') || options.contains('\0') {
+                return None;
+            }
+            encode_cstring(pattern, output).ok()?;
+            encode_cstring(options, output).ok()?;
+            0x0b
+        }
+        ("$timestamp", Value::Object(timestamp)) => {
+            let increment = u32::try_from(timestamp.get("i")?.as_i64()?).ok()?;
+            let seconds = u32::try_from(timestamp.get("t")?.as_i64()?).ok()?;
+            output.extend(increment.to_le_bytes());
+            output.extend(seconds.to_le_bytes());
+            0x11
+        }
+        ("$code", Value::String(code)) => {
+            encode_string(code, output);
+            0x0d
+        }
+        ("$minKey", Value::Number(n)) if *n == 1.0 => 0xff,
+        ("$maxKey", Value::Number(n)) if *n == 1.0 => 0x7f,
+        _ => return None,
+    };
+    Some(kind)
+}
+
+fn tagged(key: &str, value: Value) -> Value {
+    Value::Object(HashMap::from([(String::from(key), value)]))
+}
+
+fn object<const N: usize>(entries: [(&str, Value); N]) -> Value {
+    Value::Object(
+        entries
+            .map(|(k, v)| (String::from(k), v))
+            .into_iter()
+            .collect(),
+    )
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], BsonError> {
+        let end = self.pos.checked_add(len).ok_or(BsonError::UnexpectedEnd)?;
+        let slice = self
+            .bytes
+            .get(self.pos..end)
+            .ok_or(BsonError::UnexpectedEnd)?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], BsonError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn i32(&mut self) -> Result<i32, BsonError> {
+        Ok(i32::from_le_bytes(self.take_array()?))
+    }
+
+    fn length(&mut self) -> Result<usize, BsonError> {
+        usize::try_from(self.i32()?).map_err(|_| BsonError::InvalidLength)
+    }
+
+    fn cstring(&mut self) -> Result<String, BsonError> {
+        let rest = &self.bytes[self.pos..];
+        let len = rest
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or(BsonError::UnexpectedEnd)?;
+        let s = std::str::from_utf8(&rest[..len]).map_err(|_| BsonError::InvalidUtf8)?;
+        self.pos += len + 1;
+        Ok(String::from(s))
+    }
+
+    fn string(&mut self) -> Result<String, BsonError> {
+        let len = self.length()?;
+        let bytes = self.take(len)?;
+        let (0, contents) = bytes.split_last().ok_or(BsonError::InvalidLength)? else {
+            return Err(BsonError::InvalidLength);
+        };
+        let s = std::str::from_utf8(contents).map_err(|_| BsonError::InvalidUtf8)?;
+        Ok(String::from(s))
+    }
+
+    /// Reads a document's elements in order, so arrays can keep theirs
+    fn document(&mut self) -> Result<Vec<(String, Value)>, BsonError> {
+        let start = self.pos;
+        let len = self.length()?;
+        let end = start.checked_add(len).ok_or(BsonError::InvalidLength)?;
+        let mut entries = Vec::new();
+        loop {
+            let [kind] = self.take_array()?;
+            if kind == 0 {
+                break;
+            }
+            let key = self.cstring()?;
+            entries.push((key, self.element(kind)?));
+        }
+        if self.pos != end {
+            return Err(BsonError::InvalidLength);
+        }
+        Ok(entries)
+    }
+
+    /// Reads an embedded document or array, failing past [`DEFAULT_MAX_DEPTH`] levels
+    fn nested(&mut self) -> Result<Vec<(String, Value)>, BsonError> {
+        if self.depth == DEFAULT_MAX_DEPTH {
+            return Err(BsonError::TooDeep);
+        }
+        self.depth += 1;
+        let entries = self.document();
+        self.depth -= 1;
+        entries
+    }
+
+    fn element(&mut self, kind: u8) -> Result<Value, BsonError> {
+        let value = match kind {
+            0x01 => Value::Number(f64::from_le_bytes(self.take_array()?)),
+            0x02 | 0x0e => Value::String(self.string()?),
+            0x03 => Value::Object(self.nested()?.into_iter().collect()),
+            0x04 => Value::Array(self.nested()?.into_iter().map(|(_, v)| v).collect()),
+            0x05 => {
+                let len = self.length()?;
+                let [subtype] = self.take_array()?;
+                let data = self.take(len)?;
+                tagged(
+                    "$binary",
+                    object([
+                        ("base64", Value::String(base64(data, BASE64, true))),
+                        ("subType", Value::String(base16(&[subtype]))),
+                    ]),
+                )
+            }
+            0x06 | 0x0a => Value::Null,
+            0x07 => tagged("$oid", Value::String(base16(self.take(12)?))),
+            0x08 => match self.take_array()? {
+                [0] => Value::Boolean(false),
+                [1] => Value::Boolean(true),
+                _ => return Err(BsonError::UnsupportedType(kind)),
+            },
+            0x09 => {
+                let ms = i64::from_le_bytes(self.take_array()?);
+                tagged("$date", Value::Number(ms as f64))
+            }
+            0x0b => {
+                let pattern = Value::String(self.cstring()?);
+                let options = Value::String(self.cstring()?);
+                tagged(
+                    "$regularExpression",
+                    object([("pattern", pattern), ("options", options)]),
+                )
+            }
+            0x0d => tagged("$code", Value::String(self.string()?)),
+            0x10 => Value::Number(self.i32()? as f64),
+            0x11 => {
+                let increment = u32::from_le_bytes(self.take_array()?);
+                let seconds = u32::from_le_bytes(self.take_array()?);
+                let t = Value::Number(seconds as f64);
+                tagged(
+                    "$timestamp",
+                    object([("t", t), ("i", Value::Number(increment as f64))]),
+                )
+            }
+            0x12 => Value::Number(i64::from_le_bytes(self.take_array()?) as f64),
+            0x7f => tagged("$maxKey", Value::Number(1.0)),
+            0xff => tagged("$minKey", Value::Number(1.0)),
+            _ => return Err(BsonError::UnsupportedType(kind)),
+        };
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::{BsonError, from_bson, to_bson};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    #[test]
+    fn known_encoding() {
+        // the example from bsonspec.org
+        let expected = b"\x16\x00\x00\x00\x02hello\x00\x06\x00\x00\x00world\x00\x00";
+        let document = value(r#"{"hello": "world"}"#);
+        assert_eq!(to_bson(&document).unwrap(), expected);
+        assert_eq!(from_bson(expected), Ok(document));
+    }
+
+    #[test]
+    fn round_trip() {
+        let original =
+            value(r#"{"n": [1, 3000000000, 2.5], "b": true, "z": null, "nested": {"s": ""}}"#);
+        assert_eq!(from_bson(&to_bson(&original).unwrap()), Ok(original));
+    }
+
+    #[test]
+    fn tagged_types_round_trip() {
+        let original = value(
+            r#"{"id": {"$oid": "507f1f77bcf86cd799439011"},
+                "at": {"$date": 1700000000000},
+                "data": {"$binary": {"base64": "AQID", "subType": "00"}},
+                "re": {"$regularExpression": {"pattern": "^a", "options": "i"}},
+                "ts": {"$timestamp": {"t": 5, "i": 1}},
+                "low": {"$minKey": 1}}"#,
+        );
+        let bytes = to_bson(&original).unwrap();
+        // the ObjectId is stored as its 12 raw bytes
+        assert!(bytes.windows(3).any(|w| w == [0x07, b'i', b'd']));
+        assert_eq!(from_bson(&bytes), Ok(original));
+    }
+
+    #[test]
+    fn invalid_documents() {
+        assert_eq!(to_bson(&value("[1]")), Err(BsonError::NotADocument));
+        assert_eq!(
+            to_bson(&value(r#"{"a\u0000": 1}"#)),
+            Err(BsonError::NulInKey)
+        );
+        assert_eq!(from_bson(b"\x05\x00\x00"), Err(BsonError::UnexpectedEnd));
+        assert_eq!(
+            from_bson(b"\x06\x00\x00\x00\x00\x00"),
+            Err(BsonError::InvalidLength)
+        );
+        let decimal = b"\x18\x00\x00\x00\x13d\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00";
+        assert_eq!(from_bson(decimal), Err(BsonError::UnsupportedType(0x13)));
+        let nested = b"\x05\x00\x00\x00\x04a\x00".repeat(200_000);
+        assert_eq!(from_bson(&nested), Err(BsonError::TooDeep));
+        let mut deepest = Value::Null;
+        for _ in 0..crate::DEFAULT_MAX_DEPTH {
+            deepest = Value::Array(vec![deepest]);
+        }
+        let deepest = Value::object([("a", deepest)]);
+        assert_eq!(from_bson(&to_bson(&deepest).unwrap()), Ok(deepest));
+    }
+}
@@ -0,0 +1,465 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use super::{BASE64, BASE64URL, base16, base64};
+use crate::{DEFAULT_MAX_DEPTH, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CborError {
+    /// The input ended in the middle of a data item
+    UnexpectedEnd,
+
+    /// The initial byte uses a reserved additional information value, ex. `0x1c`
+    InvalidInitialByte(u8),
+
+    /// A text string was not valid UTF-8
+    InvalidUtf8,
+
+    /// A "break" stop code appeared outside of an indefinite-length item
+    UnexpectedBreak,
+
+    /// There were bytes left over after the first data item
+    TrailingBytes,
+
+    /// Arrays, maps and tags are nested deeper than [`DEFAULT_MAX_DEPTH`]
+    TooDeep,
+}
+
+impl fmt::Display for CborError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => f.write_str("unexpected end of input"),
+            Self::InvalidInitialByte(initial) => write!(f, "invalid initial byte 0x{initial:02x}"),
+            Self::InvalidUtf8 => f.write_str("a text string is not valid UTF-8"),
+            Self::UnexpectedBreak => f.write_str("unexpected break stop code"),
+            Self::TrailingBytes => f.write_str("unexpected bytes after the data item"),
+            Self::TooDeep => write!(f, "nested deeper than {DEFAULT_MAX_DEPTH} levels"),
+        }
+    }
+}
+
+impl std::error::Error for CborError {}
+
+/// Encodes a value as CBOR, following RFC 8949's preferred serialization
+///
+/// Integral numbers become integers and other numbers use the shortest float that holds
+/// them exactly. Object keys are sorted in the deterministic (length-first) order.
+pub fn to_cbor(value: &Value) -> Vec<u8> {
+    let mut output = Vec::new();
+    encode(value, &mut output);
+    output
+}
+
+/// Decodes a single CBOR data item, converting it to JSON as described in RFC 8949 §6.1
+///
+/// Byte strings become base64url strings (or base64/base16 with tags 22 and 23),
+/// non-finite floats and simple values become `null`, bignums become numbers and
+/// non-string map keys are replaced with their JSON text.
+pub fn from_cbor(bytes: &[u8]) -> Result<Value, CborError> {
+    let mut decoder = Decoder {
+        bytes,
+        pos: 0,
+        depth: 0,
+    };
+    let value = decoder.value()?.ok_or(CborError::UnexpectedBreak)?;
+    if decoder.pos != bytes.len() {
+        return Err(CborError::TrailingBytes);
+    }
+    Ok(value)
+}
+
+fn encode_head(major: u8, argument: u64, output: &mut Vec<u8>) {
+    let major = major << 5;
+    match argument {
+        0..=23 => output.push(major | argument as u8),
+        24..=0xff => output.extend([major | 24, argument as u8]),
+        0x100..=0xffff => {
+            output.push(major | 25);
+            output.extend((argument as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            output.push(major | 26);
+            output.extend((argument as u32).to_be_bytes());
+        }
+        _ => {
+            output.push(major | 27);
+            output.extend(argument.to_be_bytes());
+        }
+    }
+}
+
+fn encode(value: &Value, output: &mut Vec<u8>) {
+    match value {
+        Value::Null => output.push(0xf6),
+        Value::Boolean(false) => output.push(0xf4),
+        Value::Boolean(true) => output.push(0xf5),
+        Value::Number(n) => encode_number(*n, output),
+        Value::String(s) => {
+            encode_head(3, s.len() as u64, output);
+            output.extend_from_slice(s.as_bytes());
+        }
+        Value::Array(array) => {
+            encode_head(4, array.len() as u64, output);
+            for item in array {
+                encode(item, output);
+            }
+        }
+        Value::Object(map) => {
+            encode_head(5, map.len() as u64, output);
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+            for (key, item) in entries {
+                encode_head(3, key.len() as u64, output);
+                output.extend_from_slice(key.as_bytes());
+                encode(item, output);
+            }
+        }
+    }
+}
+
+fn encode_number(n: f64, output: &mut Vec<u8>) {
+    // 2^64, the magnitude just outside of the range of either integer major type
+    const U64_LIMIT: f64 = 18_446_744_073_709_551_616.0;
+
+    if n.fract() == 0.0 && n.abs() < U64_LIMIT && !(n == 0.0 && n.is_sign_negative()) {
+        if n >= 0.0 {
+            encode_head(0, n as u64, output);
+        } else {
+            // major type 1 holds -1 - argument, computed in floating point since
+            // -2^64 itself would not fit in a u64
+            encode_head(1, (-1.0 - n) as u64, output);
+        }
+    } else if let Some(half) = to_half(n) {
+        output.push(0xf9);
+        output.extend(half.to_be_bytes());
+    } else if (n as f32) as f64 == n {
+        output.push(0xfa);
+        output.extend((n as f32).to_be_bytes());
+    } else {
+        output.push(0xfb);
+        output.extend(n.to_be_bytes());
+    }
+}
+
+/// The half-precision bits of `n`, if it can be represented exactly
+fn to_half(n: f64) -> Option<u16> {
+    if n.is_nan() {
+        return Some(0x7e00);
+    }
+    let single = n as f32;
+    if single as f64 != n {
+        return None;
+    }
+    let bits = single.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32 - 127;
+    let mantissa = bits & 0x7f_ffff;
+
+    let half = if n.is_infinite() {
+        sign | 0x7c00
+    } else if n == 0.0 {
+        sign
+    } else if (-14..=15).contains(&exponent) {
+        if mantissa & 0x1fff != 0 {
+            return None;
+        }
+        sign | (((exponent + 15) as u16) << 10) | (mantissa >> 13) as u16
+    } else if (-24..-14).contains(&exponent) {
+        let full = mantissa | 0x80_0000;
+        let shift = (-14 - exponent + 13) as u32;
+        if full & ((1 << shift) - 1) != 0 {
+            return None;
+        }
+        sign | (full >> shift) as u16
+    } else {
+        return None;
+    };
+    Some(half)
+}
+
+fn from_half(half: u16) -> f64 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((half >> 10) & 0x1f) as i32;
+    let mantissa = (half & 0x3ff) as f64;
+    let magnitude = match exponent {
+        0 => mantissa * 2f64.powi(-24),
+        31 if mantissa == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2f64.powi(exponent - 15),
+    };
+    sign * magnitude
+}
+
+/// How a byte string should be written as JSON text
+#[derive(Clone, Copy)]
+enum ByteEncoding {
+    Base64Url,
+    Base64,
+    Base16,
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+
+    /// How many arrays, maps and tags enclose the current position
+    depth: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: u64) -> Result<&'a [u8], CborError> {
+        let end = usize::try_from(len)
+            .ok()
+            .and_then(|len| self.pos.checked_add(len))
+            .ok_or(CborError::UnexpectedEnd)?;
+        let slice = self
+            .bytes
+            .get(self.pos..end)
+            .ok_or(CborError::UnexpectedEnd)?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], CborError> {
+        Ok(self.take(N as u64)?.try_into().unwrap())
+    }
+
+    /// Reads an initial byte and its argument, `None` for an indefinite length
+    fn head(&mut self) -> Result<(u8, u8, Option<u64>), CborError> {
+        let [initial] = self.take_array()?;
+        let info = initial & 0x1f;
+        let argument = match info {
+            0..=23 => Some(info as u64),
+            24 => Some(self.take_array::<1>()?[0] as u64),
+            25 => Some(u16::from_be_bytes(self.take_array()?) as u64),
+            26 => Some(u32::from_be_bytes(self.take_array()?) as u64),
+            27 => Some(u64::from_be_bytes(self.take_array()?)),
+            31 => None,
+            _ => return Err(CborError::InvalidInitialByte(initial)),
+        };
+        Ok((initial, info, argument))
+    }
+
+    /// Decodes the next data item, `None` for a break stop code
+    fn value(&mut self) -> Result<Option<Value>, CborError> {
+        self.value_with(ByteEncoding::Base64Url)
+    }
+
+    fn required(&mut self, encoding: ByteEncoding) -> Result<Value, CborError> {
+        self.value_with(encoding)?.ok_or(CborError::UnexpectedBreak)
+    }
+
+    fn value_with(&mut self, encoding: ByteEncoding) -> Result<Option<Value>, CborError> {
+        let (initial, info, argument) = self.head()?;
+        let value = match (initial >> 5, argument) {
+            (0, Some(n)) => Value::Number(n as f64),
+            (1, Some(n)) => Value::Number(-1.0 - n as f64),
+            (2, _) => {
+                let bytes = self.string_bytes(2, argument)?;
+                Value::String(match encoding {
+                    ByteEncoding::Base64Url => base64(&bytes, BASE64URL, false),
+                    ByteEncoding::Base64 => base64(&bytes, BASE64, true),
+                    ByteEncoding::Base16 => base16(&bytes),
+                })
+            }
+            (3, _) => {
+                let bytes = self.string_bytes(3, argument)?;
+                let s = String::from_utf8(bytes).map_err(|_| CborError::InvalidUtf8)?;
+                Value::String(s)
+            }
+            (4, _) => self.nested(|decoder| decoder.array(argument, encoding))?,
+            (5, _) => self.nested(|decoder| decoder.map(argument, encoding))?,
+            (6, Some(tag)) => self.nested(|decoder| decoder.tagged(tag, encoding))?,
+            (7, None) => return Ok(None),
+            (7, Some(_)) => match info {
+                20 => Value::Boolean(false),
+                21 => Value::Boolean(true),
+                25 => finite(from_half(argument.unwrap() as u16)),
+                26 => finite(f32::from_bits(argument.unwrap() as u32) as f64),
+                27 => finite(f64::from_bits(argument.unwrap())),
+                _ => Value::Null,
+            },
+            _ => return Err(CborError::InvalidInitialByte(initial)),
+        };
+        Ok(Some(value))
+    }
+
+    /// Counts a level of nesting around `read`, which reads an array, map or tag
+    fn nested(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> Result<Value, CborError>,
+    ) -> Result<Value, CborError> {
+        if self.depth == DEFAULT_MAX_DEPTH {
+            return Err(CborError::TooDeep);
+        }
+        self.depth += 1;
+        let value = read(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn array(&mut self, len: Option<u64>, encoding: ByteEncoding) -> Result<Value, CborError> {
+        let mut array = Vec::new();
+        while len.is_none_or(|len| (array.len() as u64) < len) {
+            match self.value_with(encoding)? {
+                Some(item) => array.push(item),
+                None if len.is_none() => break,
+                None => return Err(CborError::UnexpectedBreak),
+            }
+        }
+        Ok(Value::Array(array))
+    }
+
+    fn map(&mut self, len: Option<u64>, encoding: ByteEncoding) -> Result<Value, CborError> {
+        let mut map = HashMap::new();
+        let mut count = 0;
+        while len.is_none_or(|len| count < len) {
+            let key = match self.value_with(encoding)? {
+                Some(Value::String(key)) => key,
+                Some(other) => other.to_string(),
+                None if len.is_none() => break,
+                None => return Err(CborError::UnexpectedBreak),
+            };
+            map.insert(key, self.required(encoding)?);
+            count += 1;
+        }
+        Ok(Value::Object(map))
+    }
+
+    fn tagged(&mut self, tag: u64, encoding: ByteEncoding) -> Result<Value, CborError> {
+        match tag {
+            // unsigned and negative bignums
+            2 | 3 => {
+                let start = self.pos;
+                let (initial, _, len) = self.head()?;
+                if initial >> 5 != 2 {
+                    self.pos = start;
+                    return self.required(encoding);
+                }
+                let bytes = self.string_bytes(2, len)?;
+                let n = bytes.iter().fold(0.0, |acc, b| acc * 256.0 + *b as f64);
+                Ok(Value::Number(if tag == 2 { n } else { -1.0 - n }))
+            }
+            21 => self.required(ByteEncoding::Base64Url),
+            22 => self.required(ByteEncoding::Base64),
+            23 => self.required(ByteEncoding::Base16),
+            _ => self.required(encoding),
+        }
+    }
+
+    /// Reads the contents of a byte or text string, joining indefinite-length chunks
+    fn string_bytes(&mut self, major: u8, len: Option<u64>) -> Result<Vec<u8>, CborError> {
+        let Some(len) = len else {
+            let mut bytes = Vec::new();
+            loop {
+                let (initial, _, len) = self.head()?;
+                if initial == 0xff {
+                    return Ok(bytes);
+                }
+                if initial >> 5 != major || len.is_none() {
+                    return Err(CborError::InvalidInitialByte(initial));
+                }
+                bytes.extend(self.string_bytes(major, len)?);
+            }
+        };
+        Ok(self.take(len)?.to_vec())
+    }
+}
+
+fn finite(n: f64) -> Value {
+    if n.is_finite() {
+        Value::Number(n)
+    } else {
+        Value::Null
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::{CborError, from_cbor, to_cbor};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    fn hex(input: &str) -> Vec<u8> {
+        (0..input.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&input[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    // examples from RFC 8949 appendix A
+    #[test]
+    fn rfc_encodings() {
+        let cases = [
+            (Value::Number(0.0), "00"),
+            (Value::Number(24.0), "1818"),
+            (Value::Number(1000000.0), "1a000f4240"),
+            (Value::Number(-1000.0), "3903e7"),
+            (Value::Number(1.5), "f93e00"),
+            (Value::Number(-0.0), "f98000"),
+            (Value::Number(100000.5), "fa47c35040"),
+            (Value::Number(1.1), "fb3ff199999999999a"),
+            (Value::Number(5.960464477539063e-8), "f90001"),
+            (Value::Boolean(false), "f4"),
+            (Value::Null, "f6"),
+            (value(r#""IETF""#), "6449455446"),
+            (value(r#"[1, [2, 3]]"#), "8201820203"),
+            (value(r#"{"b": 1, "aa": 2}"#), "a261620162616102"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(to_cbor(&value), hex(expected), "{value:?}");
+            assert_eq!(from_cbor(&hex(expected)), Ok(value));
+        }
+    }
+
+    #[test]
+    fn cbor_to_json_mapping() {
+        // byte strings, tagged base16, bignums, undefined, infinity and an integer key
+        let cases = [
+            ("4401020304", r#""AQIDBA""#),
+            ("d74401020304", r#""01020304""#),
+            ("c249010000000000000000", "18446744073709551616"),
+            ("f7", "null"),
+            ("f97c00", "null"),
+            ("a10102", r#"{"1": 2}"#),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(from_cbor(&hex(input)), Ok(value(expected)), "{input}");
+        }
+    }
+
+    #[test]
+    fn indefinite_lengths() {
+        let input = hex("bf61610161629f0203ffff");
+        assert_eq!(from_cbor(&input), Ok(value(r#"{"a": 1, "b": [2, 3]}"#)));
+        assert_eq!(
+            from_cbor(&hex("7f657374726561646d696e67ff")),
+            Ok(value(r#""streaming""#))
+        );
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert_eq!(
+            from_cbor(&hex("1c")),
+            Err(CborError::InvalidInitialByte(0x1c))
+        );
+        assert_eq!(from_cbor(&hex("8201")), Err(CborError::UnexpectedEnd));
+        assert_eq!(from_cbor(&hex("ff")), Err(CborError::UnexpectedBreak));
+        assert_eq!(
+            CborError::InvalidInitialByte(0x1c).to_string(),
+            "invalid initial byte 0x1c"
+        );
+        assert_eq!(from_cbor(&hex("0000")), Err(CborError::TrailingBytes));
+        assert_eq!(from_cbor(&hex("62c328")), Err(CborError::InvalidUtf8));
+        assert_eq!(from_cbor(&[0x81; 200_000]), Err(CborError::TooDeep));
+        assert_eq!(from_cbor(&[0xc6; 200_000]), Err(CborError::TooDeep));
+        let mut nested = vec![0x81; crate::DEFAULT_MAX_DEPTH];
+        nested.push(0xf6);
+        assert!(from_cbor(&nested).is_ok());
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::Value;
+
+/// How delimited text is read
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    /// Field separator, `,` for CSV and `\t` for TSV
+    pub delimiter: char,
+
+    /// Whether unquoted fields are converted to numbers, booleans and nulls
+    ///
+    /// Empty fields and `null` become `null`, `true` and `false` become booleans and
+    /// anything that reads as a decimal number becomes a number. Quoted fields are
+    /// always strings.
+    pub infer_types: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            infer_types: true,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Tab-separated values
+    pub fn tsv() -> Self {
+        Self {
+            delimiter: '\t',
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvError {
+    /// The input has no header row
+    MissingHeader,
+
+    /// A record has a different number of fields than the header, `line` starts at 1
+    FieldCount {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+
+    /// A quoted field is never closed
+    UnterminatedQuote { line: usize },
+
+    /// A quote appears in the middle of an unquoted field, or text follows a closing
+    /// quote
+    UnexpectedQuote { line: usize },
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHeader => f.write_str("the input has no header row"),
+            Self::FieldCount {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected {expected} fields on line {line}, found {found}"
+            ),
+            Self::UnterminatedQuote { line } => {
+                write!(f, "a quoted field starting on line {line} is never closed")
+            }
+            Self::UnexpectedQuote { line } => write!(f, "unexpected quote on line {line}"),
+        }
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+/// A field and whether it was quoted
+struct Field {
+    text: String,
+    quoted: bool,
+}
+
+/// Reads delimited text with a header row into an array of objects
+pub fn from_csv(input: &str, options: &CsvOptions) -> Result<Value, CsvError> {
+    let mut records = records(input, options.delimiter)?.into_iter();
+    let (_, header) = records.next().ok_or(CsvError::MissingHeader)?;
+
+    let mut rows = Vec::new();
+    for (line, record) in records {
+        if record.len() != header.len() {
+            return Err(CsvError::FieldCount {
+                line,
+                expected: header.len(),
+                found: record.len(),
+            });
+        }
+        let row: HashMap<String, Value> = header
+            .iter()
+            .zip(record)
+            .map(|(name, field)| (name.text.clone(), convert(field, options)))
+            .collect();
+        rows.push(Value::Object(row));
+    }
+    Ok(Value::Array(rows))
+}
+
+fn convert(field: Field, options: &CsvOptions) -> Value {
+    if field.quoted || !options.infer_types {
+        return Value::String(field.text);
+    }
+    match field.text.as_str() {
+        "" | "null" => Value::Null,
+        "true" => Value::Boolean(true),
+        "false" => Value::Boolean(false),
+        text if is_number(text) => Value::Number(text.parse().unwrap()),
+        _ => Value::String(field.text),
+    }
+}
+
+/// Whether the text is a plain decimal number, so values like `inf` or `1_000` stay
+/// strings
+fn is_number(text: &str) -> bool {
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    let mantissa = digits.split(['e', 'E']).next().unwrap_or_default();
+    digits.starts_with(|ch: char| ch.is_ascii_digit())
+        && mantissa.bytes().all(|b| b.is_ascii_digit() || b == b'.')
+        && text.parse::<f64>().is_ok()
+}
+
+/// Splits the input into records of fields, with the line each record starts on
+fn records(input: &str, delimiter: char) -> Result<Vec<(usize, Vec<Field>)>, CsvError> {
+    let mut records = Vec::new();
+    let mut chars = input.chars().peekable();
+    let mut line = 1;
+
+    while chars.peek().is_some() {
+        let start_line = line;
+        let mut record = Vec::new();
+        loop {
+            let mut field = Field {
+                text: String::new(),
+                quoted: false,
+            };
+            if chars.peek() == Some(&'"') {
+                chars.next();
+                field.quoted = true;
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.text.push('"');
+                        }
+                        Some('"') => break,
+                        Some(ch) => {
+                            if ch == '\n' {
+                                line += 1;
+                            }
+                            field.text.push(ch);
+                        }
+                        None => return Err(CsvError::UnterminatedQuote { line: start_line }),
+                    }
+                }
+            }
+            let ended_record = loop {
+                match chars.next() {
+                    Some(ch) if ch == delimiter => break false,
+                    Some('\r') if chars.peek() == Some(&'\n') => {}
+                    Some('\n') | None => break true,
+                    Some('"') => return Err(CsvError::UnexpectedQuote { line }),
+                    Some(_) if field.quoted => return Err(CsvError::UnexpectedQuote { line }),
+                    Some(ch) => field.text.push(ch),
+                }
+            };
+            record.push(field);
+            if ended_record {
+                break;
+            }
+        }
+        line += 1;
+        // skip blank lines, ex. a trailing newline at the end of the file
+        if !(record.len() == 1 && record[0].text.is_empty() && !record[0].quoted) {
+            records.push((start_line, record));
+        }
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::{CsvError, CsvOptions, from_csv};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    #[test]
+    fn infers_types() {
+        let input = "name,age,admin,note\nken,35,true,\n\"ryu\",-1.5,false,null\n";
+        // the parser doesn't read negative numbers, so the second age is filled in after
+        let mut expected = value(
+            r#"[{"name": "ken", "age": 35, "admin": true, "note": null},
+                {"name": "ryu", "age": 0, "admin": false, "note": null}]"#,
+        );
+        expected.set_pointer("/1/age", Value::Number(-1.5)).unwrap();
+        assert_eq!(from_csv(input, &CsvOptions::default()), Ok(expected));
+    }
+
+    #[test]
+    fn quoted_fields_stay_strings() {
+        let input = "a,b\r\n\"1\",\"x, \"\"y\"\"\nz\"\r\n";
+        let expected = value(r#"[{"a": "1", "b": "x, \"y\"\nz"}]"#);
+        assert_eq!(from_csv(input, &CsvOptions::default()), Ok(expected));
+    }
+
+    #[test]
+    fn without_inference() {
+        let options = CsvOptions {
+            infer_types: false,
+            ..CsvOptions::tsv()
+        };
+        let actual = from_csv("a\tb\n1\ttrue", &options);
+        assert_eq!(actual, Ok(value(r#"[{"a": "1", "b": "true"}]"#)));
+        let actual = from_csv("a\tb\ninf\t1_0", &CsvOptions::tsv());
+        assert_eq!(actual, Ok(value(r#"[{"a": "inf", "b": "1_0"}]"#)));
+    }
+
+    #[test]
+    fn errors() {
+        let options = CsvOptions::default();
+        assert_eq!(from_csv("", &options), Err(CsvError::MissingHeader));
+        let field_count = CsvError::FieldCount {
+            line: 3,
+            expected: 2,
+            found: 1,
+        };
+        assert_eq!(from_csv("a,b\n1,2\n3", &options), Err(field_count));
+        assert_eq!(
+            from_csv("a\n\"open", &options),
+            Err(CsvError::UnterminatedQuote { line: 2 })
+        );
+        assert_eq!(
+            from_csv("a\nx\"y", &options),
+            Err(CsvError::UnexpectedQuote { line: 2 })
+        );
+    }
+}
@@ -0,0 +1,426 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{DEFAULT_MAX_DEPTH, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MsgpackError {
+    /// The input ended in the middle of a value
+    UnexpectedEnd,
+
+    /// The byte does not start any MessagePack value, ex. `0xc1`
+    InvalidMarker(u8),
+
+    /// A string was not valid UTF-8
+    InvalidUtf8,
+
+    /// A map key was not a string, which JSON objects cannot represent
+    NonStringKey,
+
+    /// Extension types have no JSON equivalent
+    UnsupportedExtension(i8),
+
+    /// There were bytes left over after the first value
+    TrailingBytes,
+
+    /// Arrays and maps are nested deeper than [`DEFAULT_MAX_DEPTH`]
+    TooDeep,
+}
+
+impl fmt::Display for MsgpackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => f.write_str("unexpected end of input"),
+            Self::InvalidMarker(marker) => write!(f, "invalid marker byte 0x{marker:02x}"),
+            Self::InvalidUtf8 => f.write_str("a string is not valid UTF-8"),
+            Self::NonStringKey => f.write_str("a map key is not a string"),
+            Self::UnsupportedExtension(kind) => write!(f, "unsupported extension type {kind}"),
+            Self::TrailingBytes => f.write_str("unexpected bytes after the value"),
+            Self::TooDeep => write!(f, "nested deeper than {DEFAULT_MAX_DEPTH} levels"),
+        }
+    }
+}
+
+impl std::error::Error for MsgpackError {}
+
+/// Encodes a value as MessagePack
+///
+/// Integral numbers use the smallest integer encoding and all other numbers, including
+/// `-0.0`, are written as 64-bit floats. Object keys are written in sorted order.
+pub fn to_msgpack(value: &Value) -> Vec<u8> {
+    let mut output = Vec::new();
+    encode(value, &mut output);
+    output
+}
+
+/// Decodes a single MessagePack value, binary data becomes an array of byte values
+pub fn from_msgpack(bytes: &[u8]) -> Result<Value, MsgpackError> {
+    let mut decoder = Decoder {
+        bytes,
+        pos: 0,
+        depth: 0,
+    };
+    let value = decoder.value()?;
+    if decoder.pos != bytes.len() {
+        return Err(MsgpackError::TrailingBytes);
+    }
+    Ok(value)
+}
+
+fn encode(value: &Value, output: &mut Vec<u8>) {
+    match value {
+        Value::Null => output.push(0xc0),
+        Value::Boolean(false) => output.push(0xc2),
+        Value::Boolean(true) => output.push(0xc3),
+        Value::Number(n) => encode_number(*n, output),
+        Value::String(s) => {
+            encode_length(s.len(), [0xa0, 0xd9, 0xda, 0xdb], 32, output);
+            output.extend_from_slice(s.as_bytes());
+        }
+        Value::Array(array) => {
+            encode_length(array.len(), [0x90, 0x00, 0xdc, 0xdd], 16, output);
+            for item in array {
+                encode(item, output);
+            }
+        }
+        Value::Object(map) => {
+            encode_length(map.len(), [0x80, 0x00, 0xde, 0xdf], 16, output);
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            for (key, item) in entries {
+                encode(&Value::String(key.clone()), output);
+                encode(item, output);
+            }
+        }
+    }
+}
+
+/// Writes a length prefix, `markers` are the fixed, 8, 16 and 32-bit forms, where a
+/// zero marker means the 8-bit form does not exist
+fn encode_length(len: usize, markers: [u8; 4], fixed_limit: usize, output: &mut Vec<u8>) {
+    let [fixed, len8, len16, len32] = markers;
+    if len < fixed_limit {
+        output.push(fixed | len as u8);
+    } else if len8 != 0 && len <= u8::MAX as usize {
+        output.extend([len8, len as u8]);
+    } else if len <= u16::MAX as usize {
+        output.push(len16);
+        output.extend((len as u16).to_be_bytes());
+    } else {
+        output.push(len32);
+        output.extend((len as u32).to_be_bytes());
+    }
+}
+
+fn encode_number(n: f64, output: &mut Vec<u8>) {
+    // 2^64, the first value outside of the u64 range
+    const U64_LIMIT: f64 = 18_446_744_073_709_551_616.0;
+
+    let integral = n == n.trunc() && !(n == 0.0 && n.is_sign_negative());
+    if !integral || !(i64::MIN as f64..U64_LIMIT).contains(&n) {
+        output.push(0xcb);
+        output.extend(n.to_be_bytes());
+    } else if n >= 0.0 {
+        let n = n as u64;
+        match n {
+            0..=0x7f => output.push(n as u8),
+            0x80..=0xff => output.extend([0xcc, n as u8]),
+            0x100..=0xffff => {
+                output.push(0xcd);
+                output.extend((n as u16).to_be_bytes());
+            }
+            0x1_0000..=0xffff_ffff => {
+                output.push(0xce);
+                output.extend((n as u32).to_be_bytes());
+            }
+            _ => {
+                output.push(0xcf);
+                output.extend(n.to_be_bytes());
+            }
+        }
+    } else {
+        let n = n as i64;
+        if n >= -32 {
+            output.push(n as u8);
+        } else if n >= i8::MIN as i64 {
+            output.extend([0xd0, n as u8]);
+        } else if n >= i16::MIN as i64 {
+            output.push(0xd1);
+            output.extend((n as i16).to_be_bytes());
+        } else if n >= i32::MIN as i64 {
+            output.push(0xd2);
+            output.extend((n as i32).to_be_bytes());
+        } else {
+            output.push(0xd3);
+            output.extend(n.to_be_bytes());
+        }
+    }
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+
+    /// How many arrays and maps enclose the current position
+    depth: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], MsgpackError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .ok_or(MsgpackError::UnexpectedEnd)?;
+        let slice = self
+            .bytes
+            .get(self.pos..end)
+            .ok_or(MsgpackError::UnexpectedEnd)?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], MsgpackError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn byte(&mut self) -> Result<u8, MsgpackError> {
+        Ok(self.take_array::<1>()?[0])
+    }
+
+    fn len8(&mut self) -> Result<usize, MsgpackError> {
+        Ok(self.byte()? as usize)
+    }
+
+    fn len16(&mut self) -> Result<usize, MsgpackError> {
+        Ok(u16::from_be_bytes(self.take_array()?) as usize)
+    }
+
+    fn len32(&mut self) -> Result<usize, MsgpackError> {
+        Ok(u32::from_be_bytes(self.take_array()?) as usize)
+    }
+
+    fn value(&mut self) -> Result<Value, MsgpackError> {
+        let marker = self.byte()?;
+        let value = match marker {
+            0x00..=0x7f => Value::Number(marker as f64),
+            0x80..=0x8f => self.map(marker as usize & 0x0f)?,
+            0x90..=0x9f => self.array(marker as usize & 0x0f)?,
+            0xa0..=0xbf => self.string(marker as usize & 0x1f)?,
+            0xc0 => Value::Null,
+            0xc2 => Value::Boolean(false),
+            0xc3 => Value::Boolean(true),
+            0xc4 => {
+                let len = self.len8()?;
+                self.binary(len)?
+            }
+            0xc5 => {
+                let len = self.len16()?;
+                self.binary(len)?
+            }
+            0xc6 => {
+                let len = self.len32()?;
+                self.binary(len)?
+            }
+            0xc7 => {
+                let len = self.len8()?;
+                self.extension(len)?
+            }
+            0xc8 => {
+                let len = self.len16()?;
+                self.extension(len)?
+            }
+            0xc9 => {
+                let len = self.len32()?;
+                self.extension(len)?
+            }
+            0xca => Value::Number(f32::from_be_bytes(self.take_array()?) as f64),
+            0xcb => Value::Number(f64::from_be_bytes(self.take_array()?)),
+            0xcc => Value::Number(self.byte()? as f64),
+            0xcd => Value::Number(u16::from_be_bytes(self.take_array()?) as f64),
+            0xce => Value::Number(u32::from_be_bytes(self.take_array()?) as f64),
+            0xcf => Value::Number(u64::from_be_bytes(self.take_array()?) as f64),
+            0xd0 => Value::Number(i8::from_be_bytes(self.take_array()?) as f64),
+            0xd1 => Value::Number(i16::from_be_bytes(self.take_array()?) as f64),
+            0xd2 => Value::Number(i32::from_be_bytes(self.take_array()?) as f64),
+            0xd3 => Value::Number(i64::from_be_bytes(self.take_array()?) as f64),
+            0xd4..=0xd8 => self.extension(1 << (marker - 0xd4))?,
+            0xd9 => {
+                let len = self.len8()?;
+                self.string(len)?
+            }
+            0xda => {
+                let len = self.len16()?;
+                self.string(len)?
+            }
+            0xdb => {
+                let len = self.len32()?;
+                self.string(len)?
+            }
+            0xdc => {
+                let len = self.len16()?;
+                self.array(len)?
+            }
+            0xdd => {
+                let len = self.len32()?;
+                self.array(len)?
+            }
+            0xde => {
+                let len = self.len16()?;
+                self.map(len)?
+            }
+            0xdf => {
+                let len = self.len32()?;
+                self.map(len)?
+            }
+            0xe0..=0xff => Value::Number(marker as i8 as f64),
+            0xc1 => return Err(MsgpackError::InvalidMarker(marker)),
+        };
+        Ok(value)
+    }
+
+    fn string(&mut self, len: usize) -> Result<Value, MsgpackError> {
+        let bytes = self.take(len)?;
+        let s = std::str::from_utf8(bytes).map_err(|_| MsgpackError::InvalidUtf8)?;
+        Ok(Value::String(String::from(s)))
+    }
+
+    fn binary(&mut self, len: usize) -> Result<Value, MsgpackError> {
+        let bytes = self.take(len)?;
+        Ok(Value::Array(
+            bytes.iter().map(|b| Value::Number(*b as f64)).collect(),
+        ))
+    }
+
+    fn extension(&mut self, _len: usize) -> Result<Value, MsgpackError> {
+        let kind = self.byte()? as i8;
+        Err(MsgpackError::UnsupportedExtension(kind))
+    }
+
+    /// Counts a level of nesting around `read`, which reads an array or map
+    fn nested(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> Result<Value, MsgpackError>,
+    ) -> Result<Value, MsgpackError> {
+        if self.depth == DEFAULT_MAX_DEPTH {
+            return Err(MsgpackError::TooDeep);
+        }
+        self.depth += 1;
+        let value = read(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn array(&mut self, len: usize) -> Result<Value, MsgpackError> {
+        self.nested(|decoder| {
+            // don't trust the length for the allocation, each element takes at least a byte
+            let mut array = Vec::with_capacity(len.min(decoder.bytes.len() - decoder.pos));
+            for _ in 0..len {
+                array.push(decoder.value()?);
+            }
+            Ok(Value::Array(array))
+        })
+    }
+
+    fn map(&mut self, len: usize) -> Result<Value, MsgpackError> {
+        self.nested(|decoder| {
+            let mut map = HashMap::new();
+            for _ in 0..len {
+                let Value::String(key) = decoder.value()? else {
+                    return Err(MsgpackError::NonStringKey);
+                };
+                let value = decoder.value()?;
+                map.insert(key, value);
+            }
+            Ok(Value::Object(map))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::{MsgpackError, from_msgpack, to_msgpack};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    #[test]
+    fn known_encodings() {
+        assert_eq!(to_msgpack(&Value::Null), [0xc0]);
+        assert_eq!(to_msgpack(&Value::Number(5.0)), [0x05]);
+        assert_eq!(to_msgpack(&Value::Number(-1.0)), [0xff]);
+        assert_eq!(to_msgpack(&Value::Number(200.0)), [0xcc, 200]);
+        assert_eq!(to_msgpack(&Value::Number(-200.0)), [0xd1, 0xff, 0x38]);
+        assert_eq!(
+            to_msgpack(&Value::Number(1.5)),
+            [0xcb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(to_msgpack(&Value::Number(0.0)), [0x00]);
+        assert_eq!(
+            to_msgpack(&Value::Number(-0.0)),
+            [0xcb, 0x80, 0, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(
+            to_msgpack(&value(r#"{"a": [true]}"#)),
+            [0x81, 0xa1, b'a', 0x91, 0xc3]
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let long = "x".repeat(300);
+        let mut original = value(&format!(
+            r#"{{"n": [0, 127, 128, 65536, 4294967296, 0.25],
+                "s": ["", "{long}"], "nested": {{"b": false, "z": null}}}}"#
+        ));
+        let negative = [-33.0, -40000.0, -1e12].map(Value::Number);
+        original
+            .set_pointer("/negative", Value::Array(negative.to_vec()))
+            .unwrap();
+        assert_eq!(from_msgpack(&to_msgpack(&original)), Ok(original));
+    }
+
+    #[test]
+    fn decodes_other_encodings() {
+        // float32 1.0, and binary data
+        assert_eq!(
+            from_msgpack(&[0xca, 0x3f, 0x80, 0, 0]),
+            Ok(Value::Number(1.0))
+        );
+        assert_eq!(from_msgpack(&[0xc4, 2, 7, 8]), Ok(value("[7, 8]")));
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert_eq!(
+            from_msgpack(&[0xc1]),
+            Err(MsgpackError::InvalidMarker(0xc1))
+        );
+        assert_eq!(
+            MsgpackError::InvalidMarker(0xc1).to_string(),
+            "invalid marker byte 0xc1"
+        );
+        assert_eq!(
+            from_msgpack(&[0x92, 0x01]),
+            Err(MsgpackError::UnexpectedEnd)
+        );
+        assert_eq!(
+            from_msgpack(&[0x81, 0x01, 0x01]),
+            Err(MsgpackError::NonStringKey)
+        );
+        assert_eq!(
+            from_msgpack(&[0xc0, 0xc0]),
+            Err(MsgpackError::TrailingBytes)
+        );
+        assert_eq!(
+            from_msgpack(&[0xd4, 0x01, 0x00]),
+            Err(MsgpackError::UnsupportedExtension(1))
+        );
+        assert_eq!(from_msgpack(&[0x91; 200_000]), Err(MsgpackError::TooDeep));
+        let mut nested = vec![0x91; crate::DEFAULT_MAX_DEPTH];
+        nested.push(0xc0);
+        assert!(from_msgpack(&nested).is_ok());
+    }
+}
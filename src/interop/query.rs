@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::Value;
+use crate::pointer::array_index;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+    /// A `%` is not followed by two hex digits, or the bytes are not UTF-8
+    InvalidEncoding,
+
+    /// A key is used both as a plain value and as a container, ex. `a=1&a[b]=2`
+    Conflict(String),
+
+    /// The top-level value must be an object
+    NotAnObject,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidEncoding => f.write_str("invalid percent-encoding"),
+            Self::Conflict(key) => write!(f, "`{key}` is both a value and a container"),
+            Self::NotAnObject => f.write_str("the top-level value is not an object"),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// A bracketed segment of a key, ex. `[]` or `[name]`
+enum Segment {
+    Append,
+    Key(String),
+}
+
+/// Parses a URL query string or form body into an object of strings
+///
+/// Keys nest with brackets: `a[b]=1` gives `{"a": {"b": "1"}}` and `a[]=1&a[]=2` gives
+/// `{"a": ["1", "2"]}`. A numeric segment like `a[0]` indexes an array when the index
+/// is at most the current length, otherwise it is an object key. A plain key that
+/// repeats collects its values into an array. Values are never converted from strings.
+pub fn from_query_string(input: &str) -> Result<Value, QueryError> {
+    let input = input.strip_prefix('?').unwrap_or(input);
+    let mut root = Value::Object(HashMap::new());
+    for pair in input.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let key = decode(key)?;
+        let value = Value::String(decode(value)?);
+        let (name, segments) = split_key(&key);
+        insert(&mut root, name, &segments, value)
+            .ok_or_else(|| QueryError::Conflict(key.clone()))?;
+    }
+    Ok(root)
+}
+
+/// Writes an object as a query string, the reverse of [`from_query_string`]
+///
+/// Nested objects use `a[b]=` keys, arrays of scalars `a[]=` and arrays containing
+/// containers indexed keys like `a[0][b]=`. Empty containers are left out, `null`
+/// becomes an empty value and keys are written in sorted order.
+pub fn to_query_string(value: &Value) -> Result<String, QueryError> {
+    let Value::Object(map) = value else {
+        return Err(QueryError::NotAnObject);
+    };
+    let mut pairs = Vec::new();
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by_key(|(key, _)| *key);
+    for (key, item) in entries {
+        write_pairs(&encode(key), item, &mut pairs);
+    }
+    Ok(pairs.join("&"))
+}
+
+fn write_pairs(prefix: &str, value: &Value, pairs: &mut Vec<String>) {
+    let scalar = match value {
+        Value::Null => String::new(),
+        Value::Boolean(b) => b.to_string(),
+        Value::Number(n) => crate::serialize::format_number(*n),
+        Value::String(s) => encode(s),
+        Value::Array(array) => {
+            let nested = array
+                .iter()
+                .any(|item| matches!(item, Value::Array(_) | Value::Object(_)));
+            for (i, item) in array.iter().enumerate() {
+                let key = if nested {
+                    format!("{prefix}[{i}]")
+                } else {
+                    format!("{prefix}[]")
+                };
+                write_pairs(&key, item, pairs);
+            }
+            return;
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            for (key, item) in entries {
+                write_pairs(&format!("{prefix}[{}]", encode(key)), item, pairs);
+            }
+            return;
+        }
+    };
+    pairs.push(format!("{prefix}={scalar}"));
+}
+
+fn split_key(key: &str) -> (&str, Vec<Segment>) {
+    let Some(open) = key.find('[').filter(|open| *open > 0) else {
+        return (key, Vec::new());
+    };
+    let mut segments = Vec::new();
+    let mut rest = &key[open..];
+    while let Some(inner) = rest.strip_prefix('[') {
+        let Some(close) = inner.find(']') else {
+            // unbalanced brackets make the whole thing a plain key
+            return (key, Vec::new());
+        };
+        segments.push(match &inner[..close] {
+            "" => Segment::Append,
+            name => Segment::Key(String::from(name)),
+        });
+        rest = &inner[close + 1..];
+    }
+    if !rest.is_empty() {
+        return (key, Vec::new());
+    }
+    (&key[..open], segments)
+}
+
+/// Inserts `value` under `name` and then `segments`, `None` on a conflict
+fn insert(container: &mut Value, name: &str, segments: &[Segment], value: Value) -> Option<()> {
+    let Value::Object(map) = container else {
+        return None;
+    };
+    let Some((first, rest)) = segments.split_first() else {
+        match map.get_mut(name) {
+            None => {
+                map.insert(String::from(name), value);
+            }
+            Some(Value::String(_)) => {
+                let existing = map.remove(name)?;
+                map.insert(String::from(name), Value::Array(vec![existing, value]));
+            }
+            Some(Value::Array(array))
+                if array.iter().all(|item| matches!(item, Value::String(_))) =>
+            {
+                array.push(value);
+            }
+            Some(_) => return None,
+        }
+        return Some(());
+    };
+
+    let child = map
+        .entry(String::from(name))
+        .or_insert_with(|| match first {
+            Segment::Append => Value::Array(Vec::new()),
+            Segment::Key(key) if array_index(key) == Some(0) => Value::Array(Vec::new()),
+            Segment::Key(_) => Value::Object(HashMap::new()),
+        });
+    insert_segment(child, first, rest, value)
+}
+
+fn insert_segment(
+    container: &mut Value,
+    segment: &Segment,
+    rest: &[Segment],
+    value: Value,
+) -> Option<()> {
+    let index = match (&*container, segment) {
+        (Value::Array(array), Segment::Append) => Some(array.len()),
+        (Value::Array(array), Segment::Key(key)) => array_index(key).filter(|i| *i <= array.len()),
+        _ => None,
+    };
+    match (container, index) {
+        (Value::Array(array), Some(index)) => {
+            let Some((next, rest)) = rest.split_first() else {
+                if index == array.len() {
+                    array.push(value);
+                } else {
+                    array[index] = value;
+                }
+                return Some(());
+            };
+            if index == array.len() {
+                array.push(match next {
+                    Segment::Append => Value::Array(Vec::new()),
+                    Segment::Key(_) => Value::Object(HashMap::new()),
+                });
+            }
+            insert_segment(&mut array[index], next, rest, value)
+        }
+        (container @ Value::Object(_), None) => {
+            let Segment::Key(key) = segment else {
+                return None;
+            };
+            insert(container, key, rest, value)
+        }
+        _ => None,
+    }
+}
+
+fn decode(text: &str) -> Result<String, QueryError> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut iter = text.bytes();
+    while let Some(byte) = iter.next() {
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = [iter.next(), iter.next()];
+                let [Some(high), Some(low)] = hex else {
+                    return Err(QueryError::InvalidEncoding);
+                };
+                let hex = std::str::from_utf8(&[high, low])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                bytes.push(hex.ok_or(QueryError::InvalidEncoding)?);
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| QueryError::InvalidEncoding)
+}
+
+fn encode(text: &str) -> String {
+    let mut output = String::new();
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                output.push(byte as char)
+            }
+            b' ' => output.push('+'),
+            _ => output.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::{QueryError, from_query_string, to_query_string};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    #[test]
+    fn bracket_nesting() {
+        let actual = from_query_string("a=1&b[]=2&b[]=3&c[d][e]=4&f[0][g]=5&f[1][g]=6");
+        let expected = value(
+            r#"{"a": "1", "b": ["2", "3"], "c": {"d": {"e": "4"}},
+                "f": [{"g": "5"}, {"g": "6"}]}"#,
+        );
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn decoding_and_edge_cases() {
+        let actual = from_query_string("?q=hello+w%C3%B6rld&flag&x=1&x=2&m[5]=y&%5Bk%5D=v");
+        let expected = value(
+            r#"{"q": "hello wörld", "flag": "", "x": ["1", "2"], "m": {"5": "y"}, "[k]": "v"}"#,
+        );
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(from_query_string("a=%zz"), Err(QueryError::InvalidEncoding));
+        assert_eq!(
+            from_query_string("a=1&a[b]=2"),
+            Err(QueryError::Conflict(String::from("a[b]")))
+        );
+        assert_eq!(to_query_string(&value("[1]")), Err(QueryError::NotAnObject));
+    }
+
+    #[test]
+    fn writes_query_strings() {
+        let input = value(
+            r#"{"q": "a b&c", "tags": ["x", "y"], "page": {"n": 2, "all": true},
+                "items": [{"id": 1}], "none": null, "empty": []}"#,
+        );
+        let expected = "items[0][id]=1&none=&page[all]=true&page[n]=2&q=a+b%26c&tags[]=x&tags[]=y";
+        assert_eq!(to_query_string(&input), Ok(String::from(expected)));
+    }
+
+    #[test]
+    fn round_trip() {
+        let original = value(r#"{"a": {"b": ["1", "2"]}, "c": [{"d": "e f"}]}"#);
+        let query = to_query_string(&original).unwrap();
+        assert_eq!(from_query_string(&query), Ok(original));
+    }
+}
@@ -0,0 +1,182 @@
+//! TOML conversion, enabled with the `toml` feature
+
+use std::fmt;
+
+use crate::{JsonPointer, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TomlError {
+    /// The input is not valid TOML, with the parser's message
+    Syntax(String),
+
+    /// The top-level value must be an object, a TOML document is always a table
+    NotATable,
+
+    /// TOML has no `null`
+    Null { pointer: JsonPointer },
+
+    /// An array mixes objects with other values, so it cannot be written as an array
+    /// of tables
+    MixedArray { pointer: JsonPointer },
+}
+
+impl fmt::Display for TomlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(message) => write!(f, "invalid TOML: {message}"),
+            Self::NotATable => f.write_str("the top-level value is not an object"),
+            Self::Null { pointer } => write!(f, "TOML cannot represent the null at `{pointer}`"),
+            Self::MixedArray { pointer } => {
+                write!(
+                    f,
+                    "the array at `{pointer}` mixes objects with other values"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for TomlError {}
+
+/// Converts a TOML document into an object, dates and times become strings
+pub fn from_toml(input: &str) -> Result<Value, TomlError> {
+    let table: toml::Table = input
+        .parse()
+        .map_err(|err: toml::de::Error| TomlError::Syntax(err.message().to_string()))?;
+    Ok(from_table(table))
+}
+
+/// Writes an object as a TOML document, with keys in sorted order
+pub fn to_toml(value: &Value) -> Result<String, TomlError> {
+    let Value::Object(_) = value else {
+        return Err(TomlError::NotATable);
+    };
+    let toml::Value::Table(table) = to_node(value, &mut JsonPointer::root())? else {
+        unreachable!("objects become tables")
+    };
+    Ok(table.to_string())
+}
+
+fn from_table(table: toml::Table) -> Value {
+    Value::Object(table.into_iter().map(|(k, v)| (k, from_node(v))).collect())
+}
+
+fn from_node(node: toml::Value) -> Value {
+    match node {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(n) => Value::Number(n as f64),
+        toml::Value::Float(n) => Value::Number(n),
+        toml::Value::Boolean(b) => Value::Boolean(b),
+        toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+        toml::Value::Array(array) => Value::Array(array.into_iter().map(from_node).collect()),
+        toml::Value::Table(table) => from_table(table),
+    }
+}
+
+fn to_node(value: &Value, pointer: &mut JsonPointer) -> Result<toml::Value, TomlError> {
+    // 2^63, the first value outside of the i64 range
+    const I64_LIMIT: f64 = 9_223_372_036_854_775_808.0;
+
+    let node = match value {
+        Value::Null => {
+            return Err(TomlError::Null {
+                pointer: pointer.clone(),
+            });
+        }
+        Value::Boolean(b) => toml::Value::Boolean(*b),
+        Value::Number(n) if n.fract() == 0.0 && (-I64_LIMIT..I64_LIMIT).contains(n) => {
+            toml::Value::Integer(*n as i64)
+        }
+        Value::Number(n) => toml::Value::Float(*n),
+        Value::String(s) => toml::Value::String(s.clone()),
+        Value::Array(array) => {
+            let tables = array.iter().filter(|item| matches!(item, Value::Object(_)));
+            let count = tables.count();
+            if count != 0 && count != array.len() {
+                return Err(TomlError::MixedArray {
+                    pointer: pointer.clone(),
+                });
+            }
+            let mut nodes = Vec::with_capacity(array.len());
+            for (i, item) in array.iter().enumerate() {
+                pointer.push(i.to_string());
+                nodes.push(to_node(item, pointer)?);
+                pointer.pop();
+            }
+            toml::Value::Array(nodes)
+        }
+        Value::Object(map) => {
+            let mut table = toml::Table::new();
+            for (key, item) in map {
+                pointer.push(key.clone());
+                table.insert(key.clone(), to_node(item, pointer)?);
+                pointer.pop();
+            }
+            toml::Value::Table(table)
+        }
+    };
+    Ok(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{JsonPointer, Parser, Value};
+
+    use super::{TomlError, from_toml, to_toml};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    #[test]
+    fn reads_a_config_file() {
+        let input = r#"
+title = "example"
+created = 1979-05-27T07:32:00Z
+
+[server]
+port = 8080
+ratio = 0.5
+
+[[users]]
+name = "ken"
+
+[[users]]
+name = "ryu"
+"#;
+        let expected = value(
+            r#"{"title": "example", "created": "1979-05-27T07:32:00Z",
+                "server": {"port": 8080, "ratio": 0.5},
+                "users": [{"name": "ken"}, {"name": "ryu"}]}"#,
+        );
+        assert_eq!(from_toml(input), Ok(expected));
+    }
+
+    #[test]
+    fn round_trip() {
+        let original = value(r#"{"a": 1, "b": [true, false], "c": {"d": "e"}, "f": [{"g": 2.5}]}"#);
+        let text = to_toml(&original).unwrap();
+        assert!(text.contains("[[f]]"), "{text}");
+        assert_eq!(from_toml(&text), Ok(original));
+    }
+
+    #[test]
+    fn unrepresentable_values() {
+        assert_eq!(to_toml(&value("[1]")), Err(TomlError::NotATable));
+        let pointer = JsonPointer::parse("/a/0/b").unwrap();
+        assert_eq!(
+            to_toml(&value(r#"{"a": [{"b": null}]}"#)),
+            Err(TomlError::Null { pointer })
+        );
+        let pointer = JsonPointer::parse("/list").unwrap();
+        assert_eq!(
+            to_toml(&value(r#"{"list": [{"a": 1}, 2]}"#)),
+            Err(TomlError::MixedArray { pointer })
+        );
+    }
+
+    #[test]
+    fn syntax_error() {
+        assert!(matches!(from_toml("a = "), Err(TomlError::Syntax(_))));
+    }
+}
@@ -0,0 +1,578 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{DEFAULT_MAX_DEPTH, Value};
+
+/// How elements, attributes and text map onto objects
+#[derive(Debug, Clone)]
+pub struct XmlOptions {
+    /// Prefix marking object keys that are attributes, ex. `{"@id": "1"}`
+    pub attribute_prefix: String,
+
+    /// Key holding an element's text when it also has attributes or children
+    pub text_key: String,
+
+    /// Whether attributes are read at all, when `false` they are dropped
+    pub attributes: bool,
+
+    /// Element names that always become arrays, even when they appear only once
+    ///
+    /// Repeated elements always become arrays.
+    pub array_elements: Vec<String>,
+}
+
+impl Default for XmlOptions {
+    fn default() -> Self {
+        Self {
+            attribute_prefix: String::from("@"),
+            text_key: String::from("#text"),
+            attributes: true,
+            array_elements: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XmlError {
+    /// The input ended inside of an element
+    UnexpectedEnd,
+
+    /// A character that is not allowed at this position
+    UnexpectedChar { position: usize, found: char },
+
+    /// A closing tag does not match the element it closes, ex. `<a></b>`
+    MismatchedTag { expected: String, found: String },
+
+    /// An entity reference that is not predefined or a valid character reference
+    InvalidEntity(String),
+
+    /// The document has no root element, or the value is not a single-key object
+    NoRootElement,
+
+    /// There is content after the root element
+    TrailingContent,
+
+    /// An object key cannot be used as an element or attribute name
+    InvalidName(String),
+
+    /// An attribute value must be a string, number, boolean or `null`
+    InvalidAttribute(String),
+
+    /// Elements are nested deeper than [`DEFAULT_MAX_DEPTH`]
+    TooDeep,
+}
+
+impl fmt::Display for XmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => f.write_str("unexpected end of input inside of an element"),
+            Self::UnexpectedChar { position, found } => {
+                write!(f, "unexpected character {found:?} at position {position}")
+            }
+            Self::MismatchedTag { expected, found } => {
+                write!(f, "expected closing tag `{expected}`, found `{found}`")
+            }
+            Self::InvalidEntity(entity) => write!(f, "invalid entity reference `{entity}`"),
+            Self::NoRootElement => f.write_str("there is no single root element"),
+            Self::TrailingContent => f.write_str("unexpected content after the root element"),
+            Self::InvalidName(name) => write!(f, "{name:?} is not a valid XML name"),
+            Self::InvalidAttribute(name) => {
+                write!(
+                    f,
+                    "attribute `{name}` is not a string, number, boolean or null"
+                )
+            }
+            Self::TooDeep => write!(f, "nested deeper than {DEFAULT_MAX_DEPTH} levels"),
+        }
+    }
+}
+
+impl std::error::Error for XmlError {}
+
+/// Converts an XML document into an object with the root element as its only key
+///
+/// Elements with only text become strings, and empty elements `null`. Other elements
+/// become objects of their attributes, children and text. All text stays a string.
+pub fn from_xml(input: &str, options: &XmlOptions) -> Result<Value, XmlError> {
+    let mut parser = Parser {
+        chars: input.chars().collect(),
+        pos: 0,
+        depth: 0,
+        options,
+    };
+    parser.skip_misc()?;
+    if !parser.eat("<") {
+        return Err(XmlError::NoRootElement);
+    }
+    let (name, value) = parser.element()?;
+    parser.skip_misc()?;
+    if parser.pos != parser.chars.len() {
+        return Err(XmlError::TrailingContent);
+    }
+    Ok(Value::Object(HashMap::from([(name, value)])))
+}
+
+/// Writes a single-key object as an XML document, the reverse of [`from_xml`]
+///
+/// Arrays become repeated elements, and children are written in sorted key order.
+pub fn to_xml(value: &Value, options: &XmlOptions) -> Result<String, XmlError> {
+    let Value::Object(map) = value else {
+        return Err(XmlError::NoRootElement);
+    };
+    let mut entries = map.iter();
+    let (Some((name, root)), None) = (entries.next(), entries.next()) else {
+        return Err(XmlError::NoRootElement);
+    };
+    if let Value::Array(_) = root {
+        return Err(XmlError::NoRootElement);
+    }
+    let mut output = String::new();
+    write_element(name, root, options, &mut output)?;
+    Ok(output)
+}
+
+fn is_name_start(ch: char) -> bool {
+    ch.is_alphabetic() || ch == '_' || ch == ':'
+}
+
+fn is_name_char(ch: char) -> bool {
+    is_name_start(ch) || ch.is_numeric() || ch == '-' || ch == '.'
+}
+
+fn check_name(name: &str) -> Result<(), XmlError> {
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(is_name_start) && chars.all(is_name_char);
+    if valid {
+        Ok(())
+    } else {
+        Err(XmlError::InvalidName(String::from(name)))
+    }
+}
+
+fn escape(text: &str, output: &mut String) {
+    for ch in text.chars() {
+        match ch {
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '&' => output.push_str("&amp;"),
+            '"' => output.push_str("&quot;"),
+            _ => output.push(ch),
+        }
+    }
+}
+
+fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => Some(String::new()),
+        Value::Boolean(b) => Some(b.to_string()),
+        Value::Number(n) => Some(crate::serialize::format_number(*n)),
+        Value::String(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+fn write_element(
+    name: &str,
+    value: &Value,
+    options: &XmlOptions,
+    output: &mut String,
+) -> Result<(), XmlError> {
+    check_name(name)?;
+    output.push('<');
+    output.push_str(name);
+
+    let mut text = None;
+    let mut children = Vec::new();
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            for (key, item) in entries {
+                if *key == options.text_key {
+                    text = scalar_text(item);
+                } else if let Some(attribute) = key.strip_prefix(&options.attribute_prefix) {
+                    check_name(attribute)?;
+                    let item = scalar_text(item)
+                        .ok_or_else(|| XmlError::InvalidAttribute(String::from(attribute)))?;
+                    output.push(' ');
+                    output.push_str(attribute);
+                    output.push_str("=\"");
+                    escape(&item, output);
+                    output.push('"');
+                } else {
+                    children.push((key, item));
+                }
+            }
+        }
+        Value::Array(_) => return Err(XmlError::InvalidName(String::from(name))),
+        scalar => text = scalar_text(scalar),
+    }
+
+    if children.is_empty() && text.as_deref().is_none_or(str::is_empty) {
+        output.push_str("/>");
+        return Ok(());
+    }
+    output.push('>');
+    if let Some(text) = text {
+        escape(&text, output);
+    }
+    for (key, item) in children {
+        match item {
+            Value::Array(items) => {
+                for item in items {
+                    write_element(key, item, options, output)?;
+                }
+            }
+            item => write_element(key, item, options, output)?,
+        }
+    }
+    output.push_str("</");
+    output.push_str(name);
+    output.push('>');
+    Ok(())
+}
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    depth: usize,
+    options: &'a XmlOptions,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        let rest = self.chars.get(self.pos..).unwrap_or_default();
+        let mut rest = rest.iter();
+        s.chars().all(|ch| rest.next() == Some(&ch))
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        let matched = self.starts_with(s);
+        if matched {
+            self.pos += s.chars().count();
+        }
+        matched
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), XmlError> {
+        if self.eat(s) {
+            return Ok(());
+        }
+        match self.peek() {
+            Some(found) => Err(XmlError::UnexpectedChar {
+                position: self.pos,
+                found,
+            }),
+            None => Err(XmlError::UnexpectedEnd),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// Skips past the next `end`, returning what came before it
+    fn until(&mut self, end: &str) -> Result<String, XmlError> {
+        let start = self.pos;
+        while !self.starts_with(end) {
+            if self.peek().is_none() {
+                return Err(XmlError::UnexpectedEnd);
+            }
+            self.pos += 1;
+        }
+        let skipped = self.chars[start..self.pos].iter().collect();
+        self.pos += end.chars().count();
+        Ok(skipped)
+    }
+
+    /// Skips whitespace, the XML declaration, processing instructions, comments and
+    /// the document type declaration
+    fn skip_misc(&mut self) -> Result<(), XmlError> {
+        loop {
+            self.skip_whitespace();
+            if self.eat("<?") {
+                self.until("?>")?;
+            } else if self.eat("<!--") {
+                self.until("-->")?;
+            } else if self.eat("<!DOCTYPE") {
+                self.until(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, XmlError> {
+        let start = self.pos;
+        match self.peek() {
+            Some(ch) if is_name_start(ch) => self.pos += 1,
+            Some(found) => {
+                return Err(XmlError::UnexpectedChar {
+                    position: self.pos,
+                    found,
+                });
+            }
+            None => return Err(XmlError::UnexpectedEnd),
+        }
+        while self.peek().is_some_and(is_name_char) {
+            self.pos += 1;
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    /// Reads text up to the next `<` (or `end`), replacing entity references
+    fn text(&mut self, end: char) -> Result<String, XmlError> {
+        let mut text = String::new();
+        while let Some(ch) = self.peek() {
+            if ch == '<' || ch == end {
+                break;
+            }
+            self.pos += 1;
+            if ch == '&' {
+                let entity = self.until(";")?;
+                text.push(decode_entity(&entity)?);
+            } else {
+                text.push(ch);
+            }
+        }
+        Ok(text)
+    }
+
+    /// Parses an element after its opening `<`
+    fn element(&mut self) -> Result<(String, Value), XmlError> {
+        let name = self.name()?;
+        let mut fields = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            if self.eat("/>") {
+                return Ok((name, self.build(fields, String::new())));
+            }
+            if self.eat(">") {
+                break;
+            }
+            let attribute = self.name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let quote = match self.peek() {
+                Some(quote @ ('"' | '\'')) => quote,
+                Some(found) => {
+                    return Err(XmlError::UnexpectedChar {
+                        position: self.pos,
+                        found,
+                    });
+                }
+                None => return Err(XmlError::UnexpectedEnd),
+            };
+            self.pos += 1;
+            let value = self.text(quote)?;
+            self.expect(&quote.to_string())?;
+            if self.options.attributes {
+                let key = format!("{}{attribute}", self.options.attribute_prefix);
+                fields.push((key, Value::String(value)));
+            }
+        }
+
+        let mut text = String::new();
+        loop {
+            if self.eat("</") {
+                let closing = self.name()?;
+                if closing != name {
+                    return Err(XmlError::MismatchedTag {
+                        expected: name,
+                        found: closing,
+                    });
+                }
+                self.skip_whitespace();
+                self.expect(">")?;
+                return Ok((name, self.build(fields, text)));
+            } else if self.eat("<!--") {
+                self.until("-->")?;
+            } else if self.eat("<![CDATA[") {
+                text.push_str(&self.until("]]>")?);
+            } else if self.eat("<") {
+                fields.push(self.child()?);
+            } else if self.peek().is_none() {
+                return Err(XmlError::UnexpectedEnd);
+            } else {
+                text.push_str(&self.text('<')?);
+            }
+        }
+    }
+
+    /// Reads a child element, failing past [`DEFAULT_MAX_DEPTH`] levels
+    fn child(&mut self) -> Result<(String, Value), XmlError> {
+        if self.depth == DEFAULT_MAX_DEPTH {
+            return Err(XmlError::TooDeep);
+        }
+        self.depth += 1;
+        let child = self.element();
+        self.depth -= 1;
+        child
+    }
+
+    fn build(&self, fields: Vec<(String, Value)>, text: String) -> Value {
+        if fields.is_empty() {
+            return if text.is_empty() {
+                Value::Null
+            } else {
+                Value::String(text)
+            };
+        }
+
+        let mut map: HashMap<String, Value> = HashMap::new();
+        for (key, value) in fields {
+            let always_array = self.options.array_elements.contains(&key);
+            match map.get_mut(&key) {
+                Some(Value::Array(items)) => items.push(value),
+                Some(existing) => {
+                    let first = std::mem::replace(existing, Value::Null);
+                    *existing = Value::Array(vec![first, value]);
+                }
+                None if always_array => {
+                    map.insert(key, Value::Array(vec![value]));
+                }
+                None => {
+                    map.insert(key, value);
+                }
+            }
+        }
+        let text = text.trim();
+        if !text.is_empty() {
+            map.insert(
+                self.options.text_key.clone(),
+                Value::String(String::from(text)),
+            );
+        }
+        Value::Object(map)
+    }
+}
+
+fn decode_entity(entity: &str) -> Result<char, XmlError> {
+    let ch = match entity {
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "amp" => Some('&'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        _ => {
+            let code = if let Some(hex) = entity.strip_prefix("#x") {
+                u32::from_str_radix(hex, 16).ok()
+            } else if let Some(decimal) = entity.strip_prefix('#') {
+                decimal.parse().ok()
+            } else {
+                None
+            };
+            code.and_then(char::from_u32)
+        }
+    };
+    ch.ok_or_else(|| XmlError::InvalidEntity(String::from(entity)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::{XmlError, XmlOptions, from_xml, to_xml};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    fn read(input: &str) -> Result<Value, XmlError> {
+        from_xml(input, &XmlOptions::default())
+    }
+
+    #[test]
+    fn elements_attributes_and_text() {
+        let input = r#"<?xml version="1.0"?>
+<!-- a catalog -->
+<catalog version="2">
+  <book id="b1"><title>Dune &amp; more</title><tag>sf</tag><tag>classic</tag></book>
+  <empty/>
+  <note lang='en'>hello<![CDATA[ <world>]]></note>
+</catalog>"#;
+        let expected = value(
+            r##"{"catalog": {
+                "@version": "2",
+                "book": {"@id": "b1", "title": "Dune & more", "tag": ["sf", "classic"]},
+                "empty": null,
+                "note": {"@lang": "en", "#text": "hello <world>"}}}"##,
+        );
+        assert_eq!(read(input), Ok(expected));
+    }
+
+    #[test]
+    fn configurable_mapping() {
+        let options = XmlOptions {
+            attribute_prefix: String::from("_"),
+            text_key: String::from("value"),
+            attributes: true,
+            array_elements: vec![String::from("item")],
+        };
+        let actual = from_xml(r#"<list n="1"><item>a</item>x</list>"#, &options);
+        let expected = value(r#"{"list": {"_n": "1", "item": ["a"], "value": "x"}}"#);
+        assert_eq!(actual, Ok(expected));
+
+        let options = XmlOptions {
+            attributes: false,
+            ..XmlOptions::default()
+        };
+        let actual = from_xml(r#"<a b="1">&#x41;&#66;</a>"#, &options);
+        assert_eq!(actual, Ok(value(r#"{"a": "AB"}"#)));
+    }
+
+    #[test]
+    fn writes_xml() {
+        let input = value(
+            r##"{"order": {"@id": 7, "item": [{"#text": "a<b"}, {"@sku": "x"}], "paid": true}}"##,
+        );
+        let expected =
+            r#"<order id="7"><item>a&lt;b</item><item sku="x"/><paid>true</paid></order>"#;
+        assert_eq!(
+            to_xml(&input, &XmlOptions::default()),
+            Ok(String::from(expected))
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let original = value(r##"{"r": {"@a": "1", "c": ["x", "y"], "d": {"e": null}}}"##);
+        let options = XmlOptions::default();
+        let xml = to_xml(&original, &options).unwrap();
+        assert_eq!(from_xml(&xml, &options), Ok(original));
+    }
+
+    #[test]
+    fn errors() {
+        let mismatched = XmlError::MismatchedTag {
+            expected: String::from("a"),
+            found: String::from("b"),
+        };
+        assert_eq!(read("<a></b>"), Err(mismatched));
+        assert_eq!(read("<a>"), Err(XmlError::UnexpectedEnd));
+        assert_eq!(read("<a/><b/>"), Err(XmlError::TrailingContent));
+        assert_eq!(
+            read("<a>&nope;</a>"),
+            Err(XmlError::InvalidEntity(String::from("nope")))
+        );
+        assert_eq!(read("  "), Err(XmlError::NoRootElement));
+        let nested = format!("{}{}", "<a>".repeat(200_000), "</a>".repeat(200_000));
+        assert_eq!(read(&nested), Err(XmlError::TooDeep));
+
+        let options = XmlOptions::default();
+        assert_eq!(to_xml(&value("[]"), &options), Err(XmlError::NoRootElement));
+        let bad_name = value(r#"{"1a": null}"#);
+        assert_eq!(
+            to_xml(&bad_name, &options),
+            Err(XmlError::InvalidName(String::from("1a")))
+        );
+    }
+}
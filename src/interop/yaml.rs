@@ -0,0 +1,172 @@
+//! YAML conversion, enabled with the `yaml` feature
+
+use std::fmt;
+
+use yaml_rust2::yaml::Hash;
+use yaml_rust2::{Yaml, YamlEmitter, YamlLoader};
+
+use crate::Value;
+use crate::serialize::format_number;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum YamlError {
+    /// The input is not valid YAML, with the scanner's message and location
+    Syntax(String),
+
+    /// The input contains no document
+    NoDocument,
+
+    /// The input contains more than one document
+    MultipleDocuments,
+
+    /// A mapping key is a sequence or mapping, which JSON objects cannot represent
+    ComplexKey,
+
+    /// Aliases are not supported
+    Alias,
+}
+
+impl fmt::Display for YamlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(message) => write!(f, "invalid YAML: {message}"),
+            Self::NoDocument => f.write_str("the input contains no document"),
+            Self::MultipleDocuments => f.write_str("the input contains more than one document"),
+            Self::ComplexKey => f.write_str("a mapping key is a sequence or mapping"),
+            Self::Alias => f.write_str("aliases are not supported"),
+        }
+    }
+}
+
+impl std::error::Error for YamlError {}
+
+/// Converts a single YAML document into a value
+///
+/// Scalar keys that are not strings, like `1` or `true`, are converted to strings.
+pub fn from_yaml(input: &str) -> Result<Value, YamlError> {
+    let mut documents =
+        YamlLoader::load_from_str(input).map_err(|err| YamlError::Syntax(err.to_string()))?;
+    match documents.len() {
+        0 => Err(YamlError::NoDocument),
+        1 => from_node(documents.remove(0)),
+        _ => Err(YamlError::MultipleDocuments),
+    }
+}
+
+/// Writes a value as a YAML document, with object keys in sorted order
+pub fn to_yaml(value: &Value) -> String {
+    let mut output = String::new();
+    YamlEmitter::new(&mut output)
+        .dump(&to_node(value))
+        .expect("writing to a String does not fail");
+    output.push('\n');
+    output
+}
+
+fn from_node(node: Yaml) -> Result<Value, YamlError> {
+    let value = match node {
+        Yaml::Null | Yaml::BadValue => Value::Null,
+        Yaml::Boolean(b) => Value::Boolean(b),
+        Yaml::Integer(n) => Value::Number(n as f64),
+        Yaml::Real(_) => Value::Number(node.as_f64().unwrap_or(f64::NAN)),
+        Yaml::String(s) => Value::String(s),
+        Yaml::Array(array) => {
+            Value::Array(array.into_iter().map(from_node).collect::<Result<_, _>>()?)
+        }
+        Yaml::Hash(hash) => {
+            let mut map = std::collections::HashMap::new();
+            for (key, value) in hash {
+                let key = match key {
+                    Yaml::String(s) | Yaml::Real(s) => s,
+                    Yaml::Integer(n) => n.to_string(),
+                    Yaml::Boolean(b) => b.to_string(),
+                    Yaml::Null => String::from("null"),
+                    _ => return Err(YamlError::ComplexKey),
+                };
+                map.insert(key, from_node(value)?);
+            }
+            Value::Object(map)
+        }
+        Yaml::Alias(_) => return Err(YamlError::Alias),
+    };
+    Ok(value)
+}
+
+fn to_node(value: &Value) -> Yaml {
+    // 2^63, the first value outside of the i64 range
+    const I64_LIMIT: f64 = 9_223_372_036_854_775_808.0;
+
+    match value {
+        Value::Null => Yaml::Null,
+        Value::Boolean(b) => Yaml::Boolean(*b),
+        Value::Number(n) if n.fract() == 0.0 && (-I64_LIMIT..I64_LIMIT).contains(n) => {
+            Yaml::Integer(*n as i64)
+        }
+        Value::Number(n) if n.is_nan() => Yaml::Real(String::from(".nan")),
+        Value::Number(n) if n.is_infinite() => {
+            Yaml::Real(String::from(if *n > 0.0 { ".inf" } else { "-.inf" }))
+        }
+        Value::Number(n) => Yaml::Real(format_number(*n)),
+        Value::String(s) => Yaml::String(s.clone()),
+        Value::Array(array) => Yaml::Array(array.iter().map(to_node).collect()),
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            let mut hash = Hash::new();
+            for (key, value) in entries {
+                hash.insert(Yaml::String(key.clone()), to_node(value));
+            }
+            Yaml::Hash(hash)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::{YamlError, from_yaml, to_yaml};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    #[test]
+    fn reads_a_config_file() {
+        let input = "
+name: service
+port: 8080
+ratio: 0.5
+debug: false
+owner: ~
+tags: [a, b]
+1: numeric key
+";
+        let expected = value(
+            r#"{"name": "service", "port": 8080, "ratio": 0.5, "debug": false,
+                "owner": null, "tags": ["a", "b"], "1": "numeric key"}"#,
+        );
+        assert_eq!(from_yaml(input), Ok(expected));
+    }
+
+    #[test]
+    fn writes_sorted_yaml() {
+        let input = value(r#"{"b": [1, 2.5], "a": {"x": "true"}}"#);
+        let expected = "---\na:\n  x: \"true\"\nb:\n  - 1\n  - 2.5\n";
+        assert_eq!(to_yaml(&input), expected);
+    }
+
+    #[test]
+    fn round_trip() {
+        let original = value(r#"{"s": "multi\nline", "n": [0, 123456.75, null], "e": {}}"#);
+        assert_eq!(from_yaml(&to_yaml(&original)), Ok(original));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(from_yaml(""), Err(YamlError::NoDocument));
+        assert_eq!(from_yaml("a\n---\nb"), Err(YamlError::MultipleDocuments));
+        assert_eq!(from_yaml("? [a]\n: 1"), Err(YamlError::ComplexKey));
+        assert!(matches!(from_yaml("a: [1"), Err(YamlError::Syntax(_))));
+    }
+}
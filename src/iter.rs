@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+use std::ops::{Bound, RangeBounds};
+use std::{slice, vec};
+
+use crate::Value;
+
+/// The elements of an array, any other value has none
+impl IntoIterator for Value {
+    type Item = Value;
+    type IntoIter = vec::IntoIter<Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            Value::Array(array) => array.into_iter(),
+            _ => Vec::new().into_iter(),
+        }
+    }
+}
+
+/// The elements of an array, any other value has none
+impl<'a> IntoIterator for &'a Value {
+    type Item = &'a Value;
+    type IntoIter = slice::Iter<'a, Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            Value::Array(array) => array.iter(),
+            _ => [].iter(),
+        }
+    }
+}
+
+/// Collects into an array
+impl FromIterator<Value> for Value {
+    fn from_iter<I: IntoIterator<Item = Value>>(iter: I) -> Self {
+        Value::Array(iter.into_iter().collect())
+    }
+}
+
+/// Collects into an object, a repeated key keeps its last value
+impl<K: Into<String>> FromIterator<(K, Value)> for Value {
+    fn from_iter<I: IntoIterator<Item = (K, Value)>>(iter: I) -> Self {
+        let map = iter.into_iter().map(|(key, value)| (key.into(), value));
+        Value::Object(map.collect())
+    }
+}
+
+/// Appends to an array, `null` is treated as an empty array
+///
+/// # Panics
+///
+/// If the value is not an array or `null`.
+impl Extend<Value> for Value {
+    fn extend<I: IntoIterator<Item = Value>>(&mut self, iter: I) {
+        if self.is_null() {
+            *self = Value::Array(Vec::new());
+        }
+        match self {
+            Value::Array(array) => array.extend(iter),
+            other => panic!("cannot extend {} with elements", other.type_name()),
+        }
+    }
+}
+
+/// Inserts into an object, replacing existing keys, `null` is treated as an empty object
+///
+/// # Panics
+///
+/// If the value is not an object or `null`.
+impl<K: Into<String>> Extend<(K, Value)> for Value {
+    fn extend<I: IntoIterator<Item = (K, Value)>>(&mut self, iter: I) {
+        if self.is_null() {
+            *self = Value::Object(HashMap::new());
+        }
+        match self {
+            Value::Object(map) => {
+                map.extend(iter.into_iter().map(|(key, value)| (key.into(), value)))
+            }
+            other => panic!("cannot extend {} with members", other.type_name()),
+        }
+    }
+}
+
+impl Value {
+    /// The members of an object in arbitrary order, nothing for any other value
+    pub fn items(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.as_object()
+            .into_iter()
+            .flatten()
+            .map(|(key, value)| (key.as_str(), value))
+    }
+
+    /// The keys of an object in arbitrary order, nothing for any other value
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.items().map(|(key, _)| key)
+    }
+
+    /// The elements of an array in order or the values of an object in arbitrary order,
+    /// nothing for a scalar
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        let elements = self.as_array().into_iter().flatten();
+        let members = self.as_object().into_iter().flat_map(|map| map.values());
+        elements.chain(members)
+    }
+
+    /// The elements of an array within `range`, ex. `20..40` for a page of results,
+    /// borrowed rather than cloned
+    ///
+    /// The range is clamped to the array, so it may be shorter or empty, and any other
+    /// value has no elements.
+    pub fn slice_array(&self, range: impl RangeBounds<usize>) -> &[Value] {
+        let elements = self.as_array().map_or(&[][..], Vec::as_slice);
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.saturating_add(1),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => elements.len(),
+        };
+        let end = end.min(elements.len());
+        &elements[start.min(end)..end]
+    }
+
+    /// The elements of an array in pages of `size`, the last of which may be shorter
+    ///
+    /// # Panics
+    ///
+    /// If `size` is 0.
+    pub fn chunks(&self, size: usize) -> slice::Chunks<'_, Value> {
+        self.slice_array(..).chunks(size)
+    }
+
+    /// Up to the first `n` elements of an array
+    pub fn take_elements(&self, n: usize) -> &[Value] {
+        self.slice_array(..n)
+    }
+
+    /// The elements of an array after the first `n`
+    pub fn skip_elements(&self, n: usize) -> &[Value] {
+        self.slice_array(n..)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    #[test]
+    fn arrays() {
+        let value = Parser::new().parse_str("[1, 2]").unwrap();
+        let borrowed: Vec<&Value> = (&value).into_iter().collect();
+        assert_eq!(borrowed, [&Value::Number(1.0), &Value::Number(2.0)]);
+        assert_eq!(value.values().count(), 2);
+        assert_eq!(value.items().count(), 0);
+
+        let mut total = 0.0;
+        for item in value {
+            total += item.as_f64().unwrap();
+        }
+        assert_eq!(total, 3.0);
+    }
+
+    #[test]
+    fn objects() {
+        let value = Parser::new().parse_str(r#"{"a": 1, "b": true}"#).unwrap();
+        let mut keys: Vec<&str> = value.keys().collect();
+        keys.sort();
+        assert_eq!(keys, ["a", "b"]);
+        let mut items: Vec<_> = value.items().collect();
+        items.sort_by_key(|(key, _)| *key);
+        assert_eq!(items[1], ("b", &Value::Boolean(true)));
+        assert_eq!(value.values().count(), 2);
+        assert_eq!(value.into_iter().count(), 0);
+    }
+
+    #[test]
+    fn collects_into_containers() {
+        let array: Value = (1..=3).map(|n| Value::Number(f64::from(n))).collect();
+        assert_eq!(array, Parser::new().parse_str("[1, 2, 3]").unwrap());
+        let object: Value = [("a", Value::Null), ("b", Value::Boolean(true))]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            object,
+            Parser::new()
+                .parse_str(r#"{"a": null, "b": true}"#)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn extends_containers() {
+        let mut array = Value::Null;
+        array.extend([Value::Number(1.0)]);
+        array.extend(vec![Value::Number(2.0)]);
+        assert_eq!(array, Parser::new().parse_str("[1, 2]").unwrap());
+
+        let mut object = Value::object([("a", Value::Number(1.0))]);
+        object.extend([
+            (String::from("a"), Value::Null),
+            (String::from("b"), Value::Null),
+        ]);
+        assert_eq!(
+            object,
+            Value::object([("a", Value::Null), ("b", Value::Null)])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot extend string with elements")]
+    fn extend_wrong_type() {
+        Value::string("text").extend([Value::Null]);
+    }
+
+    #[test]
+    fn scalars_are_empty() {
+        let value = Value::string("text");
+        assert_eq!(value.keys().count(), 0);
+        assert_eq!(value.values().count(), 0);
+        assert_eq!((&value).into_iter().count(), 0);
+    }
+
+    #[test]
+    fn windows() {
+        let array = Value::Array((0..10).map(|n| Value::Number(n.into())).collect());
+        let numbers = |elements: &[Value]| -> Vec<f64> {
+            elements.iter().filter_map(Value::as_f64).collect()
+        };
+        assert_eq!(numbers(array.slice_array(2..5)), [2.0, 3.0, 4.0]);
+        assert_eq!(numbers(array.slice_array(8..=20)), [8.0, 9.0]);
+        assert!(array.slice_array(12..15).is_empty());
+        assert_eq!(numbers(array.take_elements(2)), [0.0, 1.0]);
+        assert_eq!(numbers(array.skip_elements(7)), [7.0, 8.0, 9.0]);
+        assert_eq!(array.skip_elements(11).len(), 0);
+
+        let pages: Vec<_> = array.chunks(4).map(numbers).collect();
+        assert_eq!(pages.len(), 3);
+        assert_eq!(pages[2], [8.0, 9.0]);
+
+        let object = Value::Object(Default::default());
+        assert!(object.slice_array(..).is_empty());
+        assert_eq!(object.chunks(3).count(), 0);
+    }
+}
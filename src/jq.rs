@@ -0,0 +1,988 @@
+//! A small jq-like filter language, ex. `.users[] | select(.age > 30) | .name`
+//!
+//! Supported: identity `.`, field access `.a.b` / `."key"` / `.["key"]`, indexing and
+//! slicing `.[0]` / `.[1:3]`, iteration `.[]`, recursion `..`, the optional suffix `?`,
+//! pipes `|`, commas `,`, literals, array and object construction, arithmetic,
+//! comparisons, `and` / `or`, and the builtins `select`, `map`, `length`, `keys`, `has`,
+//! `type`, `not`, `empty`, `first`, `last`, `reverse`, `sort` and `add`.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::Value;
+
+/// A compiled filter expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    ast: Ast,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterError {
+    /// The expression ended early
+    UnexpectedEnd,
+
+    /// The character or token is not valid at this position of the expression
+    UnexpectedChar { position: usize, found: char },
+
+    /// The builtin does not exist
+    UnknownFunction(String),
+
+    /// The builtin exists but takes a different number of arguments
+    WrongArgumentCount(String),
+
+    /// Indexing a value with an incompatible key, ex. a number with `"foo"`
+    CannotIndex {
+        target: &'static str,
+        index: &'static str,
+    },
+
+    /// `.[]` on a value that is not an array or object
+    CannotIterate(&'static str),
+
+    /// An arithmetic operator applied to incompatible values
+    InvalidOperands {
+        operator: &'static str,
+        left: &'static str,
+        right: &'static str,
+    },
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => f.write_str("unexpected end of the filter"),
+            Self::UnexpectedChar { position, found } => {
+                write!(f, "unexpected {found:?} at position {position}")
+            }
+            Self::UnknownFunction(name) => write!(f, "unknown builtin `{name}`"),
+            Self::WrongArgumentCount(name) => write!(f, "wrong number of arguments to `{name}`"),
+            Self::CannotIndex { target, index } => write!(f, "cannot index {target} with {index}"),
+            Self::CannotIterate(target) => write!(f, "cannot iterate over {target}"),
+            Self::InvalidOperands {
+                operator,
+                left,
+                right,
+            } => write!(f, "cannot apply `{operator}` to {left} and {right}"),
+        }
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Ast {
+    Identity,
+    Recurse,
+    Literal(Value),
+    Field(Box<Ast>, String),
+    Index(Box<Ast>, Box<Ast>),
+    Slice(Box<Ast>, Option<Box<Ast>>, Option<Box<Ast>>),
+    Iterate(Box<Ast>),
+    Optional(Box<Ast>),
+    Array(Option<Box<Ast>>),
+    Object(Vec<(Ast, Ast)>),
+    Pipe(Box<Ast>, Box<Ast>),
+    Comma(Box<Ast>, Box<Ast>),
+    And(Box<Ast>, Box<Ast>),
+    Or(Box<Ast>, Box<Ast>),
+    Binary(Operator, Box<Ast>, Box<Ast>),
+    Call(String, Vec<Ast>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Filter {
+    pub fn compile(expression: &str) -> Result<Self, FilterError> {
+        let chars: Vec<char> = expression.chars().collect();
+        let mut index = 0;
+        let ast = parse_pipe(&chars, &mut index)?;
+        skip_whitespace(&chars, &mut index);
+        if let Some(&found) = chars.get(index) {
+            return Err(FilterError::UnexpectedChar {
+                position: index,
+                found,
+            });
+        }
+        Ok(Self { ast })
+    }
+
+    /// Runs the filter, returning every value it produces
+    pub fn apply(&self, input: &Value) -> Result<Vec<Value>, FilterError> {
+        eval(&self.ast, input)
+    }
+}
+
+fn skip_whitespace(chars: &[char], index: &mut usize) {
+    while chars.get(*index).is_some_and(|ch| ch.is_whitespace()) {
+        *index += 1;
+    }
+}
+
+fn unexpected(chars: &[char], index: usize) -> FilterError {
+    match chars.get(index) {
+        Some(&found) => FilterError::UnexpectedChar {
+            position: index,
+            found,
+        },
+        None => FilterError::UnexpectedEnd,
+    }
+}
+
+fn expect(chars: &[char], index: &mut usize, expected: char) -> Result<(), FilterError> {
+    skip_whitespace(chars, index);
+    if chars.get(*index) == Some(&expected) {
+        *index += 1;
+        Ok(())
+    } else {
+        Err(unexpected(chars, *index))
+    }
+}
+
+/// Consumes `symbol` if it is next, ignoring leading whitespace
+fn eat(chars: &[char], index: &mut usize, symbol: &str) -> bool {
+    skip_whitespace(chars, index);
+    let symbol: Vec<char> = symbol.chars().collect();
+    let is_word = symbol[0].is_ascii_alphabetic();
+    let after = chars.get(*index + symbol.len());
+    if chars[*index..].starts_with(&symbol)
+        && !(is_word && after.is_some_and(|ch| is_name_char(*ch)))
+    {
+        *index += symbol.len();
+        true
+    } else {
+        false
+    }
+}
+
+fn is_name_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '_'
+}
+
+fn parse_pipe(chars: &[char], index: &mut usize) -> Result<Ast, FilterError> {
+    let left = parse_comma(chars, index)?;
+    if eat(chars, index, "|") {
+        let right = parse_pipe(chars, index)?;
+        return Ok(Ast::Pipe(Box::new(left), Box::new(right)));
+    }
+    Ok(left)
+}
+
+fn parse_comma(chars: &[char], index: &mut usize) -> Result<Ast, FilterError> {
+    let mut left = parse_or(chars, index)?;
+    while eat(chars, index, ",") {
+        let right = parse_or(chars, index)?;
+        left = Ast::Comma(Box::new(left), Box::new(right));
+    }
+    Ok(left)
+}
+
+fn parse_or(chars: &[char], index: &mut usize) -> Result<Ast, FilterError> {
+    let mut left = parse_and(chars, index)?;
+    while eat(chars, index, "or") {
+        let right = parse_and(chars, index)?;
+        left = Ast::Or(Box::new(left), Box::new(right));
+    }
+    Ok(left)
+}
+
+fn parse_and(chars: &[char], index: &mut usize) -> Result<Ast, FilterError> {
+    let mut left = parse_comparison(chars, index)?;
+    while eat(chars, index, "and") {
+        let right = parse_comparison(chars, index)?;
+        left = Ast::And(Box::new(left), Box::new(right));
+    }
+    Ok(left)
+}
+
+fn parse_comparison(chars: &[char], index: &mut usize) -> Result<Ast, FilterError> {
+    let left = parse_additive(chars, index)?;
+    let operators = [
+        ("==", Operator::Equal),
+        ("!=", Operator::NotEqual),
+        ("<=", Operator::LessOrEqual),
+        (">=", Operator::GreaterOrEqual),
+        ("<", Operator::Less),
+        (">", Operator::Greater),
+    ];
+    for (symbol, operator) in operators {
+        if eat(chars, index, symbol) {
+            let right = parse_additive(chars, index)?;
+            return Ok(Ast::Binary(operator, Box::new(left), Box::new(right)));
+        }
+    }
+    Ok(left)
+}
+
+fn parse_additive(chars: &[char], index: &mut usize) -> Result<Ast, FilterError> {
+    let mut left = parse_multiplicative(chars, index)?;
+    loop {
+        let operator = if eat(chars, index, "+") {
+            Operator::Add
+        } else if eat(chars, index, "-") {
+            Operator::Subtract
+        } else {
+            return Ok(left);
+        };
+        let right = parse_multiplicative(chars, index)?;
+        left = Ast::Binary(operator, Box::new(left), Box::new(right));
+    }
+}
+
+fn parse_multiplicative(chars: &[char], index: &mut usize) -> Result<Ast, FilterError> {
+    let mut left = parse_postfix(chars, index)?;
+    loop {
+        let operator = if eat(chars, index, "*") {
+            Operator::Multiply
+        } else if eat(chars, index, "/") {
+            Operator::Divide
+        } else if eat(chars, index, "%") {
+            Operator::Modulo
+        } else {
+            return Ok(left);
+        };
+        let right = parse_postfix(chars, index)?;
+        left = Ast::Binary(operator, Box::new(left), Box::new(right));
+    }
+}
+
+fn parse_postfix(chars: &[char], index: &mut usize) -> Result<Ast, FilterError> {
+    let mut ast = parse_term(chars, index)?;
+    loop {
+        // suffixes must directly follow the term, `. [0]` is not an index
+        match (chars.get(*index), chars.get(*index + 1)) {
+            (Some('.'), Some(ch)) if is_name_char(*ch) && !ch.is_ascii_digit() => {
+                *index += 1;
+                ast = Ast::Field(Box::new(ast), parse_name(chars, index));
+            }
+            (Some('.'), Some('"')) => {
+                *index += 1;
+                ast = Ast::Field(Box::new(ast), parse_string(chars, index)?);
+            }
+            (Some('.'), Some('[')) => {
+                *index += 1;
+                ast = parse_brackets(chars, index, ast)?;
+            }
+            (Some('['), _) => ast = parse_brackets(chars, index, ast)?,
+            (Some('?'), _) => {
+                *index += 1;
+                ast = Ast::Optional(Box::new(ast));
+            }
+            _ => return Ok(ast),
+        }
+    }
+}
+
+/// Parses `[]`, `[index]` or `[start:end]` applied to `target`
+fn parse_brackets(chars: &[char], index: &mut usize, target: Ast) -> Result<Ast, FilterError> {
+    debug_assert!(chars[*index] == '[');
+    *index += 1;
+    let target = Box::new(target);
+
+    if eat(chars, index, "]") {
+        return Ok(Ast::Iterate(target));
+    }
+    let start = if eat(chars, index, ":") {
+        None
+    } else {
+        let start = parse_pipe(chars, index)?;
+        if eat(chars, index, "]") {
+            return Ok(Ast::Index(target, Box::new(start)));
+        }
+        expect(chars, index, ':')?;
+        Some(Box::new(start))
+    };
+    let end = if eat(chars, index, "]") {
+        None
+    } else {
+        let end = parse_pipe(chars, index)?;
+        expect(chars, index, ']')?;
+        Some(Box::new(end))
+    };
+    Ok(Ast::Slice(target, start, end))
+}
+
+fn parse_term(chars: &[char], index: &mut usize) -> Result<Ast, FilterError> {
+    skip_whitespace(chars, index);
+    let Some(&ch) = chars.get(*index) else {
+        return Err(FilterError::UnexpectedEnd);
+    };
+    match ch {
+        '.' if chars.get(*index + 1) == Some(&'.') => {
+            *index += 2;
+            Ok(Ast::Recurse)
+        }
+        '.' => {
+            // leave a `.` that starts field access for `parse_postfix`, ex. `.foo`
+            let next = chars.get(*index + 1);
+            let is_access = next.is_some_and(|ch| {
+                (is_name_char(*ch) && !ch.is_ascii_digit()) || *ch == '"' || *ch == '['
+            });
+            if !is_access {
+                *index += 1;
+            }
+            Ok(Ast::Identity)
+        }
+        '(' => {
+            *index += 1;
+            let inner = parse_pipe(chars, index)?;
+            expect(chars, index, ')')?;
+            Ok(inner)
+        }
+        '[' => {
+            *index += 1;
+            if eat(chars, index, "]") {
+                return Ok(Ast::Array(None));
+            }
+            let inner = parse_pipe(chars, index)?;
+            expect(chars, index, ']')?;
+            Ok(Ast::Array(Some(Box::new(inner))))
+        }
+        '{' => {
+            *index += 1;
+            parse_object(chars, index)
+        }
+        '"' => Ok(Ast::Literal(Value::String(parse_string(chars, index)?))),
+        '-' | '0'..='9' => parse_number(chars, index),
+        ch if ch.is_ascii_alphabetic() || ch == '_' => {
+            let name = parse_name(chars, index);
+            match name.as_str() {
+                "null" => return Ok(Ast::Literal(Value::Null)),
+                "true" => return Ok(Ast::Literal(Value::Boolean(true))),
+                "false" => return Ok(Ast::Literal(Value::Boolean(false))),
+                _ => {}
+            }
+            let mut args = Vec::new();
+            if chars.get(*index) == Some(&'(') {
+                *index += 1;
+                loop {
+                    args.push(parse_pipe(chars, index)?);
+                    if eat(chars, index, ")") {
+                        break;
+                    }
+                    expect(chars, index, ';')?;
+                }
+            }
+            check_arity(&name, args.len())?;
+            Ok(Ast::Call(name, args))
+        }
+        _ => Err(unexpected(chars, *index)),
+    }
+}
+
+fn check_arity(name: &str, count: usize) -> Result<(), FilterError> {
+    let expected = match name {
+        "select" | "map" | "has" => 1,
+        "length" | "keys" | "type" | "not" | "empty" | "first" | "last" | "reverse" | "sort"
+        | "add" => 0,
+        _ => return Err(FilterError::UnknownFunction(String::from(name))),
+    };
+    if count == expected {
+        Ok(())
+    } else {
+        Err(FilterError::WrongArgumentCount(String::from(name)))
+    }
+}
+
+fn parse_object(chars: &[char], index: &mut usize) -> Result<Ast, FilterError> {
+    let mut entries = Vec::new();
+    if eat(chars, index, "}") {
+        return Ok(Ast::Object(entries));
+    }
+    loop {
+        skip_whitespace(chars, index);
+        let key = match chars.get(*index) {
+            Some('"') => Ast::Literal(Value::String(parse_string(chars, index)?)),
+            Some('(') => {
+                *index += 1;
+                let key = parse_pipe(chars, index)?;
+                expect(chars, index, ')')?;
+                key
+            }
+            Some(ch) if is_name_char(*ch) => Ast::Literal(Value::String(parse_name(chars, index))),
+            _ => return Err(unexpected(chars, *index)),
+        };
+        // `{name}` is shorthand for `{name: .name}`
+        let value = if eat(chars, index, ":") {
+            parse_or(chars, index)?
+        } else if let Ast::Literal(Value::String(name)) = &key {
+            Ast::Field(Box::new(Ast::Identity), name.clone())
+        } else {
+            return Err(unexpected(chars, *index));
+        };
+        entries.push((key, value));
+
+        if eat(chars, index, "}") {
+            return Ok(Ast::Object(entries));
+        }
+        expect(chars, index, ',')?;
+    }
+}
+
+fn parse_name(chars: &[char], index: &mut usize) -> String {
+    let start = *index;
+    while chars.get(*index).is_some_and(|ch| is_name_char(*ch)) {
+        *index += 1;
+    }
+    chars[start..*index].iter().collect()
+}
+
+fn parse_string(chars: &[char], index: &mut usize) -> Result<String, FilterError> {
+    debug_assert!(chars[*index] == '"');
+    let mut output = String::new();
+    loop {
+        *index += 1;
+        let ch = *chars.get(*index).ok_or(FilterError::UnexpectedEnd)?;
+        match ch {
+            '"' => break,
+            '\\' => {
+                *index += 1;
+                let escaped = *chars.get(*index).ok_or(FilterError::UnexpectedEnd)?;
+                output.push(match escaped {
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    other => other,
+                });
+            }
+            ch => output.push(ch),
+        }
+    }
+    *index += 1;
+    Ok(output)
+}
+
+fn parse_number(chars: &[char], index: &mut usize) -> Result<Ast, FilterError> {
+    let start = *index;
+    if chars[*index] == '-' {
+        *index += 1;
+    }
+    while chars
+        .get(*index)
+        .is_some_and(|ch| ch.is_ascii_digit() || *ch == '.')
+    {
+        *index += 1;
+    }
+    let text: String = chars[start..*index].iter().collect();
+    text.parse()
+        .map(|number| Ast::Literal(Value::Number(number)))
+        .map_err(|_| unexpected(chars, start))
+}
+
+fn eval(ast: &Ast, input: &Value) -> Result<Vec<Value>, FilterError> {
+    match ast {
+        Ast::Identity => Ok(vec![input.clone()]),
+        Ast::Recurse => {
+            let mut output = Vec::new();
+            recurse(input, &mut output);
+            Ok(output)
+        }
+        Ast::Literal(value) => Ok(vec![value.clone()]),
+        Ast::Field(target, name) => {
+            let key = Value::String(name.clone());
+            flat_map(eval(target, input)?, |value| {
+                index_value(&value, &key).map(|v| vec![v])
+            })
+        }
+        Ast::Index(target, key) => {
+            let keys = eval(key, input)?;
+            flat_map(eval(target, input)?, |value| {
+                keys.iter().map(|key| index_value(&value, key)).collect()
+            })
+        }
+        Ast::Slice(target, start, end) => {
+            let start = optional_bound(start.as_deref(), input)?;
+            let end = optional_bound(end.as_deref(), input)?;
+            flat_map(eval(target, input)?, |value| {
+                slice_value(&value, start, end).map(|v| vec![v])
+            })
+        }
+        Ast::Iterate(target) => flat_map(eval(target, input)?, |value| match value {
+            Value::Array(array) => Ok(array),
+            Value::Object(map) => Ok(sorted_entries(map).into_iter().map(|(_, v)| v).collect()),
+            other => Err(FilterError::CannotIterate(other.type_name())),
+        }),
+        Ast::Optional(inner) => Ok(eval(inner, input).unwrap_or_default()),
+        Ast::Array(None) => Ok(vec![Value::Array(Vec::new())]),
+        Ast::Array(Some(inner)) => Ok(vec![Value::Array(eval(inner, input)?)]),
+        Ast::Object(entries) => {
+            let mut objects = vec![HashMap::new()];
+            for (key, value) in entries {
+                let mut next = Vec::new();
+                for key in eval(key, input)? {
+                    let Value::String(key) = key else {
+                        return Err(FilterError::CannotIndex {
+                            target: "object",
+                            index: key.type_name(),
+                        });
+                    };
+                    for value in eval(value, input)? {
+                        for object in &objects {
+                            let mut object = object.clone();
+                            object.insert(key.clone(), value.clone());
+                            next.push(object);
+                        }
+                    }
+                }
+                objects = next;
+            }
+            Ok(objects.into_iter().map(Value::Object).collect())
+        }
+        Ast::Pipe(left, right) => flat_map(eval(left, input)?, |value| eval(right, &value)),
+        Ast::Comma(left, right) => {
+            let mut output = eval(left, input)?;
+            output.extend(eval(right, input)?);
+            Ok(output)
+        }
+        Ast::And(left, right) => flat_map(eval(left, input)?, |left| {
+            if !is_truthy(&left) {
+                return Ok(vec![Value::Boolean(false)]);
+            }
+            Ok(eval(right, input)?
+                .iter()
+                .map(|right| Value::Boolean(is_truthy(right)))
+                .collect())
+        }),
+        Ast::Or(left, right) => flat_map(eval(left, input)?, |left| {
+            if is_truthy(&left) {
+                return Ok(vec![Value::Boolean(true)]);
+            }
+            Ok(eval(right, input)?
+                .iter()
+                .map(|right| Value::Boolean(is_truthy(right)))
+                .collect())
+        }),
+        Ast::Binary(operator, left, right) => {
+            let rights = eval(right, input)?;
+            flat_map(eval(left, input)?, |left| {
+                rights
+                    .iter()
+                    .map(|right| binary(*operator, &left, right))
+                    .collect()
+            })
+        }
+        Ast::Call(name, args) => call(name, args, input),
+    }
+}
+
+fn flat_map(
+    values: Vec<Value>,
+    mut f: impl FnMut(Value) -> Result<Vec<Value>, FilterError>,
+) -> Result<Vec<Value>, FilterError> {
+    let mut output = Vec::new();
+    for value in values {
+        output.extend(f(value)?);
+    }
+    Ok(output)
+}
+
+fn recurse(value: &Value, output: &mut Vec<Value>) {
+    output.push(value.clone());
+    match value {
+        Value::Array(array) => array.iter().for_each(|v| recurse(v, output)),
+        Value::Object(map) => sorted_entries(map.clone())
+            .iter()
+            .for_each(|(_, v)| recurse(v, output)),
+        _ => {}
+    }
+}
+
+fn sorted_entries(map: HashMap<String, Value>) -> Vec<(String, Value)> {
+    let mut entries: Vec<_> = map.into_iter().collect();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    entries
+}
+
+fn is_truthy(value: &Value) -> bool {
+    !matches!(value, Value::Null | Value::Boolean(false))
+}
+
+fn index_value(value: &Value, key: &Value) -> Result<Value, FilterError> {
+    match (value, key) {
+        (Value::Object(map), Value::String(key)) => {
+            Ok(map.get(key).cloned().unwrap_or(Value::Null))
+        }
+        (Value::Array(array), Value::Number(n)) => {
+            let len = array.len() as i64;
+            let i = *n as i64;
+            let i = if i < 0 { len + i } else { i };
+            Ok(usize::try_from(i)
+                .ok()
+                .and_then(|i| array.get(i))
+                .cloned()
+                .unwrap_or(Value::Null))
+        }
+        (Value::Null, Value::String(_) | Value::Number(_)) => Ok(Value::Null),
+        (value, key) => Err(FilterError::CannotIndex {
+            target: value.type_name(),
+            index: key.type_name(),
+        }),
+    }
+}
+
+fn optional_bound(ast: Option<&Ast>, input: &Value) -> Result<Option<i64>, FilterError> {
+    let Some(ast) = ast else {
+        return Ok(None);
+    };
+    match eval(ast, input)?.as_slice() {
+        [Value::Number(n)] => Ok(Some(*n as i64)),
+        [other, ..] => Err(FilterError::CannotIndex {
+            target: "array",
+            index: other.type_name(),
+        }),
+        [] => Ok(None),
+    }
+}
+
+fn slice_value(value: &Value, start: Option<i64>, end: Option<i64>) -> Result<Value, FilterError> {
+    let bounds = |len: usize| {
+        let len = len as i64;
+        let clamp = |i: i64| (if i < 0 { len + i } else { i }).clamp(0, len) as usize;
+        let start = clamp(start.unwrap_or(0));
+        let end = clamp(end.unwrap_or(len));
+        (start, end.max(start))
+    };
+    match value {
+        Value::Array(array) => {
+            let (start, end) = bounds(array.len());
+            Ok(Value::Array(array[start..end].to_vec()))
+        }
+        Value::String(s) => {
+            let chars: Vec<char> = s.chars().collect();
+            let (start, end) = bounds(chars.len());
+            Ok(Value::String(chars[start..end].iter().collect()))
+        }
+        Value::Null => Ok(Value::Null),
+        other => Err(FilterError::CannotIndex {
+            target: other.type_name(),
+            index: "object",
+        }),
+    }
+}
+
+fn binary(operator: Operator, left: &Value, right: &Value) -> Result<Value, FilterError> {
+    let ordering = || compare(left, right);
+    let result = match operator {
+        Operator::Equal => Value::Boolean(left == right),
+        Operator::NotEqual => Value::Boolean(left != right),
+        Operator::Less => Value::Boolean(ordering() == Ordering::Less),
+        Operator::LessOrEqual => Value::Boolean(ordering() != Ordering::Greater),
+        Operator::Greater => Value::Boolean(ordering() == Ordering::Greater),
+        Operator::GreaterOrEqual => Value::Boolean(ordering() != Ordering::Less),
+        _ => return arithmetic(operator, left, right),
+    };
+    Ok(result)
+}
+
+fn arithmetic(operator: Operator, left: &Value, right: &Value) -> Result<Value, FilterError> {
+    let result = match (operator, left, right) {
+        (Operator::Add, Value::Null, other) | (Operator::Add, other, Value::Null) => {
+            Some(other.clone())
+        }
+        (Operator::Add, Value::Number(a), Value::Number(b)) => Some(Value::Number(a + b)),
+        (Operator::Add, Value::String(a), Value::String(b)) => {
+            Some(Value::String(format!("{a}{b}")))
+        }
+        (Operator::Add, Value::Array(a), Value::Array(b)) => {
+            Some(Value::Array([a.clone(), b.clone()].concat()))
+        }
+        (Operator::Add, Value::Object(a), Value::Object(b)) => {
+            let mut merged = a.clone();
+            merged.extend(b.clone());
+            Some(Value::Object(merged))
+        }
+        (Operator::Subtract, Value::Number(a), Value::Number(b)) => Some(Value::Number(a - b)),
+        (Operator::Subtract, Value::Array(a), Value::Array(b)) => Some(Value::Array(
+            a.iter().filter(|v| !b.contains(v)).cloned().collect(),
+        )),
+        (Operator::Multiply, Value::Number(a), Value::Number(b)) => Some(Value::Number(a * b)),
+        (Operator::Divide, Value::Number(a), Value::Number(b)) if *b != 0.0 => {
+            Some(Value::Number(a / b))
+        }
+        (Operator::Modulo, Value::Number(a), Value::Number(b)) if *b as i64 != 0 => {
+            // only `i64::MIN % -1` overflows, its remainder is 0
+            let remainder = (*a as i64).checked_rem(*b as i64).unwrap_or(0);
+            Some(Value::Number(remainder as f64))
+        }
+        _ => None,
+    };
+    result.ok_or(FilterError::InvalidOperands {
+        operator: match operator {
+            Operator::Add => "+",
+            Operator::Subtract => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+            _ => "%",
+        },
+        left: left.type_name(),
+        right: right.type_name(),
+    })
+}
+
+/// jq's ordering: null < false < true < numbers < strings < arrays < objects
+fn compare(left: &Value, right: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Boolean(false) => 1,
+            Value::Boolean(true) => 2,
+            Value::Number(_) => 3,
+            Value::String(_) => 4,
+            Value::Array(_) => 5,
+            Value::Object(_) => 6,
+        }
+    }
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.total_cmp(b),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Array(a), Value::Array(b)) => a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| compare(a, b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(a.len().cmp(&b.len())),
+        (Value::Object(a), Value::Object(b)) => {
+            let mut a_keys: Vec<_> = a.keys().collect();
+            let mut b_keys: Vec<_> = b.keys().collect();
+            a_keys.sort();
+            b_keys.sort();
+            a_keys.cmp(&b_keys).then_with(|| {
+                a_keys
+                    .iter()
+                    .map(|key| compare(&a[*key], &b[*key]))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            })
+        }
+        _ => rank(left).cmp(&rank(right)),
+    }
+}
+
+fn call(name: &str, args: &[Ast], input: &Value) -> Result<Vec<Value>, FilterError> {
+    let value = match name {
+        "empty" => return Ok(Vec::new()),
+        "select" => {
+            let keep = eval(&args[0], input)?
+                .iter()
+                .filter(|v| is_truthy(v))
+                .count();
+            return Ok(vec![input.clone(); keep]);
+        }
+        "map" => {
+            let items = eval(&Ast::Iterate(Box::new(Ast::Identity)), input)?;
+            Value::Array(flat_map(items, |item| eval(&args[0], &item))?)
+        }
+        "has" => {
+            return flat_map(eval(&args[0], input)?, |key| {
+                let has = match (input, &key) {
+                    (Value::Object(map), Value::String(key)) => map.contains_key(key),
+                    (Value::Array(array), Value::Number(n)) => {
+                        *n >= 0.0 && (*n as usize) < array.len()
+                    }
+                    _ => {
+                        return Err(FilterError::CannotIndex {
+                            target: input.type_name(),
+                            index: key.type_name(),
+                        });
+                    }
+                };
+                Ok(vec![Value::Boolean(has)])
+            });
+        }
+        "length" => match input {
+            Value::Null => Value::Number(0.0),
+            Value::Boolean(_) => {
+                return Err(FilterError::CannotIterate("boolean"));
+            }
+            Value::Number(n) => Value::Number(n.abs()),
+            Value::String(s) => Value::Number(s.chars().count() as f64),
+            Value::Array(array) => Value::Number(array.len() as f64),
+            Value::Object(map) => Value::Number(map.len() as f64),
+        },
+        "keys" => match input {
+            Value::Object(map) => {
+                let mut keys: Vec<_> = map.keys().cloned().map(Value::String).collect();
+                keys.sort_by(compare);
+                Value::Array(keys)
+            }
+            Value::Array(array) => {
+                Value::Array((0..array.len()).map(|i| Value::Number(i as f64)).collect())
+            }
+            other => return Err(FilterError::CannotIterate(other.type_name())),
+        },
+        "type" => Value::String(String::from(input.type_name())),
+        "not" => Value::Boolean(!is_truthy(input)),
+        "first" => index_value(input, &Value::Number(0.0))?,
+        "last" => index_value(input, &Value::Number(-1.0))?,
+        "reverse" => match input {
+            Value::Array(array) => Value::Array(array.iter().rev().cloned().collect()),
+            Value::String(s) => Value::String(s.chars().rev().collect()),
+            Value::Null => Value::Array(Vec::new()),
+            other => return Err(FilterError::CannotIterate(other.type_name())),
+        },
+        "sort" => match input {
+            Value::Array(array) => {
+                let mut sorted = array.clone();
+                sorted.sort_by(compare);
+                Value::Array(sorted)
+            }
+            other => return Err(FilterError::CannotIterate(other.type_name())),
+        },
+        "add" => {
+            let items = eval(&Ast::Iterate(Box::new(Ast::Identity)), input)?;
+            items.iter().try_fold(Value::Null, |sum, item| {
+                arithmetic(Operator::Add, &sum, item)
+            })?
+        }
+        _ => return Err(FilterError::UnknownFunction(String::from(name))),
+    };
+    Ok(vec![value])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::{Filter, FilterError};
+
+    fn check(filter: &str, input: &str, expected: &[&str]) {
+        let input = Parser::new().parse_str(input).unwrap();
+        let expected: Vec<Value> = expected
+            .iter()
+            .map(|e| Parser::new().parse_str(e).unwrap())
+            .collect();
+        let actual = Filter::compile(filter).unwrap().apply(&input).unwrap();
+        assert_eq!(actual, expected, "{filter}");
+    }
+
+    const USERS: &str = r#"{"users": [
+        {"name": "ken", "age": 35},
+        {"name": "ryu", "age": 28},
+        {"name": "chun", "age": 31}
+    ]}"#;
+
+    #[test]
+    fn identity_and_fields() {
+        check(".", "[1]", &["[1]"]);
+        check(".a.b", r#"{"a": {"b": 2}}"#, &["2"]);
+        check(r#"."a b""#, r#"{"a b": 1}"#, &["1"]);
+        check(r#".["a"]"#, r#"{"a": 1}"#, &["1"]);
+        check(".missing", "{}", &["null"]);
+    }
+
+    #[test]
+    fn select_and_pipe() {
+        check(
+            ".users[] | select(.age > 30) | .name",
+            USERS,
+            &[r#""ken""#, r#""chun""#],
+        );
+    }
+
+    #[test]
+    fn map_and_construction() {
+        check("[.users[].age] | add", USERS, &["94"]);
+        check(".users | map(.age + 1)", USERS, &["[36, 29, 32]"]);
+        check(
+            ".users[0] | {name, older: (.age + 10)}",
+            USERS,
+            &[r#"{"name": "ken", "older": 45}"#],
+        );
+    }
+
+    #[test]
+    fn indexing_and_slicing() {
+        check(".[1]", "[1, 2, 3]", &["2"]);
+        check(".[-1]", "[1, 2, 3]", &["3"]);
+        check(".[1:]", "[1, 2, 3]", &["[2, 3]"]);
+        check(".[:2]", r#""abc""#, &[r#""ab""#]);
+        check(".[-2:]", "[1, 2, 3]", &["[2, 3]"]);
+    }
+
+    #[test]
+    fn comma_and_recursion() {
+        check(".a, .b", r#"{"a": 1, "b": 2}"#, &["1", "2"]);
+        check("[..] | length", r#"{"a": [1, 2]}"#, &["4"]);
+    }
+
+    #[test]
+    fn modulo() {
+        check(". % 3", "7", &["1"]);
+        check(". % 2.5", "5", &["1"]);
+        let filter = Filter::compile(".[0] % .[1]").unwrap();
+        let input = Value::Array(vec![Value::Number(-1e19), Value::Number(-1.0)]);
+        assert_eq!(filter.apply(&input), Ok(vec![Value::Number(0.0)]));
+        let filter = Filter::compile(". % 0").unwrap();
+        assert!(matches!(
+            filter.apply(&Value::Number(1.0)),
+            Err(FilterError::InvalidOperands { operator: "%", .. })
+        ));
+    }
+
+    #[test]
+    fn builtins() {
+        check("keys", r#"{"b": 1, "a": 2}"#, &[r#"["a", "b"]"#]);
+        check("has(\"a\")", r#"{"a": 1}"#, &["true"]);
+        check(
+            "map(type)",
+            r#"[null, 1, "s", [], {}]"#,
+            &[r#"["null", "number", "string", "array", "object"]"#],
+        );
+        check("sort | reverse | first", "[3, 1, 2]", &["3"]);
+        check(".[] | select(. > 1 and . < 3)", "[1, 2, 3]", &["2"]);
+        check(".a or .b | not", r#"{"a": false, "b": null}"#, &["true"]);
+    }
+
+    #[test]
+    fn optional_suppresses_errors() {
+        check(".[]?", "1", &[]);
+        check("[.[] | .a?]", r#"[1, {"a": 2}]"#, &["[2]"]);
+    }
+
+    #[test]
+    fn runtime_errors() {
+        let input = Parser::new().parse_str("1").unwrap();
+        let filter = Filter::compile(".a").unwrap();
+        let expected = FilterError::CannotIndex {
+            target: "number",
+            index: "string",
+        };
+        assert_eq!(filter.apply(&input), Err(expected));
+        let filter = Filter::compile(". + \"x\"").unwrap();
+        assert!(matches!(
+            filter.apply(&input),
+            Err(FilterError::InvalidOperands { .. })
+        ));
+        assert_eq!(
+            filter.apply(&input).unwrap_err().to_string(),
+            "cannot apply `+` to number and string"
+        );
+    }
+
+    #[test]
+    fn compile_errors() {
+        assert_eq!(
+            Filter::compile("nope"),
+            Err(FilterError::UnknownFunction(String::from("nope")))
+        );
+        assert_eq!(
+            Filter::compile("select"),
+            Err(FilterError::WrongArgumentCount(String::from("select")))
+        );
+        assert_eq!(Filter::compile(".a |"), Err(FilterError::UnexpectedEnd));
+    }
+}
@@ -0,0 +1,1189 @@
+//! JSONPath (RFC 9535) queries, ex. `$.store.book[?(@.price < 10)].title`
+
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+
+use crate::Value;
+use crate::regex::Regex;
+
+/// A compiled JSONPath expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+/// A node selected by a [`JsonPath`] query
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPathMatch<'a> {
+    /// The normalized path to the node, ex. `$['store']['book'][0]`
+    pub path: String,
+    pub value: &'a Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonPathError {
+    /// The expression must start with `$`
+    MissingRoot,
+
+    /// The expression ended in the middle of a segment or filter
+    UnexpectedEnd,
+
+    /// The character is not valid at this position of the expression
+    UnexpectedChar { position: usize, found: char },
+
+    /// An index or number literal could not be parsed
+    InvalidNumber { position: usize },
+
+    /// The function is not one of `length`, `count`, `match`, `search` or `value`
+    UnknownFunction(String),
+
+    /// The function was called with the wrong number or kind of arguments
+    InvalidArguments(String),
+
+    /// A literal was used where a test or comparison was expected
+    ExpectedTest { position: usize },
+}
+
+impl fmt::Display for JsonPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingRoot => f.write_str("the expression must start with `$`"),
+            Self::UnexpectedEnd => f.write_str("unexpected end of the expression"),
+            Self::UnexpectedChar { position, found } => {
+                write!(f, "unexpected {found:?} at position {position}")
+            }
+            Self::InvalidNumber { position } => write!(f, "invalid number at position {position}"),
+            Self::UnknownFunction(name) => write!(f, "unknown function `{name}`"),
+            Self::InvalidArguments(name) => write!(f, "invalid arguments to `{name}`"),
+            Self::ExpectedTest { position } => {
+                write!(f, "expected a test or comparison at position {position}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for JsonPathError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Child(Vec<Selector>),
+    Descendant(Vec<Selector>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice {
+        start: Option<i64>,
+        end: Option<i64>,
+        step: Option<i64>,
+    },
+    Filter(Expr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Or(Vec<Expr>),
+    And(Vec<Expr>),
+    Not(Box<Expr>),
+    Compare(Comparable, Comparison, Comparable),
+    Exists(Query),
+    Function(Function),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Comparable {
+    Literal(Value),
+    Query(Query),
+    Function(Function),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Query {
+    /// Whether the query starts at the current node `@` rather than the root `$`
+    relative: bool,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Function {
+    name: FunctionName,
+    args: Vec<Argument>,
+
+    /// The pattern of `match` or `search` compiled once when it is a string literal, `None`
+    /// inside when the literal is not a valid pattern
+    regex: Option<Option<Regex>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FunctionName {
+    Length,
+    Count,
+    Match,
+    Search,
+    Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Argument {
+    Literal(Value),
+    Query(Query),
+    Function(Function),
+    Logical(Expr),
+}
+
+impl JsonPath {
+    pub fn compile(expression: &str) -> Result<Self, JsonPathError> {
+        let chars: Vec<char> = expression.chars().collect();
+        let mut index = 0;
+        if chars.first() != Some(&'$') {
+            return Err(JsonPathError::MissingRoot);
+        }
+        index += 1;
+
+        let segments = parse_segments(&chars, &mut index)?;
+        skip_whitespace(&chars, &mut index);
+        if let Some(&found) = chars.get(index) {
+            return Err(JsonPathError::UnexpectedChar {
+                position: index,
+                found,
+            });
+        }
+        Ok(Self { segments })
+    }
+
+    /// Selects the matching nodes of `value`, in document order
+    pub fn query<'a>(&self, value: &'a Value) -> Vec<JsonPathMatch<'a>> {
+        let start = vec![Node {
+            path: Vec::new(),
+            value,
+        }];
+        select(&self.segments, value, start)
+            .into_iter()
+            .map(|node| JsonPathMatch {
+                path: normalized_path(&node.path),
+                value: node.value,
+            })
+            .collect()
+    }
+}
+
+impl Value {
+    /// Compiles and runs a JSONPath query against this value
+    pub fn query_path(&self, expression: &str) -> Result<Vec<JsonPathMatch<'_>>, JsonPathError> {
+        Ok(JsonPath::compile(expression)?.query(self))
+    }
+}
+
+fn skip_whitespace(chars: &[char], index: &mut usize) {
+    while chars
+        .get(*index)
+        .is_some_and(|ch| matches!(ch, ' ' | '\t' | '\n' | '\r'))
+    {
+        *index += 1;
+    }
+}
+
+fn unexpected(chars: &[char], index: usize) -> JsonPathError {
+    match chars.get(index) {
+        Some(&found) => JsonPathError::UnexpectedChar {
+            position: index,
+            found,
+        },
+        None => JsonPathError::UnexpectedEnd,
+    }
+}
+
+fn expect(chars: &[char], index: &mut usize, expected: char) -> Result<(), JsonPathError> {
+    skip_whitespace(chars, index);
+    if chars.get(*index) == Some(&expected) {
+        *index += 1;
+        Ok(())
+    } else {
+        Err(unexpected(chars, *index))
+    }
+}
+
+fn parse_segments(chars: &[char], index: &mut usize) -> Result<Vec<Segment>, JsonPathError> {
+    let mut segments = Vec::new();
+    loop {
+        let before = *index;
+        skip_whitespace(chars, index);
+        let segment = match (chars.get(*index), chars.get(*index + 1)) {
+            (Some('.'), Some('.')) => {
+                *index += 2;
+                Segment::Descendant(parse_dotted_selectors(chars, index)?)
+            }
+            (Some('.'), _) => {
+                *index += 1;
+                Segment::Child(parse_dotted_selectors(chars, index)?)
+            }
+            (Some('['), _) => Segment::Child(parse_bracketed(chars, index)?),
+            _ => {
+                *index = before;
+                return Ok(segments);
+            }
+        };
+        segments.push(segment);
+    }
+}
+
+/// Parses what follows a `.` or `..`, ex. `name`, `*` or `[0]`
+fn parse_dotted_selectors(
+    chars: &[char],
+    index: &mut usize,
+) -> Result<Vec<Selector>, JsonPathError> {
+    match chars.get(*index) {
+        Some('*') => {
+            *index += 1;
+            Ok(vec![Selector::Wildcard])
+        }
+        Some('[') => parse_bracketed(chars, index),
+        Some(&ch) if is_name_first(ch) => {
+            let start = *index;
+            while chars
+                .get(*index)
+                .is_some_and(|ch| is_name_first(*ch) || ch.is_ascii_digit())
+            {
+                *index += 1;
+            }
+            Ok(vec![Selector::Name(chars[start..*index].iter().collect())])
+        }
+        _ => Err(unexpected(chars, *index)),
+    }
+}
+
+fn is_name_first(ch: char) -> bool {
+    ch.is_ascii_alphabetic() || ch == '_' || !ch.is_ascii()
+}
+
+fn parse_bracketed(chars: &[char], index: &mut usize) -> Result<Vec<Selector>, JsonPathError> {
+    debug_assert!(chars[*index] == '[');
+    *index += 1;
+
+    let mut selectors = Vec::new();
+    loop {
+        skip_whitespace(chars, index);
+        selectors.push(parse_selector(chars, index)?);
+        skip_whitespace(chars, index);
+        match chars.get(*index) {
+            Some(',') => *index += 1,
+            Some(']') => {
+                *index += 1;
+                return Ok(selectors);
+            }
+            _ => return Err(unexpected(chars, *index)),
+        }
+    }
+}
+
+fn parse_selector(chars: &[char], index: &mut usize) -> Result<Selector, JsonPathError> {
+    match chars.get(*index) {
+        Some('\'' | '"') => Ok(Selector::Name(parse_string(chars, index)?)),
+        Some('*') => {
+            *index += 1;
+            Ok(Selector::Wildcard)
+        }
+        Some('?') => {
+            *index += 1;
+            Ok(Selector::Filter(parse_or(chars, index)?))
+        }
+        Some(_) => {
+            let start = parse_optional_int(chars, index)?;
+            skip_whitespace(chars, index);
+            if chars.get(*index) != Some(&':') {
+                return start
+                    .map(Selector::Index)
+                    .ok_or_else(|| unexpected(chars, *index));
+            }
+            *index += 1;
+            skip_whitespace(chars, index);
+            let end = parse_optional_int(chars, index)?;
+            skip_whitespace(chars, index);
+            let step = if chars.get(*index) == Some(&':') {
+                *index += 1;
+                skip_whitespace(chars, index);
+                parse_optional_int(chars, index)?
+            } else {
+                None
+            };
+            Ok(Selector::Slice { start, end, step })
+        }
+        None => Err(JsonPathError::UnexpectedEnd),
+    }
+}
+
+fn parse_optional_int(chars: &[char], index: &mut usize) -> Result<Option<i64>, JsonPathError> {
+    let start = *index;
+    if chars.get(*index) == Some(&'-') {
+        *index += 1;
+    }
+    let digits_start = *index;
+    while chars.get(*index).is_some_and(char::is_ascii_digit) {
+        *index += 1;
+    }
+    if digits_start == *index {
+        *index = start;
+        return Ok(None);
+    }
+
+    let text: String = chars[start..*index].iter().collect();
+    let leading_zero = chars[digits_start] == '0' && *index - digits_start > 1;
+    if leading_zero || text == "-0" {
+        return Err(JsonPathError::InvalidNumber { position: start });
+    }
+    text.parse()
+        .map(Some)
+        .map_err(|_| JsonPathError::InvalidNumber { position: start })
+}
+
+fn parse_string(chars: &[char], index: &mut usize) -> Result<String, JsonPathError> {
+    let quote = chars[*index];
+    *index += 1;
+
+    let mut output = String::new();
+    loop {
+        let ch = *chars.get(*index).ok_or(JsonPathError::UnexpectedEnd)?;
+        *index += 1;
+        match ch {
+            ch if ch == quote => return Ok(output),
+            '\\' => {
+                let escaped = *chars.get(*index).ok_or(JsonPathError::UnexpectedEnd)?;
+                *index += 1;
+                match escaped {
+                    'b' => output.push('\u{8}'),
+                    'f' => output.push('\u{c}'),
+                    'n' => output.push('\n'),
+                    'r' => output.push('\r'),
+                    't' => output.push('\t'),
+                    '/' | '\\' | '\'' | '"' => output.push(escaped),
+                    'u' => output.push(parse_unicode_escape(chars, index)?),
+                    _ => return Err(unexpected(chars, *index - 1)),
+                }
+            }
+            ch => output.push(ch),
+        }
+    }
+}
+
+fn parse_hex4(chars: &[char], index: &mut usize) -> Result<u32, JsonPathError> {
+    let digits = chars
+        .get(*index..*index + 4)
+        .ok_or(JsonPathError::UnexpectedEnd)?;
+    let mut sum = 0;
+    for (i, digit) in digits.iter().enumerate() {
+        let digit = digit
+            .to_digit(16)
+            .ok_or_else(|| unexpected(chars, *index + i))?;
+        sum = sum * 16 + digit;
+    }
+    *index += 4;
+    Ok(sum)
+}
+
+fn parse_unicode_escape(chars: &[char], index: &mut usize) -> Result<char, JsonPathError> {
+    let start = *index;
+    let high = parse_hex4(chars, index)?;
+    let code = if (0xD800..0xDC00).contains(&high) {
+        if chars.get(*index..*index + 2) != Some(&['\\', 'u']) {
+            return Err(unexpected(chars, *index));
+        }
+        *index += 2;
+        let low = parse_hex4(chars, index)?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err(JsonPathError::UnexpectedChar {
+                position: start,
+                found: 'u',
+            });
+        }
+        0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+    } else {
+        high
+    };
+    char::from_u32(code).ok_or(JsonPathError::UnexpectedChar {
+        position: start,
+        found: 'u',
+    })
+}
+
+fn parse_or(chars: &[char], index: &mut usize) -> Result<Expr, JsonPathError> {
+    let mut operands = vec![parse_and(chars, index)?];
+    loop {
+        skip_whitespace(chars, index);
+        if chars[*index..].starts_with(&['|', '|']) {
+            *index += 2;
+            operands.push(parse_and(chars, index)?);
+        } else if operands.len() == 1 {
+            return Ok(operands.remove(0));
+        } else {
+            return Ok(Expr::Or(operands));
+        }
+    }
+}
+
+fn parse_and(chars: &[char], index: &mut usize) -> Result<Expr, JsonPathError> {
+    let mut operands = vec![parse_basic(chars, index)?];
+    loop {
+        skip_whitespace(chars, index);
+        if chars[*index..].starts_with(&['&', '&']) {
+            *index += 2;
+            operands.push(parse_basic(chars, index)?);
+        } else if operands.len() == 1 {
+            return Ok(operands.remove(0));
+        } else {
+            return Ok(Expr::And(operands));
+        }
+    }
+}
+
+fn parse_basic(chars: &[char], index: &mut usize) -> Result<Expr, JsonPathError> {
+    skip_whitespace(chars, index);
+    match chars.get(*index) {
+        Some('!') => {
+            *index += 1;
+            let inner = parse_basic(chars, index)?;
+            Ok(Expr::Not(Box::new(inner)))
+        }
+        Some('(') => {
+            *index += 1;
+            let inner = parse_or(chars, index)?;
+            expect(chars, index, ')')?;
+            Ok(inner)
+        }
+        _ => {
+            let start = *index;
+            let left = parse_comparable(chars, index)?;
+            skip_whitespace(chars, index);
+            if let Some(comparison) = parse_comparison(chars, index) {
+                skip_whitespace(chars, index);
+                let right = parse_comparable(chars, index)?;
+                return Ok(Expr::Compare(left, comparison, right));
+            }
+            match left {
+                Comparable::Query(query) => Ok(Expr::Exists(query)),
+                Comparable::Function(function) => Ok(Expr::Function(function)),
+                Comparable::Literal(_) => Err(JsonPathError::ExpectedTest { position: start }),
+            }
+        }
+    }
+}
+
+fn parse_comparison(chars: &[char], index: &mut usize) -> Option<Comparison> {
+    let rest = &chars[*index..];
+    let (comparison, len) = if rest.starts_with(&['=', '=']) {
+        (Comparison::Equal, 2)
+    } else if rest.starts_with(&['!', '=']) {
+        (Comparison::NotEqual, 2)
+    } else if rest.starts_with(&['<', '=']) {
+        (Comparison::LessOrEqual, 2)
+    } else if rest.starts_with(&['>', '=']) {
+        (Comparison::GreaterOrEqual, 2)
+    } else if rest.starts_with(&['<']) {
+        (Comparison::Less, 1)
+    } else if rest.starts_with(&['>']) {
+        (Comparison::Greater, 1)
+    } else {
+        return None;
+    };
+    *index += len;
+    Some(comparison)
+}
+
+fn parse_comparable(chars: &[char], index: &mut usize) -> Result<Comparable, JsonPathError> {
+    match chars.get(*index) {
+        Some('@' | '$') => Ok(Comparable::Query(parse_query(chars, index)?)),
+        Some('\'' | '"') => Ok(Comparable::Literal(Value::String(parse_string(
+            chars, index,
+        )?))),
+        Some(ch) if *ch == '-' || ch.is_ascii_digit() => {
+            Ok(Comparable::Literal(parse_number(chars, index)?))
+        }
+        Some(ch) if ch.is_ascii_lowercase() => {
+            let start = *index;
+            while chars
+                .get(*index)
+                .is_some_and(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || *ch == '_')
+            {
+                *index += 1;
+            }
+            let name: String = chars[start..*index].iter().collect();
+            match name.as_str() {
+                "true" => Ok(Comparable::Literal(Value::Boolean(true))),
+                "false" => Ok(Comparable::Literal(Value::Boolean(false))),
+                "null" => Ok(Comparable::Literal(Value::Null)),
+                _ => Ok(Comparable::Function(parse_function(name, chars, index)?)),
+            }
+        }
+        _ => Err(unexpected(chars, *index)),
+    }
+}
+
+fn parse_query(chars: &[char], index: &mut usize) -> Result<Query, JsonPathError> {
+    let relative = chars[*index] == '@';
+    *index += 1;
+    let segments = parse_segments(chars, index)?;
+    Ok(Query { relative, segments })
+}
+
+fn parse_number(chars: &[char], index: &mut usize) -> Result<Value, JsonPathError> {
+    let start = *index;
+    while chars
+        .get(*index)
+        .is_some_and(|ch| ch.is_ascii_digit() || matches!(ch, '-' | '+' | '.' | 'e' | 'E'))
+    {
+        *index += 1;
+    }
+    let text: String = chars[start..*index].iter().collect();
+    text.parse()
+        .map(Value::Number)
+        .map_err(|_| JsonPathError::InvalidNumber { position: start })
+}
+
+fn parse_function(
+    name: String,
+    chars: &[char],
+    index: &mut usize,
+) -> Result<Function, JsonPathError> {
+    let function_name = match name.as_str() {
+        "length" => FunctionName::Length,
+        "count" => FunctionName::Count,
+        "match" => FunctionName::Match,
+        "search" => FunctionName::Search,
+        "value" => FunctionName::Value,
+        _ => return Err(JsonPathError::UnknownFunction(name)),
+    };
+    if chars.get(*index) != Some(&'(') {
+        return Err(unexpected(chars, *index));
+    }
+    *index += 1;
+
+    let mut args = Vec::new();
+    skip_whitespace(chars, index);
+    if chars.get(*index) == Some(&')') {
+        *index += 1;
+    } else {
+        loop {
+            skip_whitespace(chars, index);
+            args.push(parse_argument(chars, index)?);
+            skip_whitespace(chars, index);
+            match chars.get(*index) {
+                Some(',') => *index += 1,
+                Some(')') => {
+                    *index += 1;
+                    break;
+                }
+                _ => return Err(unexpected(chars, *index)),
+            }
+        }
+    }
+
+    let arity = match function_name {
+        FunctionName::Length | FunctionName::Count | FunctionName::Value => 1,
+        FunctionName::Match | FunctionName::Search => 2,
+    };
+    let nodes_argument = matches!(function_name, FunctionName::Count | FunctionName::Value);
+    if args.len() != arity || (nodes_argument && !matches!(args[0], Argument::Query(_))) {
+        return Err(JsonPathError::InvalidArguments(name));
+    }
+    let regex = match (function_name, args.get(1)) {
+        (
+            FunctionName::Match | FunctionName::Search,
+            Some(Argument::Literal(Value::String(pattern))),
+        ) => Some(Regex::new(pattern).ok()),
+        _ => None,
+    };
+    Ok(Function {
+        name: function_name,
+        args,
+        regex,
+    })
+}
+
+fn parse_argument(chars: &[char], index: &mut usize) -> Result<Argument, JsonPathError> {
+    let start = *index;
+    let expr = parse_or(chars, index);
+    match expr {
+        Ok(Expr::Exists(query)) => Ok(Argument::Query(query)),
+        Ok(Expr::Function(function)) => Ok(Argument::Function(function)),
+        Ok(expr) => Ok(Argument::Logical(expr)),
+        Err(JsonPathError::ExpectedTest { .. }) => {
+            *index = start;
+            match parse_comparable(chars, index)? {
+                Comparable::Literal(value) => Ok(Argument::Literal(value)),
+                _ => Err(unexpected(chars, start)),
+            }
+        }
+        Err(err) => Err(err),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PathElement {
+    Key(String),
+    Index(usize),
+}
+
+#[derive(Debug, Clone)]
+struct Node<'a> {
+    path: Vec<PathElement>,
+    value: &'a Value,
+}
+
+impl<'a> Node<'a> {
+    fn child(&self, element: PathElement, value: &'a Value) -> Self {
+        let mut path = self.path.clone();
+        path.push(element);
+        Self { path, value }
+    }
+
+    /// The children in document order, object members are ordered by key
+    fn children(&self) -> Vec<Node<'a>> {
+        match self.value {
+            Value::Array(array) => array
+                .iter()
+                .enumerate()
+                .map(|(i, value)| self.child(PathElement::Index(i), value))
+                .collect(),
+            Value::Object(map) => {
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort();
+                keys.into_iter()
+                    .map(|key| self.child(PathElement::Key(key.clone()), &map[key]))
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn descendants_or_self(self, output: &mut Vec<Node<'a>>) {
+        let children = self.children();
+        output.push(self);
+        for child in children {
+            child.descendants_or_self(output);
+        }
+    }
+}
+
+fn select<'a>(segments: &[Segment], root: &'a Value, mut nodes: Vec<Node<'a>>) -> Vec<Node<'a>> {
+    for segment in segments {
+        let (selectors, inputs) = match segment {
+            Segment::Child(selectors) => (selectors, nodes),
+            Segment::Descendant(selectors) => {
+                let mut inputs = Vec::new();
+                for node in nodes {
+                    node.descendants_or_self(&mut inputs);
+                }
+                (selectors, inputs)
+            }
+        };
+
+        nodes = Vec::new();
+        for node in &inputs {
+            for selector in selectors {
+                apply_selector(selector, node, root, &mut nodes);
+            }
+        }
+    }
+    nodes
+}
+
+fn apply_selector<'a>(
+    selector: &Selector,
+    node: &Node<'a>,
+    root: &'a Value,
+    output: &mut Vec<Node<'a>>,
+) {
+    match (selector, node.value) {
+        (Selector::Name(name), Value::Object(map)) => {
+            if let Some(value) = map.get(name) {
+                output.push(node.child(PathElement::Key(name.clone()), value));
+            }
+        }
+        (Selector::Wildcard, _) => output.extend(node.children()),
+        (Selector::Index(index), Value::Array(array)) => {
+            let len = array.len() as i64;
+            let index = if *index < 0 { len + index } else { *index };
+            if (0..len).contains(&index) {
+                let index = index as usize;
+                output.push(node.child(PathElement::Index(index), &array[index]));
+            }
+        }
+        (Selector::Slice { start, end, step }, Value::Array(array)) => {
+            for index in slice_indices(array.len() as i64, *start, *end, step.unwrap_or(1)) {
+                output.push(node.child(PathElement::Index(index), &array[index]));
+            }
+        }
+        (Selector::Filter(expr), _) => {
+            for child in node.children() {
+                if evaluate(expr, child.value, root) {
+                    output.push(child);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn slice_indices(len: i64, start: Option<i64>, end: Option<i64>, step: i64) -> Vec<usize> {
+    let normalize = |i: i64| if i >= 0 { i } else { len + i };
+    let mut indices = Vec::new();
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).clamp(0, len);
+        let upper = normalize(end.unwrap_or(len)).clamp(0, len);
+        let mut i = lower;
+        while i < upper {
+            indices.push(i as usize);
+            // a huge step goes past the end, ex. `[::9223372036854775807]`
+            let Some(following) = i.checked_add(step) else {
+                break;
+            };
+            i = following;
+        }
+    } else if step < 0 {
+        let upper = normalize(start.unwrap_or(len - 1)).clamp(-1, len - 1);
+        let lower = end.map_or(-1, |end| normalize(end).clamp(-1, len - 1));
+        let mut i = upper;
+        while lower < i {
+            indices.push(i as usize);
+            let Some(following) = i.checked_add(step) else {
+                break;
+            };
+            i = following;
+        }
+    }
+    indices
+}
+
+fn run_query<'a>(query: &Query, current: &'a Value, root: &'a Value) -> Vec<&'a Value> {
+    let start = if query.relative { current } else { root };
+    let nodes = vec![Node {
+        path: Vec::new(),
+        value: start,
+    }];
+    select(&query.segments, root, nodes)
+        .into_iter()
+        .map(|node| node.value)
+        .collect()
+}
+
+fn evaluate(expr: &Expr, current: &Value, root: &Value) -> bool {
+    match expr {
+        Expr::Or(operands) => operands.iter().any(|expr| evaluate(expr, current, root)),
+        Expr::And(operands) => operands.iter().all(|expr| evaluate(expr, current, root)),
+        Expr::Not(inner) => !evaluate(inner, current, root),
+        Expr::Exists(query) => !run_query(query, current, root).is_empty(),
+        Expr::Function(function) => match call(function, current, root) {
+            FunctionResult::Logical(result) => result,
+            FunctionResult::Value(value) => value.is_some(),
+        },
+        Expr::Compare(left, comparison, right) => {
+            let left = comparable_value(left, current, root);
+            let right = comparable_value(right, current, root);
+            compare(left.as_deref(), *comparison, right.as_deref())
+        }
+    }
+}
+
+/// The value of a comparison operand, `None` when a query selects nothing
+fn comparable_value<'a>(
+    comparable: &'a Comparable,
+    current: &'a Value,
+    root: &'a Value,
+) -> Option<Cow<'a, Value>> {
+    match comparable {
+        Comparable::Literal(value) => Some(Cow::Borrowed(value)),
+        Comparable::Query(query) => single(run_query(query, current, root)).map(Cow::Borrowed),
+        Comparable::Function(function) => match call(function, current, root) {
+            FunctionResult::Value(value) => value,
+            FunctionResult::Logical(_) => None,
+        },
+    }
+}
+
+fn single(nodes: Vec<&Value>) -> Option<&Value> {
+    match nodes.as_slice() {
+        [value] => Some(value),
+        _ => None,
+    }
+}
+
+fn compare(left: Option<&Value>, comparison: Comparison, right: Option<&Value>) -> bool {
+    let equal = left == right;
+    let less = match (left, right) {
+        (Some(Value::Number(a)), Some(Value::Number(b))) => a < b,
+        (Some(Value::String(a)), Some(Value::String(b))) => a < b,
+        _ => false,
+    };
+    let greater = match (left, right) {
+        (Some(Value::Number(a)), Some(Value::Number(b))) => a > b,
+        (Some(Value::String(a)), Some(Value::String(b))) => a > b,
+        _ => false,
+    };
+    match comparison {
+        Comparison::Equal => equal,
+        Comparison::NotEqual => !equal,
+        Comparison::Less => less,
+        Comparison::LessOrEqual => less || equal,
+        Comparison::Greater => greater,
+        Comparison::GreaterOrEqual => greater || equal,
+    }
+}
+
+enum FunctionResult<'a> {
+    Value(Option<Cow<'a, Value>>),
+    Logical(bool),
+}
+
+fn argument_value<'a>(
+    argument: &'a Argument,
+    current: &'a Value,
+    root: &'a Value,
+) -> Option<Cow<'a, Value>> {
+    match argument {
+        Argument::Literal(value) => Some(Cow::Borrowed(value)),
+        Argument::Query(query) => single(run_query(query, current, root)).map(Cow::Borrowed),
+        Argument::Function(function) => match call(function, current, root) {
+            FunctionResult::Value(value) => value,
+            FunctionResult::Logical(_) => None,
+        },
+        Argument::Logical(_) => None,
+    }
+}
+
+fn call<'a>(function: &'a Function, current: &'a Value, root: &'a Value) -> FunctionResult<'a> {
+    let args = &function.args;
+    match function.name {
+        FunctionName::Length => {
+            let length = match argument_value(&args[0], current, root).as_deref() {
+                Some(Value::String(s)) => Some(s.chars().count()),
+                Some(Value::Array(array)) => Some(array.len()),
+                Some(Value::Object(map)) => Some(map.len()),
+                _ => None,
+            };
+            FunctionResult::Value(length.map(|length| Cow::Owned(Value::Number(length as f64))))
+        }
+        FunctionName::Count => {
+            let Argument::Query(query) = &args[0] else {
+                unreachable!("checked when compiling");
+            };
+            let count = run_query(query, current, root).len();
+            FunctionResult::Value(Some(Cow::Owned(Value::Number(count as f64))))
+        }
+        FunctionName::Value => {
+            let Argument::Query(query) = &args[0] else {
+                unreachable!("checked when compiling");
+            };
+            FunctionResult::Value(single(run_query(query, current, root)).map(Cow::Borrowed))
+        }
+        FunctionName::Match | FunctionName::Search => {
+            let input = argument_value(&args[0], current, root);
+            let Some(Value::String(input)) = input.as_deref() else {
+                return FunctionResult::Logical(false);
+            };
+            let compiled;
+            let regex = match &function.regex {
+                Some(regex) => regex.as_ref(),
+                None => match argument_value(&args[1], current, root).as_deref() {
+                    Some(Value::String(pattern)) => {
+                        compiled = Regex::new(pattern).ok();
+                        compiled.as_ref()
+                    }
+                    _ => None,
+                },
+            };
+            let result = regex.is_some_and(|regex| match function.name {
+                FunctionName::Match => regex.is_full_match(input),
+                _ => regex.is_match(input),
+            });
+            FunctionResult::Logical(result)
+        }
+    }
+}
+
+fn normalized_path(path: &[PathElement]) -> String {
+    let mut output = String::from("$");
+    for element in path {
+        match element {
+            PathElement::Index(index) => write!(output, "[{index}]").unwrap(),
+            PathElement::Key(key) => {
+                output.push_str("['");
+                for ch in key.chars() {
+                    match ch {
+                        '\'' => output.push_str("\\'"),
+                        '\\' => output.push_str("\\\\"),
+                        '\u{8}' => output.push_str("\\b"),
+                        '\u{c}' => output.push_str("\\f"),
+                        '\n' => output.push_str("\\n"),
+                        '\r' => output.push_str("\\r"),
+                        '\t' => output.push_str("\\t"),
+                        ch if (ch as u32) < 0x20 => write!(output, "\\u{:04x}", ch as u32).unwrap(),
+                        ch => output.push(ch),
+                    }
+                }
+                output.push_str("']");
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::{JsonPath, JsonPathError};
+
+    fn store() -> Value {
+        Parser::new().parse_str(
+            r#"{ "store": {
+                "book": [
+                    { "category": "reference", "author": "Nigel Rees", "title": "Sayings of the Century", "price": 8.95 },
+                    { "category": "fiction", "author": "Evelyn Waugh", "title": "Sword of Honour", "price": 12.99 },
+                    { "category": "fiction", "author": "Herman Melville", "title": "Moby Dick", "isbn": "0-553-21311-3", "price": 8.99 },
+                    { "category": "fiction", "author": "J. R. R. Tolkien", "title": "The Lord of the Rings", "isbn": "0-395-19395-8", "price": 22.99 }
+                ],
+                "bicycle": { "color": "red", "price": 399 }
+            } }"#,
+        )
+        .unwrap()
+    }
+
+    fn check_paths(expression: &str, expected: &[&str]) {
+        let value = store();
+        let actual: Vec<String> = JsonPath::compile(expression)
+            .unwrap()
+            .query(&value)
+            .into_iter()
+            .map(|m| m.path)
+            .collect();
+        assert_eq!(actual, expected, "{expression}");
+    }
+
+    fn check_values(expression: &str, expected: &[Value]) {
+        let value = store();
+        let actual: Vec<Value> = value
+            .query_path(expression)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.value.clone())
+            .collect();
+        assert_eq!(actual, expected, "{expression}");
+    }
+
+    #[test]
+    fn root() {
+        check_paths("$", &["$"]);
+    }
+
+    #[test]
+    fn dot_and_bracket_names() {
+        check_values("$.store.bicycle.color", &[Value::string("red")]);
+        check_values("$['store'][\"bicycle\"]['color']", &[Value::string("red")]);
+    }
+
+    #[test]
+    fn filter_with_comparison() {
+        check_values(
+            "$.store.book[?(@.price < 10)].title",
+            &[
+                Value::string("Sayings of the Century"),
+                Value::string("Moby Dick"),
+            ],
+        );
+    }
+
+    #[test]
+    fn normalized_paths() {
+        check_paths(
+            "$.store.book[?@.isbn].title",
+            &[
+                "$['store']['book'][2]['title']",
+                "$['store']['book'][3]['title']",
+            ],
+        );
+    }
+
+    #[test]
+    fn indexes_and_slices() {
+        check_paths("$.store.book[-1]", &["$['store']['book'][3]"]);
+        check_paths(
+            "$.store.book[0, 2]",
+            &["$['store']['book'][0]", "$['store']['book'][2]"],
+        );
+        check_paths(
+            "$.store.book[1:3]",
+            &["$['store']['book'][1]", "$['store']['book'][2]"],
+        );
+        check_paths(
+            "$.store.book[::-2]",
+            &["$['store']['book'][3]", "$['store']['book'][1]"],
+        );
+        check_paths("$.store.book[:0]", &[]);
+        check_paths(
+            "$.store.book[::9223372036854775807]",
+            &["$['store']['book'][0]"],
+        );
+        check_paths(
+            "$.store.book[::-9223372036854775808]",
+            &["$['store']['book'][3]"],
+        );
+    }
+
+    #[test]
+    fn descendants() {
+        check_values(
+            "$..price",
+            &[
+                Value::Number(399.0),
+                Value::Number(8.95),
+                Value::Number(12.99),
+                Value::Number(8.99),
+                Value::Number(22.99),
+            ],
+        );
+        check_paths("$..book[0].author", &["$['store']['book'][0]['author']"]);
+    }
+
+    #[test]
+    fn wildcards() {
+        check_paths(
+            "$.store.*",
+            &["$['store']['bicycle']", "$['store']['book']"],
+        );
+        check_paths(
+            "$.store.book[*].author",
+            &[
+                "$['store']['book'][0]['author']",
+                "$['store']['book'][1]['author']",
+                "$['store']['book'][2]['author']",
+                "$['store']['book'][3]['author']",
+            ],
+        );
+    }
+
+    #[test]
+    fn logical_operators() {
+        check_values(
+            "$.store.book[?@.category == 'fiction' && !(@.price > 20)].title",
+            &[Value::string("Sword of Honour"), Value::string("Moby Dick")],
+        );
+        check_values(
+            "$.store.book[?@.price < 9 || @.price > 20].price",
+            &[
+                Value::Number(8.95),
+                Value::Number(8.99),
+                Value::Number(22.99),
+            ],
+        );
+    }
+
+    #[test]
+    fn absolute_query_in_filter() {
+        check_values(
+            "$.store.book[?@.price > $.store.book[1].price].title",
+            &[Value::string("The Lord of the Rings")],
+        );
+    }
+
+    #[test]
+    fn functions() {
+        check_values(
+            "$.store.book[?length(@.title) == 9].title",
+            &[Value::string("Moby Dick")],
+        );
+        check_values("$.store[?count(@.*) == 2].color", &[Value::string("red")]);
+        check_values(
+            "$.store.book[?match(@.author, 'J.*')].title",
+            &[Value::string("The Lord of the Rings")],
+        );
+        check_values(
+            "$.store.book[?search(@.author, 'Mel')].price",
+            &[Value::Number(8.99)],
+        );
+        check_values(
+            "$.store.book[?value(@.isbn) == '0-553-21311-3'].price",
+            &[Value::Number(8.99)],
+        );
+    }
+
+    #[test]
+    fn regex_functions_on_long_strings() {
+        let value = Value::Array(vec![Value::String("a".repeat(200_000))]);
+        for (expression, expected) in [
+            ("$[?match(@, '[a-z]*')]", 1),
+            ("$[?match(@, '(a|aa)*b')]", 0),
+            ("$[?search(@, 'a*b')]", 0),
+            ("$[?search(@, 'a{3}$')]", 1),
+        ] {
+            let matches = value.query_path(expression).unwrap();
+            assert_eq!(matches.len(), expected, "{expression}");
+        }
+    }
+
+    #[test]
+    fn regex_patterns_from_the_document() {
+        let nested = format!("{}a{}", "(".repeat(200_000), ")".repeat(200_000));
+        let value = Value::Array(vec![
+            Value::object([("s", Value::string("abc")), ("p", Value::string("a.c"))]),
+            Value::object([("s", Value::string("abc")), ("p", Value::string("("))]),
+            Value::object([("s", Value::string("a")), ("p", Value::String(nested))]),
+        ]);
+        assert_eq!(value.query_path("$[?match(@.s, @.p)]").unwrap().len(), 1);
+        assert_eq!(value.query_path("$[?search(@.s, '(')]").unwrap().len(), 0);
+    }
+
+    #[test]
+    fn surrogate_pairs_in_names() {
+        let value = Value::object([("\u{1F600}", Value::Null)]);
+        let matches = value.query_path(r"$['\uD83D\uDE00']").unwrap();
+        assert_eq!(matches.len(), 1);
+        assert!(matches!(
+            JsonPath::compile(r"$['\uD83D\u0041']"),
+            Err(JsonPathError::UnexpectedChar { found: 'u', .. })
+        ));
+    }
+
+    #[test]
+    fn escaped_key_in_normalized_path() {
+        let value = Value::object([("it's", Value::Null)]);
+        let matches = value.query_path("$['it\\'s']").unwrap();
+        assert_eq!(matches[0].path, r"$['it\'s']");
+    }
+
+    #[test]
+    fn compile_errors() {
+        assert_eq!(JsonPath::compile("store"), Err(JsonPathError::MissingRoot));
+        assert_eq!(
+            JsonPath::compile("$.store["),
+            Err(JsonPathError::UnexpectedEnd)
+        );
+        assert_eq!(
+            JsonPath::compile("$[?nope(@)]"),
+            Err(JsonPathError::UnknownFunction(String::from("nope")))
+        );
+        assert_eq!(
+            JsonPath::compile("$[01]"),
+            Err(JsonPathError::InvalidNumber { position: 2 })
+        );
+        assert_eq!(
+            JsonPath::compile("$[?1]"),
+            Err(JsonPathError::ExpectedTest { position: 3 })
+        );
+        assert_eq!(
+            JsonPath::compile("$[01]").unwrap_err().to_string(),
+            "invalid number at position 2"
+        );
+    }
+}
@@ -0,0 +1,775 @@
+//! JSON Type Definition (RFC 8927) schemas
+//!
+//! Validation errors follow the RFC's standard form: a pointer into the instance plus a
+//! pointer to the schema keyword that rejected it.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::{JsonPointer, Value};
+
+/// A compiled JSON Type Definition schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct JtdSchema {
+    definitions: BTreeMap<String, Node>,
+    root: Node,
+}
+
+/// The schema document is not a valid JTD schema
+#[derive(Debug, Clone, PartialEq)]
+pub enum JtdError {
+    /// The schema at this location is not an object or mixes keywords of different forms
+    InvalidForm(JsonPointer),
+
+    /// The keyword's value has the wrong shape, ex. `"nullable": 1`
+    InvalidKeyword {
+        pointer: JsonPointer,
+        keyword: &'static str,
+    },
+
+    /// The `type` is not one of the JTD primitive types
+    UnknownType(String),
+
+    /// A `ref` names a definition that does not exist
+    MissingDefinition(String),
+
+    /// `definitions` may only appear on the root schema
+    NestedDefinitions(JsonPointer),
+
+    /// Definitions refer to each other without ever describing a value
+    CyclicRef(String),
+}
+
+impl fmt::Display for JtdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidForm(pointer) => write!(f, "the schema at `{pointer}` has no valid form"),
+            Self::InvalidKeyword { pointer, keyword } => {
+                write!(f, "invalid `{keyword}` keyword at `{pointer}`")
+            }
+            Self::UnknownType(name) => write!(f, "unknown type {name:?}"),
+            Self::MissingDefinition(name) => write!(f, "no definition named {name:?}"),
+            Self::NestedDefinitions(pointer) => {
+                write!(f, "`definitions` at `{pointer}` is not on the root schema")
+            }
+            Self::CyclicRef(name) => write!(f, "definition {name:?} only refers to itself"),
+        }
+    }
+}
+
+impl std::error::Error for JtdError {}
+
+/// The instance does not conform, in the standard RFC 8927 form
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    pub instance_path: JsonPointer,
+    pub schema_path: JsonPointer,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the value at `{}` is rejected by `{}`",
+            self.instance_path, self.schema_path
+        )
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Node {
+    nullable: bool,
+    form: Form,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Form {
+    Empty,
+    Ref(String),
+    Type(&'static str),
+    Enum(Vec<String>),
+    Elements(Box<Node>),
+    Properties {
+        required: BTreeMap<String, Node>,
+        optional: BTreeMap<String, Node>,
+        additional: bool,
+    },
+    Values(Box<Node>),
+    Discriminator {
+        tag: String,
+        mapping: BTreeMap<String, Node>,
+    },
+}
+
+const TYPES: [&str; 11] = [
+    "boolean",
+    "string",
+    "timestamp",
+    "float32",
+    "float64",
+    "int8",
+    "uint8",
+    "int16",
+    "uint16",
+    "int32",
+    "uint32",
+];
+
+impl JtdSchema {
+    pub fn compile(schema: &Value) -> Result<Self, JtdError> {
+        let mut pointer = JsonPointer::root();
+        let map = schema
+            .as_object()
+            .ok_or(JtdError::InvalidForm(JsonPointer::root()))?;
+
+        let mut definitions = BTreeMap::new();
+        if let Some(defs) = map.get("definitions") {
+            let defs = defs.as_object().ok_or(JtdError::InvalidKeyword {
+                pointer: JsonPointer::root(),
+                keyword: "definitions",
+            })?;
+            for (name, def) in defs {
+                pointer.push("definitions");
+                pointer.push(name.clone());
+                definitions.insert(name.clone(), compile_node(def, &mut pointer)?);
+                pointer.pop();
+                pointer.pop();
+            }
+        }
+        let root = compile_node(schema, &mut pointer)?;
+
+        let schema = Self { definitions, root };
+        schema.check_refs(&schema.root)?;
+        for def in schema.definitions.values() {
+            schema.check_refs(def)?;
+        }
+        for name in schema.definitions.keys() {
+            schema.check_ref_cycle(name)?;
+        }
+        Ok(schema)
+    }
+
+    pub fn validate(&self, instance: &Value) -> Result<(), Vec<ValidationError>> {
+        let mut validator = Validator {
+            schema: self,
+            instance_path: JsonPointer::root(),
+            schema_path: JsonPointer::root(),
+            errors: Vec::new(),
+        };
+        validator.validate(&self.root, instance, None);
+        if validator.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(validator.errors)
+        }
+    }
+
+    pub fn is_valid(&self, instance: &Value) -> bool {
+        self.validate(instance).is_ok()
+    }
+
+    fn check_refs(&self, node: &Node) -> Result<(), JtdError> {
+        match &node.form {
+            Form::Ref(name) if !self.definitions.contains_key(name) => {
+                Err(JtdError::MissingDefinition(name.clone()))
+            }
+            Form::Elements(inner) | Form::Values(inner) => self.check_refs(inner),
+            Form::Properties {
+                required, optional, ..
+            } => required
+                .values()
+                .chain(optional.values())
+                .try_for_each(|node| self.check_refs(node)),
+            Form::Discriminator { mapping, .. } => {
+                mapping.values().try_for_each(|node| self.check_refs(node))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// A definition that is only a `ref` to itself, directly or through others, can never be satisfied
+    fn check_ref_cycle(&self, start: &str) -> Result<(), JtdError> {
+        let mut current = start;
+        for _ in 0..=self.definitions.len() {
+            match &self.definitions[current].form {
+                Form::Ref(next) if next == start => {
+                    return Err(JtdError::CyclicRef(String::from(start)));
+                }
+                Form::Ref(next) => current = next,
+                _ => return Ok(()),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn compile_node(schema: &Value, pointer: &mut JsonPointer) -> Result<Node, JtdError> {
+    let map = schema
+        .as_object()
+        .ok_or_else(|| JtdError::InvalidForm(pointer.clone()))?;
+    let here = pointer.clone();
+    let invalid = |keyword| JtdError::InvalidKeyword {
+        pointer: here.clone(),
+        keyword,
+    };
+
+    if !pointer.is_root() && map.contains_key("definitions") {
+        return Err(JtdError::NestedDefinitions(pointer.clone()));
+    }
+    let nullable = match map.get("nullable") {
+        None => false,
+        Some(nullable) => nullable.as_bool().ok_or(invalid("nullable"))?,
+    };
+    let string = |keyword| {
+        map.get(keyword)
+            .map(|value| value.as_str().map(String::from).ok_or(invalid(keyword)))
+    };
+
+    let form_keywords = [
+        "ref",
+        "type",
+        "enum",
+        "elements",
+        "properties",
+        "optionalProperties",
+        "values",
+        "discriminator",
+    ];
+    let present: Vec<&str> = form_keywords
+        .into_iter()
+        .filter(|k| map.contains_key(*k))
+        .collect();
+    let allowed = [
+        "definitions",
+        "nullable",
+        "metadata",
+        "additionalProperties",
+        "mapping",
+    ];
+    let unknown = map
+        .keys()
+        .any(|k| !form_keywords.contains(&k.as_str()) && !allowed.contains(&k.as_str()));
+
+    let form = match present.as_slice() {
+        _ if unknown => return Err(JtdError::InvalidForm(pointer.clone())),
+        [] => Form::Empty,
+        ["ref"] => Form::Ref(string("ref").unwrap()?),
+        ["type"] => {
+            let name = string("type").unwrap()?;
+            let name = TYPES
+                .into_iter()
+                .find(|t| *t == name)
+                .ok_or(JtdError::UnknownType(name))?;
+            Form::Type(name)
+        }
+        ["enum"] => {
+            let values = map["enum"].as_array().ok_or(invalid("enum"))?;
+            let mut variants: Vec<String> = Vec::new();
+            for value in values {
+                let variant = value.as_str().ok_or(invalid("enum"))?;
+                if variants.iter().any(|v| v == variant) {
+                    return Err(invalid("enum"));
+                }
+                variants.push(String::from(variant));
+            }
+            if variants.is_empty() {
+                return Err(invalid("enum"));
+            }
+            Form::Enum(variants)
+        }
+        ["elements"] => Form::Elements(Box::new(compile_child(
+            &map["elements"],
+            pointer,
+            &["elements"],
+        )?)),
+        ["values"] => Form::Values(Box::new(compile_child(
+            &map["values"],
+            pointer,
+            &["values"],
+        )?)),
+        ["properties"] | ["optionalProperties"] | ["properties", "optionalProperties"] => {
+            let mut properties = [BTreeMap::new(), BTreeMap::new()];
+            for (keyword, compiled) in ["properties", "optionalProperties"]
+                .into_iter()
+                .zip(&mut properties)
+            {
+                let Some(members) = map.get(keyword) else {
+                    continue;
+                };
+                let members = members.as_object().ok_or(invalid(keyword))?;
+                for (name, member) in members {
+                    compiled.insert(
+                        name.clone(),
+                        compile_child(member, pointer, &[keyword, name])?,
+                    );
+                }
+            }
+            let [required, optional] = properties;
+            if required.keys().any(|key| optional.contains_key(key)) {
+                return Err(invalid("optionalProperties"));
+            }
+            let additional = match map.get("additionalProperties") {
+                None => false,
+                Some(additional) => additional
+                    .as_bool()
+                    .ok_or(invalid("additionalProperties"))?,
+            };
+            Form::Properties {
+                required,
+                optional,
+                additional,
+            }
+        }
+        ["discriminator"] => {
+            let tag = string("discriminator").unwrap()?;
+            let mappings = map
+                .get("mapping")
+                .and_then(Value::as_object)
+                .ok_or(invalid("mapping"))?;
+            let mut mapping = BTreeMap::new();
+            for (name, variant) in mappings {
+                let node = compile_child(variant, pointer, &["mapping", name])?;
+                let is_valid_variant = match &node.form {
+                    Form::Properties {
+                        required, optional, ..
+                    } => {
+                        !node.nullable
+                            && !required.contains_key(&tag)
+                            && !optional.contains_key(&tag)
+                    }
+                    _ => false,
+                };
+                if !is_valid_variant {
+                    return Err(invalid("mapping"));
+                }
+                mapping.insert(name.clone(), node);
+            }
+            Form::Discriminator { tag, mapping }
+        }
+        _ => return Err(JtdError::InvalidForm(pointer.clone())),
+    };
+
+    let only_with = |keyword, form_keywords: &[&str]| {
+        map.contains_key(keyword) && !present.iter().any(|p| form_keywords.contains(p))
+    };
+    if only_with(
+        "additionalProperties",
+        &["properties", "optionalProperties"],
+    ) || only_with("mapping", &["discriminator"])
+    {
+        return Err(JtdError::InvalidForm(pointer.clone()));
+    }
+    Ok(Node { nullable, form })
+}
+
+fn compile_child(
+    schema: &Value,
+    pointer: &mut JsonPointer,
+    tokens: &[&str],
+) -> Result<Node, JtdError> {
+    for token in tokens {
+        pointer.push(*token);
+    }
+    let node = compile_node(schema, pointer);
+    for _ in tokens {
+        pointer.pop();
+    }
+    node
+}
+
+struct Validator<'a> {
+    schema: &'a JtdSchema,
+    instance_path: JsonPointer,
+    schema_path: JsonPointer,
+    errors: Vec<ValidationError>,
+}
+
+impl Validator<'_> {
+    fn fail(&mut self, keyword: &str) {
+        self.errors.push(ValidationError {
+            instance_path: self.instance_path.clone(),
+            schema_path: self.schema_path.join(keyword),
+        });
+    }
+
+    /// Validates `instance`, `parent_tag` is the discriminator to skip when checking properties
+    fn validate(&mut self, node: &Node, instance: &Value, parent_tag: Option<&str>) {
+        if node.nullable && instance.is_null() {
+            return;
+        }
+
+        match &node.form {
+            Form::Empty => {}
+            Form::Ref(name) => {
+                let definition = &self.schema.definitions[name];
+                let saved = std::mem::replace(
+                    &mut self.schema_path,
+                    JsonPointer::root().join("definitions").join(name.clone()),
+                );
+                self.validate(definition, instance, None);
+                self.schema_path = saved;
+            }
+            Form::Type(name) => {
+                if !has_type(instance, name) {
+                    self.fail("type");
+                }
+            }
+            Form::Enum(variants) => {
+                if !instance
+                    .as_str()
+                    .is_some_and(|s| variants.iter().any(|v| v == s))
+                {
+                    self.fail("enum");
+                }
+            }
+            Form::Elements(inner) => {
+                let Value::Array(array) = instance else {
+                    self.fail("elements");
+                    return;
+                };
+                self.schema_path.push("elements");
+                for (i, item) in array.iter().enumerate() {
+                    self.instance_path.push(i.to_string());
+                    self.validate(inner, item, None);
+                    self.instance_path.pop();
+                }
+                self.schema_path.pop();
+            }
+            Form::Values(inner) => {
+                let Value::Object(map) = instance else {
+                    self.fail("values");
+                    return;
+                };
+                self.schema_path.push("values");
+                for (key, value) in sorted(map) {
+                    self.instance_path.push(key.clone());
+                    self.validate(inner, value, None);
+                    self.instance_path.pop();
+                }
+                self.schema_path.pop();
+            }
+            Form::Properties {
+                required,
+                optional,
+                additional,
+            } => self.validate_properties(required, optional, *additional, instance, parent_tag),
+            Form::Discriminator { tag, mapping } => {
+                let Value::Object(map) = instance else {
+                    self.fail("discriminator");
+                    return;
+                };
+                let Some(value) = map.get(tag) else {
+                    self.fail("discriminator");
+                    return;
+                };
+                self.instance_path.push(tag.clone());
+                let variant = value.as_str().map(|name| (name, mapping.get(name)));
+                match variant {
+                    None => self.fail("discriminator"),
+                    Some((_, None)) => self.fail("mapping"),
+                    Some((name, Some(node))) => {
+                        self.instance_path.pop();
+                        self.schema_path.push("mapping");
+                        self.schema_path.push(name);
+                        self.validate(node, instance, Some(tag));
+                        self.schema_path.pop();
+                        self.schema_path.pop();
+                        return;
+                    }
+                }
+                self.instance_path.pop();
+            }
+        }
+    }
+
+    fn validate_properties(
+        &mut self,
+        required: &BTreeMap<String, Node>,
+        optional: &BTreeMap<String, Node>,
+        additional: bool,
+        instance: &Value,
+        parent_tag: Option<&str>,
+    ) {
+        let Value::Object(map) = instance else {
+            self.fail(if required.is_empty() {
+                "optionalProperties"
+            } else {
+                "properties"
+            });
+            return;
+        };
+
+        for (keyword, properties) in [("properties", required), ("optionalProperties", optional)] {
+            for (name, node) in properties {
+                match map.get(name) {
+                    Some(value) => {
+                        self.schema_path.push(keyword);
+                        self.schema_path.push(name.clone());
+                        self.instance_path.push(name.clone());
+                        self.validate(node, value, None);
+                        self.instance_path.pop();
+                        self.schema_path.pop();
+                        self.schema_path.pop();
+                    }
+                    None if keyword == "properties" => {
+                        self.errors.push(ValidationError {
+                            instance_path: self.instance_path.clone(),
+                            schema_path: self.schema_path.join(keyword).join(name.clone()),
+                        });
+                    }
+                    None => {}
+                }
+            }
+        }
+
+        if !additional {
+            for (key, _) in sorted(map) {
+                let known = required.contains_key(key)
+                    || optional.contains_key(key)
+                    || parent_tag == Some(key);
+                if !known {
+                    self.errors.push(ValidationError {
+                        instance_path: self.instance_path.join(key.clone()),
+                        schema_path: self.schema_path.clone(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn sorted(map: &HashMap<String, Value>) -> Vec<(&String, &Value)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by_key(|(key, _)| *key);
+    entries
+}
+
+fn has_type(instance: &Value, name: &str) -> bool {
+    let integer_range = |min: f64, max: f64| match instance {
+        Value::Number(n) => n.fract() == 0.0 && min <= *n && *n <= max,
+        _ => false,
+    };
+    match name {
+        "boolean" => matches!(instance, Value::Boolean(_)),
+        "string" => matches!(instance, Value::String(_)),
+        "timestamp" => instance.as_str().is_some_and(is_rfc3339),
+        "float32" | "float64" => matches!(instance, Value::Number(_)),
+        "int8" => integer_range(-128.0, 127.0),
+        "uint8" => integer_range(0.0, 255.0),
+        "int16" => integer_range(-32768.0, 32767.0),
+        "uint16" => integer_range(0.0, 65535.0),
+        "int32" => integer_range(-2147483648.0, 2147483647.0),
+        "uint32" => integer_range(0.0, 4294967295.0),
+        _ => false,
+    }
+}
+
+/// Whether `s` is an RFC 3339 `date-time`, ex. `1985-04-12T23:20:50.52Z`
+pub(crate) fn is_rfc3339(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    let digits = |range: std::ops::Range<usize>| {
+        bytes
+            .get(range.clone())
+            .is_some_and(|b| b.iter().all(u8::is_ascii_digit))
+            .then(|| s[range].parse::<u32>().unwrap())
+    };
+    let at = |i: usize, expected: &[u8]| bytes.get(i).is_some_and(|b| expected.contains(b));
+
+    let (Some(year), Some(month), Some(day)) = (digits(0..4), digits(5..7), digits(8..10)) else {
+        return false;
+    };
+    let (Some(hour), Some(minute), Some(second)) = (digits(11..13), digits(14..16), digits(17..19))
+    else {
+        return false;
+    };
+    if !(at(4, b"-") && at(7, b"-") && at(10, b"Tt") && at(13, b":") && at(16, b":")) {
+        return false;
+    }
+
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    if day == 0 || day > days_in_month || hour > 23 || minute > 59 || second > 60 {
+        return false;
+    }
+
+    let mut i = 19;
+    if at(i, b".") {
+        i += 1;
+        let start = i;
+        while at(i, b"0123456789") {
+            i += 1;
+        }
+        if i == start {
+            return false;
+        }
+    }
+    match bytes.get(i) {
+        Some(b'Z' | b'z') => i + 1 == bytes.len(),
+        Some(b'+' | b'-') => {
+            let (Some(offset_hour), Some(offset_minute)) =
+                (digits(i + 1..i + 3), digits(i + 4..i + 6))
+            else {
+                return false;
+            };
+            at(i + 3, b":") && i + 6 == bytes.len() && offset_hour <= 23 && offset_minute <= 59
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{JsonPointer, Parser};
+
+    use super::{JtdError, JtdSchema, is_rfc3339};
+
+    fn compile(input: &str) -> JtdSchema {
+        JtdSchema::compile(&Parser::new().parse_str(input).unwrap()).unwrap()
+    }
+
+    fn errors(schema: &JtdSchema, instance: &str) -> Vec<(String, String)> {
+        let instance = Parser::new().parse_str(instance).unwrap();
+        match schema.validate(&instance) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors
+                .into_iter()
+                .map(|e| (e.instance_path.to_string(), e.schema_path.to_string()))
+                .collect(),
+        }
+    }
+
+    fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+        expected
+            .iter()
+            .map(|(i, s)| (String::from(*i), String::from(*s)))
+            .collect()
+    }
+
+    const USER: &str = r#"{
+        "properties": {
+            "name": {"type": "string"},
+            "age": {"type": "uint8"},
+            "tags": {"elements": {"type": "string"}}
+        },
+        "optionalProperties": {"role": {"enum": ["admin", "user"]}}
+    }"#;
+
+    #[test]
+    fn valid_instance() {
+        let schema = compile(USER);
+        assert_eq!(
+            errors(
+                &schema,
+                r#"{"name": "ken", "age": 35, "tags": [], "role": "user"}"#
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn standard_error_indicators() {
+        let schema = compile(USER);
+        let instance = r#"{"age": 300, "tags": ["a", 1], "role": "root", "extra": true}"#;
+        let expected = pairs(&[
+            ("/age", "/properties/age/type"),
+            ("", "/properties/name"),
+            ("/tags/1", "/properties/tags/elements/type"),
+            ("/role", "/optionalProperties/role/enum"),
+            ("/extra", ""),
+        ]);
+        assert_eq!(errors(&schema, instance), expected);
+    }
+
+    #[test]
+    fn nullable_and_values() {
+        let schema = compile(r#"{"values": {"type": "int32", "nullable": true}}"#);
+        assert_eq!(errors(&schema, r#"{"a": 1, "b": null}"#), vec![]);
+        assert_eq!(
+            errors(&schema, r#"{"a": 1.5}"#),
+            pairs(&[("/a", "/values/type")])
+        );
+        assert_eq!(errors(&schema, "[]"), pairs(&[("", "/values")]));
+    }
+
+    #[test]
+    fn discriminator() {
+        let schema = compile(
+            r#"{"discriminator": "kind", "mapping": {
+                "user": {"properties": {"name": {"type": "string"}}},
+                "bot": {"properties": {"owner": {"type": "string"}}}
+            }}"#,
+        );
+        assert_eq!(
+            errors(&schema, r#"{"kind": "user", "name": "ken"}"#),
+            vec![]
+        );
+        assert_eq!(
+            errors(&schema, r#"{"kind": "bot"}"#),
+            pairs(&[("", "/mapping/bot/properties/owner")])
+        );
+        assert_eq!(
+            errors(&schema, r#"{"kind": "cat"}"#),
+            pairs(&[("/kind", "/mapping")])
+        );
+        assert_eq!(
+            errors(&schema, r#"{"kind": 1}"#),
+            pairs(&[("/kind", "/discriminator")])
+        );
+        assert_eq!(errors(&schema, "{}"), pairs(&[("", "/discriminator")]));
+    }
+
+    #[test]
+    fn definitions_and_refs() {
+        let schema = compile(
+            r#"{"definitions": {"node": {"properties": {"next": {"ref": "node", "nullable": true}}}}, "ref": "node"}"#,
+        );
+        assert_eq!(errors(&schema, r#"{"next": {"next": null}}"#), vec![]);
+        assert_eq!(
+            errors(&schema, r#"{"next": 1}"#),
+            pairs(&[("/next", "/definitions/node/properties")])
+        );
+    }
+
+    #[test]
+    fn timestamps() {
+        assert!(is_rfc3339("1985-04-12T23:20:50.52Z"));
+        assert!(is_rfc3339("1996-12-19T16:39:57-08:00"));
+        assert!(is_rfc3339("2000-02-29t00:00:00z"));
+        assert!(!is_rfc3339("2001-02-29T00:00:00Z"));
+        assert!(!is_rfc3339("1985-04-12 23:20:50Z"));
+        assert!(!is_rfc3339("1985-04-12T23:20:50"));
+    }
+
+    #[test]
+    fn invalid_schemas() {
+        let compile = |input: &str| JtdSchema::compile(&Parser::new().parse_str(input).unwrap());
+        assert_eq!(
+            compile(r#"{"type": "int64"}"#),
+            Err(JtdError::UnknownType(String::from("int64")))
+        );
+        assert_eq!(
+            compile(r#"{"ref": "nope"}"#),
+            Err(JtdError::MissingDefinition(String::from("nope")))
+        );
+        assert_eq!(
+            compile(r#"{"type": "string", "enum": ["a"]}"#),
+            Err(JtdError::InvalidForm(JsonPointer::root()))
+        );
+        assert_eq!(
+            compile(r#"{"definitions": {"a": {"ref": "b"}, "b": {"ref": "a"}}}"#),
+            Err(JtdError::CyclicRef(String::from("a")))
+        );
+        assert!(matches!(
+            compile(r#"{"elements": {"definitions": {}}}"#),
+            Err(JtdError::NestedDefinitions(_))
+        ));
+    }
+}
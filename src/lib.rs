@@ -1,18 +1,269 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+#[cfg(not(any(feature = "std", feature = "alloc")))]
+compile_error!("either the `std` or the `alloc` feature must be enabled");
+
 mod tokenize;
 mod parse;
-
-use std::collections::HashMap;
+#[cfg(feature = "std")]
+mod suggestion;
+#[cfg(feature = "std")]
+mod pointer;
+#[cfg(feature = "std")]
+mod merge;
+#[cfg(feature = "std")]
+mod regex;
+#[cfg(feature = "std")]
+mod path;
+#[cfg(feature = "std")]
+mod serialize;
+mod hash;
+#[cfg(feature = "std")]
+mod access;
+#[cfg(feature = "std")]
+mod approx;
+#[cfg(feature = "std")]
+mod convert;
+#[cfg(feature = "std")]
+mod coerce;
+#[cfg(feature = "std")]
+mod reader;
+#[cfg(feature = "std")]
+mod writer;
+#[cfg(feature = "std")]
+mod transcode;
+#[cfg(feature = "std")]
+mod minify;
+#[cfg(feature = "std")]
+mod pretty;
+#[cfg(feature = "std")]
+mod color;
+#[cfg(feature = "std")]
+mod spanned;
+#[cfg(feature = "std")]
+mod walk;
+#[cfg(feature = "std")]
+mod redact;
+#[cfg(feature = "std")]
+mod prune;
+#[cfg(feature = "std")]
+mod resolve;
+#[cfg(feature = "std")]
+mod env;
+#[cfg(feature = "std")]
+mod flatten;
+#[cfg(feature = "std")]
+mod cache;
+#[cfg(feature = "std")]
+mod case;
+#[cfg(feature = "std")]
+mod stats;
+#[cfg(feature = "std")]
+mod group;
+#[cfg(feature = "std")]
+mod sort;
+#[cfg(feature = "std")]
+mod set;
+#[cfg(feature = "std")]
+mod tagged;
+#[cfg(feature = "std")]
+mod template;
+#[cfg(feature = "std")]
+mod preview;
+mod ord;
+#[cfg(feature = "std")]
+mod shard;
+#[cfg(feature = "std")]
+mod shared;
+#[cfg(feature = "std")]
+mod intern;
+#[cfg(feature = "std")]
+mod hjson;
+#[cfg(feature = "std")]
+mod iter;
+#[cfg(feature = "std")]
+mod ndjson;
+#[cfg(feature = "std")]
+mod number_text;
+#[cfg(feature = "std")]
+mod observe;
+#[cfg(feature = "std")]
+mod options;
+#[cfg(feature = "std")]
+mod parallel;
+#[cfg(feature = "std")]
+mod parser;
+#[cfg(feature = "std")]
+mod projection;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(any(feature = "proptest", feature = "quickcheck"))]
+mod arbitrary;
+#[cfg(feature = "serde")]
+mod value_serde;
+#[cfg(feature = "serde_json")]
+mod serde_json_value;
+#[cfg(feature = "chrono")]
+mod datetime;
+#[cfg(feature = "decimal")]
+mod decimal;
+#[cfg(feature = "std")]
+pub mod jsonpath;
+#[cfg(feature = "std")]
+pub mod jq;
+#[cfg(feature = "std")]
+pub mod schema;
+#[cfg(feature = "std")]
+pub mod jtd;
+#[cfg(feature = "std")]
+pub mod diff;
+#[cfg(feature = "std")]
+pub mod interop;
+#[cfg(feature = "std")]
+pub mod cst;
+#[cfg(feature = "std")]
+pub mod generate;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "std")]
+use std::collections::{HashMap, hash_map::Entry};
+#[cfg(not(feature = "std"))]
+use hashbrown::HashMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::BuildHasher;
 use parse::{parse_tokens, TokenParseError};
 use tokenize::{tokenize, TokenizeError};
 
+/// The hasher of a [`Map`] when no other is chosen
+#[cfg(feature = "std")]
+type DefaultState = std::hash::RandomState;
+#[cfg(not(feature = "std"))]
+type DefaultState = hashbrown::DefaultHashBuilder;
+
+#[cfg(not(feature = "std"))]
+type Entry<'a, K, V> = hashbrown::hash_map::Entry<'a, K, V, DefaultState>;
+
+#[cfg(feature = "derive")]
+pub use json_parsing_derive::{FromJson, ToJson};
+#[cfg(feature = "std")]
+pub use access::{AccessError, Expect};
+#[cfg(feature = "std")]
+pub use cache::{MemoryCache, SubtreeCache};
+#[cfg(feature = "std")]
+pub use case::Case;
+#[cfg(feature = "std")]
+pub use coerce::CoerceOptions;
+#[cfg(feature = "std")]
+pub use color::{ColorChoice, ColorScheme, write_value_colored};
+#[cfg(feature = "std")]
+pub use convert::{FromJson, FromJsonError, FromJsonErrorKind, ToJson, field, optional_field};
+#[cfg(feature = "std")]
+pub use env::{ExpandError, ExpandErrorKind};
+#[cfg(feature = "std")]
+pub use flatten::{UnflattenError, flatten, unflatten};
+#[cfg(feature = "std")]
+pub use merge::{ArrayMerge, ConflictResolution, MergeConflict, MergeStrategy};
+#[cfg(feature = "std")]
+pub use minify::{MinifyError, minify, minify_lenient};
+#[cfg(feature = "std")]
+pub use ndjson::{Lines, NdjsonError, parse_lines};
+#[cfg(feature = "std")]
+pub use number_text::{NumberTexts, parse_with_number_texts};
+#[cfg(feature = "std")]
+pub use observe::ParseObserver;
+#[cfg(feature = "std")]
+pub use options::{
+    DEFAULT_MAX_DEPTH, Dialect, DuplicateKeys, Limits, NumberMode, NumberOverflow, ParserOptions,
+    parse_with_options,
+};
+#[cfg(feature = "std")]
+pub use parallel::parse_array_parallel;
+#[cfg(feature = "std")]
+pub use parser::Parser;
+#[cfg(feature = "std")]
+pub use projection::{ProjectionError, extract_field, parse_projection};
+#[cfg(feature = "std")]
+pub use path::PathEntry;
+#[cfg(feature = "std")]
+pub use pointer::{JsonPointer, PointerError};
+#[cfg(feature = "std")]
+pub use pretty::PrettyOptions;
+#[cfg(feature = "std")]
+pub use prune::PruneOptions;
+#[cfg(feature = "std")]
+pub use reader::{Event, JsonReader, Limit, Position, ReaderError};
+#[cfg(feature = "std")]
+pub use redact::{REDACTED, redact};
+#[cfg(feature = "std")]
+pub use resolve::{ResolveError, Resolver};
+#[cfg(feature = "std")]
+pub use serialize::{
+    CanonicalError, SerializeOptions, write_value, write_value_pretty, write_value_with,
+};
+#[cfg(feature = "std")]
+pub use shard::{ShardError, ShardLimits, split_array, split_array_file};
+#[cfg(feature = "std")]
+pub use shared::SharedValue;
+#[cfg(feature = "std")]
+pub use sort::Order;
+#[cfg(feature = "std")]
+pub use spanned::{
+    Span, SpannedDocument, SpannedKind, SpannedMember, SpannedValue, parse_with_spans,
+};
+#[cfg(feature = "std")]
+pub use stats::Stats;
+#[cfg(feature = "std")]
+pub use suggestion::Suggestion;
+#[cfg(feature = "std")]
+pub use tagged::{Tagged, TaggedError, match_tagged};
+#[cfg(feature = "std")]
+pub use template::{TemplateError, TemplateErrorKind};
+#[cfg(feature = "std")]
+pub use transcode::{
+    PathPattern, TranscodeError, drop_stream, filter_stream, transcode, transcode_filtered,
+};
+#[cfg(feature = "std")]
+pub use walk::Walk;
+#[cfg(feature = "std")]
+pub use writer::{JsonWriter, WriterError};
+#[cfg(any(feature = "proptest", feature = "quickcheck"))]
+pub use arbitrary::ArbitraryOptions;
+#[cfg(feature = "mmap")]
+pub use mmap::parse_file;
+#[cfg(feature = "rayon")]
+pub use ndjson::{ParallelLines, parse_lines_parallel};
+#[cfg(feature = "serde")]
+pub use value_serde::{SerdeError, from_value, to_value};
+
+/// Parses a whole document
+///
+/// This is the only parser in builds without the `std` feature, `JsonReader` and the
+/// parsers built on it read their input through `std::io`. With `std`, `Parser::parse_str`
+/// reads the whole JSON grammar with options and limits, and reports where errors are.
+#[cfg_attr(
+    feature = "std",
+    deprecated(note = "use `Parser::new().parse_str(&input)`, or `Dialect::parse`")
+)]
 pub fn parse(input: String) -> Result<Value, ParseError> {
     let tokens = tokenize(input)?;
     let value = parse_tokens(&tokens, &mut 0)?;
     Ok(value)
 }
+/// The map held by [`Value::Object`]
+///
+/// This is `std`'s `HashMap`, by default with its randomly seeded SipHash hasher. Builds
+/// without the `std` feature use `hashbrown`'s map instead, which has the same API. Another
+/// hasher is chosen with `Parser::hasher`, which parses into a `Value<S>` for that hasher.
+pub type Map<S = DefaultState> = HashMap<String, Value<S>, S>;
+
 /// Representation of a JSON value
-#[derive(Debug, Clone, PartialEq)]
-pub enum Value {
+#[derive(Debug, Clone)]
+pub enum Value<S = DefaultState> {
     /// literal characters `null`
     Null,
 
@@ -26,10 +277,138 @@ pub enum Value {
     Number(f64),
 
     /// Zero to many JSON values
-    Array(Vec<Value>),
+    Array(Vec<Value<S>>),
 
     /// String keys with JSON values
-    Object(HashMap<String, Value>),
+    Object(Map<S>),
+}
+
+impl<S: BuildHasher> Value<S> {
+    /// The name of the variant as it's called in JSON, ex. `"object"`
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Boolean(_) => "boolean",
+            Value::String(_) => "string",
+            Value::Number(_) => "number",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// The number as an integer, if it has no fractional part and fits in an `i64`
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            // `f64::fract` needs std, converting there and back checks the same thing
+            Value::Number(n)
+                if *n >= i64::MIN as f64 && *n < i64::MAX as f64 && *n as i64 as f64 == *n =>
+            {
+                Some(*n as i64)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Self>> {
+        match self {
+            Value::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&Map<S>> {
+        match self {
+            Value::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<Self>> {
+        match self {
+            Value::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    pub fn as_object_mut(&mut self) -> Option<&mut Map<S>> {
+        match self {
+            Value::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// Moves the value out, leaving `null` in its place
+    pub fn take(&mut self) -> Self {
+        core::mem::replace(self, Value::Null)
+    }
+
+    /// Appends to an array, giving `value` back when this is not an array
+    pub fn push(&mut self, value: Self) -> Result<(), Self> {
+        match self {
+            Value::Array(array) => {
+                array.push(value);
+                Ok(())
+            }
+            _ => Err(value),
+        }
+    }
+
+    /// Inserts into an object, returning the value that was replaced if any, or giving
+    /// `value` back when this is not an object
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        value: Self,
+    ) -> Result<Option<Self>, Self> {
+        match self {
+            Value::Object(map) => Ok(map.insert(key.into(), value)),
+            _ => Err(value),
+        }
+    }
+
+    /// Removes a member from an object
+    pub fn remove(&mut self, key: &str) -> Option<Self> {
+        self.as_object_mut()?.remove(key)
+    }
+
+    /// Removes an element from an array, shifting the following elements
+    pub fn remove_index(&mut self, index: usize) -> Option<Self> {
+        let array = self.as_array_mut()?;
+        (index < array.len()).then(|| array.remove(index))
+    }
+
+}
+
+impl Value {
+    /// The entry for `key` in an object, for in-place updates
+    pub fn entry(&mut self, key: impl Into<String>) -> Option<Entry<'_, String, Value>> {
+        Some(self.as_object_mut()?.entry(key.into()))
+    }
 }
 
 
@@ -40,6 +419,24 @@ pub enum ParseError {
     ParseError(TokenParseError),
 }
 
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TokenizeError(err) => write!(f, "invalid token: {err}"),
+            Self::ParseError(err) => write!(f, "invalid document: {err}"),
+        }
+    }
+}
+
+impl core::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::TokenizeError(err) => Some(err),
+            Self::ParseError(err) => Some(err),
+        }
+    }
+}
+
 impl From<TokenParseError> for ParseError {
     fn from(err: TokenParseError) -> Self {
         Self::ParseError(err)
@@ -67,6 +464,7 @@ impl Value {
 }
 
 #[cfg(test)]
+#[allow(deprecated)]
 mod tests {
     use super::*;
 
@@ -81,6 +479,15 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn error_messages() {
+        let err = parse(String::from("[@]")).unwrap_err();
+        assert_eq!(err.to_string(), "invalid token: unexpected `@`");
+        let err = parse(String::from("]")).unwrap_err();
+        assert_eq!(err.to_string(), "invalid document: expected a value");
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
     #[test]
     fn just_null() {
         check("null", Value::Null);
@@ -191,6 +598,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn edit_in_place() {
+        let mut value = parse(String::from(r#"{"list": [1], "count": 1}"#)).unwrap();
+        let list = value.as_object_mut().unwrap().get_mut("list").unwrap();
+        assert_eq!(list.push(Value::Number(2.0)), Ok(()));
+        assert_eq!(list.remove_index(0), Some(Value::Number(1.0)));
+        assert_eq!(list.remove_index(5), None);
+        assert_eq!(list.take(), Value::Array(vec![Value::Number(2.0)]));
+
+        let count = value.entry("count").unwrap().or_insert(Value::Null);
+        if let Value::Number(count) = count {
+            *count += 1.0;
+        }
+        assert_eq!(value.insert("name", Value::Null), Ok(None));
+        assert_eq!(value.remove("list"), Some(Value::Null));
+        let expected = Value::object([("count", Value::Number(2.0)), ("name", Value::Null)]);
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn edit_wrong_type() {
+        let mut value = Value::Boolean(true);
+        assert_eq!(value.push(Value::Null), Err(Value::Null));
+        assert_eq!(value.insert("a", Value::Null), Err(Value::Null));
+        assert_eq!(value.remove("a"), None);
+        assert!(value.entry("a").is_none());
+    }
+
     #[test]
     #[ignore = "this fails - for the sake of brevity, leaving this unfixed"]
     fn err_unclosed_array() {
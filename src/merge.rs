@@ -0,0 +1,360 @@
+use std::collections::HashMap;
+
+use crate::{JsonPointer, Value};
+
+/// How [`Value::deep_merge`] combines two documents
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MergeStrategy {
+    pub arrays: ArrayMerge,
+    pub conflicts: ConflictResolution,
+}
+
+/// How two arrays at the same location are combined
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum ArrayMerge {
+    /// The incoming array replaces the existing one
+    #[default]
+    Replace,
+
+    /// The incoming elements are appended
+    Concat,
+
+    /// The incoming elements are appended unless an equal element is already present
+    Union,
+
+    /// Objects sharing the same value for this key are merged, others are appended
+    UnionByKey(String),
+}
+
+/// What happens when both documents have a different non-object value at the same location
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum ConflictResolution {
+    /// The incoming value wins
+    #[default]
+    PreferOther,
+
+    /// The existing value is kept
+    PreferSelf,
+
+    /// Merging stops with a [`MergeConflict`]
+    Error,
+}
+
+/// Both documents had a different value at this location
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+    pub pointer: JsonPointer,
+}
+
+impl Value {
+    /// Applies a JSON Merge Patch (RFC 7386) to this value
+    ///
+    /// Objects in the patch are merged recursively, `null` removes the key, and any
+    /// other value replaces the target outright.
+    pub fn merge_patch(&mut self, patch: &Value) {
+        let Value::Object(patch) = patch else {
+            *self = patch.clone();
+            return;
+        };
+
+        if !matches!(self, Value::Object(_)) {
+            *self = Value::Object(HashMap::new());
+        }
+        let Value::Object(target) = self else {
+            unreachable!("target was just made an object");
+        };
+
+        for (key, value) in patch {
+            if *value == Value::Null {
+                target.remove(key);
+            } else {
+                target
+                    .entry(key.clone())
+                    .or_insert(Value::Null)
+                    .merge_patch(value);
+            }
+        }
+    }
+}
+
+impl Value {
+    /// Recursively merges `other` into this value, ex. layering user config over defaults
+    ///
+    /// Objects are always merged key by key, arrays and conflicting values are combined
+    /// according to the `strategy`.
+    pub fn deep_merge(
+        &mut self,
+        other: Value,
+        strategy: &MergeStrategy,
+    ) -> Result<(), MergeConflict> {
+        deep_merge_at(self, other, strategy, &mut JsonPointer::root())
+    }
+}
+
+impl Value {
+    /// This value with keys missing from its objects filled in from `defaults`, recursively
+    ///
+    /// Values that are present are never replaced, even by an object or when they are
+    /// `null`, and arrays are not merged element by element.
+    pub fn with_defaults(mut self, defaults: &Value) -> Value {
+        fill_defaults(&mut self, defaults);
+        self
+    }
+}
+
+fn fill_defaults(target: &mut Value, defaults: &Value) {
+    let (Value::Object(target), Value::Object(defaults)) = (target, defaults) else {
+        return;
+    };
+    for (key, default) in defaults {
+        match target.get_mut(key) {
+            Some(existing) => fill_defaults(existing, default),
+            None => {
+                target.insert(key.clone(), default.clone());
+            }
+        }
+    }
+}
+
+fn deep_merge_at(
+    target: &mut Value,
+    other: Value,
+    strategy: &MergeStrategy,
+    pointer: &mut JsonPointer,
+) -> Result<(), MergeConflict> {
+    match (target, other) {
+        (Value::Object(target), Value::Object(other)) => {
+            for (key, value) in other {
+                match target.get_mut(&key) {
+                    Some(existing) => {
+                        pointer.push(key);
+                        deep_merge_at(existing, value, strategy, pointer)?;
+                        pointer.pop();
+                    }
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+            Ok(())
+        }
+        (Value::Array(target), Value::Array(other)) => {
+            merge_arrays(target, other, strategy, pointer)
+        }
+        (target, other) if *target == other => Ok(()),
+        (target, other) => match strategy.conflicts {
+            ConflictResolution::PreferOther => {
+                *target = other;
+                Ok(())
+            }
+            ConflictResolution::PreferSelf => Ok(()),
+            ConflictResolution::Error => Err(MergeConflict {
+                pointer: pointer.clone(),
+            }),
+        },
+    }
+}
+
+fn merge_arrays(
+    target: &mut Vec<Value>,
+    other: Vec<Value>,
+    strategy: &MergeStrategy,
+    pointer: &mut JsonPointer,
+) -> Result<(), MergeConflict> {
+    match &strategy.arrays {
+        ArrayMerge::Replace => *target = other,
+        ArrayMerge::Concat => target.extend(other),
+        ArrayMerge::Union => {
+            for value in other {
+                if !target.contains(&value) {
+                    target.push(value);
+                }
+            }
+        }
+        ArrayMerge::UnionByKey(key) => {
+            for value in other {
+                let id = match &value {
+                    Value::Object(map) => map.get(key),
+                    _ => None,
+                };
+                let position = id.and_then(|id| {
+                    target.iter().position(|existing| match existing {
+                        Value::Object(map) => map.get(key) == Some(id),
+                        _ => false,
+                    })
+                });
+                match position {
+                    Some(index) => {
+                        pointer.push(index.to_string());
+                        deep_merge_at(&mut target[index], value, strategy, pointer)?;
+                        pointer.pop();
+                    }
+                    None => target.push(value),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{JsonPointer, Parser, Value};
+
+    use super::{ArrayMerge, ConflictResolution, MergeConflict, MergeStrategy};
+
+    fn check_merge(target: &str, other: &str, strategy: MergeStrategy, expected: &str) {
+        let mut target = Parser::new().parse_str(target).unwrap();
+        let other = Parser::new().parse_str(other).unwrap();
+        let expected = Parser::new().parse_str(expected).unwrap();
+
+        target.deep_merge(other, &strategy).unwrap();
+
+        assert_eq!(target, expected);
+    }
+
+    fn with_arrays(arrays: ArrayMerge) -> MergeStrategy {
+        MergeStrategy {
+            arrays,
+            ..MergeStrategy::default()
+        }
+    }
+
+    fn check(target: &str, patch: &str, expected: &str) {
+        let mut target = Parser::new().parse_str(target).unwrap();
+        let patch = Parser::new().parse_str(patch).unwrap();
+        let expected = Parser::new().parse_str(expected).unwrap();
+
+        target.merge_patch(&patch);
+
+        assert_eq!(target, expected);
+    }
+
+    #[test]
+    fn replaces_value() {
+        check(r#"{"a": "b"}"#, r#"{"a": "c"}"#, r#"{"a": "c"}"#);
+    }
+
+    #[test]
+    fn adds_value() {
+        check(r#"{"a": "b"}"#, r#"{"b": "c"}"#, r#"{"a": "b", "b": "c"}"#);
+    }
+
+    #[test]
+    fn null_removes_key() {
+        check(r#"{"a": "b", "b": "c"}"#, r#"{"a": null}"#, r#"{"b": "c"}"#);
+    }
+
+    #[test]
+    fn arrays_are_replaced() {
+        check(r#"{"a": [1, 2]}"#, r#"{"a": [3]}"#, r#"{"a": [3]}"#);
+    }
+
+    #[test]
+    fn merges_nested_objects() {
+        check(
+            r#"{"a": {"b": "c", "d": "e"}}"#,
+            r#"{"a": {"b": null, "f": {"g": null}}}"#,
+            r#"{"a": {"d": "e", "f": {}}}"#,
+        );
+    }
+
+    #[test]
+    fn non_object_patch_replaces_target() {
+        check(r#"{"a": "b"}"#, r#"["c"]"#, r#"["c"]"#);
+        check(r#"["a"]"#, r#"{"a": "b"}"#, r#"{"a": "b"}"#);
+    }
+
+    #[test]
+    fn deep_merge_layers_objects() {
+        check_merge(
+            r#"{"server": {"host": "localhost", "port": 80}, "debug": false}"#,
+            r#"{"server": {"port": 8080}, "debug": true}"#,
+            MergeStrategy::default(),
+            r#"{"server": {"host": "localhost", "port": 8080}, "debug": true}"#,
+        );
+    }
+
+    #[test]
+    fn deep_merge_array_strategies() {
+        let (target, other) = ("[1, 2]", "[2, 3]");
+        check_merge(target, other, with_arrays(ArrayMerge::Replace), "[2, 3]");
+        check_merge(
+            target,
+            other,
+            with_arrays(ArrayMerge::Concat),
+            "[1, 2, 2, 3]",
+        );
+        check_merge(target, other, with_arrays(ArrayMerge::Union), "[1, 2, 3]");
+    }
+
+    #[test]
+    fn deep_merge_union_by_key() {
+        check_merge(
+            r#"[{"id": 1, "a": 1}, {"id": 2}]"#,
+            r#"[{"id": 1, "b": 2}, {"id": 3}]"#,
+            with_arrays(ArrayMerge::UnionByKey(String::from("id"))),
+            r#"[{"id": 1, "a": 1, "b": 2}, {"id": 2}, {"id": 3}]"#,
+        );
+    }
+
+    #[test]
+    fn deep_merge_prefer_self() {
+        let strategy = MergeStrategy {
+            conflicts: ConflictResolution::PreferSelf,
+            ..MergeStrategy::default()
+        };
+        check_merge(
+            r#"{"a": 1, "b": 2}"#,
+            r#"{"a": 3, "c": 4}"#,
+            strategy,
+            r#"{"a": 1, "b": 2, "c": 4}"#,
+        );
+    }
+
+    #[test]
+    fn deep_merge_conflict_error() {
+        let strategy = MergeStrategy {
+            conflicts: ConflictResolution::Error,
+            ..MergeStrategy::default()
+        };
+        let mut target = Parser::new()
+            .parse_str(r#"{"a": {"b": 1, "c": 2}}"#)
+            .unwrap();
+        let other = Parser::new()
+            .parse_str(r#"{"a": {"b": 1, "c": "x"}}"#)
+            .unwrap();
+
+        let actual = target.deep_merge(other, &strategy);
+
+        let expected = MergeConflict {
+            pointer: JsonPointer::parse("/a/c").unwrap(),
+        };
+        assert_eq!(actual, Err(expected));
+    }
+
+    #[test]
+    fn patch_on_scalar() {
+        let mut target = Value::Null;
+        target.merge_patch(&Value::Boolean(true));
+        assert_eq!(target, Value::Boolean(true));
+    }
+
+    #[test]
+    fn with_defaults() {
+        let defaults = Parser::new()
+            .parse_str(
+                r#"{"port": 80, "tls": {"on": false, "ciphers": ["a"]}, "log": {"level": 1}}"#,
+            )
+            .unwrap();
+        let config = Parser::new()
+            .parse_str(r#"{"port": 8080, "tls": {"on": true}, "log": null, "name": "x"}"#)
+            .unwrap();
+        let expected = Parser::new().parse_str(
+            r#"{"port": 8080, "tls": {"on": true, "ciphers": ["a"]}, "log": null, "name": "x"}"#,
+        )
+        .unwrap();
+        assert_eq!(config.with_defaults(&defaults), expected);
+        assert_eq!(Value::Null.with_defaults(&defaults), Value::Null);
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MinifyError {
+    /// A string is missing its closing quote, `offset` is the byte offset of its start
+    UnterminatedString { offset: usize },
+
+    /// A `/* */` comment is never closed
+    UnterminatedComment { offset: usize },
+
+    /// A character that cannot appear outside of a string, including comments when
+    /// they are not allowed
+    UnexpectedChar { offset: usize, found: char },
+}
+
+impl fmt::Display for MinifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnterminatedString { offset } => {
+                write!(f, "unterminated string starting at offset {offset}")
+            }
+            Self::UnterminatedComment { offset } => {
+                write!(f, "unterminated comment starting at offset {offset}")
+            }
+            Self::UnexpectedChar { offset, found } => {
+                write!(f, "unexpected character {found:?} at offset {offset}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MinifyError {}
+
+/// Removes insignificant whitespace, copying every token exactly as written
+///
+/// This works on the text without building a [`crate::Value`], so it checks that
+/// strings are closed but does not validate the structure of the document.
+pub fn minify(input: &str) -> Result<String, MinifyError> {
+    minify_inner(input, false)
+}
+
+/// Like [`minify`], but also removes `//` line comments and `/* */` block comments
+pub fn minify_lenient(input: &str) -> Result<String, MinifyError> {
+    minify_inner(input, true)
+}
+
+fn minify_inner(input: &str, comments: bool) -> Result<String, MinifyError> {
+    let mut output = String::with_capacity(input.len());
+    let mut chars = input.char_indices().peekable();
+
+    while let Some((offset, ch)) = chars.next() {
+        match ch {
+            ' ' | '\t' | '\n' | '\r' => {}
+            '"' => {
+                output.push('"');
+                loop {
+                    match chars.next() {
+                        Some((_, '\\')) => {
+                            output.push('\\');
+                            if let Some((_, escaped)) = chars.next() {
+                                output.push(escaped);
+                            }
+                        }
+                        Some((_, '"')) => break,
+                        Some((_, ch)) => output.push(ch),
+                        None => return Err(MinifyError::UnterminatedString { offset }),
+                    }
+                }
+                output.push('"');
+            }
+            '/' if comments && chars.next_if(|(_, ch)| *ch == '/').is_some() => {
+                while chars.next_if(|(_, ch)| *ch != '\n').is_some() {}
+            }
+            '/' if comments && chars.next_if(|(_, ch)| *ch == '*').is_some() => loop {
+                match chars.next() {
+                    Some((_, '*')) if chars.next_if(|(_, ch)| *ch == '/').is_some() => break,
+                    Some(_) => {}
+                    None => return Err(MinifyError::UnterminatedComment { offset }),
+                }
+            },
+            '{' | '}' | '[' | ']' | ':' | ',' | '-' | '+' | '.' => output.push(ch),
+            ch if ch.is_ascii_alphanumeric() => output.push(ch),
+            found => return Err(MinifyError::UnexpectedChar { offset, found }),
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MinifyError, minify, minify_lenient};
+
+    #[test]
+    fn strips_whitespace() {
+        let input = "{\n  \"a b\" : [ 1.50, -2e+3 ],\r\n\t\"c\\\"\": \"x\\ny\"\n}";
+        assert_eq!(
+            minify(input),
+            Ok(String::from(r#"{"a b":[1.50,-2e+3],"c\"":"x\ny"}"#))
+        );
+    }
+
+    #[test]
+    fn strips_comments_when_lenient() {
+        let input = "{ // line\n  \"url\": \"http://x/*y*/\", /* block\n */ \"n\": 1 }";
+        let expected = String::from(r#"{"url":"http://x/*y*/","n":1}"#);
+        assert_eq!(minify_lenient(input), Ok(expected));
+        assert_eq!(
+            minify(input),
+            Err(MinifyError::UnexpectedChar {
+                offset: 2,
+                found: '/'
+            })
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            minify("[\"abc"),
+            Err(MinifyError::UnterminatedString { offset: 1 })
+        );
+        assert_eq!(
+            minify_lenient("[1 /* x"),
+            Err(MinifyError::UnterminatedComment { offset: 3 })
+        );
+        assert_eq!(
+            minify("['a']"),
+            Err(MinifyError::UnexpectedChar {
+                offset: 1,
+                found: '\''
+            })
+        );
+    }
+}
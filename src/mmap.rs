@@ -0,0 +1,56 @@
+use std::fs::File;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::reader::read_document;
+use crate::{ReaderError, Value};
+
+/// Parses a file by memory-mapping it, so the input is read straight from the page cache
+/// rather than copied into a `String` first
+///
+/// Accepts the full JSON grammar. Failing to open or map the file is reported as
+/// [`ReaderError::Io`]. The file must not be modified while it is being parsed.
+pub fn parse_file(path: impl AsRef<Path>) -> Result<Value, ReaderError> {
+    let file = File::open(path)?;
+    // mapping an empty file fails on some platforms, and it has no document anyway
+    if file.metadata()?.len() == 0 {
+        return Err(ReaderError::UnexpectedEof);
+    }
+    // SAFETY: the map is only read while parsing, and the caller keeps the file unchanged
+    let map = unsafe { Mmap::map(&file)? };
+    read_document(&map[..])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{ReaderError, Value};
+
+    use super::parse_file;
+
+    #[test]
+    fn parses_mapped_files() {
+        let dir = std::env::temp_dir().join(format!("json_parsing_mmap_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("document.json");
+        fs::write(&path, r#"{"ids": [-1, 2e3], "name": "café"}"#).unwrap();
+        let value = parse_file(&path).unwrap();
+        assert_eq!(value.pointer("/ids/1"), Some(&Value::Number(2000.0)));
+        assert_eq!(value.pointer("/name"), Some(&Value::string("café")));
+
+        let empty = dir.join("empty.json");
+        fs::write(&empty, "").unwrap();
+        assert!(matches!(
+            parse_file(&empty),
+            Err(ReaderError::UnexpectedEof)
+        ));
+        assert!(matches!(
+            parse_file(dir.join("missing.json")),
+            Err(ReaderError::Io(_))
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,188 @@
+use std::fmt;
+use std::io::{self, BufRead};
+
+use crate::reader::read_document;
+use crate::{ReaderError, Value};
+
+#[derive(Debug)]
+pub enum NdjsonError {
+    /// Reading from the underlying reader failed, no more lines are read
+    Io(io::Error),
+
+    /// A line is not a valid JSON document, `line` starts at 1
+    Parse { line: usize, error: ReaderError },
+}
+
+impl fmt::Display for NdjsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "reading input failed: {err}"),
+            Self::Parse { line, error } => write!(f, "invalid JSON on line {line}: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for NdjsonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Parse { error, .. } => Some(error),
+        }
+    }
+}
+
+/// Iterator over the documents of newline-delimited JSON, see [`parse_lines`]
+pub struct Lines<R> {
+    inner: R,
+    line: usize,
+    done: bool,
+}
+
+/// Parses newline-delimited JSON (NDJSON / JSON Lines), one document per line
+///
+/// Blank lines are skipped. A line that fails to parse yields an error and reading
+/// continues with the next line.
+pub fn parse_lines<R: BufRead>(inner: R) -> Lines<R> {
+    Lines {
+        inner,
+        line: 0,
+        done: false,
+    }
+}
+
+impl<R: BufRead> Lines<R> {
+    /// The next non-blank line and its number, `None` at the end of the input
+    fn next_line(&mut self) -> Option<Result<(usize, String), NdjsonError>> {
+        while !self.done {
+            let mut text = String::new();
+            match self.inner.read_line(&mut text) {
+                Ok(0) => self.done = true,
+                Ok(_) => {
+                    self.line += 1;
+                    if !text.trim().is_empty() {
+                        return Some(Ok((self.line, text)));
+                    }
+                }
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(NdjsonError::Io(err)));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<R: BufRead> Iterator for Lines<R> {
+    type Item = Result<Value, NdjsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(
+            self.next_line()?
+                .and_then(|(line, text)| parse_line(line, &text)),
+        )
+    }
+}
+
+fn parse_line(line: usize, text: &str) -> Result<Value, NdjsonError> {
+    read_document(text.as_bytes()).map_err(|error| NdjsonError::Parse { line, error })
+}
+
+/// Iterator over newline-delimited JSON parsed on the rayon thread pool, see
+/// [`parse_lines_parallel`]
+#[cfg(feature = "rayon")]
+pub struct ParallelLines<R> {
+    lines: Lines<R>,
+    parsed: std::collections::VecDeque<Result<Value, NdjsonError>>,
+}
+
+/// Lines read ahead and parsed together by [`parse_lines_parallel`]
+#[cfg(feature = "rayon")]
+const BATCH_LINES: usize = 1024;
+
+/// Like [`parse_lines`], but parses batches of lines across threads
+///
+/// Results are still yielded in the order of the input.
+#[cfg(feature = "rayon")]
+pub fn parse_lines_parallel<R: BufRead>(inner: R) -> ParallelLines<R> {
+    ParallelLines {
+        lines: parse_lines(inner),
+        parsed: std::collections::VecDeque::new(),
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<R: BufRead> Iterator for ParallelLines<R> {
+    type Item = Result<Value, NdjsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use rayon::prelude::*;
+
+        if self.parsed.is_empty() {
+            let mut batch = Vec::new();
+            while batch.len() < BATCH_LINES {
+                match self.lines.next_line() {
+                    Some(line) => batch.push(line),
+                    None => break,
+                }
+            }
+            let parsed: Vec<_> = batch
+                .into_par_iter()
+                .map(|line| line.and_then(|(line, text)| parse_line(line, &text)))
+                .collect();
+            self.parsed.extend(parsed);
+        }
+        self.parsed.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ReaderError, Value};
+
+    use super::{NdjsonError, parse_lines};
+
+    const INPUT: &str = "{\"a\": 1}\n\n[true, -2]\r\nnot json\n\"last\"";
+
+    fn check(results: Vec<Result<Value, NdjsonError>>) {
+        assert_eq!(results.len(), 4);
+        assert_eq!(
+            results[0].as_ref().unwrap(),
+            &Value::object([("a", Value::Number(1.0))])
+        );
+        assert_eq!(
+            results[1].as_ref().unwrap(),
+            &Value::Array(vec![Value::Boolean(true), Value::Number(-2.0)])
+        );
+        assert!(matches!(
+            results[2],
+            Err(NdjsonError::Parse {
+                line: 4,
+                error: ReaderError::UnexpectedByte { .. }
+            })
+        ));
+        assert_eq!(results[3].as_ref().unwrap(), &Value::string("last"));
+    }
+
+    #[test]
+    fn one_document_per_line() {
+        check(parse_lines(INPUT.as_bytes()).collect());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_keeps_order() {
+        use super::parse_lines_parallel;
+
+        check(parse_lines_parallel(INPUT.as_bytes()).collect());
+
+        let input: String = (0..5000).map(|n| format!("[{n}]\n")).collect();
+        let values: Vec<_> = parse_lines_parallel(input.as_bytes())
+            .map(Result::unwrap)
+            .collect();
+        let expected: Vec<_> = (0..5000)
+            .map(|n| Value::Array(vec![Value::Number(n as f64)]))
+            .collect();
+        assert_eq!(values, expected);
+    }
+}
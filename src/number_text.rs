@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use crate::serialize::write_json_with_texts;
+use crate::{JsonPointer, ReaderError, SpannedKind, SpannedValue, Value, parse_with_spans};
+
+/// The source text of each number in a document, ex. `1.50` or `1e3`, so it can be
+/// written back verbatim by [`Value::to_json_string_with_texts`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NumberTexts {
+    texts: HashMap<JsonPointer, String>,
+}
+
+impl NumberTexts {
+    /// The text of the number at `pointer`, if it was recorded
+    pub fn get(&self, pointer: &JsonPointer) -> Option<&str> {
+        self.texts.get(pointer).map(String::as_str)
+    }
+
+    /// The text at `pointer` if it still reads as `n`, so edited numbers are not
+    /// replaced by their old text
+    pub(crate) fn matching(&self, pointer: &JsonPointer, n: f64) -> Option<&str> {
+        let text = self.get(pointer)?;
+        (text.parse::<f64>().ok()? == n).then_some(text)
+    }
+
+    pub fn len(&self) -> usize {
+        self.texts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.texts.is_empty()
+    }
+
+    fn record(&mut self, value: &SpannedValue, source: &str, pointer: &mut JsonPointer) {
+        match &value.kind {
+            SpannedKind::Number(_) => {
+                let text = &source[value.span.range()];
+                self.texts.insert(pointer.clone(), String::from(text));
+            }
+            SpannedKind::Array(elements) => {
+                for (i, element) in elements.iter().enumerate() {
+                    pointer.push(i.to_string());
+                    self.record(element, source, pointer);
+                    pointer.pop();
+                }
+            }
+            SpannedKind::Object(members) => {
+                // like `to_value`, a repeated key keeps its last value
+                for member in members {
+                    pointer.push(member.key.as_str());
+                    self.record(&member.value, source, pointer);
+                    pointer.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Parses a document along with the source text of each of its numbers
+pub fn parse_with_number_texts(input: &str) -> Result<(Value, NumberTexts), ReaderError> {
+    let root = parse_with_spans(input)?;
+    let mut texts = NumberTexts::default();
+    texts.record(&root, input, &mut JsonPointer::root());
+    Ok((root.to_value(), texts))
+}
+
+impl Value {
+    /// Serializes to compact JSON text, writing each number that still has the value it
+    /// was parsed with exactly as it appeared in the source
+    ///
+    /// Numbers that were changed, added or moved since parsing are formatted as usual.
+    pub fn to_json_string_with_texts(&self, texts: &NumberTexts) -> String {
+        let mut output = String::new();
+        write_json_with_texts(self, texts, None, &mut output);
+        output
+    }
+
+    /// Like [`Value::to_json_string_with_texts`], indented by two spaces
+    pub fn to_json_string_pretty_with_texts(&self, texts: &NumberTexts) -> String {
+        let mut output = String::new();
+        write_json_with_texts(self, texts, Some("  "), &mut output);
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{JsonPointer, Value};
+
+    use super::parse_with_number_texts;
+
+    #[test]
+    fn records_number_texts() {
+        let input = r#"{"price": 1.50, "qty": 1e3, "items": [-0.0, 2], "a": 1, "a": 1.0}"#;
+        let (value, texts) = parse_with_number_texts(input).unwrap();
+        assert_eq!(texts.len(), 5);
+        let text = |pointer: &str| texts.get(&JsonPointer::parse(pointer).unwrap());
+        assert_eq!(text("/price"), Some("1.50"));
+        assert_eq!(text("/items/0"), Some("-0.0"));
+        assert_eq!(text("/a"), Some("1.0"));
+        assert_eq!(
+            value.to_json_string_with_texts(&texts),
+            r#"{"a":1.0,"items":[-0.0,2],"price":1.50,"qty":1e3}"#
+        );
+    }
+
+    #[test]
+    fn edited_numbers_are_reformatted() {
+        let (mut value, texts) = parse_with_number_texts(r#"{"a": 1.50, "b": [1e3]}"#).unwrap();
+        *value.pointer_mut("/a").unwrap() = Value::Number(2.5);
+        value
+            .pointer_mut("/b")
+            .unwrap()
+            .push(Value::Number(1e3))
+            .unwrap();
+        let expected = "{\n  \"a\": 2.5,\n  \"b\": [\n    1e3,\n    1000\n  ]\n}";
+        assert_eq!(value.to_json_string_pretty_with_texts(&texts), expected);
+    }
+}
@@ -0,0 +1,130 @@
+use std::io;
+
+use crate::reader::{SubtreeHooks, read_subtree_with};
+use crate::{DefaultState, Event, JsonReader, Position, ReaderError, Value};
+
+/// Callbacks made while a document is parsed, see [`crate::Parser::parse_observed`]
+///
+/// Every method does nothing by default, so an observer only implements what it needs,
+/// ex. [`ParseObserver::token`] to report progress through the input.
+pub trait ParseObserver {
+    /// A token was read, with where it starts in the input
+    fn token(&mut self, _event: &Event, _position: Position) {}
+
+    /// A value is complete, arrays and objects after their contents and the root last
+    fn value_completed(&mut self, _value: &Value) {}
+
+    /// An array or object was opened or closed, leaving the nesting at `depth`
+    fn depth_changed(&mut self, _depth: usize) {}
+}
+
+struct ObserverHooks<'a, O> {
+    observer: &'a mut O,
+    depth: usize,
+}
+
+impl<R, O: ParseObserver> SubtreeHooks<R> for ObserverHooks<'_, O> {
+    fn enter(&mut self, _: &mut JsonReader<R>, _: Position) -> Result<Option<Value>, ReaderError> {
+        Ok(None)
+    }
+
+    fn leave(&mut self, _: &JsonReader<R>, _: Position, _: &Value) {}
+
+    fn event(&mut self, event: &Event, position: Position) {
+        self.observer.token(event, position);
+        match event {
+            Event::StartArray | Event::StartObject => self.depth += 1,
+            Event::EndArray | Event::EndObject => self.depth -= 1,
+            _ => return,
+        }
+        self.observer.depth_changed(self.depth);
+    }
+
+    fn value(&mut self, value: &Value) {
+        self.observer.value_completed(value);
+    }
+}
+
+/// Reads a whole document, reporting its progress to `observer`
+pub(crate) fn read_observed<R: io::Read>(
+    mut reader: JsonReader<R>,
+    observer: &mut impl ParseObserver,
+) -> Result<Value, ReaderError> {
+    let first = reader.next_event()?.ok_or(ReaderError::UnexpectedEof)?;
+    let mut hooks = ObserverHooks { observer, depth: 0 };
+    let value = read_subtree_with(&mut reader, first, &DefaultState::default(), &mut hooks)?;
+    // the reader reports anything after the root value
+    reader.next_event()?;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Event, Parser, Position, Value};
+
+    use super::ParseObserver;
+
+    #[derive(Default)]
+    struct Recorder {
+        tokens: Vec<(Event, usize)>,
+        values: Vec<String>,
+        depths: Vec<usize>,
+    }
+
+    impl ParseObserver for Recorder {
+        fn token(&mut self, event: &Event, position: Position) {
+            self.tokens.push((event.clone(), position.offset));
+        }
+
+        fn value_completed(&mut self, value: &Value) {
+            self.values.push(value.to_json_string());
+        }
+
+        fn depth_changed(&mut self, depth: usize) {
+            self.depths.push(depth);
+        }
+    }
+
+    #[test]
+    fn reports_every_step() {
+        let mut recorder = Recorder::default();
+        let input = r#"{"a": [1, {}], "b": null}"#;
+        let value = Parser::new()
+            .parse_observed(input.as_bytes(), &mut recorder)
+            .unwrap();
+        assert_eq!(value.to_json_string(), r#"{"a":[1,{}],"b":null}"#);
+
+        let expected = [
+            (Event::StartObject, 0),
+            (Event::Key(String::from("a")), 1),
+            (Event::StartArray, 6),
+            (Event::Number(1.0), 7),
+            (Event::StartObject, 10),
+            (Event::EndObject, 11),
+            (Event::EndArray, 12),
+            (Event::Key(String::from("b")), 15),
+            (Event::Null, 20),
+            (Event::EndObject, 24),
+        ];
+        assert_eq!(recorder.tokens, expected);
+        assert_eq!(
+            recorder.values,
+            ["1", "{}", "[1,{}]", "null", r#"{"a":[1,{}],"b":null}"#]
+        );
+        assert_eq!(recorder.depths, [1, 2, 3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn default_methods_do_nothing() {
+        struct Quiet;
+        impl ParseObserver for Quiet {}
+
+        let value = Parser::new().parse_observed(&b"[true]"[..], &mut Quiet);
+        assert_eq!(value.unwrap(), Value::Array(vec![Value::Boolean(true)]));
+        assert!(
+            Parser::new()
+                .parse_observed(&b"[true"[..], &mut Quiet)
+                .is_err()
+        );
+    }
+}
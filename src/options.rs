@@ -0,0 +1,384 @@
+use crate::{Parser, ReaderError, Value};
+
+/// Which extensions to the JSON grammar a parser accepts
+///
+/// Start from one of the presets, [`ParserOptions::strict`], [`ParserOptions::default`]
+/// or [`ParserOptions::lenient`], and change individual toggles as needed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParserOptions {
+    /// `//` line comments and `/* */` block comments wherever whitespace is allowed
+    pub comments: bool,
+
+    /// A comma after the last element of an array or the last member of an object
+    pub trailing_commas: bool,
+
+    /// The literals `NaN`, `Infinity` and `-Infinity` as numbers
+    pub nan_and_infinity: bool,
+
+    /// Unescaped control characters, ex. a raw tab or newline, inside strings
+    pub control_characters: bool,
+
+    /// Hexadecimal, octal and binary integers, ex. `0xFF`, `0o755` and `0b1010`
+    pub radix_integers: bool,
+
+    /// Multi-line strings without escapes, either between `'''` as in Hjson, with the
+    /// indentation of the opening quotes removed from each line, or between backticks
+    /// exactly as written
+    pub multiline_strings: bool,
+
+    /// What happens when an object has the same key more than once
+    pub duplicate_keys: DuplicateKeys,
+
+    /// Which number literals are accepted
+    pub numbers: NumberMode,
+
+    /// What happens to a number literal outside the range of [`ParserOptions::numbers`]
+    pub overflow: NumberOverflow,
+}
+
+/// How an object with a repeated key is read
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// The document is rejected with [`ReaderError::DuplicateKey`]
+    Error,
+
+    /// The first value is kept
+    First,
+
+    /// The last value is kept, like most JSON parsers
+    #[default]
+    Last,
+}
+
+/// How number literals are read into `f64`s
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NumberMode {
+    /// Every number is rounded to the nearest `f64`
+    #[default]
+    Float,
+
+    /// Integers beyond ±(2^53 - 1), which an `f64` cannot hold exactly, are out of range
+    /// instead of being silently rounded, ex. 64-bit IDs
+    SafeIntegers,
+}
+
+/// How a number literal outside the range of the [`NumberMode`] is read
+///
+/// Numbers past `f64::MAX`, ex. `1e400`, are always out of range. With
+/// [`NumberMode::SafeIntegers`] so are integers beyond ±(2^53 - 1).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NumberOverflow {
+    /// The document is rejected with [`ReaderError::NumberOutOfRange`], or with
+    /// [`ReaderError::UnsafeInteger`] for integers that are too large to be exact
+    #[default]
+    Error,
+
+    /// The number is clamped to the largest value in range with the same sign
+    Saturate,
+
+    /// The number is rounded to the nearest `f64`, which is infinite past `f64::MAX`
+    Float,
+
+    /// The literal is kept as a string holding its source text, ex. `"1e400"`
+    Text,
+}
+
+/// A family of JSON-like formats, each read into the same [`Value`] tree
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Dialect {
+    /// RFC 8259, as read by [`ParserOptions::default`]
+    #[default]
+    Json,
+
+    /// JSON with every extension of [`ParserOptions::lenient`]
+    Lenient,
+
+    /// [Hjson](https://hjson.github.io), for human-edited configuration files
+    ///
+    /// Extends the lenient grammar with keys and strings without quotes, newlines in
+    /// place of commas and an object root without braces. A value without quotes runs
+    /// to the end of its line, unless the line starts with a number, `true`, `false` or
+    /// `null` followed by nothing but a separator or a comment.
+    Hjson,
+}
+
+impl Dialect {
+    /// Parses a whole document written in this dialect
+    ///
+    /// A shorthand for [`Parser::parse_str`] with [`Parser::dialect`] and the default
+    /// [`Limits`].
+    pub fn parse(self, input: &str) -> Result<Value, ReaderError> {
+        Parser::new().dialect(self).parse_str(input)
+    }
+}
+
+/// How deeply arrays and objects may be nested unless [`Limits::max_depth`] says otherwise
+///
+/// Code that walks a [`Value`], including dropping it, recurses once per level, so much
+/// deeper documents could overflow the stack.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Caps on the resources a document may use while it is parsed, `None` means unlimited
+///
+/// By default only the depth is limited, to [`DEFAULT_MAX_DEPTH`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Limits {
+    /// How many arrays and objects can be nested, the root container is depth 1
+    pub max_depth: Option<usize>,
+
+    /// The total size of the input, including whitespace
+    pub max_bytes: Option<usize>,
+
+    /// The most characters in a number literal, checked while it is read so a huge
+    /// literal fails before it is buffered
+    pub max_number_len: Option<usize>,
+
+    /// The most bytes in a string or key once escapes are decoded, also checked while
+    /// it is read
+    pub max_string_len: Option<usize>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            max_bytes: None,
+            max_number_len: None,
+            max_string_len: None,
+        }
+    }
+}
+
+impl ParserOptions {
+    /// Exactly RFC 8259, also rejecting duplicate keys, ex. for validating input from
+    /// untrusted sources
+    pub fn strict() -> Self {
+        Self {
+            duplicate_keys: DuplicateKeys::Error,
+            ..Self::default()
+        }
+    }
+
+    /// Every extension, ex. for hand-written configuration files
+    pub fn lenient() -> Self {
+        Self {
+            comments: true,
+            trailing_commas: true,
+            nan_and_infinity: true,
+            control_characters: true,
+            radix_integers: true,
+            multiline_strings: true,
+            duplicate_keys: DuplicateKeys::Last,
+            numbers: NumberMode::Float,
+            overflow: NumberOverflow::Float,
+        }
+    }
+}
+
+/// The RFC 8259 grammar, where a repeated key keeps its last value
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            comments: false,
+            trailing_commas: false,
+            nan_and_infinity: false,
+            control_characters: false,
+            radix_integers: false,
+            multiline_strings: false,
+            duplicate_keys: DuplicateKeys::default(),
+            numbers: NumberMode::default(),
+            overflow: NumberOverflow::default(),
+        }
+    }
+}
+
+/// Parses a whole document, accepting the extensions enabled in `options`
+///
+/// A shorthand for [`Parser::parse_str`] with the default [`Limits`].
+pub fn parse_with_options(input: &str, options: &ParserOptions) -> Result<Value, ReaderError> {
+    Parser::new().options(options.clone()).parse_str(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ReaderError, Value};
+
+    use super::{DuplicateKeys, NumberMode, NumberOverflow, ParserOptions, parse_with_options};
+
+    const EXTENDED: &str = "{
+        // ports
+        \"ports\": [80, /* tls */ 443,],
+        \"ratio\": NaN,
+        \"tab\": \"a\tb\",
+    }";
+
+    #[test]
+    fn presets() {
+        assert!(parse_with_options(EXTENDED, &ParserOptions::default()).is_err());
+        assert!(parse_with_options(EXTENDED, &ParserOptions::strict()).is_err());
+
+        let value = parse_with_options(EXTENDED, &ParserOptions::lenient()).unwrap();
+        let ports = Value::Array(vec![Value::Number(80.0), Value::Number(443.0)]);
+        assert_eq!(value.pointer("/ports"), Some(&ports));
+        assert!(
+            value
+                .pointer("/ratio")
+                .and_then(Value::as_f64)
+                .unwrap()
+                .is_nan()
+        );
+        assert_eq!(value.pointer("/tab"), Some(&Value::string("a\tb")));
+    }
+
+    #[test]
+    fn individual_toggles() {
+        let options = ParserOptions {
+            trailing_commas: true,
+            ..ParserOptions::default()
+        };
+        assert!(parse_with_options("[1,]", &options).is_ok());
+        assert!(parse_with_options(r#"{"a": 1,}"#, &options).is_ok());
+        assert!(matches!(
+            parse_with_options("[1,,]", &options),
+            Err(ReaderError::UnexpectedByte { found: b',', .. })
+        ));
+        assert!(parse_with_options("[1] // done", &options).is_err());
+
+        let options = ParserOptions {
+            nan_and_infinity: true,
+            ..ParserOptions::default()
+        };
+        let value = parse_with_options("[Infinity, -Infinity]", &options).unwrap();
+        let expected = [f64::INFINITY, f64::NEG_INFINITY].map(Value::Number);
+        assert_eq!(value, Value::Array(expected.to_vec()));
+
+        let options = ParserOptions {
+            comments: true,
+            ..ParserOptions::default()
+        };
+        assert!(matches!(
+            parse_with_options("[1 /* open", &options),
+            Err(ReaderError::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let input = r#"{"a": 1, "b": {"a": 2, "a": 3}}"#;
+        let mut options = ParserOptions::default();
+        let value = parse_with_options(input, &options).unwrap();
+        assert_eq!(value.pointer("/b/a"), Some(&Value::Number(3.0)));
+
+        options.duplicate_keys = DuplicateKeys::First;
+        let value = parse_with_options(input, &options).unwrap();
+        assert_eq!(value.pointer("/b/a"), Some(&Value::Number(2.0)));
+
+        match parse_with_options(input, &ParserOptions::strict()) {
+            Err(ReaderError::DuplicateKey { position, key }) => {
+                assert_eq!((position.offset, key.as_str()), (23, "a"));
+            }
+            other => panic!("expected a duplicate key error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn number_overflow() {
+        let input = "[1e400, -1e400, 9007199254740993, 1]";
+        let read = |numbers, overflow| {
+            let options = ParserOptions {
+                numbers,
+                overflow,
+                ..ParserOptions::default()
+            };
+            parse_with_options(input, &options)
+        };
+        assert!(matches!(
+            read(NumberMode::Float, NumberOverflow::Error),
+            Err(ReaderError::NumberOutOfRange { position }) if position.offset == 1
+        ));
+
+        let max = 9_007_199_254_740_991.0;
+        let expected = [f64::MAX, -f64::MAX, max, 1.0].map(Value::Number);
+        let saturated = read(NumberMode::SafeIntegers, NumberOverflow::Saturate).unwrap();
+        assert_eq!(saturated, Value::Array(expected.to_vec()));
+
+        let expected = [f64::INFINITY, f64::NEG_INFINITY, max + 1.0, 1.0].map(Value::Number);
+        let rounded = read(NumberMode::SafeIntegers, NumberOverflow::Float).unwrap();
+        assert_eq!(rounded, Value::Array(expected.to_vec()));
+
+        let text = read(NumberMode::SafeIntegers, NumberOverflow::Text).unwrap();
+        let expected = Value::Array(vec![
+            Value::string("1e400"),
+            Value::string("-1e400"),
+            Value::string("9007199254740993"),
+            Value::Number(1.0),
+        ]);
+        assert_eq!(text, expected);
+    }
+
+    #[test]
+    fn radix_integers() {
+        let input = "[0xFF, 0o755, 0b1010, -0x10, 0XaB, 0]";
+        assert!(parse_with_options(input, &ParserOptions::default()).is_err());
+        let value = parse_with_options(input, &ParserOptions::lenient()).unwrap();
+        let expected = [255.0, 493.0, 10.0, -16.0, 171.0, 0.0].map(Value::Number);
+        assert_eq!(value, Value::Array(expected.to_vec()));
+
+        for input in ["0x", "0b2", "0o8", "0x1.5", "1x1"] {
+            assert!(
+                parse_with_options(input, &ParserOptions::lenient()).is_err(),
+                "{input}"
+            );
+        }
+
+        let options = ParserOptions {
+            radix_integers: true,
+            multiline_strings: true,
+            numbers: NumberMode::SafeIntegers,
+            ..ParserOptions::default()
+        };
+        assert!(matches!(
+            parse_with_options("0xFFFFFFFFFFFFFFFF", &options),
+            Err(ReaderError::UnsafeInteger { .. })
+        ));
+        let options = ParserOptions {
+            overflow: NumberOverflow::Text,
+            ..options
+        };
+        assert_eq!(
+            parse_with_options("0xFFFFFFFFFFFFFFFF", &options).unwrap(),
+            Value::string("0xFFFFFFFFFFFFFFFF")
+        );
+    }
+
+    #[test]
+    fn multiline_strings() {
+        let input = "{
+\"text\": '''
+          first
+            second
+          ''',
+            \"raw\": `C:\\dir\\ \"quoted\"
+line`,
+            \"inline\": '''a'b'''
+        }";
+        assert!(parse_with_options(input, &ParserOptions::default()).is_err());
+        let value = parse_with_options(input, &ParserOptions::lenient()).unwrap();
+        assert_eq!(
+            value.pointer("/text"),
+            Some(&Value::string("  first\n    second"))
+        );
+        assert_eq!(
+            value.pointer("/raw"),
+            Some(&Value::string("C:\\dir\\ \"quoted\"\nline"))
+        );
+        assert_eq!(value.pointer("/inline"), Some(&Value::string("a'b")));
+
+        for input in ["'''open", "`open", "'single'", "''"] {
+            assert!(
+                parse_with_options(input, &ParserOptions::lenient()).is_err(),
+                "{input}"
+            );
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::hash::{BuildHasher, Hash, Hasher};
+
+use crate::Value;
+use crate::hash::hash_value;
+
+/// Values are equal when they have the same type and contents, regardless of object key
+/// order
+///
+/// Unlike `f64`, NaN is equal to itself so that this is a true equivalence, and `-0.0`
+/// equals `0.0`.
+impl<S: BuildHasher> PartialEq for Value<S> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::Number(a), Value::Number(b)) => a == b || (a.is_nan() && b.is_nan()),
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Array(a), Value::Array(b)) => a == b,
+            (Value::Object(a), Value::Object(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl<S: BuildHasher> Eq for Value<S> {}
+
+/// A total order: first by type, `null < boolean < number < string < array < object`,
+/// then by contents
+///
+/// Numbers are ordered numerically with NaN after every other number, strings by their
+/// bytes, arrays element by element, and objects by their members sorted by key.
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
+            (Value::Number(a), Value::Number(b)) => match (a.is_nan(), b.is_nan()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+            },
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            (Value::Array(a), Value::Array(b)) => a.cmp(b),
+            (Value::Object(a), Value::Object(b)) => {
+                let mut a: Vec<_> = a.iter().collect();
+                let mut b: Vec<_> = b.iter().collect();
+                a.sort_by_key(|(key, _)| *key);
+                b.sort_by_key(|(key, _)| *key);
+                a.cmp(&b)
+            }
+            _ => type_rank(self).cmp(&type_rank(other)),
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Hashes the same way as [`Value::structural_hash`], consistent with `Eq`
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_value(self, state);
+    }
+}
+
+fn type_rank(value: &Value) -> u8 {
+    match value {
+        Value::Null => 0,
+        Value::Boolean(_) => 1,
+        Value::Number(_) => 2,
+        Value::String(_) => 3,
+        Value::Array(_) => 4,
+        Value::Object(_) => 5,
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use std::collections::{BTreeSet, HashSet};
+
+    use crate::{Value, parse};
+
+    fn value(input: &str) -> Value {
+        parse(String::from(input)).unwrap()
+    }
+
+    #[test]
+    fn orders_by_type_then_contents() {
+        let sorted = [
+            "null",
+            "false",
+            "true",
+            "1",
+            "2.5",
+            "\"a\"",
+            "\"b\"",
+            "[]",
+            "[1]",
+            "[1, 2]",
+            "[2]",
+            "{}",
+            r#"{"a": 1}"#,
+            r#"{"a": 2}"#,
+            r#"{"b": 0}"#,
+        ]
+        .map(value);
+        for pair in sorted.windows(2) {
+            assert!(pair[0] < pair[1], "{:?} < {:?}", pair[0], pair[1]);
+        }
+        let set: BTreeSet<_> = sorted.iter().rev().cloned().collect();
+        assert!(set.into_iter().eq(sorted));
+    }
+
+    #[test]
+    fn special_numbers() {
+        let nan = Value::Number(f64::NAN);
+        assert_eq!(nan, Value::Number(-f64::NAN));
+        assert!(nan > Value::Number(f64::INFINITY));
+        let zero: Value = Value::Number(0.0);
+        assert_eq!(Value::Number(-0.0), zero);
+        assert!(Value::Number(-1.0) < Value::Number(0.0));
+    }
+
+    #[test]
+    fn equal_values_hash_the_same() {
+        let mut set = HashSet::new();
+        set.insert(value(r#"{"a": [1, null], "b": "x"}"#));
+        assert!(set.contains(&value(r#"{"b": "x", "a": [1, null]}"#)));
+        set.insert(Value::Number(0.0));
+        assert!(set.contains(&Value::Number(-0.0)));
+        set.insert(Value::Number(f64::NAN));
+        assert!(set.contains(&Value::Number(-f64::NAN)));
+    }
+}
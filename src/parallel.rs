@@ -0,0 +1,149 @@
+use std::ops::Range;
+use std::thread;
+
+use crate::reader::read_document;
+use crate::{ReaderError, Value};
+
+/// Parses a document whose root is a large array, splitting its elements across threads
+///
+/// A quick scan over the bytes finds where each top-level element starts and ends, then
+/// the elements are parsed concurrently and merged in order. Any other document, or one
+/// with an error, is parsed on the current thread so errors report their usual position.
+pub fn parse_array_parallel(input: &str) -> Result<Value, ReaderError> {
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let Some(elements) = split_elements(input.as_bytes()) else {
+        return read_document(input.as_bytes());
+    };
+    if threads == 1 || elements.len() < 2 {
+        return read_document(input.as_bytes());
+    }
+
+    let chunk_size = elements.len().div_ceil(threads);
+    let chunks: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = elements
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(|| {
+                    chunk
+                        .iter()
+                        .map(|range| read_document(&input.as_bytes()[range.clone()]))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("parser threads do not panic"))
+            .collect()
+    });
+
+    let mut array = Vec::with_capacity(elements.len());
+    for chunk in chunks {
+        match chunk {
+            Ok(values) => array.extend(values),
+            Err(_) => return read_document(input.as_bytes()),
+        }
+    }
+    Ok(Value::Array(array))
+}
+
+/// The byte ranges of the elements of a root array, `None` if the input does not look
+/// like one
+///
+/// Only strings and brackets are tracked, the elements themselves are checked when they
+/// are parsed.
+fn split_elements(input: &[u8]) -> Option<Vec<Range<usize>>> {
+    let is_whitespace = |byte: &u8| matches!(byte, b' ' | b'\t' | b'\n' | b'\r');
+    let open = input.iter().position(|byte| !is_whitespace(byte))?;
+    if input[open] != b'[' {
+        return None;
+    }
+
+    let mut elements = Vec::new();
+    let mut start = open + 1;
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, &byte) in input.iter().enumerate().skip(open + 1) {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => depth += 1,
+            b']' | b'}' if depth > 0 => depth -= 1,
+            b',' if depth == 0 => {
+                elements.push(start..i);
+                start = i + 1;
+            }
+            b']' => {
+                let last = start..i;
+                let empty = input[last.clone()].iter().all(is_whitespace);
+                if !(empty && elements.is_empty()) {
+                    elements.push(last);
+                }
+                let rest = &input[i + 1..];
+                return rest.iter().all(is_whitespace).then_some(elements);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ReaderError, Value};
+
+    use super::{parse_array_parallel, split_elements};
+
+    #[test]
+    fn finds_element_boundaries() {
+        let input = r#" [1, "a,]\"", {"b": [2, 3]}, []] "#;
+        let elements: Vec<_> = split_elements(input.as_bytes())
+            .unwrap()
+            .into_iter()
+            .map(|range| input[range].trim())
+            .collect();
+        assert_eq!(elements, ["1", r#""a,]\"""#, r#"{"b": [2, 3]}"#, "[]"]);
+        assert_eq!(split_elements(b"[ ]"), Some(vec![]));
+        assert_eq!(split_elements(b"{}"), None);
+        assert_eq!(split_elements(b"[1] 2"), None);
+    }
+
+    #[test]
+    fn parses_large_arrays() {
+        let input: String = format!(
+            "[{}]",
+            (0..1000)
+                .map(|n| format!(r#"{{"id": {n}, "tags": ["x", "y"]}}"#))
+                .collect::<Vec<_>>()
+                .join(",\n")
+        );
+        let Value::Array(array) = parse_array_parallel(&input).unwrap() else {
+            panic!("expected an array");
+        };
+        assert_eq!(array.len(), 1000);
+        assert_eq!(array[999].pointer("/id"), Some(&Value::Number(999.0)));
+    }
+
+    #[test]
+    fn other_documents_and_errors() {
+        assert_eq!(parse_array_parallel(" 1 ").unwrap(), Value::Number(1.0));
+        assert_eq!(parse_array_parallel("[]").unwrap(), Value::Array(vec![]));
+        assert!(matches!(
+            parse_array_parallel("[1, 2, tru]"),
+            Err(ReaderError::UnexpectedByte { .. })
+        ));
+        assert!(matches!(
+            parse_array_parallel("[1, 2,]"),
+            Err(ReaderError::UnexpectedByte { .. })
+        ));
+    }
+}
@@ -1,13 +1,15 @@
-use std::collections::HashMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
 
-use crate::Value;
+use crate::{HashMap, Value};
 
 use super::tokenize::Token;
 
 pub type ParseResult = Result<Value, TokenParseError>;
 
 pub fn parse_tokens(tokens: &[Token], index: &mut usize) -> ParseResult {
-    let token = &tokens[*index];
+    let token = token_at(tokens, *index)?;
     if matches!(
         token,
         Token::Null | Token::False | Token::True | Token::Number(_) | Token::String(_)
@@ -26,6 +28,11 @@ pub fn parse_tokens(tokens: &[Token], index: &mut usize) -> ParseResult {
     }
 }
 
+/// The token at `index`, or an error if the input ended before it
+fn token_at(tokens: &[Token], index: usize) -> Result<&Token, TokenParseError> {
+    tokens.get(index).ok_or(TokenParseError::EarlyEOF)
+}
+
 fn parse_string(input: &str) -> ParseResult {
     let unescaped = unescape_string(input)?;
     Ok(Value::String(unescaped))
@@ -82,14 +89,17 @@ fn parse_array(tokens: &[Token], index: &mut usize) -> ParseResult {
     loop {
         // consume the previous LeftBracket or Comma token
         *index += 1;
-        if tokens[*index] == Token::RightBracket {
+        if *token_at(tokens, *index)? == Token::RightBracket {
+            if !array.is_empty() {
+                return Err(TokenParseError::TrailingComma);
+            }
             break;
         }
 
         let value = parse_tokens(tokens, index)?;
         array.push(value);
 
-        let token = &tokens[*index];
+        let token = token_at(tokens, *index)?;
         match token {
             Token::Comma => {}
             Token::RightBracket => break,
@@ -109,13 +119,16 @@ fn parse_object(tokens: &[Token], index: &mut usize) -> ParseResult {
     loop {
         // consume the previous LeftBrace or Comma token
         *index += 1;
-        if tokens[*index] == Token::RightBrace {
+        if *token_at(tokens, *index)? == Token::RightBrace {
+            if !map.is_empty() {
+                return Err(TokenParseError::TrailingComma);
+            }
             break;
         }
 
-        if let Token::String(s) = &tokens[*index] {
+        if let Token::String(s) = token_at(tokens, *index)? {
             *index += 1;
-            if Token::Colon == tokens[*index] {
+            if Token::Colon == *token_at(tokens, *index)? {
                 *index += 1;
                 let key = unescape_string(s)?;
                 let value = parse_tokens(tokens, index)?;
@@ -124,7 +137,7 @@ fn parse_object(tokens: &[Token], index: &mut usize) -> ParseResult {
                 return Err(TokenParseError::ExpectedColon);
             }
 
-            match &tokens[*index] {
+            match token_at(tokens, *index)? {
                 Token::Comma => {}
                 Token::RightBrace => break,
                 _ => return Err(TokenParseError::ExpectedComma),
@@ -157,12 +170,33 @@ pub enum TokenParseError {
     TrailingComma,
 }
 
+impl fmt::Display for TokenParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::EarlyEOF => "unexpected end of input",
+            Self::UnclosedBracket => "unclosed array",
+            Self::UnclosedBrace => "unclosed object",
+            Self::UnfinishedEscape => "unfinished escape",
+            Self::InvalidHexValue => "invalid hex digits in a unicode escape",
+            Self::InvalidCodePointValue => "unicode escape is not a valid character",
+            Self::ExpectedColon => "expected `:`",
+            Self::ExpectedComma => "expected `,`",
+            Self::ExpectedValue => "expected a value",
+            Self::ExpectedProperty => "expected a key",
+            Self::NeedsComma => "missing `,` between values",
+            Self::TrailingComma => "trailing comma",
+        })
+    }
+}
+
+impl core::error::Error for TokenParseError {}
+
 #[cfg(test)]
 mod tests {
     use crate::tokenize::Token;
     use crate::Value;
 
-    use super::parse_tokens;
+    use super::{parse_tokens, TokenParseError};
 
     #[test]
     fn parses_null() {
@@ -173,4 +207,18 @@ mod tests {
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn truncated_input() {
+        let inputs: [&[Token]; 3] = [
+            &[],
+            &[Token::LeftBracket, Token::Number(1.0)],
+            &[Token::LeftBrace, Token::String(String::from("a")), Token::Colon],
+        ];
+        for input in inputs {
+            let actual = parse_tokens(input, &mut 0);
+
+            assert_eq!(actual, Err(TokenParseError::EarlyEOF), "{input:?}");
+        }
+    }
 }
\ No newline at end of file
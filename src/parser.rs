@@ -0,0 +1,389 @@
+use std::hash::BuildHasher;
+use std::io;
+
+use crate::cache::read_cached;
+use crate::hjson::read_hjson;
+use crate::intern::read_interned;
+use crate::observe::read_observed;
+use crate::reader::read_value;
+use crate::{
+    DefaultState, Dialect, DuplicateKeys, JsonReader, Limits, NumberMode, NumberOverflow,
+    ParseObserver, ParserOptions, ReaderError, SharedValue, SubtreeCache, Value,
+};
+
+/// A configured parser for whole documents, accepting the full JSON grammar
+///
+/// Settings are chained from [`Parser::new`], ex.
+/// `Parser::new().dialect(Dialect::Hjson).max_depth(64)`, and the same parser can be reused
+/// for any number of documents.
+///
+/// Objects are read into a [`Map`](crate::Map) with `std`'s randomly seeded hasher unless
+/// another is chosen with [`Parser::hasher`], each map getting a clone of it.
+#[derive(Debug, Clone, Default)]
+pub struct Parser<S = DefaultState> {
+    dialect: Dialect,
+    options: ParserOptions,
+    limits: Limits,
+    hasher: S,
+}
+
+impl Parser {
+    /// The RFC 8259 grammar with the default [`Limits`], see [`ParserOptions::default`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S: BuildHasher + Clone> Parser<S> {
+    /// Builds the objects of parsed documents with clones of `hasher`, ex. a fixed-seed or
+    /// faster hasher for trusted input
+    ///
+    /// [`Parser::parse_observed`], [`Parser::parse_cached`] and [`Parser::parse_interned`]
+    /// are only available with the default hasher.
+    pub fn hasher<T: BuildHasher + Clone>(self, hasher: T) -> Parser<T> {
+        Parser {
+            dialect: self.dialect,
+            options: self.options,
+            limits: self.limits,
+            hasher,
+        }
+    }
+
+    /// Reads documents written in `dialect`
+    ///
+    /// [`Dialect::Json`] and [`Dialect::Lenient`] replace the options with their presets.
+    /// [`Dialect::Hjson`] has a grammar of its own, which uses the limits on depth and size
+    /// and the duplicate key policy but no other settings. [`Parser::parse_observed`],
+    /// [`Parser::parse_cached`] and [`Parser::parse_interned`] read through a [`JsonReader`]
+    /// and ignore it.
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        match dialect {
+            Dialect::Json => self.options = ParserOptions::default(),
+            Dialect::Lenient => self.options = ParserOptions::lenient(),
+            Dialect::Hjson => {}
+        }
+        self.dialect = dialect;
+        self
+    }
+
+    /// Replaces the options, ex. with one of the [`ParserOptions`] presets
+    pub fn options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.options.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    pub fn numbers(mut self, numbers: NumberMode) -> Self {
+        self.options.numbers = numbers;
+        self
+    }
+
+    pub fn overflow(mut self, overflow: NumberOverflow) -> Self {
+        self.options.overflow = overflow;
+        self
+    }
+
+    /// Replaces all of the limits
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// How many arrays and objects can be nested, the root container is depth 1
+    ///
+    /// Defaults to [`crate::DEFAULT_MAX_DEPTH`].
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.limits.max_depth = Some(max_depth);
+        self
+    }
+
+    /// The largest input accepted, in bytes
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.limits.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// The most characters in a number literal
+    pub fn max_number_len(mut self, max_number_len: usize) -> Self {
+        self.limits.max_number_len = Some(max_number_len);
+        self
+    }
+
+    /// The most bytes in a decoded string or key
+    pub fn max_string_len(mut self, max_string_len: usize) -> Self {
+        self.limits.max_string_len = Some(max_string_len);
+        self
+    }
+
+    pub fn parse_str(&self, input: &str) -> Result<Value<S>, ReaderError> {
+        self.parse_reader(input.as_bytes())
+    }
+
+    /// Parses UTF-8 encoded bytes, strings that are not valid UTF-8 are rejected with
+    /// [`ReaderError::InvalidUtf8`]
+    pub fn parse_bytes(&self, input: &[u8]) -> Result<Value<S>, ReaderError> {
+        self.parse_reader(input)
+    }
+
+    /// Parses a document as it is read, without loading the whole input first
+    pub fn parse_reader<R: io::Read>(&self, reader: R) -> Result<Value<S>, ReaderError> {
+        if self.dialect == Dialect::Hjson {
+            let duplicate_keys = self.options.duplicate_keys;
+            return read_hjson(reader, duplicate_keys, &self.limits, &self.hasher);
+        }
+        let reader = JsonReader::with_options(reader, self.options.clone());
+        read_value(reader.with_limits(self.limits.clone()), &self.hasher)
+    }
+}
+
+/// Parsers are equal when they read documents the same way, the hasher is not compared
+impl<S> PartialEq for Parser<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.dialect == other.dialect
+            && self.options == other.options
+            && self.limits == other.limits
+    }
+}
+
+impl Parser {
+    /// Parses a document as it is read, calling `observer` for every token, completed
+    /// value and change of nesting depth, ex. to drive a progress bar or a profiler
+    pub fn parse_observed<R: io::Read>(
+        &self,
+        reader: R,
+        observer: &mut impl ParseObserver,
+    ) -> Result<Value, ReaderError> {
+        let reader = JsonReader::with_options(reader, self.options.clone());
+        read_observed(reader.with_limits(self.limits.clone()), observer)
+    }
+
+    /// Parses `input`, looking up each array and object in `cache` by its source text
+    /// before reading it, ex. to speed up payloads that repeat the same large fragments
+    ///
+    /// A cached subtree is copied into the result while its text is only checked for
+    /// nesting, see [`JsonReader::skip_value`]. Finding the text of a subtree scans it once
+    /// more for each array or object it is nested in.
+    pub fn parse_cached(
+        &self,
+        input: &[u8],
+        cache: &mut impl SubtreeCache,
+    ) -> Result<Value, ReaderError> {
+        let reader = JsonReader::with_options(input, self.options.clone());
+        read_cached(reader.with_limits(self.limits.clone()), input, cache)
+    }
+
+    /// Parses into a [`SharedValue`] where all equal strings, arrays and objects share one
+    /// allocation, ex. for documents that repeat large fragments many times
+    ///
+    /// Repeats are found while reading, so they are never held in memory twice. Keeping
+    /// track of every subtree seen takes extra memory until parsing ends.
+    pub fn parse_interned<R: io::Read>(&self, reader: R) -> Result<SharedValue, ReaderError> {
+        let reader = JsonReader::with_options(reader, self.options.clone());
+        read_interned(
+            reader.with_limits(self.limits.clone()),
+            self.options.duplicate_keys,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::BuildHasherDefault;
+    use std::io::Cursor;
+
+    use crate::hash::FnvHasher;
+    use crate::{Dialect, DuplicateKeys, Limit, NumberMode, ParserOptions, ReaderError, Value};
+
+    use super::Parser;
+
+    #[test]
+    fn entry_points_agree() {
+        let parser = Parser::new().options(ParserOptions::lenient());
+        let input = "[1, -2.5e1, /* three */ \"3\",]";
+        let expected = parser.parse_str(input).unwrap();
+        assert_eq!(parser.parse_bytes(input.as_bytes()).unwrap(), expected);
+        assert_eq!(parser.parse_reader(Cursor::new(input)).unwrap(), expected);
+        assert_eq!(expected.pointer("/1"), Some(&Value::Number(-25.0)));
+    }
+
+    #[test]
+    fn settings_chain() {
+        let parser = Parser::new()
+            .options(ParserOptions::lenient())
+            .duplicate_keys(DuplicateKeys::Error)
+            .max_depth(8);
+        assert!(parser.parse_str("[1, // lenient\n 2,]").is_ok());
+        assert!(matches!(
+            parser.parse_str(r#"{"a": 1, "a": 2}"#),
+            Err(ReaderError::DuplicateKey { .. })
+        ));
+    }
+
+    #[test]
+    fn depth_limit() {
+        let parser = Parser::new().max_depth(2);
+        assert!(parser.parse_str("[[1], {\"a\": 2}]").is_ok());
+        match parser.parse_str("[[[]]]") {
+            Err(ReaderError::LimitExceeded { position, limit }) => {
+                assert_eq!((position.offset, limit), (2, Limit::Depth));
+            }
+            other => panic!("expected the depth limit, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn default_depth_limit() {
+        let deep = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+        for options in [ParserOptions::strict(), ParserOptions::lenient()] {
+            let parser = Parser::new().options(options.clone());
+            assert!(matches!(
+                parser.parse_str(&deep),
+                Err(ReaderError::LimitExceeded {
+                    limit: Limit::Depth,
+                    ..
+                })
+            ));
+            assert!(matches!(
+                crate::parse_with_options(&deep, &options),
+                Err(ReaderError::LimitExceeded {
+                    limit: Limit::Depth,
+                    ..
+                })
+            ));
+        }
+        let nested = format!("{}{}", "[".repeat(128), "]".repeat(128));
+        assert!(Parser::new().parse_str(&nested).is_ok());
+    }
+
+    #[test]
+    fn size_limit() {
+        let parser = Parser::new().max_bytes(5);
+        assert!(parser.parse_str("[1,2]").is_ok());
+        assert!(matches!(
+            parser.parse_str("[1,2] "),
+            Err(ReaderError::LimitExceeded {
+                limit: Limit::Bytes,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn token_limits() {
+        let parser = Parser::new().max_number_len(4).max_string_len(3);
+        assert!(
+            parser
+                .parse_str(r#"{"abc": [-1.5, 1e10, "\u00e9"]}"#)
+                .is_ok()
+        );
+
+        let long_number = format!("[1, {}]", "9".repeat(10_000));
+        match parser.parse_str(&long_number) {
+            Err(ReaderError::LimitExceeded { position, limit }) => {
+                assert_eq!((position.offset, limit), (4, Limit::NumberLength));
+            }
+            other => panic!("expected the number limit, got {other:?}"),
+        }
+        // rejected before the missing closing quote is noticed
+        let unterminated = format!("[\"{}", "a".repeat(10_000));
+        assert!(matches!(
+            parser.parse_str(&unterminated),
+            Err(ReaderError::LimitExceeded {
+                limit: Limit::StringLength,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parser.parse_str(r#"{"abcd": 1}"#),
+            Err(ReaderError::LimitExceeded {
+                limit: Limit::StringLength,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn number_modes() {
+        let input = "[9007199254740991, 9007199254740993, 1e300]";
+        assert!(Parser::new().parse_str(input).is_ok());
+        let parser = Parser::new().numbers(NumberMode::SafeIntegers);
+        match parser.parse_str(input) {
+            Err(ReaderError::UnsafeInteger { position }) => assert_eq!(position.offset, 19),
+            other => panic!("expected an unsafe integer, got {other:?}"),
+        }
+        assert!(parser.parse_str("[-9007199254740991, 1e300]").is_ok());
+    }
+
+    #[test]
+    fn dialects() {
+        let hjson = Parser::new().dialect(Dialect::Hjson);
+        let expected = Parser::new().parse_str(r#"{"a": 1, "b": "two words"}"#);
+        let input = "a: 1\nb: two words";
+        assert_eq!(hjson.parse_str(input).unwrap(), expected.unwrap());
+        assert_eq!(
+            hjson.parse_reader(Cursor::new(input)).unwrap(),
+            hjson.parse_str(input).unwrap()
+        );
+        assert!(Parser::new().parse_str(input).is_err());
+        assert!(
+            Parser::new()
+                .dialect(Dialect::Lenient)
+                .parse_str("[1,]")
+                .is_ok()
+        );
+
+        // limits and the duplicate key policy carry over to Hjson
+        let strict = hjson
+            .clone()
+            .max_depth(1)
+            .duplicate_keys(DuplicateKeys::Error);
+        assert!(matches!(
+            strict.parse_str("a: [1]"),
+            Err(ReaderError::LimitExceeded {
+                limit: Limit::Depth,
+                ..
+            })
+        ));
+        match strict.parse_str("a: 1\na: 2") {
+            Err(ReaderError::DuplicateKey { position, key }) => {
+                assert_eq!((position.offset, position.line, key.as_str()), (5, 2, "a"));
+            }
+            other => panic!("expected a duplicate key, got {other:?}"),
+        }
+        assert!(matches!(
+            hjson.clone().max_bytes(4).parse_str("a: 12"),
+            Err(ReaderError::LimitExceeded {
+                limit: Limit::Bytes,
+                ..
+            })
+        ));
+        assert!(matches!(
+            hjson.parse_bytes(b"a: \xff"),
+            Err(ReaderError::InvalidUtf8 { position }) if position.offset == 3
+        ));
+        let value = hjson
+            .hasher(BuildHasherDefault::<FnvHasher>::default())
+            .parse_str("a: {b: true}")
+            .unwrap();
+        assert_eq!(value.to_json_string(), r#"{"a":{"b":true}}"#);
+    }
+
+    #[test]
+    fn custom_hasher() {
+        type Fnv = BuildHasherDefault<FnvHasher>;
+
+        let parser = Parser::new().max_depth(3).hasher(Fnv::default());
+        let value: Value<Fnv> = parser
+            .parse_str(r#"{"a": [1, {"b": null}], "c": "d"}"#)
+            .unwrap();
+        let map = value.as_object().unwrap();
+        assert_eq!(map["c"].as_str(), Some("d"));
+        assert_eq!(value.to_json_string(), r#"{"a":[1,{"b":null}],"c":"d"}"#);
+        // the other settings are kept
+        assert!(parser.parse_str("[[[[]]]]").is_err());
+    }
+}
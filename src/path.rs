@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::collections::hash_map::{Entry, VacantEntry};
+
+use crate::Value;
+
+/// One step of a dot path such as `config.servers[2].host`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PathToken {
+    Key(String),
+    Index(usize),
+}
+
+/// Splits a dot path into keys and array indexes, `None` if it is malformed
+pub(crate) fn parse_dot_path(path: &str) -> Option<Vec<PathToken>> {
+    let mut tokens = Vec::new();
+    if path.is_empty() {
+        return Some(tokens);
+    }
+
+    for (i, part) in path.split('.').enumerate() {
+        let (key, mut indexes) = match part.find('[') {
+            Some(bracket) => part.split_at(bracket),
+            None => (part, ""),
+        };
+        // only the first part may start directly with an index, ex. `[0].name`
+        if !key.is_empty() {
+            tokens.push(PathToken::Key(String::from(key)));
+        } else if i > 0 || indexes.is_empty() {
+            return None;
+        }
+
+        while !indexes.is_empty() {
+            let close = indexes.find(']')?;
+            let index = indexes[1..close].parse().ok()?;
+            tokens.push(PathToken::Index(index));
+            indexes = &indexes[close + 1..];
+            if !indexes.is_empty() && !indexes.starts_with('[') {
+                return None;
+            }
+        }
+    }
+    Some(tokens)
+}
+
+impl Value {
+    /// Looks up a value by a dot path, ex. `config.servers[2].host`
+    ///
+    /// Keys containing `.` or `[` can't be expressed, use [`Value::pointer`] for those.
+    pub fn get_path(&self, path: &str) -> Option<&Value> {
+        parse_dot_path(path)?
+            .iter()
+            .try_fold(self, |value, token| match (value, token) {
+                (Value::Object(map), PathToken::Key(key)) => map.get(key),
+                (Value::Array(array), PathToken::Index(index)) => array.get(*index),
+                _ => None,
+            })
+    }
+
+    /// Looks up a value by a dot path, returning a mutable reference
+    pub fn get_path_mut(&mut self, path: &str) -> Option<&mut Value> {
+        parse_dot_path(path)?
+            .iter()
+            .try_fold(self, |value, token| match (value, token) {
+                (Value::Object(map), PathToken::Key(key)) => map.get_mut(key),
+                (Value::Array(array), PathToken::Index(index)) => array.get_mut(*index),
+                _ => None,
+            })
+    }
+
+    pub fn get_path_str(&self, path: &str) -> Option<&str> {
+        self.get_path(path)?.as_str()
+    }
+
+    pub fn get_path_i64(&self, path: &str) -> Option<i64> {
+        self.get_path(path)?.as_i64()
+    }
+
+    pub fn get_path_f64(&self, path: &str) -> Option<f64> {
+        self.get_path(path)?.as_f64()
+    }
+
+    pub fn get_path_bool(&self, path: &str) -> Option<bool> {
+        self.get_path(path)?.as_bool()
+    }
+
+    /// The entry at a dot path for in-place updates, ex.
+    /// `value.entry_path("server.tls.port")?.or_insert(Value::Number(443.0))`
+    ///
+    /// Missing or `null` values along the way become empty objects right away, even if
+    /// nothing is inserted. Indexes must already exist, except that the last token may be
+    /// the array's length to append. `None` if the path is malformed or passes through
+    /// any other value, in which case nothing is changed.
+    pub fn entry_path(&mut self, path: &str) -> Option<PathEntry<'_>> {
+        let tokens = parse_dot_path(path)?;
+        let Some((last, parents)) = tokens.split_last() else {
+            return Some(PathEntry(Slot::Occupied(self)));
+        };
+        if !entry_fits(self, parents, last) {
+            return None;
+        }
+
+        let mut current = self;
+        for token in parents {
+            current = match (current, token) {
+                (Value::Object(map), PathToken::Key(key)) => {
+                    map.entry(key.clone()).or_insert(Value::Null)
+                }
+                (Value::Array(array), PathToken::Index(index)) => array.get_mut(*index)?,
+                _ => return None,
+            };
+            if current.is_null() {
+                *current = Value::Object(HashMap::new());
+            }
+        }
+        let slot = match (current, last) {
+            (Value::Object(map), PathToken::Key(key)) => match map.entry(key.clone()) {
+                Entry::Occupied(entry) => Slot::Occupied(entry.into_mut()),
+                Entry::Vacant(entry) => Slot::VacantKey(entry),
+            },
+            (Value::Array(array), PathToken::Index(index)) => {
+                if *index == array.len() {
+                    Slot::VacantIndex(array)
+                } else {
+                    Slot::Occupied(array.get_mut(*index)?)
+                }
+            }
+            _ => return None,
+        };
+        Some(PathEntry(slot))
+    }
+}
+
+/// Whether [`Value::entry_path`] can reach `last` through `parents` without changing
+/// anything on the way that it would have to undo
+fn entry_fits(value: &Value, parents: &[PathToken], last: &PathToken) -> bool {
+    // `None` once the path reaches a missing or `null` value, which becomes an empty object
+    let mut current = Some(value);
+    for token in parents {
+        let next = match (current, token) {
+            (None, PathToken::Key(_)) => None,
+            (Some(Value::Object(map)), PathToken::Key(key)) => map.get(key),
+            (Some(Value::Array(array)), PathToken::Index(index)) => match array.get(*index) {
+                Some(element) => Some(element),
+                None => return false,
+            },
+            _ => return false,
+        };
+        current = next.filter(|value| !value.is_null());
+    }
+    match (current, last) {
+        (None | Some(Value::Object(_)), PathToken::Key(_)) => true,
+        (Some(Value::Array(array)), PathToken::Index(index)) => *index <= array.len(),
+        _ => false,
+    }
+}
+
+/// A value at a dot path that may not exist yet, from [`Value::entry_path`]
+pub struct PathEntry<'a>(Slot<'a>);
+
+enum Slot<'a> {
+    Occupied(&'a mut Value),
+    VacantKey(VacantEntry<'a, String, Value>),
+    VacantIndex(&'a mut Vec<Value>),
+}
+
+impl<'a> PathEntry<'a> {
+    /// The existing value, or `default` after inserting it
+    pub fn or_insert(self, default: Value) -> &'a mut Value {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with(self, default: impl FnOnce() -> Value) -> &'a mut Value {
+        match self.0 {
+            Slot::Occupied(value) => value,
+            Slot::VacantKey(entry) => entry.insert(default()),
+            Slot::VacantIndex(array) => {
+                array.push(default());
+                array.last_mut().expect("an element was just pushed")
+            }
+        }
+    }
+
+    /// Updates the value if it exists
+    pub fn and_modify(mut self, f: impl FnOnce(&mut Value)) -> Self {
+        if let Slot::Occupied(value) = &mut self.0 {
+            f(value);
+        }
+        self
+    }
+
+    pub fn is_occupied(&self) -> bool {
+        matches!(self.0, Slot::Occupied(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::{PathToken, parse_dot_path};
+
+    fn config() -> Value {
+        Parser::new()
+            .parse_str(
+                r#"{"config": {"debug": true, "servers": [
+                {"host": "a.example.com", "port": 80},
+                {"host": "b.example.com", "port": 81.5},
+                {"host": "c.example.com", "port": 8080}
+            ]}}"#,
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_keys_and_indexes() {
+        let expected = vec![
+            PathToken::Key(String::from("a")),
+            PathToken::Index(2),
+            PathToken::Index(0),
+            PathToken::Key(String::from("b")),
+        ];
+        assert_eq!(parse_dot_path("a[2][0].b"), Some(expected));
+        assert_eq!(parse_dot_path("[1]"), Some(vec![PathToken::Index(1)]));
+    }
+
+    #[test]
+    fn rejects_malformed_paths() {
+        assert_eq!(parse_dot_path("a..b"), None);
+        assert_eq!(parse_dot_path("a[x]"), None);
+        assert_eq!(parse_dot_path("a[0"), None);
+        assert_eq!(parse_dot_path("a[0]b"), None);
+    }
+
+    #[test]
+    fn typed_getters() {
+        let value = config();
+        assert_eq!(
+            value.get_path_str("config.servers[2].host"),
+            Some("c.example.com")
+        );
+        assert_eq!(value.get_path_i64("config.servers[0].port"), Some(80));
+        assert_eq!(value.get_path_i64("config.servers[1].port"), None);
+        assert_eq!(value.get_path_f64("config.servers[1].port"), Some(81.5));
+        assert_eq!(value.get_path_bool("config.debug"), Some(true));
+    }
+
+    #[test]
+    fn missing_or_mismatched() {
+        let value = config();
+        assert_eq!(value.get_path("config.servers[3]"), None);
+        assert_eq!(value.get_path("config.debug.x"), None);
+        assert_eq!(value.get_path_str("config.debug"), None);
+    }
+
+    #[test]
+    fn entry_path_creates_objects() {
+        let mut value = Value::object([("a", Value::Null)]);
+        let port = value.entry_path("a.b.port").unwrap();
+        assert!(!port.is_occupied());
+        port.or_insert(Value::Number(80.0));
+        value
+            .entry_path("a.b.port")
+            .unwrap()
+            .and_modify(|port| *port = Value::Number(443.0))
+            .or_insert(Value::Null);
+        let expected = Parser::new()
+            .parse_str(r#"{"a": {"b": {"port": 443}}}"#)
+            .unwrap();
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn entry_path_arrays() {
+        let mut value = config();
+        let len = value.entry_path("config.servers[3]").unwrap();
+        len.or_insert_with(|| Value::object([("host", Value::string("d.example.com"))]));
+        assert_eq!(
+            value.get_path_str("config.servers[3].host"),
+            Some("d.example.com")
+        );
+        let host = value.entry_path("config.servers[0].host").unwrap();
+        assert_eq!(host.or_insert(Value::Null), &Value::string("a.example.com"));
+
+        assert!(value.entry_path("config.servers[9]").is_none());
+        assert!(value.entry_path("config.servers[9].host").is_none());
+        assert!(value.entry_path("config.debug.x").is_none());
+        assert!(value.entry_path("config[0]").is_none());
+        assert!(value.entry_path("a..b").is_none());
+    }
+
+    #[test]
+    fn failed_entry_path_changes_nothing() {
+        let original = Value::object([("a", Value::Null), ("n", Value::Number(1.0))]);
+        let mut value = original.clone();
+        for path in ["a.b.c[0]", "a[0].b", "x.y[0]", "n.x", "x.y.n.z[1]"] {
+            assert!(value.entry_path(path).is_none(), "{path}");
+            assert_eq!(value, original, "{path}");
+        }
+        let mut value = config();
+        let original = value.clone();
+        assert!(value.entry_path("config.servers[9].host").is_none());
+        assert!(value.entry_path("config.debug.x").is_none());
+        assert!(value.entry_path("config.servers[0].port.x").is_none());
+        assert_eq!(value, original);
+    }
+
+    #[test]
+    fn get_path_mut_edits_in_place() {
+        let mut value = config();
+        *value.get_path_mut("config.debug").unwrap() = Value::Boolean(false);
+        assert_eq!(value.get_path_bool("config.debug"), Some(false));
+    }
+}
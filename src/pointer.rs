@@ -0,0 +1,461 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::Value;
+
+/// A parsed JSON Pointer (RFC 6901), ex. `/users/0/name`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct JsonPointer {
+    tokens: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PointerError {
+    /// A non-empty pointer must start with `/`
+    MissingLeadingSlash,
+
+    /// `~` must be followed by `0` or `1`
+    InvalidEscape,
+
+    /// The pointer passes through a value that is not an object or array
+    NotAContainer,
+
+    /// The token is not a usable index into the array
+    InvalidIndex,
+}
+
+impl fmt::Display for PointerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingLeadingSlash => f.write_str("a non-empty pointer must start with `/`"),
+            Self::InvalidEscape => f.write_str("`~` must be followed by `0` or `1`"),
+            Self::NotAContainer => f.write_str("the pointer passes through a scalar value"),
+            Self::InvalidIndex => f.write_str("the token is not a usable array index"),
+        }
+    }
+}
+
+impl std::error::Error for PointerError {}
+
+impl JsonPointer {
+    /// The empty pointer, which refers to the whole document
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(input: &str) -> Result<Self, PointerError> {
+        if input.is_empty() {
+            return Ok(Self::root());
+        }
+        let rest = input
+            .strip_prefix('/')
+            .ok_or(PointerError::MissingLeadingSlash)?;
+
+        let tokens = rest
+            .split('/')
+            .map(unescape_token)
+            .collect::<Result<_, _>>()?;
+        Ok(Self { tokens })
+    }
+
+    /// The unescaped reference tokens
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+
+    pub fn push(&mut self, token: impl Into<String>) {
+        self.tokens.push(token.into());
+    }
+
+    pub fn pop(&mut self) -> Option<String> {
+        self.tokens.pop()
+    }
+
+    /// A new pointer with `token` appended
+    pub fn join(&self, token: impl Into<String>) -> Self {
+        let mut child = self.clone();
+        child.push(token);
+        child
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.tokens.is_empty()
+    }
+}
+
+impl fmt::Display for JsonPointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in &self.tokens {
+            write!(f, "/{}", escape_token(token))?;
+        }
+        Ok(())
+    }
+}
+
+fn unescape_token(token: &str) -> Result<String, PointerError> {
+    let mut output = String::new();
+    let mut chars = token.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '~' {
+            match chars.next() {
+                Some('0') => output.push('~'),
+                Some('1') => output.push('/'),
+                _ => return Err(PointerError::InvalidEscape),
+            }
+        } else {
+            output.push(ch);
+        }
+    }
+    Ok(output)
+}
+
+fn escape_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Interprets a reference token as an array index, per RFC 6901 leading zeros are not allowed
+pub(crate) fn array_index(token: &str) -> Option<usize> {
+    let valid = token == "0" || (!token.starts_with('0') && !token.is_empty());
+    if valid && token.bytes().all(|b| b.is_ascii_digit()) {
+        token.parse().ok()
+    } else {
+        None
+    }
+}
+
+impl Value {
+    /// Looks up a value by JSON Pointer, ex. `/users/0/name`
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        let pointer = JsonPointer::parse(pointer).ok()?;
+        pointer
+            .tokens
+            .iter()
+            .try_fold(self, |value, token| match value {
+                Value::Object(map) => map.get(token),
+                Value::Array(array) => array.get(array_index(token)?),
+                _ => None,
+            })
+    }
+
+    /// Looks up a value by JSON Pointer, returning a mutable reference
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value> {
+        let pointer = JsonPointer::parse(pointer).ok()?;
+        pointer
+            .tokens
+            .iter()
+            .try_fold(self, |value, token| match value {
+                Value::Object(map) => map.get_mut(token),
+                Value::Array(array) => array.get_mut(array_index(token)?),
+                _ => None,
+            })
+    }
+}
+
+impl Value {
+    /// Sets the value at `pointer`, creating intermediate objects and arrays as needed
+    ///
+    /// Returns the value that was replaced, if any. An array index equal to the length
+    /// (or `-`) appends to the array.
+    pub fn set_pointer(
+        &mut self,
+        pointer: &str,
+        value: Value,
+    ) -> Result<Option<Value>, PointerError> {
+        let pointer = JsonPointer::parse(pointer)?;
+        let Some((last, parents)) = pointer.tokens.split_last() else {
+            return Ok(Some(std::mem::replace(self, value)));
+        };
+
+        self.check_path(parents, last)?;
+        match self.create_path(parents, last)? {
+            Value::Object(map) => Ok(map.insert(last.clone(), value)),
+            Value::Array(array) => {
+                let index = append_index(array, last)?;
+                if index == array.len() {
+                    array.push(value);
+                    Ok(None)
+                } else {
+                    Ok(Some(std::mem::replace(&mut array[index], value)))
+                }
+            }
+            _ => Err(PointerError::NotAContainer),
+        }
+    }
+
+    /// Inserts the value at `pointer`, creating intermediate objects and arrays as needed
+    ///
+    /// Unlike [`Value::set_pointer`], inserting into an array shifts the following elements
+    /// rather than replacing the element at that index.
+    pub fn insert_pointer(&mut self, pointer: &str, value: Value) -> Result<(), PointerError> {
+        let pointer = JsonPointer::parse(pointer)?;
+        let Some((last, parents)) = pointer.tokens.split_last() else {
+            *self = value;
+            return Ok(());
+        };
+
+        self.check_path(parents, last)?;
+        match self.create_path(parents, last)? {
+            Value::Object(map) => {
+                map.insert(last.clone(), value);
+            }
+            Value::Array(array) => {
+                let index = append_index(array, last)?;
+                array.insert(index, value);
+            }
+            _ => return Err(PointerError::NotAContainer),
+        }
+        Ok(())
+    }
+
+    /// Removes and returns the value at `pointer`, the whole document cannot be removed
+    pub fn remove_pointer(&mut self, pointer: &str) -> Option<Value> {
+        let pointer = JsonPointer::parse(pointer).ok()?;
+        let (last, parents) = pointer.tokens.split_last()?;
+
+        let parent = parents.iter().try_fold(self, |value, token| match value {
+            Value::Object(map) => map.get_mut(token),
+            Value::Array(array) => array.get_mut(array_index(token)?),
+            _ => None,
+        })?;
+        match parent {
+            Value::Object(map) => map.remove(last),
+            Value::Array(array) => {
+                let index = array_index(last).filter(|index| *index < array.len())?;
+                Some(array.remove(index))
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether [`Value::create_path`] and the final step at `last` can succeed, so that a
+    /// pointer that fails changes nothing
+    fn check_path(&self, tokens: &[String], last: &str) -> Result<(), PointerError> {
+        // stand-ins for the containers `create_path` would add
+        let empty_array = Value::Array(Vec::new());
+        let empty_object = Value::Object(HashMap::new());
+        let empty_for = |token: &str| {
+            if token == "-" || array_index(token).is_some() {
+                &empty_array
+            } else {
+                &empty_object
+            }
+        };
+
+        let mut current = self;
+        for (i, token) in tokens.iter().enumerate() {
+            let next = tokens.get(i + 1).map_or(last, String::as_str);
+            let found = match current {
+                Value::Object(map) => map.get(token),
+                Value::Array(array) => array.get(append_index(array, token)?),
+                _ => return Err(PointerError::NotAContainer),
+            };
+            current = found
+                .filter(|value| !value.is_null())
+                .unwrap_or_else(|| empty_for(next));
+        }
+        match current {
+            Value::Object(_) => Ok(()),
+            Value::Array(array) => append_index(array, last).map(drop),
+            _ => Err(PointerError::NotAContainer),
+        }
+    }
+
+    /// Walks `tokens`, replacing missing or `null` values with a container suited to the
+    /// token that follows, and returns the final container
+    fn create_path(&mut self, tokens: &[String], last: &str) -> Result<&mut Value, PointerError> {
+        let mut current = self;
+        for (i, token) in tokens.iter().enumerate() {
+            let next = tokens.get(i + 1).map_or(last, String::as_str);
+            current = match current {
+                Value::Object(map) => map
+                    .entry(token.clone())
+                    .or_insert_with(|| empty_container_for(next)),
+                Value::Array(array) => {
+                    let index = append_index(array, token)?;
+                    if index == array.len() {
+                        array.push(empty_container_for(next));
+                    }
+                    &mut array[index]
+                }
+                _ => return Err(PointerError::NotAContainer),
+            };
+            if *current == Value::Null {
+                *current = empty_container_for(next);
+            }
+        }
+        Ok(current)
+    }
+}
+
+/// An array index that may also be one past the end, spelled as `-` or the length
+fn append_index(array: &[Value], token: &str) -> Result<usize, PointerError> {
+    let index = if token == "-" {
+        array.len()
+    } else {
+        array_index(token).ok_or(PointerError::InvalidIndex)?
+    };
+    if index > array.len() {
+        return Err(PointerError::InvalidIndex);
+    }
+    Ok(index)
+}
+
+fn empty_container_for(token: &str) -> Value {
+    if token == "-" || array_index(token).is_some() {
+        Value::Array(Vec::new())
+    } else {
+        Value::Object(HashMap::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Value;
+
+    use super::{JsonPointer, PointerError};
+
+    fn sample() -> Value {
+        Value::object([
+            (
+                "users",
+                Value::Array(vec![Value::object([("name", Value::string("ken"))])]),
+            ),
+            ("a/b", Value::Number(1.0)),
+            ("m~n", Value::Number(2.0)),
+            ("", Value::Number(3.0)),
+        ])
+    }
+
+    #[test]
+    fn whole_document() {
+        let value = sample();
+        assert_eq!(value.pointer(""), Some(&value));
+    }
+
+    #[test]
+    fn nested_array_and_object() {
+        let value = sample();
+        assert_eq!(value.pointer("/users/0/name"), Some(&Value::string("ken")));
+    }
+
+    #[test]
+    fn escaped_tokens() {
+        let value = sample();
+        assert_eq!(value.pointer("/a~1b"), Some(&Value::Number(1.0)));
+        assert_eq!(value.pointer("/m~0n"), Some(&Value::Number(2.0)));
+        assert_eq!(value.pointer("/"), Some(&Value::Number(3.0)));
+    }
+
+    #[test]
+    fn missing_values() {
+        let value = sample();
+        assert_eq!(value.pointer("/users/1"), None);
+        assert_eq!(value.pointer("/users/01"), None);
+        assert_eq!(value.pointer("/users/-"), None);
+        assert_eq!(value.pointer("/users/0/name/x"), None);
+        assert_eq!(value.pointer("users"), None);
+    }
+
+    #[test]
+    fn pointer_mut_edits_in_place() {
+        let mut value = sample();
+        *value.pointer_mut("/users/0/name").unwrap() = Value::Null;
+        assert_eq!(value.pointer("/users/0/name"), Some(&Value::Null));
+    }
+
+    #[test]
+    fn set_replaces_existing() {
+        let mut value = sample();
+        let old = value.set_pointer("/users/0/name", Value::string("ryu"));
+        assert_eq!(old, Ok(Some(Value::string("ken"))));
+        assert_eq!(value.pointer("/users/0/name"), Some(&Value::string("ryu")));
+    }
+
+    #[test]
+    fn set_creates_intermediates() {
+        let mut value = Value::object([]);
+        value.set_pointer("/a/b/0/c", Value::Null).unwrap();
+        let expected = Value::object([(
+            "a",
+            Value::object([("b", Value::Array(vec![Value::object([("c", Value::Null)])]))]),
+        )]);
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn set_appends_to_array() {
+        let mut value = sample();
+        value.set_pointer("/users/-", Value::Null).unwrap();
+        value.set_pointer("/users/2", Value::Null).unwrap();
+        assert_eq!(value.pointer("/users/2"), Some(&Value::Null));
+        assert_eq!(
+            value.set_pointer("/users/9", Value::Null),
+            Err(PointerError::InvalidIndex)
+        );
+    }
+
+    #[test]
+    fn set_through_scalar_fails() {
+        let mut value = sample();
+        assert_eq!(
+            value.set_pointer("/a~1b/c", Value::Null),
+            Err(PointerError::NotAContainer)
+        );
+    }
+
+    #[test]
+    fn failed_pointer_changes_nothing() {
+        let mut value = Value::object([("users", Value::Array(Vec::new()))]);
+        let before = value.clone();
+        assert_eq!(
+            value.set_pointer("/x/y/9", Value::Null),
+            Err(PointerError::InvalidIndex)
+        );
+        assert_eq!(
+            value.insert_pointer("/x/y/z/0/1", Value::Null),
+            Err(PointerError::InvalidIndex)
+        );
+        assert_eq!(
+            value.set_pointer("/users/1/name", Value::Null),
+            Err(PointerError::InvalidIndex)
+        );
+        assert_eq!(value, before);
+
+        value.set_pointer("/x/y/0", Value::Null).unwrap();
+        assert_eq!(
+            value.pointer("/x/y"),
+            Some(&Value::Array(vec![Value::Null]))
+        );
+    }
+
+    #[test]
+    fn insert_shifts_array() {
+        let mut value = Value::Array(vec![Value::Number(1.0), Value::Number(3.0)]);
+        value.insert_pointer("/1", Value::Number(2.0)).unwrap();
+        let expected = vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)];
+        assert_eq!(value, Value::Array(expected));
+    }
+
+    #[test]
+    fn remove_from_object_and_array() {
+        let mut value = sample();
+        assert_eq!(value.remove_pointer("/a~1b"), Some(Value::Number(1.0)));
+        assert_eq!(value.pointer("/a~1b"), None);
+        assert!(value.remove_pointer("/users/0").is_some());
+        assert_eq!(value.pointer("/users"), Some(&Value::Array(vec![])));
+        assert_eq!(value.remove_pointer("/users/0"), None);
+        assert_eq!(value.remove_pointer(""), None);
+    }
+
+    #[test]
+    fn invalid_escape() {
+        assert_eq!(JsonPointer::parse("/a~2"), Err(PointerError::InvalidEscape));
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let pointer = JsonPointer::parse("/a~1b/m~0n/0").unwrap();
+        assert_eq!(pointer.tokens(), ["a/b", "m~n", "0"]);
+        assert_eq!(pointer.to_string(), "/a~1b/m~0n/0");
+    }
+}
@@ -0,0 +1,196 @@
+use crate::Value;
+use crate::serialize::{format_number, write_string};
+
+/// Layout settings for [`Value::to_json_string_pretty_with`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrettyOptions {
+    /// Spaces per level of nesting
+    pub indent: usize,
+
+    /// Arrays and objects that fit within this many columns stay on one line
+    pub max_width: usize,
+}
+
+impl Default for PrettyOptions {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            max_width: 80,
+        }
+    }
+}
+
+impl Value {
+    /// Serializes to indented JSON text, keeping arrays and objects on one line when
+    /// they fit within `options.max_width`
+    ///
+    /// Object keys are written in sorted order.
+    pub fn to_json_string_pretty_with(&self, options: &PrettyOptions) -> String {
+        let mut output = String::new();
+        layout(self, options, 0, 0, 0, &mut output);
+        output
+    }
+}
+
+/// Writes `value` starting at `column`, where `suffix` characters (ex. a comma) follow it
+/// on the same line
+fn layout(
+    value: &Value,
+    options: &PrettyOptions,
+    depth: usize,
+    column: usize,
+    suffix: usize,
+    output: &mut String,
+) {
+    let budget = options.max_width.saturating_sub(column + suffix);
+    let start = output.len();
+    if write_flat(value, budget, output) {
+        return;
+    }
+    output.truncate(start);
+
+    let inner = " ".repeat(options.indent * (depth + 1));
+    match value {
+        Value::Array(array) => {
+            output.push('[');
+            for (i, item) in array.iter().enumerate() {
+                output.push('\n');
+                output.push_str(&inner);
+                let suffix = usize::from(i + 1 < array.len());
+                layout(item, options, depth + 1, inner.len(), suffix, output);
+                if suffix == 1 {
+                    output.push(',');
+                }
+            }
+            output.push('\n');
+            output.push_str(&" ".repeat(options.indent * depth));
+            output.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            output.push('{');
+            for (i, (key, item)) in entries.iter().enumerate() {
+                output.push('\n');
+                output.push_str(&inner);
+                let line_start = output.len() - inner.len();
+                write_string(key, output);
+                output.push_str(": ");
+                let column = output.len() - line_start;
+                let suffix = usize::from(i + 1 < entries.len());
+                layout(item, options, depth + 1, column, suffix, output);
+                if suffix == 1 {
+                    output.push(',');
+                }
+            }
+            output.push('\n');
+            output.push_str(&" ".repeat(options.indent * depth));
+            output.push('}');
+        }
+        // scalars never break, even when they are too long
+        _ => unreachable!("scalars always fit"),
+    }
+}
+
+/// Writes `value` on one line, returning `false` as soon as it exceeds `budget` bytes
+///
+/// Scalars and empty containers are always written, whatever their length.
+fn write_flat(value: &Value, budget: usize, output: &mut String) -> bool {
+    let start = output.len();
+    let fits = |output: &String| output.len() - start <= budget;
+    match value {
+        Value::Null => output.push_str("null"),
+        Value::Boolean(b) => output.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => output.push_str(&format_number(*n)),
+        Value::String(s) => write_string(s, output),
+        Value::Array(array) if array.is_empty() => output.push_str("[]"),
+        Value::Object(map) if map.is_empty() => output.push_str("{}"),
+        Value::Array(array) => {
+            output.push('[');
+            for (i, item) in array.iter().enumerate() {
+                if i > 0 {
+                    output.push_str(", ");
+                }
+                let remaining = budget.saturating_sub(output.len() - start);
+                if !write_flat(item, remaining, output) || !fits(output) {
+                    return false;
+                }
+            }
+            output.push(']');
+            return fits(output);
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            output.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    output.push_str(", ");
+                }
+                write_string(key, output);
+                output.push_str(": ");
+                let remaining = budget.saturating_sub(output.len() - start);
+                if !write_flat(item, remaining, output) || !fits(output) {
+                    return false;
+                }
+            }
+            output.push('}');
+            return fits(output);
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::PrettyOptions;
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    fn pretty(input: &str, max_width: usize) -> String {
+        let options = PrettyOptions {
+            max_width,
+            ..PrettyOptions::default()
+        };
+        value(input).to_json_string_pretty_with(&options)
+    }
+
+    #[test]
+    fn short_values_stay_on_one_line() {
+        assert_eq!(pretty(r#"{"a": [1, 2, 3]}"#, 80), r#"{"a": [1, 2, 3]}"#);
+    }
+
+    #[test]
+    fn breaks_only_what_does_not_fit() {
+        let input = r#"{"name": "a fairly long name", "point": [1, 2], "tags": ["x", "y", "z"]}"#;
+        let expected = r#"{
+  "name": "a fairly long name",
+  "point": [1, 2],
+  "tags": ["x", "y", "z"]
+}"#;
+        assert_eq!(pretty(input, 40), expected);
+    }
+
+    #[test]
+    fn width_includes_key_and_comma() {
+        // `  "ab": [1, 2],` is 15 columns wide
+        let input = r#"{"ab": [1, 2], "c": 0}"#;
+        assert!(pretty(input, 15).contains("\"ab\": [1, 2],"));
+        let expected = "{\n  \"ab\": [\n    1,\n    2\n  ],\n  \"c\": 0\n}";
+        assert_eq!(pretty(input, 14), expected);
+    }
+
+    #[test]
+    fn long_scalars_are_not_broken() {
+        let expected = "[\n  \"abcdefghij\"\n]";
+        assert_eq!(pretty(r#"["abcdefghij"]"#, 5), expected);
+        assert_eq!(
+            pretty(r#"{"a": [], "b": {}}"#, 5),
+            "{\n  \"a\": [],\n  \"b\": {}\n}"
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use crate::Value;
+use crate::serialize::{format_number, write_string};
+
+impl Value {
+    /// Renders compact JSON of about `max_len` bytes for logging, shortening long strings
+    /// and ending large arrays and objects early, ex. `[1,2,…1532 more]`
+    ///
+    /// Elided strings end in `…`. The output is only meant to be read, it is not valid
+    /// JSON once anything has been elided, and may run a little past `max_len` to close
+    /// its brackets.
+    pub fn preview(&self, max_len: usize) -> String {
+        let mut output = String::new();
+        write_preview(self, max_len, &mut output);
+        output
+    }
+}
+
+fn write_preview(value: &Value, budget: usize, output: &mut String) {
+    let start = output.len();
+    let remaining = |output: &String| budget.saturating_sub(output.len() - start);
+    match value {
+        Value::Null => output.push_str("null"),
+        Value::Boolean(b) => output.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => output.push_str(&format_number(*n)),
+        Value::String(s) => write_truncated(s, budget, output),
+        Value::Array(array) => {
+            output.push('[');
+            for (i, item) in array.iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                if remaining(output) == 0 {
+                    output.push_str(&format!("…{} more", array.len() - i));
+                    break;
+                }
+                write_preview(item, remaining(output), output);
+            }
+            output.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            output.push('{');
+            for (i, (key, item)) in entries.iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                if remaining(output) == 0 {
+                    output.push_str(&format!("…{} more", entries.len() - i));
+                    break;
+                }
+                write_string(key, output);
+                output.push(':');
+                write_preview(item, remaining(output), output);
+            }
+            output.push('}');
+        }
+    }
+}
+
+/// Writes a quoted string, cut short with `…` if it would take more than `budget` bytes
+fn write_truncated(s: &str, budget: usize, output: &mut String) {
+    let start = output.len();
+    write_string(s, output);
+    if output.len() - start <= budget {
+        return;
+    }
+    output.truncate(start);
+
+    // keep a few characters even when the budget is spent, so the value is recognizable
+    let keep = budget.saturating_sub(5).max(8);
+    let mut end = keep.min(s.len());
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    let mut shortened = String::from(&s[..end]);
+    shortened.push('…');
+    write_string(&shortened, output);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    #[test]
+    fn small_values_are_unchanged() {
+        let document = value(r#"{"b": [1, 2], "a": "x"}"#);
+        assert_eq!(document.preview(80), document.to_json_string());
+    }
+
+    #[test]
+    fn large_arrays_are_cut_short() {
+        let document = Value::Array((0..2000).map(|n| Value::Number(n as f64)).collect());
+        let preview = document.preview(20);
+        assert_eq!(preview, "[0,1,2,3,4,5,6,7,8,9,…1990 more]");
+    }
+
+    #[test]
+    fn long_strings_are_elided() {
+        let document = value(&format!(
+            r#"{{"a": "{}", "b": 1, "c": 2}}"#,
+            "x".repeat(100)
+        ));
+        let preview = document.preview(30);
+        assert_eq!(preview, format!(r#"{{"a":"{}…",…2 more}}"#, "x".repeat(20)));
+    }
+
+    #[test]
+    fn cuts_on_char_boundaries() {
+        let document = Value::String("é".repeat(20));
+        assert_eq!(document.preview(0), format!("\"{}…\"", "é".repeat(4)));
+    }
+}
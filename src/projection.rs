@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::pointer::array_index;
+use crate::reader::read_subtree;
+use crate::{Event, JsonPointer, JsonReader, PointerError, Position, ReaderError, Value};
+
+#[derive(Debug)]
+pub enum ProjectionError {
+    /// One of the requested paths is not a valid JSON Pointer
+    Pointer(PointerError),
+    Read(ReaderError),
+}
+
+impl fmt::Display for ProjectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pointer(err) => write!(f, "invalid path: {err}"),
+            Self::Read(err) => write!(f, "reading the document failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ProjectionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Pointer(err) => Some(err),
+            Self::Read(err) => Some(err),
+        }
+    }
+}
+
+impl From<PointerError> for ProjectionError {
+    fn from(err: PointerError) -> Self {
+        Self::Pointer(err)
+    }
+}
+
+impl From<ReaderError> for ProjectionError {
+    fn from(err: ReaderError) -> Self {
+        Self::Read(err)
+    }
+}
+
+/// Parses only the parts of `input` named by `paths`, ex. `["/id", "/items/*/price"]`
+///
+/// Paths are JSON Pointers where a `*` token matches any key or index. The result keeps
+/// the nesting of the input, holding the matched values and the objects and arrays that
+/// lead to them; everything else is read past without being built. Array elements that
+/// are not kept become `null` when a later element is kept, so indexes don't shift.
+/// Paths that match nothing are left out, and a document with no matches is `null`.
+pub fn parse_projection(input: &str, paths: &[&str]) -> Result<Value, ProjectionError> {
+    let patterns = paths
+        .iter()
+        .map(|path| JsonPointer::parse(path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut reader = JsonReader::new(input.as_bytes());
+    let first = reader.next_event()?.ok_or(ReaderError::UnexpectedEof)?;
+    let value = project(&mut reader, first, &mut Vec::new(), &patterns)?;
+    // the reader reports anything after the root value
+    reader.next_event()?;
+    Ok(value.unwrap_or(Value::Null))
+}
+
+/// A step from a container to one of its children
+enum Step {
+    Key(String),
+    Index(usize),
+}
+
+enum Match {
+    /// A pattern names this value, so all of it is kept
+    Whole,
+    /// A pattern names something inside this value
+    Inside,
+    None,
+}
+
+fn matches(path: &[Step], patterns: &[JsonPointer]) -> Match {
+    let mut result = Match::None;
+    for pattern in patterns {
+        let tokens = pattern.tokens();
+        if tokens.len() < path.len() {
+            continue;
+        }
+        let prefix = path.iter().zip(tokens).all(|(step, token)| match step {
+            _ if token == "*" => true,
+            Step::Key(key) => key == token,
+            Step::Index(index) => array_index(token) == Some(*index),
+        });
+        if prefix && tokens.len() == path.len() {
+            return Match::Whole;
+        } else if prefix {
+            result = Match::Inside;
+        }
+    }
+    result
+}
+
+/// Reads the value that starts with `first`, keeping the parts matched by `patterns`
+fn project(
+    reader: &mut JsonReader<&[u8]>,
+    first: (Event, Position),
+    path: &mut Vec<Step>,
+    patterns: &[JsonPointer],
+) -> Result<Option<Value>, ReaderError> {
+    let container = matches!(first.0, Event::StartObject | Event::StartArray);
+    match matches(path, patterns) {
+        Match::Whole => return read_subtree(reader, first).map(Some),
+        Match::None | Match::Inside if !container => return Ok(None),
+        Match::None => {
+            reader.skip_value()?;
+            return Ok(None);
+        }
+        Match::Inside => {}
+    }
+
+    if let Event::StartObject = first.0 {
+        let mut map = HashMap::new();
+        loop {
+            let (event, _) = reader.next_event()?.ok_or(ReaderError::UnexpectedEof)?;
+            let Event::Key(key) = event else {
+                break;
+            };
+            let event = reader.next_event()?.ok_or(ReaderError::UnexpectedEof)?;
+            path.push(Step::Key(key));
+            let value = project(reader, event, path, patterns)?;
+            if let (Some(Step::Key(key)), Some(value)) = (path.pop(), value) {
+                map.insert(key, value);
+            }
+        }
+        Ok(Some(Value::Object(map)))
+    } else {
+        let mut array = Vec::new();
+        loop {
+            let event = reader.next_event()?.ok_or(ReaderError::UnexpectedEof)?;
+            if let Event::EndArray = event.0 {
+                break;
+            }
+            path.push(Step::Index(array.len()));
+            array.push(project(reader, event, path, patterns)?);
+            path.pop();
+        }
+        while let Some(None) = array.last() {
+            array.pop();
+        }
+        let array = array
+            .into_iter()
+            .map(|value| value.unwrap_or(Value::Null))
+            .collect();
+        Ok(Some(Value::Array(array)))
+    }
+}
+
+/// The value at `pointer` in `input`, reading no further into the document than needed
+///
+/// Everything before the value is skipped without being built and nothing after it is
+/// read, so later syntax errors go unnoticed. Returns `None` when the pointer is invalid,
+/// the value is missing or the document is malformed before reaching it.
+pub fn extract_field(input: &str, pointer: &str) -> Option<Value> {
+    let pointer = JsonPointer::parse(pointer).ok()?;
+    let mut reader = JsonReader::new(input.as_bytes());
+    let mut first = reader.next_event().ok()??;
+    for token in pointer.tokens() {
+        let index = match first.0 {
+            Event::StartObject => None,
+            Event::StartArray => Some(array_index(token)?),
+            _ => return None,
+        };
+        let mut i = 0;
+        first = loop {
+            let (event, position, found) = match reader.next_event().ok()?? {
+                (Event::EndObject | Event::EndArray, _) => return None,
+                (Event::Key(key), _) => {
+                    let (event, position) = reader.next_event().ok()??;
+                    (event, position, key == *token)
+                }
+                (event, position) => {
+                    let found = index == Some(i);
+                    i += 1;
+                    (event, position, found)
+                }
+            };
+            if found {
+                break (event, position);
+            }
+            if let Event::StartObject | Event::StartArray = event {
+                reader.skip_value().ok()?;
+            }
+        };
+    }
+    read_subtree(&mut reader, first).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, PointerError, Value};
+
+    use super::{ProjectionError, extract_field, parse_projection};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    const INPUT: &str = r#"{
+        "id": 7,
+        "user": {"name": "ada", "email": "ada@example.com", "tags": ["a", "b"]},
+        "items": [{"price": 2, "sku": "x"}, {"sku": "y"}, 5, {"price": {"amount": 3}}],
+        "blob": [[1, 2], {"deep": [null]}]
+    }"#;
+
+    #[test]
+    fn keeps_requested_paths() {
+        let projected = parse_projection(INPUT, &["/id", "/user/name", "/items/*/price"]);
+        let expected = value(
+            r#"{"id": 7, "user": {"name": "ada"},
+                "items": [{"price": 2}, {}, null, {"price": {"amount": 3}}]}"#,
+        );
+        assert_eq!(projected.unwrap(), expected);
+    }
+
+    #[test]
+    fn array_indexes() {
+        let projected = parse_projection(INPUT, &["/items/1/sku", "/user/tags/0"]).unwrap();
+        let expected = value(r#"{"user": {"tags": ["a"]}, "items": [null, {"sku": "y"}]}"#);
+        assert_eq!(projected, expected);
+    }
+
+    #[test]
+    fn missing_paths() {
+        assert_eq!(parse_projection(INPUT, &["/nope/x"]).unwrap(), value("{}"));
+        assert_eq!(parse_projection("[1]", &["/a"]).unwrap(), value("[]"));
+        assert_eq!(parse_projection("3", &["/a"]).unwrap(), Value::Null);
+        assert_eq!(parse_projection("3", &[""]).unwrap(), Value::Number(3.0));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            parse_projection(INPUT, &["id"]),
+            Err(ProjectionError::Pointer(PointerError::MissingLeadingSlash))
+        ));
+        // skipped values are still checked for nesting
+        assert!(matches!(
+            parse_projection(r#"{"a": 1, "b": [1, 2}"#, &["/a"]),
+            Err(ProjectionError::Read(_))
+        ));
+        assert!(matches!(
+            parse_projection(r#"{"a": 1} x"#, &["/a"]),
+            Err(ProjectionError::Read(_))
+        ));
+    }
+
+    #[test]
+    fn extracts_one_field() {
+        assert_eq!(extract_field(INPUT, "/id"), Some(Value::Number(7.0)));
+        assert_eq!(
+            extract_field(INPUT, "/user/tags/1"),
+            Some(Value::string("b"))
+        );
+        assert_eq!(
+            extract_field(INPUT, "/items/3/price"),
+            Some(value(r#"{"amount": 3}"#))
+        );
+        assert_eq!(extract_field(INPUT, "/items/9"), None);
+        assert_eq!(extract_field(INPUT, "/id/x"), None);
+        assert_eq!(extract_field(INPUT, "id"), None);
+    }
+
+    #[test]
+    fn stops_after_the_field() {
+        let input = r#"{"type": "user", "payload": [1, 2"#;
+        assert_eq!(extract_field(input, "/type"), Some(Value::string("user")));
+        assert_eq!(extract_field(input, "/payload/0"), Some(Value::Number(1.0)));
+        assert_eq!(extract_field(input, "/payload/2"), None);
+    }
+}
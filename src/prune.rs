@@ -0,0 +1,102 @@
+use crate::Value;
+
+/// Which values [`Value::prune`] removes, all are on by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PruneOptions {
+    pub nulls: bool,
+    pub empty_strings: bool,
+
+    /// Arrays that are empty, including those emptied by pruning their elements
+    pub empty_arrays: bool,
+
+    /// Objects that are empty, including those emptied by pruning their members
+    pub empty_objects: bool,
+}
+
+impl Default for PruneOptions {
+    fn default() -> Self {
+        Self {
+            nulls: true,
+            empty_strings: true,
+            empty_arrays: true,
+            empty_objects: true,
+        }
+    }
+}
+
+impl Value {
+    /// Removes null and empty values from arrays and objects at any depth, ex. before
+    /// sending a PATCH body
+    ///
+    /// Children are pruned before their parent is checked, so `{"a": {"b": null}}`
+    /// becomes `{}`. The root itself is never removed.
+    pub fn prune(&mut self, options: PruneOptions) {
+        match self {
+            Value::Array(array) => array.retain_mut(|item| !prune_child(item, options)),
+            Value::Object(map) => map.retain(|_, item| !prune_child(item, options)),
+            _ => {}
+        }
+    }
+}
+
+/// Prunes `value` and reports whether it should then be removed from its parent
+fn prune_child(value: &mut Value, options: PruneOptions) -> bool {
+    value.prune(options);
+    match value {
+        Value::Null => options.nulls,
+        Value::String(s) => options.empty_strings && s.is_empty(),
+        Value::Array(array) => options.empty_arrays && array.is_empty(),
+        Value::Object(map) => options.empty_objects && map.is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::PruneOptions;
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    const INPUT: &str = r#"{
+        "name": "ada", "nick": "", "age": null, "zero": 0, "off": false,
+        "tags": [null, "", "x", []], "meta": {"a": null, "b": {}}, "list": [[null]]
+    }"#;
+
+    #[test]
+    fn prunes_everything_by_default() {
+        let mut document = value(INPUT);
+        document.prune(PruneOptions::default());
+        let expected = r#"{"name": "ada", "zero": 0, "off": false, "tags": ["x"]}"#;
+        assert_eq!(document, value(expected));
+
+        let mut root = value("[null]");
+        root.prune(PruneOptions::default());
+        assert_eq!(root, value("[]"));
+    }
+
+    #[test]
+    fn toggles() {
+        let mut document = value(INPUT);
+        document.prune(PruneOptions {
+            empty_strings: false,
+            empty_arrays: false,
+            ..PruneOptions::default()
+        });
+        let expected = r#"{"name": "ada", "nick": "", "zero": 0, "off": false,
+            "tags": ["", "x", []], "list": [[]]}"#;
+        assert_eq!(document, value(expected));
+
+        let mut document = value(INPUT);
+        document.prune(PruneOptions {
+            nulls: false,
+            ..PruneOptions::default()
+        });
+        let expected = r#"{"name": "ada", "age": null, "zero": 0, "off": false,
+            "tags": [null, "x"], "meta": {"a": null}, "list": [[null]]}"#;
+        assert_eq!(document, value(expected));
+    }
+}
@@ -0,0 +1,1123 @@
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::{fmt, io};
+
+use crate::{
+    DefaultState, DuplicateKeys, Limits, Map, NumberMode, NumberOverflow, ParserOptions, Value,
+};
+
+/// A location in the input, `line` and `column` start at 1 and columns count bytes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A single step through a JSON document
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+
+    /// An object key, the next event is its value
+    Key(String),
+
+    String(String),
+    Number(f64),
+    Boolean(bool),
+    Null,
+}
+
+#[derive(Debug)]
+pub enum ReaderError {
+    /// Reading from the underlying reader failed
+    Io(io::Error),
+
+    /// The input ended inside of a value
+    UnexpectedEof,
+
+    /// A byte that is not allowed at this position
+    UnexpectedByte { position: Position, found: u8 },
+
+    /// A number does not follow the JSON grammar, ex. `01` or `1.`
+    InvalidNumber { position: Position },
+
+    /// A backslash escape is not valid, including unpaired surrogates
+    InvalidEscape { position: Position },
+
+    /// A string is not valid UTF-8
+    InvalidUtf8 { position: Position },
+
+    /// There is more than whitespace after the root value
+    TrailingContent { position: Position },
+
+    /// An integer cannot be represented exactly, [`ParserOptions::numbers`] is
+    /// [`NumberMode::SafeIntegers`] and [`ParserOptions::overflow`] is
+    /// [`NumberOverflow::Error`]
+    UnsafeInteger { position: Position },
+
+    /// A number is past `f64::MAX` and [`ParserOptions::overflow`] is
+    /// [`NumberOverflow::Error`]
+    NumberOutOfRange { position: Position },
+
+    /// The document is larger or more deeply nested than the [`Limits`] allow
+    LimitExceeded { position: Position, limit: Limit },
+
+    /// An object repeats a key and [`ParserOptions::duplicate_keys`] is
+    /// [`DuplicateKeys::Error`], `position` is where the second one starts
+    DuplicateKey { position: Position, key: String },
+}
+
+/// Which of the [`Limits`] was exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Depth,
+    Bytes,
+    NumberLength,
+    StringLength,
+}
+
+impl From<io::Error> for ReaderError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+impl fmt::Display for ReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "reading input failed: {err}"),
+            Self::UnexpectedEof => f.write_str("unexpected end of input"),
+            Self::UnexpectedByte { position, found } => write!(
+                f,
+                "unexpected `{}` at {position}",
+                char::from(*found).escape_default()
+            ),
+            Self::InvalidNumber { position } => write!(f, "invalid number at {position}"),
+            Self::InvalidEscape { position } => write!(f, "invalid escape at {position}"),
+            Self::InvalidUtf8 { position } => write!(f, "invalid UTF-8 at {position}"),
+            Self::TrailingContent { position } => {
+                write!(f, "unexpected content after the document at {position}")
+            }
+            Self::UnsafeInteger { position } => {
+                write!(f, "integer too large to be exact at {position}")
+            }
+            Self::NumberOutOfRange { position } => write!(f, "number out of range at {position}"),
+            Self::LimitExceeded { position, limit } => {
+                let limit = match limit {
+                    Limit::Depth => "depth",
+                    Limit::Bytes => "size",
+                    Limit::NumberLength => "number length",
+                    Limit::StringLength => "string length",
+                };
+                write!(f, "exceeded the {limit} limit at {position}")
+            }
+            Self::DuplicateKey { position, key } => {
+                write!(f, "duplicate key {key:?} at {position}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReaderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Container {
+    Array,
+    Object,
+}
+
+/// What the reader accepts next
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    Value,
+    Key,
+    FirstElement,
+    NextElement,
+    FirstKey,
+    NextKey,
+    Colon,
+    Done,
+}
+
+/// Reads a JSON document as a stream of [`Event`]s
+///
+/// Only the current string or number and the nesting of containers are kept in memory,
+/// so documents of any size can be processed.
+pub struct JsonReader<R> {
+    inner: R,
+    buffer: Box<[u8]>,
+    start: usize,
+    end: usize,
+    position: Position,
+    stack: Vec<Container>,
+    expect: Expect,
+    options: ParserOptions,
+    limits: Limits,
+}
+
+const BUFFER_SIZE: usize = 8 * 1024;
+
+/// The largest integer an `f64` holds exactly, along with every integer below it
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+impl<R: io::Read> JsonReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self::with_options(inner, ParserOptions::default())
+    }
+
+    /// A reader accepting the extensions enabled in `options`
+    ///
+    /// [`ParserOptions::duplicate_keys`] does not apply to events, only to documents read
+    /// into a [`Value`].
+    pub fn with_options(inner: R, options: ParserOptions) -> Self {
+        Self {
+            inner,
+            buffer: vec![0; BUFFER_SIZE].into_boxed_slice(),
+            start: 0,
+            end: 0,
+            position: Position {
+                offset: 0,
+                line: 1,
+                column: 1,
+            },
+            stack: Vec::new(),
+            expect: Expect::Value,
+            options,
+            limits: Limits::default(),
+        }
+    }
+
+    /// Stops reading with [`ReaderError::LimitExceeded`] once the input passes `limits`
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Nesting depth of the open arrays and objects
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Where the reader is in the input, just past the end of the last event
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// The next event and where it starts, `None` after the root value has ended
+    pub fn next_event(&mut self) -> Result<Option<(Event, Position)>, ReaderError> {
+        self.skip_whitespace()?;
+        // separators belong to the event that follows them
+        let position = self.position;
+        match (self.expect, self.peek()?) {
+            // the first element and key states also accept the end of the container
+            (Expect::NextElement, Some(b',')) => {
+                self.bump();
+                self.skip_whitespace()?;
+                let trailing = self.options.trailing_commas && self.peek()? == Some(b']');
+                self.expect = if trailing {
+                    Expect::FirstElement
+                } else {
+                    Expect::Value
+                };
+            }
+            (Expect::Colon, Some(b':')) => {
+                self.bump();
+                self.skip_whitespace()?;
+                self.expect = Expect::Value;
+            }
+            (Expect::NextKey, Some(b',')) => {
+                self.bump();
+                self.skip_whitespace()?;
+                let trailing = self.options.trailing_commas && self.peek()? == Some(b'}');
+                self.expect = if trailing {
+                    Expect::FirstKey
+                } else {
+                    Expect::Key
+                };
+            }
+            (Expect::Colon, Some(found)) => {
+                return Err(ReaderError::UnexpectedByte { position, found });
+            }
+            _ => {}
+        }
+
+        let position = self.position;
+        let Some(byte) = self.peek()? else {
+            return match self.expect {
+                Expect::Done => Ok(None),
+                _ => Err(ReaderError::UnexpectedEof),
+            };
+        };
+        let event = match (self.expect, byte) {
+            (Expect::Done, _) => return Err(ReaderError::TrailingContent { position }),
+            (Expect::FirstElement | Expect::NextElement, b']')
+            | (Expect::FirstKey | Expect::NextKey, b'}') => self.end_container(),
+            (Expect::Value | Expect::FirstElement, _) => self.value()?,
+            (Expect::FirstKey | Expect::Key, b'"') => {
+                let key = self.string()?;
+                self.expect = Expect::Colon;
+                Event::Key(key)
+            }
+            (_, found) => return Err(ReaderError::UnexpectedByte { position, found }),
+        };
+        Ok(Some((event, position)))
+    }
+
+    /// Fast-forwards past the end of the innermost open array or object, ex. right after
+    /// its start event, without building or allocating anything for the skipped values
+    ///
+    /// The skipped bytes are only checked for string boundaries, nesting and the limits,
+    /// so malformed scalars inside them are not reported. Does nothing at the root level.
+    pub fn skip_value(&mut self) -> Result<(), ReaderError> {
+        let Some(container) = self.stack.last().copied() else {
+            return Ok(());
+        };
+        let mut depth = 1;
+        loop {
+            let position = self.position;
+            let byte = self.peek()?.ok_or(ReaderError::UnexpectedEof)?;
+            match byte {
+                b'"' => {
+                    self.bump();
+                    self.skip_string()?;
+                    continue;
+                }
+                b'\'' | b'`' if self.options.multiline_strings => {
+                    let delimiter: &[u8] = if byte == b'`' { b"`" } else { b"'''" };
+                    self.skip_raw_string(delimiter)?;
+                    continue;
+                }
+                b'/' if self.options.comments => {
+                    self.comment()?;
+                    continue;
+                }
+                b'{' | b'[' => {
+                    if self
+                        .limits
+                        .max_depth
+                        .is_some_and(|max| self.stack.len() + depth > max)
+                    {
+                        let limit = Limit::Depth;
+                        return Err(ReaderError::LimitExceeded { position, limit });
+                    }
+                    depth += 1;
+                }
+                b'}' | b']' => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                let expected = match container {
+                    Container::Array => b']',
+                    Container::Object => b'}',
+                };
+                if byte != expected {
+                    return Err(ReaderError::UnexpectedByte {
+                        position,
+                        found: byte,
+                    });
+                }
+                self.end_container();
+                return Ok(());
+            }
+            self.bump();
+        }
+    }
+
+    /// Reads past a string after its opening quote
+    fn skip_string(&mut self) -> Result<(), ReaderError> {
+        loop {
+            let position = self.position;
+            let byte = self.peek()?.ok_or(ReaderError::UnexpectedEof)?;
+            self.bump();
+            match byte {
+                b'"' => return Ok(()),
+                b'\\' => {
+                    self.peek()?.ok_or(ReaderError::UnexpectedEof)?;
+                    self.bump();
+                }
+                0x00..=0x1f if !self.options.control_characters => {
+                    return Err(ReaderError::UnexpectedByte {
+                        position,
+                        found: byte,
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Reads past a `'''` or backtick string from its opening delimiter
+    fn skip_raw_string(&mut self, delimiter: &[u8]) -> Result<(), ReaderError> {
+        self.literal(delimiter)?;
+        let mut run = 0;
+        while run < delimiter.len() {
+            let byte = self.peek()?.ok_or(ReaderError::UnexpectedEof)?;
+            self.bump();
+            run = if byte == delimiter[0] { run + 1 } else { 0 };
+        }
+        Ok(())
+    }
+
+    fn end_container(&mut self) -> Event {
+        self.bump();
+        let event = match self.stack.pop() {
+            Some(Container::Array) => Event::EndArray,
+            _ => Event::EndObject,
+        };
+        self.after_value();
+        event
+    }
+
+    fn after_value(&mut self) {
+        self.expect = match self.stack.last() {
+            None => Expect::Done,
+            Some(Container::Array) => Expect::NextElement,
+            Some(Container::Object) => Expect::NextKey,
+        };
+    }
+
+    fn value(&mut self) -> Result<Event, ReaderError> {
+        let position = self.position;
+        let byte = self.peek()?.ok_or(ReaderError::UnexpectedEof)?;
+        if matches!(byte, b'{' | b'[')
+            && let Some(max_depth) = self.limits.max_depth
+            && self.stack.len() >= max_depth
+        {
+            let limit = Limit::Depth;
+            return Err(ReaderError::LimitExceeded { position, limit });
+        }
+        let event = match byte {
+            b'{' => {
+                self.bump();
+                self.stack.push(Container::Object);
+                self.expect = Expect::FirstKey;
+                return Ok(Event::StartObject);
+            }
+            b'[' => {
+                self.bump();
+                self.stack.push(Container::Array);
+                self.expect = Expect::FirstElement;
+                return Ok(Event::StartArray);
+            }
+            b'"' => Event::String(self.string()?),
+            b'\'' if self.options.multiline_strings => {
+                let indent = self.position.column - 1;
+                let text = self.raw_string(b"'''")?;
+                Event::String(dedent(&text, indent))
+            }
+            b'`' if self.options.multiline_strings => Event::String(self.raw_string(b"`")?),
+            b't' => {
+                self.literal(b"true")?;
+                Event::Boolean(true)
+            }
+            b'f' => {
+                self.literal(b"false")?;
+                Event::Boolean(false)
+            }
+            b'n' => {
+                self.literal(b"null")?;
+                Event::Null
+            }
+            b'N' if self.options.nan_and_infinity => {
+                self.literal(b"NaN")?;
+                Event::Number(f64::NAN)
+            }
+            b'I' if self.options.nan_and_infinity => {
+                self.literal(b"Infinity")?;
+                Event::Number(f64::INFINITY)
+            }
+            b'-' | b'0'..=b'9' => self.number()?,
+            found => return Err(ReaderError::UnexpectedByte { position, found }),
+        };
+        self.after_value();
+        Ok(event)
+    }
+
+    fn literal(&mut self, expected: &[u8]) -> Result<(), ReaderError> {
+        for byte in expected {
+            let position = self.position;
+            match self.peek()? {
+                Some(found) if found == *byte => self.bump(),
+                Some(found) => return Err(ReaderError::UnexpectedByte { position, found }),
+                None => return Err(ReaderError::UnexpectedEof),
+            }
+        }
+        Ok(())
+    }
+
+    fn number(&mut self) -> Result<Event, ReaderError> {
+        let position = self.position;
+        let invalid = ReaderError::InvalidNumber { position };
+        let mut text = String::new();
+
+        if self.peek()? == Some(b'-') {
+            text.push('-');
+            self.bump();
+            if self.options.nan_and_infinity && self.peek()? == Some(b'I') {
+                self.literal(b"Infinity")?;
+                return Ok(Event::Number(f64::NEG_INFINITY));
+            }
+        }
+        match self.peek()? {
+            Some(b'0') => {
+                text.push('0');
+                self.bump();
+                if self.options.radix_integers
+                    && let Some(radix) = self.peek()?.and_then(radix_prefix)
+                {
+                    return self.radix_integer(text, radix, position);
+                }
+                if self.peek()?.is_some_and(|b| b.is_ascii_digit()) {
+                    return Err(invalid);
+                }
+            }
+            Some(b'1'..=b'9') => self.digits(&mut text, 10, position)?,
+            _ => return Err(invalid),
+        }
+        if self.peek()? == Some(b'.') {
+            text.push('.');
+            self.bump();
+            if !self.peek()?.is_some_and(|b| b.is_ascii_digit()) {
+                return Err(invalid);
+            }
+            self.digits(&mut text, 10, position)?;
+        }
+        if let Some(b'e' | b'E') = self.peek()? {
+            text.push('e');
+            self.bump();
+            if let Some(sign @ (b'+' | b'-')) = self.peek()? {
+                text.push(sign as char);
+                self.bump();
+            }
+            if !self.peek()?.is_some_and(|b| b.is_ascii_digit()) {
+                return Err(invalid);
+            }
+            self.digits(&mut text, 10, position)?;
+        }
+        let n: f64 = text.parse().map_err(|_| invalid)?;
+        // only integer literals can be exact, `0.1` is always rounded
+        let integer = !text.contains(['.', 'e']);
+        self.in_range(n, integer, text, position)
+    }
+
+    /// Reads the digits of a `0x`, `0o` or `0b` integer, `text` holds the sign and `0`
+    fn radix_integer(
+        &mut self,
+        mut text: String,
+        radix: u32,
+        position: Position,
+    ) -> Result<Event, ReaderError> {
+        text.push(char::from(self.peek()?.ok_or(ReaderError::UnexpectedEof)?));
+        self.bump();
+        let start = text.len();
+        self.digits(&mut text, radix, position)?;
+        if text.len() == start {
+            return Err(ReaderError::InvalidNumber { position });
+        }
+        // too many digits for a u128 is far past `f64::MAX` as well
+        let n = u128::from_str_radix(&text[start..], radix).map_or(f64::INFINITY, |n| n as f64);
+        let n = if text.starts_with('-') { -n } else { n };
+        self.in_range(n, true, text, position)
+    }
+
+    /// Applies [`ParserOptions::overflow`] to a number that was read as `text`
+    fn in_range(
+        &self,
+        n: f64,
+        integer: bool,
+        text: String,
+        position: Position,
+    ) -> Result<Event, ReaderError> {
+        let unsafe_integer = self.options.numbers == NumberMode::SafeIntegers
+            && integer
+            && n.abs() > MAX_SAFE_INTEGER;
+        if !unsafe_integer && n.is_finite() {
+            return Ok(Event::Number(n));
+        }
+        let n = match self.options.overflow {
+            NumberOverflow::Error if n.is_infinite() => {
+                return Err(ReaderError::NumberOutOfRange { position });
+            }
+            NumberOverflow::Error => return Err(ReaderError::UnsafeInteger { position }),
+            NumberOverflow::Saturate if n.is_infinite() => f64::MAX.copysign(n),
+            NumberOverflow::Saturate => MAX_SAFE_INTEGER.copysign(n),
+            NumberOverflow::Float => n,
+            NumberOverflow::Text => return Ok(Event::String(text)),
+        };
+        Ok(Event::Number(n))
+    }
+
+    /// Reads a run of digits in `radix` for the number starting at `start`
+    fn digits(
+        &mut self,
+        text: &mut String,
+        radix: u32,
+        start: Position,
+    ) -> Result<(), ReaderError> {
+        while let Some(byte) = self.peek()?.filter(|b| char::from(*b).is_digit(radix)) {
+            if self
+                .limits
+                .max_number_len
+                .is_some_and(|max| text.len() >= max)
+            {
+                let limit = Limit::NumberLength;
+                return Err(ReaderError::LimitExceeded {
+                    position: start,
+                    limit,
+                });
+            }
+            text.push(byte as char);
+            self.bump();
+        }
+        Ok(())
+    }
+
+    /// Reads a string between two `delimiter`s, without escapes, starting at the first
+    fn raw_string(&mut self, delimiter: &[u8]) -> Result<String, ReaderError> {
+        let start = self.position;
+        self.literal(delimiter)?;
+        let mut bytes = Vec::new();
+        while !bytes.ends_with(delimiter) {
+            bytes.push(self.peek()?.ok_or(ReaderError::UnexpectedEof)?);
+            self.bump();
+            if self
+                .limits
+                .max_string_len
+                .is_some_and(|max| bytes.len() > max + delimiter.len())
+            {
+                let limit = Limit::StringLength;
+                return Err(ReaderError::LimitExceeded {
+                    position: start,
+                    limit,
+                });
+            }
+        }
+        bytes.truncate(bytes.len() - delimiter.len());
+        String::from_utf8(bytes).map_err(|_| ReaderError::InvalidUtf8 { position: start })
+    }
+
+    /// Reads a string starting at its opening quote
+    fn string(&mut self) -> Result<String, ReaderError> {
+        let start = self.position;
+        self.bump();
+        let mut bytes = Vec::new();
+        loop {
+            let position = self.position;
+            let byte = self.peek()?.ok_or(ReaderError::UnexpectedEof)?;
+            self.bump();
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let ch = self.escape(position)?;
+                    bytes.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                0x00..=0x1f if !self.options.control_characters => {
+                    return Err(ReaderError::UnexpectedByte {
+                        position,
+                        found: byte,
+                    });
+                }
+                _ => bytes.push(byte),
+            }
+            if self
+                .limits
+                .max_string_len
+                .is_some_and(|max| bytes.len() > max)
+            {
+                let limit = Limit::StringLength;
+                return Err(ReaderError::LimitExceeded {
+                    position: start,
+                    limit,
+                });
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| ReaderError::InvalidUtf8 { position: start })
+    }
+
+    /// Reads an escape after its backslash
+    fn escape(&mut self, position: Position) -> Result<char, ReaderError> {
+        let invalid = || ReaderError::InvalidEscape { position };
+        let byte = self.peek()?.ok_or(ReaderError::UnexpectedEof)?;
+        self.bump();
+        let ch = match byte {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                let high = self.hex4().ok_or_else(invalid)?;
+                if (0xdc00..0xe000).contains(&high) {
+                    return Err(invalid());
+                }
+                if !(0xd800..0xdc00).contains(&high) {
+                    return char::from_u32(high as u32).ok_or_else(invalid);
+                }
+                if self.peek()? != Some(b'\\') {
+                    return Err(invalid());
+                }
+                self.bump();
+                if self.peek()? != Some(b'u') {
+                    return Err(invalid());
+                }
+                self.bump();
+                let low = self.hex4().ok_or_else(invalid)?;
+                if !(0xdc00..0xe000).contains(&low) {
+                    return Err(invalid());
+                }
+                let code = 0x10000 + ((high as u32 - 0xd800) << 10) + (low as u32 - 0xdc00);
+                char::from_u32(code).ok_or_else(invalid)?
+            }
+            _ => return Err(invalid()),
+        };
+        Ok(ch)
+    }
+
+    fn hex4(&mut self) -> Option<u16> {
+        let mut value = 0;
+        for _ in 0..4 {
+            let digit = (self.peek().ok()?? as char).to_digit(16)?;
+            self.bump();
+            value = value * 16 + digit as u16;
+        }
+        Some(value)
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), ReaderError> {
+        loop {
+            match self.peek()? {
+                Some(b' ' | b'\t' | b'\n' | b'\r') => self.bump(),
+                Some(b'/') if self.options.comments => self.comment()?,
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Skips a `//` or `/* */` comment starting at its first slash
+    fn comment(&mut self) -> Result<(), ReaderError> {
+        self.bump();
+        let position = self.position;
+        match self.peek()? {
+            Some(b'/') => {
+                while self.peek()?.is_some_and(|byte| byte != b'\n') {
+                    self.bump();
+                }
+            }
+            Some(b'*') => {
+                self.bump();
+                loop {
+                    match self.peek()?.ok_or(ReaderError::UnexpectedEof)? {
+                        b'*' => {
+                            self.bump();
+                            if self.peek()? == Some(b'/') {
+                                self.bump();
+                                break;
+                            }
+                        }
+                        _ => self.bump(),
+                    }
+                }
+            }
+            Some(found) => return Err(ReaderError::UnexpectedByte { position, found }),
+            None => return Err(ReaderError::UnexpectedEof),
+        }
+        Ok(())
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, ReaderError> {
+        if self.start == self.end {
+            self.start = 0;
+            self.end = loop {
+                match self.inner.read(&mut self.buffer) {
+                    Ok(len) => break len,
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(err) => return Err(err.into()),
+                }
+            };
+        }
+        let byte = self.buffer[self.start..self.end].first().copied();
+        if byte.is_some()
+            && let Some(max_bytes) = self.limits.max_bytes
+            && self.position.offset >= max_bytes
+        {
+            let (position, limit) = (self.position, Limit::Bytes);
+            return Err(ReaderError::LimitExceeded { position, limit });
+        }
+        Ok(byte)
+    }
+
+    /// Consumes the byte returned by the last `peek`
+    fn bump(&mut self) {
+        let byte = self.buffer[self.start];
+        self.start += 1;
+        self.position.offset += 1;
+        if byte == b'\n' {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+    }
+}
+
+/// Tidies the text of a `'''` string whose quotes start `indent` columns in, as in Hjson
+///
+/// A line break right after the opening quotes and the one before the closing quotes are
+/// dropped, along with up to `indent` spaces at the start of each following line.
+pub(crate) fn dedent(text: &str, indent: usize) -> String {
+    let text = text.strip_prefix('\n').unwrap_or(text);
+    let text = match text.rfind('\n') {
+        Some(end) if text[end + 1..].trim_start_matches(' ').is_empty() => &text[..end],
+        _ => text,
+    };
+    let lines: Vec<_> = text
+        .split('\n')
+        .map(|line| {
+            let spaces = line.len() - line.trim_start_matches(' ').len();
+            &line[spaces.min(indent)..]
+        })
+        .collect();
+    lines.join("\n")
+}
+
+/// The radix for the letter after the `0` of a `0x`, `0o` or `0b` integer
+fn radix_prefix(byte: u8) -> Option<u32> {
+    match byte {
+        b'x' | b'X' => Some(16),
+        b'o' | b'O' => Some(8),
+        b'b' | b'B' => Some(2),
+        _ => None,
+    }
+}
+
+/// A container that is still being read by [`read_document`], with where it starts
+enum Partial<S> {
+    Array(Vec<Value<S>>, Position),
+    Object(Map<S>, Option<(String, Position)>, Position),
+}
+
+/// Callbacks around each array and object read by [`read_subtree_with`]
+pub(crate) trait SubtreeHooks<R, S = DefaultState> {
+    /// Called right after the container's start event at `start`, returning a value skips
+    /// the rest of the container and uses the value in its place
+    fn enter(
+        &mut self,
+        reader: &mut JsonReader<R>,
+        start: Position,
+    ) -> Result<Option<Value<S>>, ReaderError>;
+
+    /// Called with the container once it has been read, the reader is just past its end
+    fn leave(&mut self, reader: &JsonReader<R>, start: Position, value: &Value<S>);
+
+    /// Called with every event read, before it is handled
+    fn event(&mut self, _event: &Event, _position: Position) {}
+
+    /// Called with every value once it is complete, containers after their contents
+    fn value(&mut self, _value: &Value<S>) {}
+}
+
+struct NoHooks;
+
+impl<R, S> SubtreeHooks<R, S> for NoHooks {
+    fn enter(
+        &mut self,
+        _: &mut JsonReader<R>,
+        _: Position,
+    ) -> Result<Option<Value<S>>, ReaderError> {
+        Ok(None)
+    }
+
+    fn leave(&mut self, _: &JsonReader<R>, _: Position, _: &Value<S>) {}
+}
+
+/// Reads a whole document into a [`Value`], accepting the full JSON grammar
+pub(crate) fn read_document<R: io::Read>(inner: R) -> Result<Value, ReaderError> {
+    read_value(JsonReader::new(inner), &DefaultState::default())
+}
+
+/// Reads the whole document from a reader that has not started yet, building objects with
+/// clones of `hasher`
+pub(crate) fn read_value<R: io::Read, S: BuildHasher + Clone>(
+    mut reader: JsonReader<R>,
+    hasher: &S,
+) -> Result<Value<S>, ReaderError> {
+    let first = reader.next_event()?.ok_or(ReaderError::UnexpectedEof)?;
+    let value = read_subtree_with(&mut reader, first, hasher, &mut NoHooks)?;
+    // the reader reports anything after the root value
+    reader.next_event()?;
+    Ok(value)
+}
+
+/// Reads the value that starts with `first`, the event just read from `reader`
+pub(crate) fn read_subtree<R: io::Read>(
+    reader: &mut JsonReader<R>,
+    first: (Event, Position),
+) -> Result<Value, ReaderError> {
+    read_subtree_with(reader, first, &DefaultState::default(), &mut NoHooks)
+}
+
+/// [`read_subtree`] with objects built from clones of `hasher`, calling `hooks` around each
+/// array and object
+pub(crate) fn read_subtree_with<R: io::Read, S: BuildHasher + Clone>(
+    reader: &mut JsonReader<R>,
+    first: (Event, Position),
+    hasher: &S,
+    hooks: &mut impl SubtreeHooks<R, S>,
+) -> Result<Value<S>, ReaderError> {
+    let mut stack = Vec::new();
+    let mut next = Some(first);
+    loop {
+        let (event, position) = match next.take() {
+            Some(first) => first,
+            None => reader.next_event()?.ok_or(ReaderError::UnexpectedEof)?,
+        };
+        hooks.event(&event, position);
+        let value = match event {
+            Event::StartArray | Event::StartObject => match hooks.enter(reader, position)? {
+                Some(value) => value,
+                None => {
+                    stack.push(match event {
+                        Event::StartArray => Partial::Array(Vec::new(), position),
+                        _ => Partial::Object(HashMap::with_hasher(hasher.clone()), None, position),
+                    });
+                    continue;
+                }
+            },
+            Event::Key(key) => {
+                if let Some(Partial::Object(_, pending, _)) = stack.last_mut() {
+                    *pending = Some((key, position));
+                }
+                continue;
+            }
+            Event::EndArray | Event::EndObject => {
+                let (value, start) = match stack.pop() {
+                    Some(Partial::Array(array, start)) => (Value::Array(array), start),
+                    Some(Partial::Object(map, _, start)) => (Value::Object(map), start),
+                    None => unreachable!("reader balances containers"),
+                };
+                hooks.leave(reader, start, &value);
+                value
+            }
+            Event::String(s) => Value::String(s),
+            Event::Number(n) => Value::Number(n),
+            Event::Boolean(b) => Value::Boolean(b),
+            Event::Null => Value::Null,
+        };
+        hooks.value(&value);
+        match stack.last_mut() {
+            None => return Ok(value),
+            Some(Partial::Array(array, _)) => array.push(value),
+            Some(Partial::Object(map, pending, _)) => {
+                let (key, position) = pending
+                    .take()
+                    .expect("reader emits a key before each value");
+                match reader.options.duplicate_keys {
+                    DuplicateKeys::Error if map.contains_key(&key) => {
+                        return Err(ReaderError::DuplicateKey { position, key });
+                    }
+                    DuplicateKeys::First if map.contains_key(&key) => {}
+                    _ => {
+                        map.insert(key, value);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<R: io::Read> Iterator for JsonReader<R> {
+    type Item = Result<(Event, Position), ReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, ParserOptions, Value};
+
+    use super::{Event, JsonReader, Limit, Position, ReaderError, read_document};
+
+    fn events(input: &str) -> Result<Vec<Event>, ReaderError> {
+        JsonReader::new(input.as_bytes())
+            .map(|result| result.map(|(event, _)| event))
+            .collect()
+    }
+
+    #[test]
+    fn nested_document() {
+        let input = r#"{"a": [1, -2.5e3, true, null], "b": {"c": "d\u00e9\ud83d\ude00"}}"#;
+        let expected = vec![
+            Event::StartObject,
+            Event::Key(String::from("a")),
+            Event::StartArray,
+            Event::Number(1.0),
+            Event::Number(-2500.0),
+            Event::Boolean(true),
+            Event::Null,
+            Event::EndArray,
+            Event::Key(String::from("b")),
+            Event::StartObject,
+            Event::Key(String::from("c")),
+            Event::String(String::from("d\u{e9}\u{1f600}")),
+            Event::EndObject,
+            Event::EndObject,
+        ];
+        assert_eq!(events(input).unwrap(), expected);
+    }
+
+    #[test]
+    fn positions() {
+        let mut reader = JsonReader::new("[\n  1,\n  \"x\"\n]".as_bytes());
+        let positions: Vec<_> = reader
+            .by_ref()
+            .map(|result| result.unwrap().1)
+            .map(|Position { line, column, .. }| (line, column))
+            .collect();
+        assert_eq!(positions, [(1, 1), (2, 3), (3, 3), (4, 1)]);
+        assert_eq!(reader.depth(), 0);
+    }
+
+    #[test]
+    fn scalars_and_whitespace() {
+        assert_eq!(events(" 0 ").unwrap(), [Event::Number(0.0)]);
+        assert_eq!(events("[]").unwrap(), [Event::StartArray, Event::EndArray]);
+        assert_eq!(
+            events("{}").unwrap(),
+            [Event::StartObject, Event::EndObject]
+        );
+    }
+
+    #[test]
+    fn large_input_crosses_buffers() {
+        let input = format!("[{}1]", "1,".repeat(10_000));
+        assert_eq!(events(&input).unwrap().len(), 10_003);
+    }
+
+    #[test]
+    fn invalid_documents() {
+        let cases = [
+            "[1,]",
+            "{\"a\": 1,}",
+            "[1 2]",
+            "{\"a\" 1}",
+            "{1: 2}",
+            "01",
+            "1.",
+            "-",
+            "\"\\x\"",
+            "\"\\udc00\"",
+            "tru",
+            "[1] 2",
+            "\"a\nb\"",
+        ];
+        for input in cases {
+            assert!(events(input).is_err(), "{input}");
+        }
+        assert!(matches!(events("[1"), Err(ReaderError::UnexpectedEof)));
+        let position = Position {
+            offset: 4,
+            line: 1,
+            column: 5,
+        };
+        assert!(
+            matches!(events("[1] 2"), Err(ReaderError::TrailingContent { position: p }) if p == position)
+        );
+        assert_eq!(
+            events("[1 2]").unwrap_err().to_string(),
+            "unexpected `2` at line 1, column 4"
+        );
+        let limit = Limit::StringLength;
+        assert_eq!(
+            ReaderError::LimitExceeded { position, limit }.to_string(),
+            "exceeded the string length limit at line 1, column 5"
+        );
+    }
+
+    #[test]
+    fn skip_value() {
+        let input = r#"{"a": {"b": ["}", "\"]", {"c": [1]}], "d": 2}, "e": [true]}"#;
+        let mut reader = JsonReader::new(input.as_bytes());
+        reader.next_event().unwrap();
+        reader.next_event().unwrap();
+        assert!(matches!(
+            reader.next_event(),
+            Ok(Some((Event::StartObject, _)))
+        ));
+        reader.skip_value().unwrap();
+        assert_eq!(reader.depth(), 1);
+        let rest: Vec<_> = reader.map(|result| result.unwrap().0).collect();
+        let expected = [
+            Event::Key(String::from("e")),
+            Event::StartArray,
+            Event::Boolean(true),
+            Event::EndArray,
+            Event::EndObject,
+        ];
+        assert_eq!(rest, expected);
+
+        for input in ["[[1, 2}", "[\"a", "[\"\n\"]"] {
+            let mut reader = JsonReader::new(input.as_bytes());
+            reader.next_event().unwrap();
+            assert!(reader.skip_value().is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn skip_multiline_strings() {
+        let input = "[[`]`, '''\n]}\n'''], [`]`, 1]]";
+        let options = ParserOptions {
+            multiline_strings: true,
+            ..ParserOptions::default()
+        };
+        let mut reader = JsonReader::with_options(input.as_bytes(), options);
+        reader.next_event().unwrap();
+        reader.next_event().unwrap();
+        reader.skip_value().unwrap();
+        let rest: Vec<_> = reader.map(|result| result.unwrap().0).collect();
+        let expected = [
+            Event::StartArray,
+            Event::String(String::from("]")),
+            Event::Number(1.0),
+            Event::EndArray,
+            Event::EndArray,
+        ];
+        assert_eq!(rest, expected);
+    }
+
+    #[test]
+    fn whole_documents() {
+        let input = r#"{"a": [1, {"b": null}], "c": "d"}"#;
+        let expected = Parser::new().parse_str(input).unwrap();
+        assert_eq!(read_document(input.as_bytes()).unwrap(), expected);
+        assert_eq!(
+            read_document(&b" -1e2 "[..]).unwrap(),
+            Value::Number(-100.0)
+        );
+        assert!(matches!(
+            read_document(&b"[1] 2"[..]),
+            Err(ReaderError::TrailingContent { .. })
+        ));
+    }
+}
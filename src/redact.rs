@@ -0,0 +1,160 @@
+use crate::{JsonPointer, Value};
+
+/// The text that replaces redacted values
+pub const REDACTED: &str = "***";
+
+/// One step of a redaction pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// `*`, any single key or index
+    Any,
+
+    /// `**`, any number of keys and indexes, including none
+    AnyDepth,
+
+    Literal(String),
+}
+
+/// Replaces values matching any of `patterns` with `"***"`, returning how many were
+/// replaced
+///
+/// A pattern is one of
+/// - a key, ex. `password`, matching members with that name at any depth
+/// - a dot path, ex. `*.token` or `users[*].ssn`
+/// - a JSON Pointer, ex. `/users/*/ssn`
+///
+/// In paths and pointers `*` matches any single key or index, and `**` matches any
+/// number of them. Keys are compared exactly.
+pub fn redact(value: &mut Value, patterns: &[&str]) -> usize {
+    let patterns: Vec<_> = patterns
+        .iter()
+        .filter_map(|pattern| compile(pattern))
+        .collect();
+    let mut count = 0;
+    value.walk_mut(|pointer, value| {
+        if !pointer.is_root()
+            && patterns
+                .iter()
+                .any(|pattern| matches(pattern, pointer.tokens()))
+        {
+            *value = Value::String(String::from(REDACTED));
+            count += 1;
+        }
+    });
+    count
+}
+
+fn compile(pattern: &str) -> Option<Vec<Segment>> {
+    let tokens: Vec<String> = if pattern.starts_with('/') {
+        JsonPointer::parse(pattern).ok()?.tokens().to_vec()
+    } else if pattern.contains(['.', '[']) {
+        dot_tokens(pattern)?
+    } else {
+        return Some(vec![
+            Segment::AnyDepth,
+            Segment::Literal(pattern.to_string()),
+        ]);
+    };
+    let segments = tokens
+        .into_iter()
+        .map(|token| match token.as_str() {
+            "*" => Segment::Any,
+            "**" => Segment::AnyDepth,
+            _ => Segment::Literal(token),
+        })
+        .collect();
+    Some(segments)
+}
+
+/// Splits a dot path pattern into keys and bracketed indexes, which may be `*`
+fn dot_tokens(pattern: &str) -> Option<Vec<String>> {
+    let mut tokens = Vec::new();
+    for part in pattern.split('.') {
+        let (key, mut indexes) = part.split_at(part.find('[').unwrap_or(part.len()));
+        if !key.is_empty() {
+            tokens.push(key.to_string());
+        }
+        while let Some(rest) = indexes.strip_prefix('[') {
+            let close = rest.find(']')?;
+            tokens.push(rest[..close].to_string());
+            indexes = &rest[close + 1..];
+        }
+        if !indexes.is_empty() {
+            return None;
+        }
+    }
+    Some(tokens)
+}
+
+fn matches(pattern: &[Segment], tokens: &[String]) -> bool {
+    match (pattern.split_first(), tokens.split_first()) {
+        (None, None) => true,
+        (Some((Segment::AnyDepth, rest)), _) => {
+            matches(rest, tokens) || (!tokens.is_empty() && matches(pattern, &tokens[1..]))
+        }
+        (Some((Segment::Any, rest)), Some((_, tokens))) => matches(rest, tokens),
+        (Some((Segment::Literal(literal), rest)), Some((token, tokens))) => {
+            literal == token && matches(rest, tokens)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::redact;
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    fn sample() -> Value {
+        value(
+            r#"{
+                "password": "hunter2",
+                "auth": {"token": "abc", "user": "ken"},
+                "users": [
+                    {"ssn": "123", "name": "a", "password": {"hash": "x"}},
+                    {"ssn": "456", "name": "b"}
+                ]
+            }"#,
+        )
+    }
+
+    #[test]
+    fn keys_match_at_any_depth() {
+        let mut document = sample();
+        assert_eq!(redact(&mut document, &["password"]), 2);
+        assert_eq!(document.get_path_str("password"), Some("***"));
+        assert_eq!(document.get_path_str("users[0].password"), Some("***"));
+    }
+
+    #[test]
+    fn dot_paths_and_pointers() {
+        let mut document = sample();
+        let count = redact(&mut document, &["*.token", "/users/*/ssn"]);
+        assert_eq!(count, 3);
+        assert_eq!(document.get_path_str("auth.token"), Some("***"));
+        assert_eq!(document.get_path_str("auth.user"), Some("ken"));
+        assert_eq!(document.get_path_str("users[1].ssn"), Some("***"));
+        assert_eq!(document.get_path_str("users[1].name"), Some("b"));
+    }
+
+    #[test]
+    fn indexes_and_deep_wildcards() {
+        let mut document = sample();
+        assert_eq!(redact(&mut document, &["users[0].name", "**.hash"]), 2);
+        assert_eq!(document.get_path_str("users[0].name"), Some("***"));
+        assert_eq!(document.get_path_str("users[0].password.hash"), Some("***"));
+        assert_eq!(document.get_path_str("users[1].name"), Some("b"));
+    }
+
+    #[test]
+    fn no_matches() {
+        let mut document = sample();
+        assert_eq!(redact(&mut document, &["missing", "/users/9", "a[", ""]), 0);
+        assert_eq!(document, sample());
+    }
+}
@@ -0,0 +1,540 @@
+//! A small regular expression engine, covering the I-Regexp (RFC 9485) subset used by
+//! JSONPath filters and JSON Schema `pattern`s.
+//!
+//! Patterns compile to a program that runs without backtracking, so matching time grows
+//! linearly with the input, whatever the pattern.
+
+use crate::DEFAULT_MAX_DEPTH;
+
+/// The most instructions a compiled pattern may have, counted repetitions like `a{1000}`
+/// repeat the instructions of their operand
+const MAX_PROGRAM: usize = 10_000;
+
+/// A compiled regular expression
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Regex {
+    program: Vec<Inst>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum RegexError {
+    /// The pattern ended in the middle of a construct
+    UnexpectedEnd,
+
+    /// The character is not valid at this position of the pattern
+    UnexpectedChar(char),
+
+    /// A `{n,m}` quantifier where `m` is smaller than `n`
+    InvalidRepetition,
+
+    /// The pattern compiles to more than [`MAX_PROGRAM`] instructions
+    TooLarge,
+
+    /// Groups are nested deeper than [`DEFAULT_MAX_DEPTH`]
+    TooDeep,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class(Class),
+    Start,
+    End,
+    Concat(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: u32,
+        max: Option<u32>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Class {
+    negated: bool,
+    items: Vec<ClassItem>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ClassItem {
+    Range(char, char),
+    Digit(bool),
+    Word(bool),
+    Space(bool),
+}
+
+impl ClassItem {
+    fn matches(&self, ch: char) -> bool {
+        match *self {
+            Self::Range(low, high) => low <= ch && ch <= high,
+            Self::Digit(negated) => ch.is_ascii_digit() != negated,
+            Self::Word(negated) => (ch.is_ascii_alphanumeric() || ch == '_') != negated,
+            Self::Space(negated) => ch.is_whitespace() != negated,
+        }
+    }
+}
+
+impl Class {
+    fn matches(&self, ch: char) -> bool {
+        self.items.iter().any(|item| item.matches(ch)) != self.negated
+    }
+}
+
+impl Regex {
+    pub(crate) fn new(pattern: &str) -> Result<Self, RegexError> {
+        let chars: Vec<char> = pattern.chars().collect();
+        let mut index = 0;
+        let node = parse_alternation(&chars, &mut index, 0)?;
+        if index < chars.len() {
+            return Err(RegexError::UnexpectedChar(chars[index]));
+        }
+        let mut program = Vec::new();
+        compile(&node, &mut program)?;
+        program.push(Inst::Match);
+        Ok(Self { program })
+    }
+
+    /// Whether the pattern matches anywhere within `input`
+    pub(crate) fn is_match(&self, input: &str) -> bool {
+        let chars: Vec<char> = input.chars().collect();
+        run(&self.program, &chars, true)
+    }
+
+    /// Whether the pattern matches the whole of `input`
+    pub(crate) fn is_full_match(&self, input: &str) -> bool {
+        let chars: Vec<char> = input.chars().collect();
+        run(&self.program, &chars, false)
+    }
+}
+
+/// Parses alternatives until an unmatched `)`, `depth` counts the groups around them
+fn parse_alternation(chars: &[char], index: &mut usize, depth: usize) -> Result<Node, RegexError> {
+    let mut alternatives = vec![parse_concat(chars, index, depth)?];
+    while chars.get(*index) == Some(&'|') {
+        *index += 1;
+        alternatives.push(parse_concat(chars, index, depth)?);
+    }
+    if alternatives.len() == 1 {
+        Ok(alternatives.remove(0))
+    } else {
+        Ok(Node::Alternation(alternatives))
+    }
+}
+
+fn parse_concat(chars: &[char], index: &mut usize, depth: usize) -> Result<Node, RegexError> {
+    let mut nodes = Vec::new();
+    while let Some(&ch) = chars.get(*index) {
+        if ch == '|' || ch == ')' {
+            break;
+        }
+        let atom = parse_atom(chars, index, depth)?;
+        let node = parse_quantifier(chars, index, atom)?;
+        nodes.push(node);
+    }
+    match nodes.len() {
+        0 => Ok(Node::Empty),
+        1 => Ok(nodes.remove(0)),
+        _ => Ok(Node::Concat(nodes)),
+    }
+}
+
+fn parse_atom(chars: &[char], index: &mut usize, depth: usize) -> Result<Node, RegexError> {
+    let ch = chars[*index];
+    *index += 1;
+    let node = match ch {
+        '.' => Node::Any,
+        '^' => Node::Start,
+        '$' => Node::End,
+        '(' => {
+            if depth == DEFAULT_MAX_DEPTH {
+                return Err(RegexError::TooDeep);
+            }
+            if chars[*index..].starts_with(&['?', ':']) {
+                *index += 2;
+            }
+            let inner = parse_alternation(chars, index, depth + 1)?;
+            match chars.get(*index) {
+                Some(')') => *index += 1,
+                Some(&other) => return Err(RegexError::UnexpectedChar(other)),
+                None => return Err(RegexError::UnexpectedEnd),
+            }
+            inner
+        }
+        '[' => Node::Class(parse_class(chars, index)?),
+        '\\' => match parse_escape(chars, index)? {
+            ClassItem::Range(low, _) => Node::Char(low),
+            item => Node::Class(Class {
+                negated: false,
+                items: vec![item],
+            }),
+        },
+        '*' | '+' | '?' | '{' | ')' => return Err(RegexError::UnexpectedChar(ch)),
+        ch => Node::Char(ch),
+    };
+    Ok(node)
+}
+
+fn parse_escape(chars: &[char], index: &mut usize) -> Result<ClassItem, RegexError> {
+    let ch = *chars.get(*index).ok_or(RegexError::UnexpectedEnd)?;
+    *index += 1;
+    let item = match ch {
+        'd' => ClassItem::Digit(false),
+        'D' => ClassItem::Digit(true),
+        'w' => ClassItem::Word(false),
+        'W' => ClassItem::Word(true),
+        's' => ClassItem::Space(false),
+        'S' => ClassItem::Space(true),
+        'n' => ClassItem::Range('\n', '\n'),
+        'r' => ClassItem::Range('\r', '\r'),
+        't' => ClassItem::Range('\t', '\t'),
+        'u' => {
+            let hex: String = chars
+                .get(*index..*index + 4)
+                .ok_or(RegexError::UnexpectedEnd)?
+                .iter()
+                .collect();
+            let code =
+                u32::from_str_radix(&hex, 16).map_err(|_| RegexError::UnexpectedChar('u'))?;
+            let ch = char::from_u32(code).ok_or(RegexError::UnexpectedChar('u'))?;
+            *index += 4;
+            ClassItem::Range(ch, ch)
+        }
+        ch if ch.is_ascii_alphanumeric() => return Err(RegexError::UnexpectedChar(ch)),
+        ch => ClassItem::Range(ch, ch),
+    };
+    Ok(item)
+}
+
+fn parse_class(chars: &[char], index: &mut usize) -> Result<Class, RegexError> {
+    let negated = chars.get(*index) == Some(&'^');
+    if negated {
+        *index += 1;
+    }
+
+    let mut items = Vec::new();
+    loop {
+        let ch = *chars.get(*index).ok_or(RegexError::UnexpectedEnd)?;
+        *index += 1;
+        let item = match ch {
+            ']' if !items.is_empty() => break,
+            '\\' => parse_escape(chars, index)?,
+            ch => ClassItem::Range(ch, ch),
+        };
+
+        let is_range =
+            chars.get(*index) == Some(&'-') && chars.get(*index + 1).is_some_and(|ch| *ch != ']');
+        match item {
+            ClassItem::Range(low, _) if is_range => {
+                *index += 1;
+                let high = match chars[*index] {
+                    '\\' => {
+                        *index += 1;
+                        match parse_escape(chars, index)? {
+                            ClassItem::Range(high, _) => high,
+                            _ => return Err(RegexError::UnexpectedChar('-')),
+                        }
+                    }
+                    high => {
+                        *index += 1;
+                        high
+                    }
+                };
+                if high < low {
+                    return Err(RegexError::UnexpectedChar(high));
+                }
+                items.push(ClassItem::Range(low, high));
+            }
+            item => items.push(item),
+        }
+    }
+    Ok(Class { negated, items })
+}
+
+fn parse_quantifier(chars: &[char], index: &mut usize, atom: Node) -> Result<Node, RegexError> {
+    let (min, max) = match chars.get(*index) {
+        Some('*') => (0, None),
+        Some('+') => (1, None),
+        Some('?') => (0, Some(1)),
+        Some('{') => {
+            *index += 1;
+            let min = parse_count(chars, index)?.ok_or(RegexError::UnexpectedEnd)?;
+            let max = if chars.get(*index) == Some(&',') {
+                *index += 1;
+                parse_count(chars, index)?
+            } else {
+                Some(min)
+            };
+            if chars.get(*index) != Some(&'}') {
+                return Err(RegexError::UnexpectedEnd);
+            }
+            if max.is_some_and(|max| max < min) {
+                return Err(RegexError::InvalidRepetition);
+            }
+            (min, max)
+        }
+        _ => return Ok(atom),
+    };
+    *index += 1;
+
+    // a lazy quantifier only changes which match is preferred, not whether one exists
+    if chars.get(*index) == Some(&'?') {
+        *index += 1;
+    }
+    Ok(Node::Repeat {
+        node: Box::new(atom),
+        min,
+        max,
+    })
+}
+
+fn parse_count(chars: &[char], index: &mut usize) -> Result<Option<u32>, RegexError> {
+    let start = *index;
+    while chars.get(*index).is_some_and(char::is_ascii_digit) {
+        *index += 1;
+    }
+    if start == *index {
+        return Ok(None);
+    }
+    let digits: String = chars[start..*index].iter().collect();
+    digits
+        .parse()
+        .map(Some)
+        .map_err(|_| RegexError::InvalidRepetition)
+}
+
+/// An instruction of a compiled pattern
+#[derive(Debug, Clone, PartialEq)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Class),
+    Start,
+    End,
+
+    /// Continues at both targets, the first is preferred
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+/// Appends the instructions for `node`, failing if the program outgrows [`MAX_PROGRAM`]
+fn compile(node: &Node, program: &mut Vec<Inst>) -> Result<(), RegexError> {
+    match node {
+        Node::Empty => {}
+        Node::Char(ch) => program.push(Inst::Char(*ch)),
+        Node::Any => program.push(Inst::Any),
+        Node::Class(class) => program.push(Inst::Class(class.clone())),
+        Node::Start => program.push(Inst::Start),
+        Node::End => program.push(Inst::End),
+        Node::Concat(nodes) => {
+            for node in nodes {
+                compile(node, program)?;
+            }
+        }
+        Node::Alternation(alternatives) => {
+            let (last, rest) = alternatives
+                .split_last()
+                .expect("at least two alternatives");
+            let mut jumps = Vec::new();
+            for alternative in rest {
+                let split = program.len();
+                program.push(Inst::Split(split + 1, 0));
+                compile(alternative, program)?;
+                jumps.push(program.len());
+                program.push(Inst::Jump(0));
+                program[split] = Inst::Split(split + 1, program.len());
+            }
+            compile(last, program)?;
+            for jump in jumps {
+                program[jump] = Inst::Jump(program.len());
+            }
+        }
+        Node::Repeat { node, min, max } => {
+            for _ in 0..*min {
+                let start = program.len();
+                compile(node, program)?;
+                if program.len() == start {
+                    // the operand matches only the empty string, ex. `(){1000000000}`
+                    break;
+                }
+            }
+            match max {
+                None => {
+                    let split = program.len();
+                    program.push(Inst::Split(split + 1, 0));
+                    compile(node, program)?;
+                    program.push(Inst::Jump(split));
+                    program[split] = Inst::Split(split + 1, program.len());
+                }
+                Some(max) => {
+                    let mut splits = Vec::new();
+                    for _ in *min..*max {
+                        splits.push(program.len());
+                        program.push(Inst::Split(program.len() + 1, 0));
+                        compile(node, program)?;
+                    }
+                    for split in splits {
+                        program[split] = Inst::Split(split + 1, program.len());
+                    }
+                }
+            }
+        }
+    }
+    if program.len() > MAX_PROGRAM {
+        return Err(RegexError::TooLarge);
+    }
+    Ok(())
+}
+
+/// The instructions that are waiting for the character at one position
+struct Threads {
+    pcs: Vec<usize>,
+
+    /// Whether each instruction is already in `pcs`, so every one runs once per position
+    added: Vec<bool>,
+
+    stack: Vec<usize>,
+}
+
+impl Threads {
+    fn new(len: usize) -> Self {
+        Self {
+            pcs: Vec::new(),
+            added: vec![false; len],
+            stack: Vec::new(),
+        }
+    }
+
+    fn clear(&mut self) {
+        for pc in self.pcs.drain(..) {
+            self.added[pc] = false;
+        }
+    }
+
+    /// Adds the thread at `pc`, following jumps, splits and anchors that hold at `pos`
+    fn add(&mut self, program: &[Inst], pc: usize, pos: usize, len: usize) {
+        self.stack.push(pc);
+        while let Some(pc) = self.stack.pop() {
+            if self.added[pc] {
+                continue;
+            }
+            self.added[pc] = true;
+            match program[pc] {
+                Inst::Jump(target) => self.stack.push(target),
+                Inst::Split(first, second) => self.stack.extend([second, first]),
+                Inst::Start if pos == 0 => self.stack.push(pc + 1),
+                Inst::End if pos == len => self.stack.push(pc + 1),
+                _ => {}
+            }
+            // control flow is recorded too, so that `clear` resets it
+            self.pcs.push(pc);
+        }
+    }
+}
+
+/// Runs `program` over `input`, a Pike VM that advances every thread one character at a
+/// time, so it never backtracks and takes time proportional to the input times the program
+fn run(program: &[Inst], input: &[char], anywhere: bool) -> bool {
+    let len = input.len();
+    let mut current = Threads::new(program.len());
+    let mut next = Threads::new(program.len());
+    current.add(program, 0, 0, len);
+    for pos in 0..=len {
+        if anywhere && pos > 0 {
+            current.add(program, 0, pos, len);
+        }
+        for &pc in &current.pcs {
+            let advances = match &program[pc] {
+                Inst::Match if anywhere || pos == len => return true,
+                Inst::Char(ch) => input.get(pos) == Some(ch),
+                Inst::Any => input.get(pos).is_some_and(|ch| *ch != '\n'),
+                Inst::Class(class) => input.get(pos).is_some_and(|ch| class.matches(*ch)),
+                _ => false,
+            };
+            if advances {
+                next.add(program, pc + 1, pos + 1, len);
+            }
+        }
+        current.clear();
+        std::mem::swap(&mut current, &mut next);
+        if current.pcs.is_empty() && !anywhere {
+            return false;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Regex, RegexError};
+
+    fn check(pattern: &str, input: &str, expected: bool) {
+        let regex = Regex::new(pattern).unwrap();
+        assert_eq!(regex.is_match(input), expected, "{pattern} on {input}");
+    }
+
+    #[test]
+    fn literals_and_anchors() {
+        check("abc", "xabcx", true);
+        check("^abc$", "xabcx", false);
+        check("^abc$", "abc", true);
+    }
+
+    #[test]
+    fn classes_and_escapes() {
+        check("^[a-c]+$", "abcab", true);
+        check("^[^a-c]+$", "abd", false);
+        check(r"^\d{3}-\d{4}$", "555-1234", true);
+        check(r"^\w+@\w+\.com$", "ken@mail.com", true);
+        check(r"^\s*$", "  \t", true);
+    }
+
+    #[test]
+    fn alternation_and_groups() {
+        check("^(cat|dog)s?$", "dogs", true);
+        check("^(?:cat|dog)s?$", "cow", false);
+        check("^(ab)*c$", "ababc", true);
+    }
+
+    #[test]
+    fn backtracking_repetition() {
+        check("^a*ab$", "aaab", true);
+        check("^a{2,3}$", "aaaa", false);
+        check("^a{2,}$", "aaaa", true);
+        check("^(a|b)*?b$", "aab", true);
+    }
+
+    #[test]
+    fn full_match() {
+        let regex = Regex::new("b.").unwrap();
+        assert!(regex.is_match("abc"));
+        assert!(!regex.is_full_match("abc"));
+        assert!(regex.is_full_match("bc"));
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        assert_eq!(Regex::new("(a"), Err(RegexError::UnexpectedEnd));
+        assert_eq!(Regex::new("*a"), Err(RegexError::UnexpectedChar('*')));
+        assert_eq!(Regex::new("a{3,2}"), Err(RegexError::InvalidRepetition));
+        assert_eq!(Regex::new("(a{100}){200}"), Err(RegexError::TooLarge));
+        assert!(Regex::new("(()()){4000000000}").unwrap().is_full_match(""));
+
+        let nested = format!("{}a{}", "(".repeat(200_000), ")".repeat(200_000));
+        assert_eq!(Regex::new(&nested), Err(RegexError::TooDeep));
+        let nested = format!("{}a{}", "(".repeat(100), ")".repeat(100));
+        assert!(Regex::new(&nested).unwrap().is_full_match("a"));
+    }
+
+    #[test]
+    fn long_inputs() {
+        let long = "a".repeat(200_000);
+        check("^[a-z]*$", &long, true);
+        check("^(a|aa)*b$", &long, false);
+        check("^(a*)*$", &long, true);
+        assert!(Regex::new(".*").unwrap().is_full_match(&long));
+        assert!(!Regex::new("a*b").unwrap().is_match(&long));
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use crate::reader::read_document;
+use crate::{JsonPointer, ReaderError, Value};
+
+#[derive(Debug)]
+pub enum ResolveError {
+    /// A referenced file could not be read or parsed
+    Load { path: PathBuf, error: ReaderError },
+
+    /// The part after `#` is not a JSON Pointer
+    InvalidReference(String),
+
+    /// The reference points to a location that does not exist
+    NotFound(String),
+
+    /// The reference is reached again while it is being resolved
+    Cycle(String),
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Load { path, error } => write!(f, "loading {} failed: {error}", path.display()),
+            Self::InvalidReference(reference) => write!(f, "invalid reference {reference:?}"),
+            Self::NotFound(reference) => write!(f, "nothing exists at {reference:?}"),
+            Self::Cycle(reference) => write!(f, "reference {reference:?} is part of a cycle"),
+        }
+    }
+}
+
+impl std::error::Error for ResolveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Load { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+type Loader = fn(&Path) -> Result<Value, ReaderError>;
+
+/// Splices referenced documents into a value, replacing `{"$include": "other.json"}` and
+/// `{"$ref": "#/defs/x"}` nodes
+///
+/// A node is a reference when it is an object with a `$ref` or `$include` string as its
+/// only member. Both take a file path, a `#` followed by a JSON Pointer, or a path and
+/// then a pointer, ex. `common.json#/servers`. Paths are relative to the file holding the
+/// reference, and pointers without a path refer to that same document. Referenced values
+/// are resolved in turn, and each file is loaded once per resolver.
+pub struct Resolver<L = Loader> {
+    loader: L,
+    files: HashMap<PathBuf, Value>,
+    /// The references being resolved, to detect cycles
+    stack: Vec<(Option<PathBuf>, JsonPointer)>,
+}
+
+impl Resolver {
+    /// A resolver reading files from disk
+    pub fn new() -> Self {
+        Self::with_loader(|path| read_document(File::open(path)?))
+    }
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L: FnMut(&Path) -> Result<Value, ReaderError>> Resolver<L> {
+    /// A resolver that reads referenced files with `loader`
+    pub fn with_loader(loader: L) -> Self {
+        Self {
+            loader,
+            files: HashMap::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    /// Resolves the references in `value`, file paths are relative to the working directory
+    pub fn resolve(&mut self, value: &Value) -> Result<Value, ResolveError> {
+        self.resolve_document(value, None)
+    }
+
+    /// Loads the file at `path` and resolves the references in it
+    pub fn resolve_file(&mut self, path: impl AsRef<Path>) -> Result<Value, ResolveError> {
+        let path = path.as_ref();
+        let document = self.load(path)?;
+        self.resolve_document(&document, Some(path))
+    }
+
+    fn resolve_document(
+        &mut self,
+        document: &Value,
+        file: Option<&Path>,
+    ) -> Result<Value, ResolveError> {
+        self.stack
+            .push((file.map(Path::to_path_buf), JsonPointer::root()));
+        let result = self.resolve_at(document, file, document);
+        self.stack.pop();
+        result
+    }
+
+    fn load(&mut self, path: &Path) -> Result<Value, ResolveError> {
+        if let Some(document) = self.files.get(path) {
+            return Ok(document.clone());
+        }
+        let document = (self.loader)(path).map_err(|error| ResolveError::Load {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        self.files.insert(path.to_path_buf(), document.clone());
+        Ok(document)
+    }
+
+    /// Resolves `value`, which is part of `root`, the document read from `file`
+    fn resolve_at(
+        &mut self,
+        value: &Value,
+        file: Option<&Path>,
+        root: &Value,
+    ) -> Result<Value, ResolveError> {
+        if let Some(reference) = reference(value) {
+            return self.follow(reference, file, root);
+        }
+        Ok(match value {
+            Value::Array(array) => Value::Array(
+                array
+                    .iter()
+                    .map(|item| self.resolve_at(item, file, root))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(key, item)| Ok((key.clone(), self.resolve_at(item, file, root)?)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            value => value.clone(),
+        })
+    }
+
+    fn follow(
+        &mut self,
+        reference: &str,
+        file: Option<&Path>,
+        root: &Value,
+    ) -> Result<Value, ResolveError> {
+        let (path, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+        let pointer = JsonPointer::parse(fragment)
+            .map_err(|_| ResolveError::InvalidReference(String::from(reference)))?;
+        let target_file = match path {
+            "" => file.map(Path::to_path_buf),
+            path => {
+                let dir = file.and_then(Path::parent).unwrap_or(Path::new(""));
+                Some(dir.join(path))
+            }
+        };
+
+        let key = (target_file, pointer);
+        if self.stack.contains(&key) {
+            return Err(ResolveError::Cycle(String::from(reference)));
+        }
+        let loaded;
+        let (file, root) = match &key.0 {
+            Some(path) if Some(path.as_path()) != file => {
+                loaded = self.load(path)?;
+                (Some(path.clone()), &loaded)
+            }
+            _ => (file.map(Path::to_path_buf), root),
+        };
+        let target = root
+            .pointer(fragment)
+            .ok_or_else(|| ResolveError::NotFound(String::from(reference)))?;
+
+        self.stack.push(key);
+        let result = self.resolve_at(target, file.as_deref(), root);
+        self.stack.pop();
+        result
+    }
+}
+
+/// The target of a `$ref` or `$include` node
+fn reference(value: &Value) -> Option<&str> {
+    let Value::Object(map) = value else {
+        return None;
+    };
+    match map.iter().next() {
+        Some((key, Value::String(target)))
+            if map.len() == 1 && (key == "$ref" || key == "$include") =>
+        {
+            Some(target)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{Parser, ReaderError, Value};
+
+    use super::{ResolveError, Resolver};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    fn files(path: &Path) -> Result<Value, ReaderError> {
+        let text = match path.to_str().unwrap() {
+            "config/main.json" => {
+                r##"{"db": {"$include": "db.json"}, "port": {"$ref": "#/defs/port"},
+                     "defs": {"port": 80}}"##
+            }
+            "config/db.json" => r##"{"hosts": {"$ref": "shared/hosts.json#/primary"}}"##,
+            "config/shared/hosts.json" => {
+                r##"{"primary": ["a", {"$ref": "#/backup"}], "backup": "b"}"##
+            }
+            "loop/a.json" => r##"{"next": {"$include": "b.json"}}"##,
+            "loop/b.json" => r##"{"next": {"$include": "a.json"}}"##,
+            _ => return Err(ReaderError::UnexpectedEof),
+        };
+        Ok(value(text))
+    }
+
+    #[test]
+    fn splices_files_and_pointers() {
+        let resolved = Resolver::with_loader(files)
+            .resolve_file("config/main.json")
+            .unwrap();
+        let expected =
+            value(r##"{"db": {"hosts": ["a", "b"]}, "port": 80, "defs": {"port": 80}}"##);
+        assert_eq!(resolved, expected);
+    }
+
+    #[test]
+    fn local_references() {
+        let document = value(
+            r##"{"defs": {"a": {"x": {"$ref": "#/defs/b"}}, "b": [1]},
+                "use": {"$ref": "#/defs/a"}, "other": {"$ref": "#/defs/b", "extra": 1}}"##,
+        );
+        let expected = value(
+            r##"{"defs": {"a": {"x": [1]}, "b": [1]},
+                "use": {"x": [1]}, "other": {"$ref": "#/defs/b", "extra": 1}}"##,
+        );
+        assert_eq!(Resolver::new().resolve(&document).unwrap(), expected);
+    }
+
+    #[test]
+    fn errors() {
+        let mut resolver = Resolver::with_loader(files);
+        assert!(matches!(
+            resolver.resolve_file("loop/a.json"),
+            Err(ResolveError::Cycle(reference)) if reference == "a.json"
+        ));
+        let document = value(r##"{"a": {"b": {"$ref": "#/a"}}}"##);
+        assert!(matches!(
+            resolver.resolve(&document),
+            Err(ResolveError::Cycle(_))
+        ));
+        let document = value(r##"{"$ref": "#/missing"}"##);
+        assert!(matches!(
+            resolver.resolve(&document),
+            Err(ResolveError::NotFound(_))
+        ));
+        let document = value(r##"{"$ref": "#missing"}"##);
+        assert!(matches!(
+            resolver.resolve(&document),
+            Err(ResolveError::InvalidReference(_))
+        ));
+        let document = value(r##"{"$include": "nope.json"}"##);
+        assert!(matches!(
+            resolver.resolve(&document),
+            Err(ResolveError::Load { path, .. }) if path == Path::new("nope.json")
+        ));
+    }
+}
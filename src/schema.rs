@@ -0,0 +1,711 @@
+//! JSON Schema validation, covering the draft-07 core keywords
+//!
+//! Supported keywords: `type`, `enum`, `const`, `properties`, `required`,
+//! `additionalProperties`, `items`, `additionalItems`, `minItems`, `maxItems`,
+//! `minimum`, `maximum`, `exclusiveMinimum`, `exclusiveMaximum`, `minLength`,
+//! `maxLength`, `pattern`, `allOf`, `anyOf`, `oneOf` and `not`. Unknown keywords are ignored.
+//!
+//! [`infer`] goes the other way, generating a schema from sample documents.
+
+mod infer;
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::regex::Regex;
+use crate::{JsonPointer, Value};
+
+pub use infer::infer;
+
+/// A compiled JSON Schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schema {
+    node: Node,
+}
+
+/// The schema document itself is invalid
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaError {
+    /// A schema must be an object or a boolean
+    NotASchema(JsonPointer),
+
+    /// The keyword's value has the wrong shape, ex. `"minimum": "3"`
+    InvalidKeyword {
+        pointer: JsonPointer,
+        keyword: &'static str,
+    },
+
+    /// The `pattern` is not a supported regular expression
+    InvalidPattern {
+        pointer: JsonPointer,
+        pattern: String,
+    },
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotASchema(pointer) => {
+                write!(f, "the schema at `{pointer}` is not an object or a boolean")
+            }
+            Self::InvalidKeyword { pointer, keyword } => {
+                write!(f, "invalid `{keyword}` keyword at `{pointer}`")
+            }
+            Self::InvalidPattern { pointer, pattern } => {
+                write!(f, "unsupported pattern {pattern:?} at `{pointer}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// A place where the instance does not conform to the schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    /// The location of the failing value within the instance
+    pub pointer: JsonPointer,
+    pub kind: ValidationErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationErrorKind {
+    /// The `false` schema accepts nothing
+    FalseSchema,
+    TypeMismatch {
+        expected: Vec<&'static str>,
+        found: &'static str,
+    },
+    NotInEnum,
+    NotConst,
+    MissingProperty(String),
+    AdditionalProperty(String),
+    BelowMinimum(f64),
+    AboveMaximum(f64),
+    TooShort(usize),
+    TooLong(usize),
+    TooFewItems(usize),
+    TooManyItems(usize),
+    AdditionalItems,
+    PatternMismatch(String),
+    /// None of the `anyOf` schemas matched
+    NoneMatched,
+    /// `oneOf` requires exactly one match
+    OneOfMatched(usize),
+    /// The `not` schema matched
+    NotMatched,
+}
+
+impl fmt::Display for ValidationErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FalseSchema => f.write_str("the schema accepts nothing"),
+            Self::TypeMismatch { expected, found } => {
+                write!(f, "expected {}, found {found}", expected.join(" or "))
+            }
+            Self::NotInEnum => f.write_str("the value is not one of the `enum` values"),
+            Self::NotConst => f.write_str("the value is not the `const` value"),
+            Self::MissingProperty(key) => write!(f, "missing property `{key}`"),
+            Self::AdditionalProperty(key) => write!(f, "property `{key}` is not allowed"),
+            Self::BelowMinimum(minimum) => write!(f, "the number is below the minimum {minimum}"),
+            Self::AboveMaximum(maximum) => write!(f, "the number is above the maximum {maximum}"),
+            Self::TooShort(length) => write!(f, "the string is shorter than {length}"),
+            Self::TooLong(length) => write!(f, "the string is longer than {length}"),
+            Self::TooFewItems(count) => write!(f, "the array has fewer than {count} items"),
+            Self::TooManyItems(count) => write!(f, "the array has more than {count} items"),
+            Self::AdditionalItems => f.write_str("the array has more items than `items` allows"),
+            Self::PatternMismatch(pattern) => write!(f, "the string does not match {pattern:?}"),
+            Self::NoneMatched => f.write_str("none of the `anyOf` schemas match"),
+            Self::OneOfMatched(count) => {
+                write!(f, "{count} of the `oneOf` schemas match instead of one")
+            }
+            Self::NotMatched => f.write_str("the `not` schema matches"),
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at `{}`", self.kind, self.pointer)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Bool(bool),
+    Keywords(Box<Keywords>),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Keywords {
+    types: Option<Vec<&'static str>>,
+    enumeration: Option<Vec<Value>>,
+    constant: Option<Value>,
+    properties: Vec<(String, Node)>,
+    required: Vec<String>,
+    additional_properties: Option<Node>,
+    items: Option<Items>,
+    additional_items: Option<Node>,
+    min_items: Option<usize>,
+    max_items: Option<usize>,
+    minimum: Option<f64>,
+    maximum: Option<f64>,
+    exclusive_minimum: Option<f64>,
+    exclusive_maximum: Option<f64>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    pattern: Option<(String, Regex)>,
+    all_of: Vec<Node>,
+    any_of: Vec<Node>,
+    one_of: Vec<Node>,
+    not: Option<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Items {
+    All(Node),
+    Tuple(Vec<Node>),
+}
+
+const TYPES: [&str; 7] = [
+    "null", "boolean", "object", "array", "number", "string", "integer",
+];
+
+impl Schema {
+    pub fn compile(schema: &Value) -> Result<Self, SchemaError> {
+        let node = compile_node(schema, &mut JsonPointer::root())?;
+        Ok(Self { node })
+    }
+
+    /// Validates `instance`, collecting every violation rather than stopping at the first
+    pub fn validate(&self, instance: &Value) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        validate_node(&self.node, instance, &mut JsonPointer::root(), &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn is_valid(&self, instance: &Value) -> bool {
+        self.validate(instance).is_ok()
+    }
+}
+
+fn compile_node(schema: &Value, pointer: &mut JsonPointer) -> Result<Node, SchemaError> {
+    let map = match schema {
+        Value::Boolean(b) => return Ok(Node::Bool(*b)),
+        Value::Object(map) => map,
+        _ => return Err(SchemaError::NotASchema(pointer.clone())),
+    };
+
+    let mut keywords = Keywords::default();
+    let here = pointer.clone();
+    let invalid = |keyword| SchemaError::InvalidKeyword {
+        pointer: here.clone(),
+        keyword,
+    };
+
+    if let Some(types) = map.get("type") {
+        let names = match types {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names
+                .iter()
+                .map(|n| n.as_str().ok_or(invalid("type")))
+                .collect::<Result<_, _>>()?,
+            _ => return Err(invalid("type")),
+        };
+        let types = names
+            .into_iter()
+            .map(|name| {
+                TYPES
+                    .into_iter()
+                    .find(|t| *t == name)
+                    .ok_or(invalid("type"))
+            })
+            .collect::<Result<_, _>>()?;
+        keywords.types = Some(types);
+    }
+    if let Some(values) = map.get("enum") {
+        keywords.enumeration = Some(values.as_array().ok_or(invalid("enum"))?.clone());
+    }
+    keywords.constant = map.get("const").cloned();
+
+    if let Some(properties) = map.get("properties") {
+        let properties = properties.as_object().ok_or(invalid("properties"))?;
+        let mut names: Vec<&String> = properties.keys().collect();
+        names.sort();
+        for name in names {
+            pointer.push("properties");
+            pointer.push(name.clone());
+            let node = compile_node(&properties[name], pointer)?;
+            pointer.pop();
+            pointer.pop();
+            keywords.properties.push((name.clone(), node));
+        }
+    }
+    if let Some(required) = map.get("required") {
+        let required = required.as_array().ok_or(invalid("required"))?;
+        keywords.required = required
+            .iter()
+            .map(|name| name.as_str().map(String::from).ok_or(invalid("required")))
+            .collect::<Result<_, _>>()?;
+    }
+    keywords.additional_properties = compile_child(map, "additionalProperties", pointer)?;
+
+    match map.get("items") {
+        Some(Value::Array(schemas)) => {
+            keywords.items = Some(Items::Tuple(compile_list(schemas, "items", pointer)?));
+        }
+        Some(_) => keywords.items = compile_child(map, "items", pointer)?.map(Items::All),
+        None => {}
+    }
+    keywords.additional_items = compile_child(map, "additionalItems", pointer)?;
+
+    let count = |keyword| match map.get(keyword) {
+        None => Ok(None),
+        Some(value) => match value.as_i64() {
+            Some(n) if n >= 0 => Ok(Some(n as usize)),
+            _ => Err(invalid(keyword)),
+        },
+    };
+    keywords.min_items = count("minItems")?;
+    keywords.max_items = count("maxItems")?;
+    keywords.min_length = count("minLength")?;
+    keywords.max_length = count("maxLength")?;
+
+    let number = |keyword| match map.get(keyword) {
+        None => Ok(None),
+        Some(value) => value.as_f64().map(Some).ok_or(invalid(keyword)),
+    };
+    keywords.minimum = number("minimum")?;
+    keywords.maximum = number("maximum")?;
+    keywords.exclusive_minimum = number("exclusiveMinimum")?;
+    keywords.exclusive_maximum = number("exclusiveMaximum")?;
+
+    if let Some(pattern) = map.get("pattern") {
+        let pattern = pattern.as_str().ok_or(invalid("pattern"))?;
+        let regex = Regex::new(pattern).map_err(|_| SchemaError::InvalidPattern {
+            pointer: pointer.clone(),
+            pattern: String::from(pattern),
+        })?;
+        keywords.pattern = Some((String::from(pattern), regex));
+    }
+
+    for (keyword, list) in [
+        ("allOf", &mut keywords.all_of),
+        ("anyOf", &mut keywords.any_of),
+        ("oneOf", &mut keywords.one_of),
+    ] {
+        if let Some(schemas) = map.get(keyword) {
+            let schemas = schemas.as_array().ok_or(invalid(keyword))?;
+            *list = compile_list(schemas, keyword, pointer)?;
+        }
+    }
+    keywords.not = compile_child(map, "not", pointer)?;
+
+    Ok(Node::Keywords(Box::new(keywords)))
+}
+
+fn compile_child(
+    map: &HashMap<String, Value>,
+    keyword: &'static str,
+    pointer: &mut JsonPointer,
+) -> Result<Option<Node>, SchemaError> {
+    let Some(schema) = map.get(keyword) else {
+        return Ok(None);
+    };
+    pointer.push(keyword);
+    let node = compile_node(schema, pointer)?;
+    pointer.pop();
+    Ok(Some(node))
+}
+
+fn compile_list(
+    schemas: &[Value],
+    keyword: &'static str,
+    pointer: &mut JsonPointer,
+) -> Result<Vec<Node>, SchemaError> {
+    let mut nodes = Vec::new();
+    for (i, schema) in schemas.iter().enumerate() {
+        pointer.push(keyword);
+        pointer.push(i.to_string());
+        nodes.push(compile_node(schema, pointer)?);
+        pointer.pop();
+        pointer.pop();
+    }
+    Ok(nodes)
+}
+
+fn validate_node(
+    node: &Node,
+    instance: &Value,
+    pointer: &mut JsonPointer,
+    errors: &mut Vec<ValidationError>,
+) {
+    let keywords = match node {
+        Node::Bool(true) => return,
+        Node::Bool(false) => {
+            errors.push(ValidationError {
+                pointer: pointer.clone(),
+                kind: ValidationErrorKind::FalseSchema,
+            });
+            return;
+        }
+        Node::Keywords(keywords) => keywords,
+    };
+    let mut fail = |kind| {
+        errors.push(ValidationError {
+            pointer: pointer.clone(),
+            kind,
+        })
+    };
+
+    if let Some(types) = &keywords.types
+        && !types.iter().any(|t| has_type(instance, t))
+    {
+        fail(ValidationErrorKind::TypeMismatch {
+            expected: types.clone(),
+            found: instance.type_name(),
+        });
+    }
+    if let Some(values) = &keywords.enumeration
+        && !values.contains(instance)
+    {
+        fail(ValidationErrorKind::NotInEnum);
+    }
+    if let Some(constant) = &keywords.constant
+        && constant != instance
+    {
+        fail(ValidationErrorKind::NotConst);
+    }
+
+    match instance {
+        Value::Number(n) => {
+            if let Some(min) = keywords.minimum.filter(|min| n < min) {
+                fail(ValidationErrorKind::BelowMinimum(min));
+            }
+            if let Some(min) = keywords.exclusive_minimum.filter(|min| n <= min) {
+                fail(ValidationErrorKind::BelowMinimum(min));
+            }
+            if let Some(max) = keywords.maximum.filter(|max| n > max) {
+                fail(ValidationErrorKind::AboveMaximum(max));
+            }
+            if let Some(max) = keywords.exclusive_maximum.filter(|max| n >= max) {
+                fail(ValidationErrorKind::AboveMaximum(max));
+            }
+        }
+        Value::String(s) => {
+            let len = s.chars().count();
+            if let Some(min) = keywords.min_length.filter(|min| len < *min) {
+                fail(ValidationErrorKind::TooShort(min));
+            }
+            if let Some(max) = keywords.max_length.filter(|max| len > *max) {
+                fail(ValidationErrorKind::TooLong(max));
+            }
+            if let Some((pattern, regex)) = &keywords.pattern
+                && !regex.is_match(s)
+            {
+                fail(ValidationErrorKind::PatternMismatch(pattern.clone()));
+            }
+        }
+        Value::Array(array) => {
+            if let Some(min) = keywords.min_items.filter(|min| array.len() < *min) {
+                fail(ValidationErrorKind::TooFewItems(min));
+            }
+            if let Some(max) = keywords.max_items.filter(|max| array.len() > *max) {
+                fail(ValidationErrorKind::TooManyItems(max));
+            }
+            validate_items(keywords, array, pointer, errors);
+        }
+        Value::Object(map) => validate_properties(keywords, map, pointer, errors),
+        _ => {}
+    }
+
+    validate_combinators(keywords, instance, pointer, errors);
+}
+
+fn has_type(instance: &Value, name: &str) -> bool {
+    match (name, instance) {
+        ("integer", Value::Number(n)) => n.fract() == 0.0,
+        (name, instance) => name == instance.type_name(),
+    }
+}
+
+fn validate_properties(
+    keywords: &Keywords,
+    map: &HashMap<String, Value>,
+    pointer: &mut JsonPointer,
+    errors: &mut Vec<ValidationError>,
+) {
+    for name in &keywords.required {
+        if !map.contains_key(name) {
+            errors.push(ValidationError {
+                pointer: pointer.clone(),
+                kind: ValidationErrorKind::MissingProperty(name.clone()),
+            });
+        }
+    }
+
+    let mut names: Vec<&String> = map.keys().collect();
+    names.sort();
+    for name in names {
+        pointer.push(name.clone());
+        match keywords
+            .properties
+            .iter()
+            .find(|(property, _)| property == name)
+        {
+            Some((_, node)) => validate_node(node, &map[name], pointer, errors),
+            None => match &keywords.additional_properties {
+                Some(Node::Bool(false)) => {
+                    pointer.pop();
+                    errors.push(ValidationError {
+                        pointer: pointer.clone(),
+                        kind: ValidationErrorKind::AdditionalProperty(name.clone()),
+                    });
+                    continue;
+                }
+                Some(node) => validate_node(node, &map[name], pointer, errors),
+                None => {}
+            },
+        }
+        pointer.pop();
+    }
+}
+
+fn validate_items(
+    keywords: &Keywords,
+    array: &[Value],
+    pointer: &mut JsonPointer,
+    errors: &mut Vec<ValidationError>,
+) {
+    for (i, item) in array.iter().enumerate() {
+        let node = match &keywords.items {
+            Some(Items::All(node)) => node,
+            Some(Items::Tuple(nodes)) => match (nodes.get(i), &keywords.additional_items) {
+                (Some(node), _) => node,
+                (None, Some(Node::Bool(false))) => {
+                    errors.push(ValidationError {
+                        pointer: pointer.clone(),
+                        kind: ValidationErrorKind::AdditionalItems,
+                    });
+                    return;
+                }
+                (None, Some(node)) => node,
+                (None, None) => return,
+            },
+            None => return,
+        };
+        pointer.push(i.to_string());
+        validate_node(node, item, pointer, errors);
+        pointer.pop();
+    }
+}
+
+fn validate_combinators(
+    keywords: &Keywords,
+    instance: &Value,
+    pointer: &mut JsonPointer,
+    errors: &mut Vec<ValidationError>,
+) {
+    for node in &keywords.all_of {
+        validate_node(node, instance, pointer, errors);
+    }
+
+    let matches = |node: &Node| {
+        let mut scratch = Vec::new();
+        validate_node(node, instance, &mut pointer.clone(), &mut scratch);
+        scratch.is_empty()
+    };
+    if !keywords.any_of.is_empty() && !keywords.any_of.iter().any(matches) {
+        errors.push(ValidationError {
+            pointer: pointer.clone(),
+            kind: ValidationErrorKind::NoneMatched,
+        });
+    }
+    if !keywords.one_of.is_empty() {
+        let matched = keywords.one_of.iter().filter(|node| matches(node)).count();
+        if matched != 1 {
+            errors.push(ValidationError {
+                pointer: pointer.clone(),
+                kind: ValidationErrorKind::OneOfMatched(matched),
+            });
+        }
+    }
+    if keywords.not.as_ref().is_some_and(matches) {
+        errors.push(ValidationError {
+            pointer: pointer.clone(),
+            kind: ValidationErrorKind::NotMatched,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{JsonPointer, Parser, Value};
+
+    use super::{Schema, SchemaError, ValidationError, ValidationErrorKind};
+
+    fn compile(input: &str) -> Schema {
+        Schema::compile(&Parser::new().parse_str(input).unwrap()).unwrap()
+    }
+
+    fn errors(schema: &Schema, instance: &str) -> Vec<(String, ValidationErrorKind)> {
+        let instance = Parser::new().parse_str(instance).unwrap();
+        match schema.validate(&instance) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors
+                .into_iter()
+                .map(|e| (e.pointer.to_string(), e.kind))
+                .collect(),
+        }
+    }
+
+    const USER: &str = r#"{
+        "type": "object",
+        "required": ["name", "age"],
+        "properties": {
+            "name": {"type": "string", "minLength": 1},
+            "age": {"type": "integer", "minimum": 0, "maximum": 150},
+            "email": {"type": "string", "pattern": "^[^@]+@[^@]+$"},
+            "role": {"enum": ["admin", "user"]},
+            "tags": {"type": "array", "items": {"type": "string"}, "maxItems": 2}
+        }
+    }"#;
+
+    #[test]
+    fn valid_instance() {
+        let schema = compile(USER);
+        let instance = r#"{"name": "ken", "age": 35, "email": "ken@example.com", "tags": ["a"]}"#;
+        assert_eq!(errors(&schema, instance), vec![]);
+    }
+
+    #[test]
+    fn reports_every_violation_with_pointers() {
+        let schema = compile(USER);
+        let instance = r#"{"age": 35.5, "email": "nope", "role": "root", "tags": ["a", 2, "c"]}"#;
+        let expected = vec![
+            (
+                String::new(),
+                ValidationErrorKind::MissingProperty(String::from("name")),
+            ),
+            (
+                String::from("/age"),
+                ValidationErrorKind::TypeMismatch {
+                    expected: vec!["integer"],
+                    found: "number",
+                },
+            ),
+            (
+                String::from("/email"),
+                ValidationErrorKind::PatternMismatch(String::from("^[^@]+@[^@]+$")),
+            ),
+            (String::from("/role"), ValidationErrorKind::NotInEnum),
+            (String::from("/tags"), ValidationErrorKind::TooManyItems(2)),
+            (
+                String::from("/tags/1"),
+                ValidationErrorKind::TypeMismatch {
+                    expected: vec!["string"],
+                    found: "number",
+                },
+            ),
+        ];
+        assert_eq!(errors(&schema, instance), expected);
+    }
+
+    #[test]
+    fn numeric_bounds() {
+        let schema = compile(r#"{"minimum": 1, "exclusiveMaximum": 10}"#);
+        assert_eq!(
+            errors(&schema, "0"),
+            vec![(String::new(), ValidationErrorKind::BelowMinimum(1.0))]
+        );
+        assert_eq!(
+            errors(&schema, "10"),
+            vec![(String::new(), ValidationErrorKind::AboveMaximum(10.0))]
+        );
+        assert_eq!(errors(&schema, "\"not a number\""), vec![]);
+    }
+
+    #[test]
+    fn additional_properties_and_tuples() {
+        let schema = compile(
+            r#"{"properties": {"a": {}}, "additionalProperties": false,
+                "items": [{"type": "number"}], "additionalItems": false}"#,
+        );
+        assert_eq!(
+            errors(&schema, r#"{"a": 1, "b": 2}"#),
+            vec![(
+                String::new(),
+                ValidationErrorKind::AdditionalProperty(String::from("b"))
+            )]
+        );
+        assert_eq!(
+            errors(&schema, "[1, 2]"),
+            vec![(String::new(), ValidationErrorKind::AdditionalItems)]
+        );
+    }
+
+    #[test]
+    fn combinators() {
+        let schema =
+            compile(r#"{"anyOf": [{"type": "string"}, {"type": "null"}], "not": {"const": "x"}}"#);
+        assert!(schema.is_valid(&Value::Null));
+        assert_eq!(
+            errors(&schema, "1"),
+            vec![(String::new(), ValidationErrorKind::NoneMatched)]
+        );
+        assert_eq!(
+            errors(&schema, r#""x""#),
+            vec![(String::new(), ValidationErrorKind::NotMatched)]
+        );
+
+        let one_of = compile(r#"{"oneOf": [{"type": "number"}, {"type": "integer"}]}"#);
+        assert_eq!(
+            errors(&one_of, "1"),
+            vec![(String::new(), ValidationErrorKind::OneOfMatched(2))]
+        );
+        assert!(one_of.is_valid(&Value::Number(1.5)));
+    }
+
+    #[test]
+    fn boolean_schemas() {
+        let schema = compile(r#"{"properties": {"never": false}}"#);
+        let expected = ValidationError {
+            pointer: JsonPointer::parse("/never").unwrap(),
+            kind: ValidationErrorKind::FalseSchema,
+        };
+        let instance = Value::object([("never", Value::Null)]);
+        assert_eq!(schema.validate(&instance), Err(vec![expected]));
+    }
+
+    #[test]
+    fn invalid_schemas() {
+        let try_compile = |input: &str| Schema::compile(&Parser::new().parse_str(input).unwrap());
+        assert_eq!(
+            try_compile(r#"{"properties": {"a": {"type": "strin"}}}"#),
+            Err(SchemaError::InvalidKeyword {
+                pointer: JsonPointer::parse("/properties/a").unwrap(),
+                keyword: "type",
+            })
+        );
+        assert_eq!(
+            try_compile("1"),
+            Err(SchemaError::NotASchema(JsonPointer::root()))
+        );
+        assert!(matches!(
+            try_compile(r#"{"pattern": "("}"#),
+            Err(SchemaError::InvalidPattern { .. })
+        ));
+        assert_eq!(
+            try_compile(r#"{"items": {"minLength": "x"}}"#)
+                .unwrap_err()
+                .to_string(),
+            "invalid `minLength` keyword at `/items`"
+        );
+    }
+}
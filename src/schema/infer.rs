@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::Value;
+
+/// The union of everything seen at one location across the samples
+#[derive(Debug, Default)]
+struct Shape {
+    types: BTreeSet<&'static str>,
+    /// How many of the samples at this location were objects
+    objects: usize,
+    properties: BTreeMap<String, (Shape, usize)>,
+    items: Option<Box<Shape>>,
+}
+
+impl Shape {
+    fn add(&mut self, value: &Value) {
+        let name = match value {
+            Value::Number(n) if n.fract() == 0.0 => "integer",
+            other => other.type_name(),
+        };
+        self.types.insert(name);
+
+        match value {
+            Value::Object(map) => {
+                self.objects += 1;
+                for (key, value) in map {
+                    let (shape, count) = self.properties.entry(key.clone()).or_default();
+                    shape.add(value);
+                    *count += 1;
+                }
+            }
+            Value::Array(array) => {
+                let items = self.items.get_or_insert_with(Box::default);
+                for item in array {
+                    items.add(item);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn to_schema(&self) -> Value {
+        let mut schema = HashMap::new();
+
+        let mut types: Vec<&str> = self.types.iter().copied().collect();
+        if self.types.contains("number") {
+            types.retain(|t| *t != "integer");
+        }
+        match types.as_slice() {
+            [] => {}
+            [single] => {
+                schema.insert(String::from("type"), Value::String(String::from(*single)));
+            }
+            many => {
+                let many = many
+                    .iter()
+                    .map(|t| Value::String(String::from(*t)))
+                    .collect();
+                schema.insert(String::from("type"), Value::Array(many));
+            }
+        }
+
+        if self.objects > 0 {
+            let properties = self
+                .properties
+                .iter()
+                .map(|(key, (shape, _))| (key.clone(), shape.to_schema()))
+                .collect();
+            let required = self
+                .properties
+                .iter()
+                .filter(|(_, (_, count))| *count == self.objects)
+                .map(|(key, _)| Value::String(key.clone()))
+                .collect();
+            schema.insert(String::from("properties"), Value::Object(properties));
+            schema.insert(String::from("required"), Value::Array(required));
+        }
+        if let Some(items) = &self.items
+            && !items.types.is_empty()
+        {
+            schema.insert(String::from("items"), items.to_schema());
+        }
+        Value::Object(schema)
+    }
+}
+
+/// Generates a JSON Schema that every one of the `samples` conforms to
+///
+/// Properties present in every object sample are `required`, array items share a
+/// single schema, and integers widen to `number` when mixed with fractional numbers.
+pub fn infer(samples: &[Value]) -> Value {
+    let mut shape = Shape::default();
+    for sample in samples {
+        shape.add(sample);
+    }
+    shape.to_schema()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::Schema;
+    use crate::{Parser, Value};
+
+    use super::infer;
+
+    fn samples(inputs: &[&str]) -> Vec<Value> {
+        inputs
+            .iter()
+            .map(|input| Parser::new().parse_str(input).unwrap())
+            .collect()
+    }
+
+    fn check(inputs: &[&str], expected: &str) {
+        let actual = infer(&samples(inputs));
+        assert_eq!(actual, Parser::new().parse_str(expected).unwrap());
+    }
+
+    #[test]
+    fn scalars() {
+        check(&["1"], r#"{"type": "integer"}"#);
+        check(&["1", "1.5"], r#"{"type": "number"}"#);
+        check(&[r#""a""#, "null"], r#"{"type": ["null", "string"]}"#);
+    }
+
+    #[test]
+    fn optional_properties() {
+        check(
+            &[r#"{"id": 1, "name": "a"}"#, r#"{"id": 2}"#],
+            r#"{
+                "type": "object",
+                "properties": {"id": {"type": "integer"}, "name": {"type": "string"}},
+                "required": ["id"]
+            }"#,
+        );
+    }
+
+    #[test]
+    fn array_items() {
+        check(
+            &["[1, \"a\"]", "[]"],
+            r#"{"type": "array", "items": {"type": ["integer", "string"]}}"#,
+        );
+        check(&["[]"], r#"{"type": "array"}"#);
+    }
+
+    #[test]
+    fn samples_validate_against_inferred_schema() {
+        let samples = samples(&[
+            r#"{"users": [{"name": "ken", "tags": ["a"]}, {"name": "ryu", "age": 3}]}"#,
+            r#"{"users": [], "next": null}"#,
+        ]);
+        let schema = Schema::compile(&infer(&samples)).unwrap();
+        for sample in &samples {
+            assert!(schema.is_valid(sample));
+        }
+        assert!(!schema.is_valid(&Value::object([("users", Value::Null)])));
+    }
+}
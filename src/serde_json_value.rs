@@ -0,0 +1,85 @@
+//! Conversions to and from `serde_json::Value`, enabled with the `serde_json` feature
+
+use crate::Value;
+
+impl From<serde_json::Value> for Value {
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Boolean(b),
+            serde_json::Value::Number(n) => Value::Number(n.as_f64().unwrap_or(f64::NAN)),
+            serde_json::Value::String(s) => Value::String(s),
+            serde_json::Value::Array(array) => {
+                Value::Array(array.into_iter().map(Value::from).collect())
+            }
+            serde_json::Value::Object(map) => {
+                Value::Object(map.into_iter().map(|(k, v)| (k, Value::from(v))).collect())
+            }
+        }
+    }
+}
+
+impl From<Value> for serde_json::Value {
+    /// Integral numbers become JSON integers, non-finite numbers become `null`
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => serde_json::Value::Null,
+            Value::Boolean(b) => serde_json::Value::Bool(b),
+            Value::Number(n) => number(n),
+            Value::String(s) => serde_json::Value::String(s),
+            Value::Array(array) => {
+                serde_json::Value::Array(array.into_iter().map(serde_json::Value::from).collect())
+            }
+            Value::Object(map) => serde_json::Value::Object(
+                map.into_iter()
+                    .map(|(k, v)| (k, serde_json::Value::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+fn number(n: f64) -> serde_json::Value {
+    // 2^63, the first value outside of the i64 range
+    const I64_LIMIT: f64 = 9_223_372_036_854_775_808.0;
+
+    if n.fract() == 0.0 && (-I64_LIMIT..I64_LIMIT).contains(&n) {
+        serde_json::Value::from(n as i64)
+    } else {
+        serde_json::Number::from_f64(n).map_or(serde_json::Value::Null, serde_json::Value::Number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    fn both(input: &str) -> (Value, serde_json::Value) {
+        let ours = Parser::new().parse_str(input).unwrap();
+        let theirs = serde_json::from_str(input).unwrap();
+        (ours, theirs)
+    }
+
+    #[test]
+    fn converts_both_ways() {
+        let (ours, theirs) = both(r#"{"a": [1, 2.5, null, true], "b": {"c": "d"}}"#);
+        assert_eq!(Value::from(theirs.clone()), ours);
+        assert_eq!(serde_json::Value::from(ours), theirs);
+    }
+
+    #[test]
+    fn integral_numbers_stay_integers() {
+        let converted = serde_json::Value::from(Value::Number(3.0));
+        assert_eq!(converted.to_string(), "3");
+        assert_eq!(
+            serde_json::Value::from(Value::Number(-0.5)).to_string(),
+            "-0.5"
+        );
+    }
+
+    #[test]
+    fn non_finite_becomes_null() {
+        let converted = serde_json::Value::from(Value::Number(f64::INFINITY));
+        assert_eq!(converted, serde_json::Value::Null);
+    }
+}
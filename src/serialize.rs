@@ -0,0 +1,610 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt::{self, Write};
+use std::io;
+
+use crate::{JsonPointer, NumberTexts, Value};
+
+/// Escaping settings for [`Value::to_json_string_with`]
+///
+/// By default only what JSON requires is escaped: `"`, `\` and control characters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SerializeOptions {
+    /// Escape every non-ASCII character as `\uXXXX`, using surrogate pairs when needed
+    pub ascii_only: bool,
+
+    /// Escape `/` as `\/`, so `</script>` cannot appear in the output
+    pub escape_slash: bool,
+
+    /// Escape `<`, `>` and `&` as `\u003c`, `\u003e` and `\u0026` for embedding in HTML
+    pub escape_html: bool,
+
+    /// Escape U+2028 and U+2029, which end a line in JavaScript source before ES2019
+    pub escape_line_separators: bool,
+
+    /// Write integral numbers with a fraction, ex. `1.0` rather than `1`, for readers that
+    /// tell integers and floats apart
+    pub integral_fraction: bool,
+}
+
+impl SerializeOptions {
+    /// Every escape except `ascii_only`, safe to embed in HTML and JavaScript
+    pub fn web_safe() -> Self {
+        Self {
+            ascii_only: false,
+            escape_slash: true,
+            escape_html: true,
+            escape_line_separators: true,
+            integral_fraction: false,
+        }
+    }
+}
+
+impl<S> Value<S> {
+    /// Serializes to compact JSON text, with object keys in sorted order
+    ///
+    /// NaN and infinity have no JSON representation and are written as `null`.
+    pub fn to_json_string(&self) -> String {
+        self.to_json_string_with(&SerializeOptions::default())
+    }
+
+    /// Serializes to compact JSON text, escaping strings as configured
+    pub fn to_json_string_with(&self, options: &SerializeOptions) -> String {
+        let mut output = String::new();
+        write_json(self, options, None, 0, &mut output).unwrap();
+        output
+    }
+
+    /// Serializes to JSON text indented by two spaces, with object keys in sorted order
+    pub fn to_json_string_pretty(&self) -> String {
+        let mut output = String::new();
+        write_json(
+            self,
+            &SerializeOptions::default(),
+            Some("  "),
+            0,
+            &mut output,
+        )
+        .unwrap();
+        output
+    }
+}
+
+impl<S> fmt::Display for Value<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_json(self, &SerializeOptions::default(), None, 0, f)
+    }
+}
+
+/// Writes compact JSON text, without building the whole output in memory first
+///
+/// Output goes straight to `writer` in many small writes, so wrap files and sockets in
+/// an [`io::BufWriter`].
+pub fn write_value<W: io::Write, S>(writer: &mut W, value: &Value<S>) -> io::Result<()> {
+    write_value_with(writer, value, &SerializeOptions::default())
+}
+
+/// Writes JSON text indented by two spaces, see [`write_value`]
+pub fn write_value_pretty<W: io::Write, S>(writer: &mut W, value: &Value<S>) -> io::Result<()> {
+    let mut adapter = IoAdapter::new(writer);
+    let result = write_json(
+        value,
+        &SerializeOptions::default(),
+        Some("  "),
+        0,
+        &mut adapter,
+    );
+    adapter.finish(result)
+}
+
+/// Writes compact JSON text with the given escaping, see [`write_value`]
+pub fn write_value_with<W: io::Write, S>(
+    writer: &mut W,
+    value: &Value<S>,
+    options: &SerializeOptions,
+) -> io::Result<()> {
+    let mut adapter = IoAdapter::new(writer);
+    let result = write_json(value, options, None, 0, &mut adapter);
+    adapter.finish(result)
+}
+
+/// Writes a value nested `depth` levels deep, indented when `indent` is set
+pub(crate) fn write_nested<W: io::Write>(
+    writer: &mut W,
+    value: &Value,
+    options: &SerializeOptions,
+    indent: Option<&str>,
+    depth: usize,
+) -> io::Result<()> {
+    let mut adapter = IoAdapter::new(writer);
+    let result = write_json(value, options, indent, depth, &mut adapter);
+    adapter.finish(result)
+}
+
+/// Writes a quoted, escaped string to `writer`
+pub(crate) fn write_string_to<W: io::Write>(
+    writer: &mut W,
+    s: &str,
+    options: &SerializeOptions,
+) -> io::Result<()> {
+    let mut adapter = IoAdapter::new(writer);
+    let result = write_escaped(s, options, &mut adapter);
+    adapter.finish(result)
+}
+
+/// Lets the `fmt::Write` serializer write to an `io::Write`, keeping the real error
+struct IoAdapter<'a, W> {
+    inner: &'a mut W,
+    error: Option<io::Error>,
+}
+
+impl<'a, W: io::Write> IoAdapter<'a, W> {
+    fn new(inner: &'a mut W) -> Self {
+        Self { inner, error: None }
+    }
+
+    fn finish(self, result: fmt::Result) -> io::Result<()> {
+        match (result, self.error) {
+            (Ok(()), _) => Ok(()),
+            (Err(_), Some(err)) => Err(err),
+            (Err(_), None) => Err(io::Error::other("formatting failed")),
+        }
+    }
+}
+
+impl<W: io::Write> fmt::Write for IoAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
+
+/// Writes JSON text, putting each element on its own line when `indent` is set
+fn write_json<W: Write, S>(
+    value: &Value<S>,
+    options: &SerializeOptions,
+    indent: Option<&str>,
+    depth: usize,
+    output: &mut W,
+) -> fmt::Result {
+    write_json_at(value, options, indent, depth, &mut None, output)
+}
+
+/// Writes JSON text like [`Value::to_json_string`], except that numbers found unchanged
+/// in `texts` are written as they were in the source
+pub(crate) fn write_json_with_texts(
+    value: &Value,
+    texts: &NumberTexts,
+    indent: Option<&str>,
+    output: &mut String,
+) {
+    let mut numbers = Some((texts, JsonPointer::root()));
+    let options = SerializeOptions::default();
+    write_json_at(value, &options, indent, 0, &mut numbers, output).unwrap();
+}
+
+/// Writes JSON text, tracking the location of each value when `numbers` is set
+fn write_json_at<W: Write, S>(
+    value: &Value<S>,
+    options: &SerializeOptions,
+    indent: Option<&str>,
+    depth: usize,
+    numbers: &mut Option<(&NumberTexts, JsonPointer)>,
+    output: &mut W,
+) -> fmt::Result {
+    let newline = |output: &mut W, depth: usize| -> fmt::Result {
+        if let Some(indent) = indent {
+            output.write_char('\n')?;
+            for _ in 0..depth {
+                output.write_str(indent)?;
+            }
+        }
+        Ok(())
+    };
+
+    match value {
+        Value::Array(array) if !array.is_empty() => {
+            output.write_char('[')?;
+            for (i, item) in array.iter().enumerate() {
+                if i > 0 {
+                    output.write_char(',')?;
+                }
+                newline(output, depth + 1)?;
+                if let Some((_, pointer)) = numbers {
+                    pointer.push(i.to_string());
+                }
+                write_json_at(item, options, indent, depth + 1, numbers, output)?;
+                if let Some((_, pointer)) = numbers {
+                    pointer.pop();
+                }
+            }
+            newline(output, depth)?;
+            output.write_char(']')
+        }
+        Value::Object(map) if !map.is_empty() => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            output.write_char('{')?;
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    output.write_char(',')?;
+                }
+                newline(output, depth + 1)?;
+                write_escaped(key, options, output)?;
+                output.write_str(if indent.is_some() { ": " } else { ":" })?;
+                if let Some((_, pointer)) = numbers {
+                    pointer.push(key.as_str());
+                }
+                write_json_at(value, options, indent, depth + 1, numbers, output)?;
+                if let Some((_, pointer)) = numbers {
+                    pointer.pop();
+                }
+            }
+            newline(output, depth)?;
+            output.write_char('}')
+        }
+        Value::Array(_) => output.write_str("[]"),
+        Value::Object(_) => output.write_str("{}"),
+        Value::String(s) => write_escaped(s, options, output),
+        Value::Number(n) => {
+            if let Some((texts, pointer)) = numbers
+                && let Some(text) = texts.matching(pointer, *n)
+            {
+                return output.write_str(text);
+            }
+            let formatted = format_number(*n);
+            output.write_str(&formatted)?;
+            if options.integral_fraction && n.is_finite() && !formatted.contains(['.', 'e']) {
+                output.write_str(".0")?;
+            }
+            Ok(())
+        }
+        Value::Null => output.write_str("null"),
+        Value::Boolean(b) => output.write_str(if *b { "true" } else { "false" }),
+    }
+}
+
+/// A NaN or infinite number, which the JSON Canonicalization Scheme cannot represent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonicalError {
+    /// Location of the number
+    pub pointer: JsonPointer,
+}
+
+impl fmt::Display for CanonicalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "non-finite number at `{}`", self.pointer)
+    }
+}
+
+impl std::error::Error for CanonicalError {}
+
+impl<S> Value<S> {
+    /// Serializes to the JSON Canonicalization Scheme (RFC 8785)
+    ///
+    /// Object keys are sorted by their UTF-16 code units, numbers use the ECMAScript
+    /// shortest round-trip format, and strings only escape what JSON requires. Fails on
+    /// NaN and infinity, which the scheme does not allow.
+    pub fn to_canonical_string(&self) -> Result<String, CanonicalError> {
+        let mut output = String::new();
+        write_canonical(self, &mut JsonPointer::root(), &mut output)?;
+        Ok(output)
+    }
+}
+
+fn write_canonical<S>(
+    value: &Value<S>,
+    pointer: &mut JsonPointer,
+    output: &mut String,
+) -> Result<(), CanonicalError> {
+    match value {
+        Value::Null => output.push_str("null"),
+        Value::Boolean(b) => output.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) if !n.is_finite() => {
+            return Err(CanonicalError {
+                pointer: pointer.clone(),
+            });
+        }
+        Value::Number(n) => output.push_str(&format_number(*n)),
+        Value::String(s) => write_string(s, output),
+        Value::Array(array) => {
+            output.push('[');
+            for (i, item) in array.iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                pointer.push(i.to_string());
+                write_canonical(item, pointer, output)?;
+                pointer.pop();
+            }
+            output.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| utf16_cmp(a, b));
+            output.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                write_string(key, output);
+                output.push(':');
+                pointer.push(key.as_str());
+                write_canonical(value, pointer, output)?;
+                pointer.pop();
+            }
+            output.push('}');
+        }
+    }
+    Ok(())
+}
+
+fn utf16_cmp(a: &str, b: &str) -> Ordering {
+    a.encode_utf16().cmp(b.encode_utf16())
+}
+
+/// Writes a quoted string, escaping only `"`, `\\` and control characters
+pub(crate) fn write_string(s: &str, output: &mut String) {
+    write_escaped(s, &SerializeOptions::default(), output).unwrap();
+}
+
+fn write_escaped<W: Write>(s: &str, options: &SerializeOptions, output: &mut W) -> fmt::Result {
+    output.write_char('"')?;
+    // copy runs of characters that need no escaping in one write
+    let mut unescaped = 0;
+    for (i, ch) in s.char_indices() {
+        let escape = match ch {
+            '/' if options.escape_slash => Some(Cow::Borrowed("\\/")),
+            '<' | '>' | '&' if options.escape_html => {
+                Some(Cow::Owned(format!("\\u{:04x}", ch as u32)))
+            }
+            '\u{2028}' | '\u{2029}' if options.escape_line_separators => {
+                Some(Cow::Owned(format!("\\u{:04x}", ch as u32)))
+            }
+            ch if !ch.is_ascii() && options.ascii_only => Some(Cow::Owned(
+                ch.encode_utf16(&mut [0; 2])
+                    .iter()
+                    .map(|unit| format!("\\u{unit:04x}"))
+                    .collect(),
+            )),
+            '"' => Some(Cow::Borrowed("\\\"")),
+            '\\' => Some(Cow::Borrowed("\\\\")),
+            '\u{8}' => Some(Cow::Borrowed("\\b")),
+            '\u{c}' => Some(Cow::Borrowed("\\f")),
+            '\n' => Some(Cow::Borrowed("\\n")),
+            '\r' => Some(Cow::Borrowed("\\r")),
+            '\t' => Some(Cow::Borrowed("\\t")),
+            ch if (ch as u32) < 0x20 => Some(Cow::Owned(format!("\\u{:04x}", ch as u32))),
+            _ => None,
+        };
+        if let Some(escape) = escape {
+            output.write_str(&s[unescaped..i])?;
+            output.write_str(&escape)?;
+            unescaped = i + ch.len_utf8();
+        }
+    }
+    output.write_str(&s[unescaped..])?;
+    output.write_char('"')
+}
+
+/// Formats a number like ECMAScript's `Number.prototype.toString`, ex. `1e+21` or `0.000001`
+///
+/// The digits are the shortest that parse back to exactly `n`, and the output does not
+/// depend on the locale.
+pub(crate) fn format_number(n: f64) -> String {
+    if !n.is_finite() {
+        return String::from("null");
+    }
+    if n == 0.0 {
+        return String::from("0");
+    }
+
+    // `{:e}` produces the shortest digits that round-trip, ex. `1.2345e-7`
+    let scientific = format!("{:e}", n.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    let digits: String = mantissa.chars().filter(|ch| *ch != '.').collect();
+    let exponent: i32 = exponent.parse().unwrap();
+
+    let k = digits.len() as i32;
+    let point = exponent + 1;
+    let mut output = String::new();
+    if n < 0.0 {
+        output.push('-');
+    }
+    if k <= point && point <= 21 {
+        output.push_str(&digits);
+        output.extend(std::iter::repeat_n('0', (point - k) as usize));
+    } else if 0 < point && point <= 21 {
+        let (whole, fraction) = digits.split_at(point as usize);
+        write!(output, "{whole}.{fraction}").unwrap();
+    } else if -6 < point && point <= 0 {
+        output.push_str("0.");
+        output.extend(std::iter::repeat_n('0', -point as usize));
+        output.push_str(&digits);
+    } else {
+        let (first, rest) = digits.split_at(1);
+        output.push_str(first);
+        if !rest.is_empty() {
+            write!(output, ".{rest}").unwrap();
+        }
+        let sign = if point - 1 < 0 { '-' } else { '+' };
+        write!(output, "e{sign}{}", (point - 1).abs()).unwrap();
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::{SerializeOptions, format_number, write_value, write_value_pretty};
+
+    #[test]
+    fn sorts_keys_and_strips_whitespace() {
+        let value = Parser::new()
+            .parse_str(r#"{ "b": [1, 2, {"d": true, "c": null}], "a": "x" }"#)
+            .unwrap();
+        assert_eq!(
+            value.to_canonical_string().unwrap(),
+            r#"{"a":"x","b":[1,2,{"c":null,"d":true}]}"#
+        );
+    }
+
+    #[test]
+    fn keys_sort_by_utf16_code_units() {
+        // U+1F600 is a surrogate pair starting 0xD83D, which sorts before U+FB01
+        let value = Value::object([
+            ("\u{fb01}", Value::Null),
+            ("\u{1f600}", Value::Null),
+            ("a", Value::Null),
+        ]);
+        assert_eq!(
+            value.to_canonical_string().unwrap(),
+            "{\"a\":null,\"\u{1f600}\":null,\"\u{fb01}\":null}"
+        );
+    }
+
+    #[test]
+    fn minimal_string_escapes() {
+        let value: Value = Value::String(String::from("\"\\/\u{8}\u{1f}é\u{2028}"));
+        assert_eq!(
+            value.to_canonical_string().unwrap(),
+            "\"\\\"\\\\/\\b\\u001fé\u{2028}\""
+        );
+    }
+
+    #[test]
+    fn non_finite_numbers_fail() {
+        let value = Value::object([(
+            "a",
+            Value::Array(vec![Value::Null, Value::Number(f64::NAN)]),
+        )]);
+        let err = value.to_canonical_string().unwrap_err();
+        assert_eq!(err.pointer.to_string(), "/a/1");
+        let infinity: Value = Value::Number(f64::INFINITY);
+        assert!(infinity.to_canonical_string().is_err());
+    }
+
+    #[test]
+    fn ecmascript_numbers() {
+        let cases = [
+            (0.0, "0"),
+            (-0.0, "0"),
+            (1.0, "1"),
+            (-1.5, "-1.5"),
+            (123456789.0, "123456789"),
+            (1e21, "1e+21"),
+            (1e20, "100000000000000000000"),
+            (0.000001, "0.000001"),
+            (1e-7, "1e-7"),
+            (1.2345e-7, "1.2345e-7"),
+            (333333333.3333333, "333333333.3333333"),
+            (9007199254740992.0, "9007199254740992"),
+            (4.5e300, "4.5e+300"),
+            (f64::NAN, "null"),
+        ];
+        for (n, expected) in cases {
+            assert_eq!(format_number(n), expected, "{n}");
+        }
+    }
+
+    #[test]
+    fn compact_output() {
+        let value = Parser::new()
+            .parse_str(r#"{"b": [1, 2.5, null], "a": {"c": "d"}}"#)
+            .unwrap();
+        assert_eq!(
+            value.to_json_string(),
+            r#"{"a":{"c":"d"},"b":[1,2.5,null]}"#
+        );
+        assert_eq!(value.to_string(), value.to_json_string());
+    }
+
+    #[test]
+    fn escaping_options() {
+        let value: Value = Value::String(String::from("</a>&é\u{1f600}\u{2028}"));
+        assert_eq!(value.to_json_string(), "\"</a>&é\u{1f600}\u{2028}\"");
+
+        let ascii = SerializeOptions {
+            ascii_only: true,
+            ..SerializeOptions::default()
+        };
+        assert_eq!(
+            value.to_json_string_with(&ascii),
+            r#""</a>&\u00e9\ud83d\ude00\u2028""#
+        );
+        assert_eq!(
+            value.to_json_string_with(&SerializeOptions::web_safe()),
+            "\"\\u003c\\/a\\u003e\\u0026é\u{1f600}\\u2028\""
+        );
+    }
+
+    #[test]
+    fn shortest_round_trip() {
+        // a fixed pseudo-random walk over bit patterns, covering all magnitudes
+        let mut state = 0x853c_49e6_748f_ea9bu64;
+        for _ in 0..10_000 {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let n = f64::from_bits(state);
+            if !n.is_finite() {
+                continue;
+            }
+            let formatted = format_number(n);
+            assert_eq!(formatted.parse::<f64>(), Ok(n), "{formatted}");
+        }
+        assert_eq!(format_number(0.1 + 0.2), "0.30000000000000004");
+        assert_eq!(format_number(f64::MIN_POSITIVE), "2.2250738585072014e-308");
+    }
+
+    #[test]
+    fn integral_fraction() {
+        let value: Value = Value::Array(vec![
+            Value::Number(1.0),
+            Value::Number(1.5),
+            Value::Number(1e21),
+            Value::Number(f64::NAN),
+        ]);
+        let options = SerializeOptions {
+            integral_fraction: true,
+            ..SerializeOptions::default()
+        };
+        assert_eq!(value.to_json_string(), "[1,1.5,1e+21,null]");
+        assert_eq!(value.to_json_string_with(&options), "[1.0,1.5,1e+21,null]");
+    }
+
+    #[test]
+    fn pretty_output() {
+        let value = Parser::new()
+            .parse_str(r#"{"b": [1, {}], "a": [], "c": {"d": null}}"#)
+            .unwrap();
+        let expected = "{\n  \"a\": [],\n  \"b\": [\n    1,\n    {}\n  ],\n  \"c\": {\n    \"d\": null\n  }\n}";
+        assert_eq!(value.to_json_string_pretty(), expected);
+    }
+
+    #[test]
+    fn writes_to_io() {
+        let value = Parser::new().parse_str(r#"{"a": ["x\ny", true]}"#).unwrap();
+        let mut compact = Vec::new();
+        write_value(&mut compact, &value).unwrap();
+        assert_eq!(String::from_utf8(compact).unwrap(), value.to_json_string());
+
+        let mut pretty = Vec::new();
+        write_value_pretty(&mut pretty, &value).unwrap();
+        assert_eq!(
+            String::from_utf8(pretty).unwrap(),
+            value.to_json_string_pretty()
+        );
+    }
+
+    #[test]
+    fn io_errors_are_returned() {
+        let mut full = [0u8; 4];
+        let value: Value = Value::String(String::from("long"));
+        let err = write_value(&mut &mut full[..], &value).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WriteZero);
+    }
+}
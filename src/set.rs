@@ -0,0 +1,137 @@
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use crate::Value;
+
+#[derive(Clone, Copy)]
+enum SetOp {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl Value {
+    /// The elements of this array followed by those of `other` that it lacks, compared by
+    /// structural equality
+    ///
+    /// Like the other set operations the result holds each element once, in the order it
+    /// first appears, and it is `None` unless both values are arrays.
+    pub fn union(&self, other: &Value) -> Option<Value> {
+        combine(self, other, |item| item, SetOp::Union)
+    }
+
+    /// The elements of this array that are also in `other`
+    pub fn intersection(&self, other: &Value) -> Option<Value> {
+        combine(self, other, |item| item, SetOp::Intersection)
+    }
+
+    /// The elements of this array that are not in `other`
+    pub fn difference(&self, other: &Value) -> Option<Value> {
+        combine(self, other, |item| item, SetOp::Difference)
+    }
+
+    /// [`Value::union`] comparing elements by `key`, ex. a record's ID, where the first
+    /// element with each key is kept
+    pub fn union_by<K: Hash + Eq>(
+        &self,
+        other: &Value,
+        key: impl FnMut(&Value) -> K,
+    ) -> Option<Value> {
+        combine(self, other, key, SetOp::Union)
+    }
+
+    /// [`Value::intersection`] comparing elements by `key`, keeping the elements of `self`
+    pub fn intersection_by<K: Hash + Eq>(
+        &self,
+        other: &Value,
+        key: impl FnMut(&Value) -> K,
+    ) -> Option<Value> {
+        combine(self, other, key, SetOp::Intersection)
+    }
+
+    /// [`Value::difference`] comparing elements by `key`
+    pub fn difference_by<K: Hash + Eq>(
+        &self,
+        other: &Value,
+        key: impl FnMut(&Value) -> K,
+    ) -> Option<Value> {
+        combine(self, other, key, SetOp::Difference)
+    }
+}
+
+fn combine<'a, K: Hash + Eq>(
+    a: &'a Value,
+    b: &'a Value,
+    mut key: impl FnMut(&'a Value) -> K,
+    op: SetOp,
+) -> Option<Value> {
+    let (Value::Array(a), Value::Array(b)) = (a, b) else {
+        return None;
+    };
+    let others: HashSet<K> = b.iter().map(&mut key).collect();
+    let mut seen = HashSet::new();
+    let mut result = Vec::new();
+    for item in a {
+        let k = key(item);
+        let wanted = match op {
+            SetOp::Union => true,
+            SetOp::Intersection => others.contains(&k),
+            SetOp::Difference => !others.contains(&k),
+        };
+        if wanted && seen.insert(k) {
+            result.push(item.clone());
+        }
+    }
+    if let SetOp::Union = op {
+        for item in b {
+            if seen.insert(key(item)) {
+                result.push(item.clone());
+            }
+        }
+    }
+    Some(Value::Array(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    #[test]
+    fn structural() {
+        let a = value(r#"[1, {"x": [2]}, "a", 1, null]"#);
+        let b = value(r#"[null, "b", {"x": [2]}, 3, "b"]"#);
+        assert_eq!(
+            a.union(&b),
+            Some(value(r#"[1, {"x": [2]}, "a", null, "b", 3]"#))
+        );
+        assert_eq!(a.intersection(&b), Some(value(r#"[{"x": [2]}, null]"#)));
+        assert_eq!(a.difference(&b), Some(value(r#"[1, "a"]"#)));
+        assert_eq!(b.difference(&a), Some(value(r#"["b", 3]"#)));
+        assert_eq!(a.union(&Value::Null), None);
+    }
+
+    #[test]
+    fn by_key() {
+        let ours = value(r#"[{"id": 1, "v": "old"}, {"id": 2}, {"name": "no id"}]"#);
+        let theirs = value(r#"[{"id": 2, "v": "new"}, {"id": 3}]"#);
+        let id = |record: &Value| record.pointer("/id").cloned();
+        assert_eq!(
+            ours.union_by(&theirs, id),
+            Some(value(
+                r#"[{"id": 1, "v": "old"}, {"id": 2}, {"name": "no id"}, {"id": 3}]"#
+            ))
+        );
+        assert_eq!(
+            ours.intersection_by(&theirs, id),
+            Some(value(r#"[{"id": 2}]"#))
+        );
+        assert_eq!(
+            ours.difference_by(&theirs, id),
+            Some(value(r#"[{"id": 1, "v": "old"}, {"name": "no id"}]"#))
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use crate::{Event, JsonReader, JsonWriter, ReaderError, WriterError};
+
+#[derive(Debug)]
+pub enum ShardError {
+    /// Creating or writing a shard failed
+    Io(io::Error),
+    Read(ReaderError),
+    Write(WriterError),
+
+    /// The document is not an array
+    NotAnArray,
+}
+
+impl fmt::Display for ShardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "writing a shard failed: {err}"),
+            Self::Read(err) => write!(f, "reading the document failed: {err}"),
+            Self::Write(err) => write!(f, "writing a shard failed: {err}"),
+            Self::NotAnArray => f.write_str("the document is not an array"),
+        }
+    }
+}
+
+impl std::error::Error for ShardError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Read(err) => Some(err),
+            Self::Write(err) => Some(err),
+            Self::NotAnArray => None,
+        }
+    }
+}
+
+impl From<io::Error> for ShardError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ReaderError> for ShardError {
+    fn from(err: ReaderError) -> Self {
+        Self::Read(err)
+    }
+}
+
+impl From<WriterError> for ShardError {
+    fn from(err: WriterError) -> Self {
+        Self::Write(err)
+    }
+}
+
+/// How large each shard written by [`split_array`] may grow, `None` means unlimited
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShardLimits {
+    pub max_elements: Option<usize>,
+
+    /// The size of a shard including its brackets and commas, an element larger than
+    /// this on its own is written to a shard by itself
+    pub max_bytes: Option<usize>,
+}
+
+/// Splits the array read by `reader` into smaller arrays, each written minified to a
+/// new writer from `create`, which is given the shard's index starting at 0
+///
+/// Elements are copied one at a time, in order, so memory use depends only on the
+/// largest element. Returns the number of shards written, which is 0 for an empty array.
+pub fn split_array<R: io::Read, W: io::Write>(
+    reader: &mut JsonReader<R>,
+    limits: &ShardLimits,
+    mut create: impl FnMut(usize) -> io::Result<W>,
+) -> Result<usize, ShardError> {
+    match reader.next_event()? {
+        Some((Event::StartArray, _)) => {}
+        Some(_) => return Err(ShardError::NotAnArray),
+        None => return Err(ReaderError::UnexpectedEof.into()),
+    }
+
+    let mut shards = 0;
+    // the open shard, with its element count and size once closed
+    let mut current: Option<(W, usize, usize)> = None;
+    loop {
+        let (event, _) = reader.next_event()?.ok_or(ReaderError::UnexpectedEof)?;
+        if let Event::EndArray = event {
+            break;
+        }
+        let element = copy_element(reader, event)?;
+
+        if let Some((_, count, bytes)) = &current {
+            let full = limits.max_elements.is_some_and(|max| *count >= max)
+                || limits
+                    .max_bytes
+                    .is_some_and(|max| bytes + 1 + element.len() > max);
+            if full && let Some((shard, _, _)) = current.take() {
+                close(shard)?;
+            }
+        }
+        let (shard, count, bytes) = match &mut current {
+            Some(open) => open,
+            None => {
+                let mut shard = create(shards)?;
+                shard.write_all(b"[")?;
+                shards += 1;
+                current.insert((shard, 0, 1))
+            }
+        };
+        if *count > 0 {
+            shard.write_all(b",")?;
+            *bytes += 1;
+        }
+        shard.write_all(&element)?;
+        *count += 1;
+        *bytes += element.len();
+    }
+    if let Some((shard, _, _)) = current {
+        close(shard)?;
+    }
+    // the reader reports anything after the array
+    reader.next_event()?;
+    Ok(shards)
+}
+
+/// Splits the array in the file at `path` into files next to it, ex. `data.json` into
+/// `data.0.json`, `data.1.json` and so on, see [`split_array`]
+///
+/// Returns the paths of the files written.
+pub fn split_array_file(
+    path: impl AsRef<Path>,
+    limits: &ShardLimits,
+) -> Result<Vec<PathBuf>, ShardError> {
+    let path = path.as_ref();
+    let mut reader = JsonReader::new(BufReader::new(File::open(path)?));
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path
+        .extension()
+        .map_or(String::new(), |ext| format!(".{}", ext.to_string_lossy()));
+
+    let mut paths = Vec::new();
+    split_array(&mut reader, limits, |index| {
+        let shard = path.with_file_name(format!("{stem}.{index}{extension}"));
+        let file = File::create(&shard)?;
+        paths.push(shard);
+        Ok(BufWriter::new(file))
+    })?;
+    Ok(paths)
+}
+
+/// Reads the rest of the element that starts with `first` and writes it minified
+fn copy_element<R: io::Read>(
+    reader: &mut JsonReader<R>,
+    first: Event,
+) -> Result<Vec<u8>, ShardError> {
+    let mut writer = JsonWriter::new(Vec::new());
+    let mut depth = 0;
+    let mut event = first;
+    loop {
+        match event {
+            Event::StartObject | Event::StartArray => depth += 1,
+            Event::EndObject | Event::EndArray => depth -= 1,
+            _ => {}
+        }
+        writer.event(&event)?;
+        if depth == 0 {
+            return Ok(writer.finish()?);
+        }
+        (event, _) = reader.next_event()?.ok_or(ReaderError::UnexpectedEof)?;
+    }
+}
+
+fn close<W: io::Write>(mut shard: W) -> io::Result<()> {
+    shard.write_all(b"]")?;
+    shard.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{JsonReader, ReaderError};
+
+    use super::{ShardError, ShardLimits, split_array, split_array_file};
+
+    fn split(input: &str, limits: ShardLimits) -> Result<Vec<String>, ShardError> {
+        let mut buffers = vec![Vec::new(); 8];
+        let mut slots = buffers.iter_mut();
+        let mut reader = JsonReader::new(input.as_bytes());
+        let count = split_array(&mut reader, &limits, |_| Ok(slots.next().unwrap()))?;
+        let shards = buffers[..count].iter();
+        Ok(shards
+            .map(|shard| String::from_utf8(shard.clone()).unwrap())
+            .collect())
+    }
+
+    const INPUT: &str = r#"[1, "two", {"three": [3]}, null, 5]"#;
+
+    #[test]
+    fn by_element_count() {
+        let limits = ShardLimits {
+            max_elements: Some(2),
+            ..ShardLimits::default()
+        };
+        let shards = split(INPUT, limits).unwrap();
+        assert_eq!(shards, [r#"[1,"two"]"#, r#"[{"three":[3]},null]"#, "[5]"]);
+    }
+
+    #[test]
+    fn by_size() {
+        let limits = ShardLimits {
+            max_bytes: Some(10),
+            ..ShardLimits::default()
+        };
+        let shards = split(INPUT, limits).unwrap();
+        assert_eq!(shards, [r#"[1,"two"]"#, r#"[{"three":[3]}]"#, "[null,5]"]);
+        assert!(shards[0].len() <= 10 && shards[2].len() <= 10);
+
+        let limits = ShardLimits {
+            max_elements: Some(1),
+            max_bytes: Some(100),
+        };
+        assert_eq!(split(INPUT, limits).unwrap().len(), 5);
+        let whole = split(INPUT, ShardLimits::default()).unwrap();
+        assert_eq!(whole, [r#"[1,"two",{"three":[3]},null,5]"#]);
+    }
+
+    #[test]
+    fn edge_cases() {
+        assert!(split("[]", ShardLimits::default()).unwrap().is_empty());
+        assert!(matches!(
+            split(r#"{"a": 1}"#, ShardLimits::default()),
+            Err(ShardError::NotAnArray)
+        ));
+        assert!(matches!(
+            split("[1, 2", ShardLimits::default()),
+            Err(ShardError::Read(ReaderError::UnexpectedEof))
+        ));
+        assert!(matches!(
+            split("[1] 2", ShardLimits::default()),
+            Err(ShardError::Read(_))
+        ));
+    }
+
+    #[test]
+    fn splits_files() {
+        let dir = format!("json_parsing_shards_{}", std::process::id());
+        let dir = std::env::temp_dir().join(dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("data.json");
+        fs::write(&path, INPUT).unwrap();
+        let limits = ShardLimits {
+            max_elements: Some(3),
+            ..ShardLimits::default()
+        };
+        let paths = split_array_file(&path, &limits).unwrap();
+        assert_eq!(paths, [dir.join("data.0.json"), dir.join("data.1.json")]);
+        assert_eq!(fs::read_to_string(&paths[1]).unwrap(), "[null,5]");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
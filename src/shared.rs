@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::pointer::array_index;
+use crate::{JsonPointer, Value};
+
+/// An immutable JSON value whose strings, arrays and objects are reference counted
+///
+/// Cloning is O(1) and clones share their contents, so a large document can be handed to
+/// many threads cheaply. The `_mut` accessors copy a container first if it is shared
+/// (copy-on-write), leaving other clones unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SharedValue {
+    Null,
+    Boolean(bool),
+    String(Arc<str>),
+    Number(f64),
+    Array(Arc<Vec<SharedValue>>),
+    Object(Arc<HashMap<String, SharedValue>>),
+}
+
+impl SharedValue {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            SharedValue::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            SharedValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            SharedValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[SharedValue]> {
+        match self {
+            SharedValue::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&HashMap<String, SharedValue>> {
+        match self {
+            SharedValue::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// The array's elements, copied first if another clone shares them
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<SharedValue>> {
+        match self {
+            SharedValue::Array(array) => Some(Arc::make_mut(array)),
+            _ => None,
+        }
+    }
+
+    /// The object's members, copied first if another clone shares them
+    pub fn as_object_mut(&mut self) -> Option<&mut HashMap<String, SharedValue>> {
+        match self {
+            SharedValue::Object(map) => Some(Arc::make_mut(map)),
+            _ => None,
+        }
+    }
+
+    /// Looks up a value by JSON Pointer, ex. `/users/0/name`
+    pub fn pointer(&self, pointer: &str) -> Option<&SharedValue> {
+        let pointer = JsonPointer::parse(pointer).ok()?;
+        pointer
+            .tokens()
+            .iter()
+            .try_fold(self, |value, token| match value {
+                SharedValue::Object(map) => map.get(token),
+                SharedValue::Array(array) => array.get(array_index(token)?),
+                _ => None,
+            })
+    }
+
+    /// Looks up a value by JSON Pointer, copying each shared container along the way
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut SharedValue> {
+        let pointer = JsonPointer::parse(pointer).ok()?;
+        pointer
+            .tokens()
+            .iter()
+            .try_fold(self, |value, token| match value {
+                SharedValue::Object(map) => Arc::make_mut(map).get_mut(token),
+                SharedValue::Array(array) => Arc::make_mut(array).get_mut(array_index(token)?),
+                _ => None,
+            })
+    }
+
+    /// Copies the contents into a plain [`Value`]
+    pub fn to_value(&self) -> Value {
+        match self {
+            SharedValue::Null => Value::Null,
+            SharedValue::Boolean(b) => Value::Boolean(*b),
+            SharedValue::String(s) => Value::String(s.to_string()),
+            SharedValue::Number(n) => Value::Number(*n),
+            SharedValue::Array(array) => {
+                Value::Array(array.iter().map(SharedValue::to_value).collect())
+            }
+            SharedValue::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), value.to_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<Value> for SharedValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => SharedValue::Null,
+            Value::Boolean(b) => SharedValue::Boolean(b),
+            Value::String(s) => SharedValue::String(s.into()),
+            Value::Number(n) => SharedValue::Number(n),
+            Value::Array(array) => {
+                SharedValue::Array(Arc::new(array.into_iter().map(Self::from).collect()))
+            }
+            Value::Object(map) => SharedValue::Object(Arc::new(
+                map.into_iter()
+                    .map(|(key, value)| (key, Self::from(value)))
+                    .collect(),
+            )),
+        }
+    }
+}
+
+impl From<SharedValue> for Value {
+    fn from(value: SharedValue) -> Self {
+        value.to_value()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+
+    use crate::{Parser, Value};
+
+    use super::SharedValue;
+
+    fn shared(input: &str) -> SharedValue {
+        SharedValue::from(Parser::new().parse_str(input).unwrap())
+    }
+
+    #[test]
+    fn clones_share_contents() {
+        let original = shared(r#"{"users": [{"name": "ken"}]}"#);
+        let clone = original.clone();
+        let (SharedValue::Object(a), SharedValue::Object(b)) = (&original, &clone) else {
+            panic!("expected objects");
+        };
+        assert!(Arc::ptr_eq(a, b));
+    }
+
+    #[test]
+    fn mutation_copies_on_write() {
+        let original = shared(r#"{"users": [{"name": "ken"}], "other": [1]}"#);
+        let mut edited = original.clone();
+        *edited.pointer_mut("/users/0/name").unwrap() = SharedValue::String("ryu".into());
+
+        assert_eq!(
+            original
+                .pointer("/users/0/name")
+                .and_then(SharedValue::as_str),
+            Some("ken")
+        );
+        assert_eq!(
+            edited
+                .pointer("/users/0/name")
+                .and_then(SharedValue::as_str),
+            Some("ryu")
+        );
+
+        // untouched branches are still shared
+        let (Some(SharedValue::Array(a)), Some(SharedValue::Array(b))) =
+            (original.pointer("/other"), edited.pointer("/other"))
+        else {
+            panic!("expected arrays");
+        };
+        assert!(Arc::ptr_eq(a, b));
+    }
+
+    #[test]
+    fn shared_across_threads() {
+        let document = shared(r#"[1, 2, 3]"#);
+        let sums: Vec<f64> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    let document = document.clone();
+                    scope.spawn(move || {
+                        document
+                            .as_array()
+                            .unwrap()
+                            .iter()
+                            .filter_map(SharedValue::as_f64)
+                            .sum()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+        assert_eq!(sums, [6.0; 4]);
+    }
+
+    #[test]
+    fn converts_back_to_value() {
+        let value = Parser::new()
+            .parse_str(r#"{"a": [null, true, "x", 1]}"#)
+            .unwrap();
+        assert_eq!(Value::from(SharedValue::from(value.clone())), value);
+    }
+}
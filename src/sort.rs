@@ -0,0 +1,124 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use crate::Value;
+
+/// The direction of a sort, see [`Value::sort_array_by_key`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Order {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl Order {
+    fn apply(self, ordering: Ordering) -> Ordering {
+        match self {
+            Order::Asc => ordering,
+            Order::Desc => ordering.reverse(),
+        }
+    }
+}
+
+impl Value {
+    /// Sorts an array by the total order on values, see the [`Ord`] impl for how mixed
+    /// types compare
+    ///
+    /// Like the other sorting helpers this is stable and does nothing if `self` is not an
+    /// array.
+    pub fn sort_array(&mut self, order: Order) {
+        self.sort_array_by(|a, b| order.apply(a.cmp(b)));
+    }
+
+    /// Sorts the objects in an array by their `key` member, ex. `"price"`
+    ///
+    /// Members are compared by the total order on values. Elements that are not objects
+    /// or lack `key` go after all others, in their original order, in both directions.
+    pub fn sort_array_by_key(&mut self, key: &str, order: Order) {
+        self.sort_array_by(|a, b| match (member(a, key), member(b, key)) {
+            (Some(a), Some(b)) => order.apply(a.cmp(b)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+    }
+
+    /// Sorts an array with `compare`, keeping equal elements in their original order
+    pub fn sort_array_by(&mut self, compare: impl FnMut(&Value, &Value) -> Ordering) {
+        if let Value::Array(array) = self {
+            array.sort_by(compare);
+        }
+    }
+
+    /// Removes repeated elements from an array, keeping the first of each, compared by
+    /// structural equality
+    pub fn dedup(&mut self) {
+        let Value::Array(array) = self else {
+            return;
+        };
+        let mut seen = HashSet::new();
+        let keep: Vec<bool> = array.iter().map(|item| seen.insert(item)).collect();
+        let mut keep = keep.into_iter();
+        array.retain(|_| keep.next().unwrap_or(true));
+    }
+}
+
+fn member<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    value.as_object()?.get(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::Order;
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    #[test]
+    fn sorts_mixed_types() {
+        let mut array = value(r#"["b", 2, null, [1], "a", true, 1, {}]"#);
+        array.sort_array(Order::Asc);
+        assert_eq!(array, value(r#"[null, true, 1, 2, "a", "b", [1], {}]"#));
+        array.sort_array(Order::Desc);
+        assert_eq!(array, value(r#"[{}, [1], "b", "a", 2, 1, true, null]"#));
+
+        let mut scalar = Value::string("x");
+        scalar.sort_array(Order::Asc);
+        assert_eq!(scalar, Value::string("x"));
+    }
+
+    #[test]
+    fn sorts_by_key() {
+        let mut items = value(
+            r#"[{"id": 1, "price": 5}, {"id": 2}, {"id": 3, "price": 9},
+                {"id": 4, "price": 5}, 7, {"id": 5, "price": "free"}]"#,
+        );
+        items.sort_array_by_key("price", Order::Desc);
+        let ids: Vec<_> = items
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item.pointer("/id").cloned().unwrap_or(Value::Null))
+            .collect();
+        let expected = [5.0, 3.0, 1.0, 4.0, 2.0].map(Value::Number);
+        assert_eq!(ids[..5], expected);
+        assert_eq!(ids[5], Value::Null);
+    }
+
+    #[test]
+    fn sorts_by_function() {
+        let mut words = value(r#"["ccc", "a", "bb", "d"]"#);
+        words.sort_array_by(|a, b| a.as_str().map(str::len).cmp(&b.as_str().map(str::len)));
+        assert_eq!(words, value(r#"["a", "d", "bb", "ccc"]"#));
+    }
+
+    #[test]
+    fn dedup() {
+        let mut array = value(r#"[1, {"a": 1, "b": 2}, 1, "1", {"b": 2, "a": 1}, null, 1]"#);
+        array.dedup();
+        assert_eq!(array, value(r#"[1, {"a": 1, "b": 2}, "1", null]"#));
+    }
+}
@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::pointer::array_index;
+use crate::{Event, JsonPointer, JsonReader, Position, ReaderError, Value};
+
+/// Where a value appears in the source, from its first byte to just past its last
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// A JSON value along with its location in the source
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedValue {
+    pub span: Span,
+    pub kind: SpannedKind,
+}
+
+/// The variants of [`Value`], holding spanned children
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpannedKind {
+    Null,
+    Boolean(bool),
+    String(String),
+    Number(f64),
+    Array(Vec<SpannedValue>),
+
+    /// Members in source order, including repeated keys
+    Object(Vec<SpannedMember>),
+}
+
+/// A parsed document that can tell where each of its values came from
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedDocument {
+    root: SpannedValue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedMember {
+    pub key: String,
+
+    /// The location of the key, including its quotes
+    pub key_span: Span,
+
+    pub value: SpannedValue,
+}
+
+impl Span {
+    /// The byte offsets covered by the span
+    pub fn range(&self) -> Range<usize> {
+        self.start.offset..self.end.offset
+    }
+}
+
+impl SpannedValue {
+    /// The value without locations, when an object repeats a key the last one wins
+    pub fn to_value(&self) -> Value {
+        match &self.kind {
+            SpannedKind::Null => Value::Null,
+            SpannedKind::Boolean(b) => Value::Boolean(*b),
+            SpannedKind::String(s) => Value::String(s.clone()),
+            SpannedKind::Number(n) => Value::Number(*n),
+            SpannedKind::Array(array) => {
+                Value::Array(array.iter().map(SpannedValue::to_value).collect())
+            }
+            SpannedKind::Object(members) => {
+                let map: HashMap<_, _> = members
+                    .iter()
+                    .map(|member| (member.key.clone(), member.value.to_value()))
+                    .collect();
+                Value::Object(map)
+            }
+        }
+    }
+}
+
+impl SpannedValue {
+    /// Looks up a value by JSON Pointer, ex. `/users/3/email`
+    ///
+    /// When an object repeats a key, the last one is found.
+    pub fn pointer(&self, pointer: &str) -> Option<&SpannedValue> {
+        let pointer = JsonPointer::parse(pointer).ok()?;
+        pointer
+            .tokens()
+            .iter()
+            .try_fold(self, |value, token| match &value.kind {
+                SpannedKind::Object(members) => members
+                    .iter()
+                    .rev()
+                    .find(|member| member.key == *token)
+                    .map(|member| &member.value),
+                SpannedKind::Array(elements) => elements.get(array_index(token)?),
+                _ => None,
+            })
+    }
+
+    /// The integer exactly as written in `source`, the text this value was parsed from
+    ///
+    /// Numbers are stored as `f64`, which loses precision past 2^53, so IDs and counters
+    /// beyond that should be read this way. Returns `None` for other values and for
+    /// numbers with a fraction or exponent.
+    pub fn as_i128(&self, source: &str) -> Option<i128> {
+        self.number_text(source)?.parse().ok()
+    }
+
+    /// Like [`SpannedValue::as_i128`], for non-negative integers up to `u128::MAX`
+    pub fn as_u128(&self, source: &str) -> Option<u128> {
+        self.number_text(source)?.parse().ok()
+    }
+
+    fn number_text<'a>(&self, source: &'a str) -> Option<&'a str> {
+        match self.kind {
+            SpannedKind::Number(_) => source.get(self.span.range()),
+            _ => None,
+        }
+    }
+}
+
+impl SpannedDocument {
+    pub fn parse(input: &str) -> Result<Self, ReaderError> {
+        let root = parse_with_spans(input)?;
+        Ok(Self { root })
+    }
+
+    pub fn root(&self) -> &SpannedValue {
+        &self.root
+    }
+
+    /// Where the value at `pointer` is in the source, ex. to point out a field that an
+    /// application rejected
+    pub fn locate(&self, pointer: &str) -> Option<Span> {
+        self.root.pointer(pointer).map(|value| value.span)
+    }
+
+    /// The document without locations
+    pub fn to_value(&self) -> Value {
+        self.root.to_value()
+    }
+}
+
+/// A container that is still being read
+enum Frame {
+    Array(Vec<SpannedValue>),
+    Object(Vec<SpannedMember>, Option<(String, Span)>),
+}
+
+/// Parses a document, recording where each value and key is in `input`
+///
+/// Nesting is limited to [`crate::DEFAULT_MAX_DEPTH`], deeper documents fail with
+/// [`ReaderError::LimitExceeded`].
+pub fn parse_with_spans(input: &str) -> Result<SpannedValue, ReaderError> {
+    let mut reader = JsonReader::new(input.as_bytes());
+    let mut stack: Vec<(Position, Frame)> = Vec::new();
+    loop {
+        let (event, start) = reader.next_event()?.ok_or(ReaderError::UnexpectedEof)?;
+        let end = reader.position();
+        let kind = match event {
+            Event::StartArray => {
+                stack.push((start, Frame::Array(Vec::new())));
+                continue;
+            }
+            Event::StartObject => {
+                stack.push((start, Frame::Object(Vec::new(), None)));
+                continue;
+            }
+            Event::Key(key) => {
+                if let Some((_, Frame::Object(_, pending))) = stack.last_mut() {
+                    *pending = Some((key, Span { start, end }));
+                }
+                continue;
+            }
+            Event::EndArray | Event::EndObject => {
+                let (container_start, frame) = stack.pop().expect("reader balances containers");
+                let kind = match frame {
+                    Frame::Array(elements) => SpannedKind::Array(elements),
+                    Frame::Object(members, _) => SpannedKind::Object(members),
+                };
+                finish(&mut stack, container_start, end, kind)
+            }
+            Event::String(s) => finish(&mut stack, start, end, SpannedKind::String(s)),
+            Event::Number(n) => finish(&mut stack, start, end, SpannedKind::Number(n)),
+            Event::Boolean(b) => finish(&mut stack, start, end, SpannedKind::Boolean(b)),
+            Event::Null => finish(&mut stack, start, end, SpannedKind::Null),
+        };
+        if let Some(root) = kind {
+            // the reader reports anything after the root value
+            reader.next_event()?;
+            return Ok(root);
+        }
+    }
+}
+
+/// Adds a completed value to its container, or returns it when it is the root
+fn finish(
+    stack: &mut [(Position, Frame)],
+    start: Position,
+    end: Position,
+    kind: SpannedKind,
+) -> Option<SpannedValue> {
+    let value = SpannedValue {
+        span: Span { start, end },
+        kind,
+    };
+    match stack.last_mut() {
+        None => return Some(value),
+        Some((_, Frame::Array(elements))) => elements.push(value),
+        Some((_, Frame::Object(members, pending))) => {
+            let (key, key_span) = pending
+                .take()
+                .expect("reader emits a key before each value");
+            members.push(SpannedMember {
+                key,
+                key_span,
+                value,
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Limit, Parser, ReaderError};
+
+    use super::{SpannedDocument, SpannedKind, SpannedValue, parse_with_spans};
+
+    fn text<'a>(input: &'a str, value: &SpannedValue) -> &'a str {
+        &input[value.span.range()]
+    }
+
+    #[test]
+    fn records_value_and_key_spans() {
+        let input = "{\n  \"users\": [{\"name\": \"ken\"}, -1.5e2],\n  \"ok\": true\n}";
+        let root = parse_with_spans(input).unwrap();
+        assert_eq!(root.span.range(), 0..input.len());
+
+        let SpannedKind::Object(members) = &root.kind else {
+            panic!("expected an object");
+        };
+        assert_eq!(&input[members[0].key_span.range()], "\"users\"");
+        assert_eq!(members[0].key_span.start.line, 2);
+        assert_eq!(members[0].key_span.start.column, 3);
+        assert_eq!(
+            text(input, &members[0].value),
+            "[{\"name\": \"ken\"}, -1.5e2]"
+        );
+        assert_eq!(text(input, &members[1].value), "true");
+        assert_eq!(members[1].value.span.start.line, 3);
+
+        let SpannedKind::Array(elements) = &members[0].value.kind else {
+            panic!("expected an array");
+        };
+        assert_eq!(text(input, &elements[0]), "{\"name\": \"ken\"}");
+        assert_eq!(text(input, &elements[1]), "-1.5e2");
+    }
+
+    #[test]
+    fn converts_to_value() {
+        let input = r#"{"a": [1, "x", null], "b": {"c": false}}"#;
+        let root = parse_with_spans(input).unwrap();
+        assert_eq!(root.to_value(), Parser::new().parse_str(input).unwrap());
+    }
+
+    #[test]
+    fn scalar_root() {
+        let root = parse_with_spans("  \"x\" ").unwrap();
+        assert_eq!(root.span.range(), 2..5);
+    }
+
+    #[test]
+    fn locate_by_pointer() {
+        let input = "{\n  \"users\": [\n    {\"email\": \"a@b\"},\n    {\"email\": 7}\n  ]\n}";
+        let document = SpannedDocument::parse(input).unwrap();
+        let span = document.locate("/users/1/email").unwrap();
+        assert_eq!((span.start.line, span.start.column), (4, 15));
+        assert_eq!(&input[span.range()], "7");
+        assert_eq!(document.locate("/users/2"), None);
+        assert_eq!(document.locate(""), Some(document.root().span));
+    }
+
+    #[test]
+    fn repeated_keys_find_the_last() {
+        let document = SpannedDocument::parse(r#"{"a": 1, "a": 2}"#).unwrap();
+        assert_eq!(document.locate("/a").unwrap().range(), 14..15);
+    }
+
+    #[test]
+    fn exact_integers() {
+        let input =
+            r#"[18446744073709551616123, -170141183460469231731687303715884105728, 1.5, 1e3, "1"]"#;
+        let root = parse_with_spans(input).unwrap();
+        let element = |i: usize| root.pointer(&format!("/{i}")).unwrap();
+        assert_eq!(
+            element(0).as_u128(input),
+            Some(18_446_744_073_709_551_616_123)
+        );
+        assert_eq!(
+            element(0).as_i128(input),
+            Some(18_446_744_073_709_551_616_123)
+        );
+        assert_eq!(element(1).as_i128(input), Some(i128::MIN));
+        assert_eq!(element(1).as_u128(input), None);
+        for i in 2..5 {
+            assert_eq!(element(i).as_i128(input), None);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            parse_with_spans("[1,"),
+            Err(ReaderError::UnexpectedEof)
+        ));
+        assert!(matches!(
+            parse_with_spans("[1] 2"),
+            Err(ReaderError::TrailingContent { .. })
+        ));
+        let deep = "[".repeat(200_000);
+        assert!(matches!(
+            parse_with_spans(&format!("{deep}{}", "]".repeat(200_000))),
+            Err(ReaderError::LimitExceeded {
+                limit: Limit::Depth,
+                ..
+            })
+        ));
+    }
+}
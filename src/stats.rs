@@ -0,0 +1,165 @@
+use std::mem::size_of;
+
+use crate::{JsonPointer, Value};
+
+/// Size and shape of a document, see [`Value::stats`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Every value, including containers and the root
+    pub nodes: usize,
+
+    /// How deeply values are nested, the root is at depth 0
+    pub max_depth: usize,
+
+    /// Bytes of UTF-8 in string values, not counting quotes or escapes
+    pub string_bytes: usize,
+
+    /// Bytes of UTF-8 in object keys
+    pub key_bytes: usize,
+
+    pub nulls: usize,
+    pub booleans: usize,
+    pub numbers: usize,
+    pub strings: usize,
+    pub arrays: usize,
+    pub objects: usize,
+
+    /// The array with the most elements and its length, the first one found on ties
+    pub largest_array: Option<(JsonPointer, usize)>,
+
+    /// The object with the most members and its length, the first one found on ties
+    pub largest_object: Option<(JsonPointer, usize)>,
+}
+
+impl Value {
+    /// Counts the values in the document by type, along with its depth and largest
+    /// containers
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats::default();
+        for (pointer, value) in self.walk() {
+            stats.nodes += 1;
+            stats.max_depth = stats.max_depth.max(pointer.tokens().len());
+            match value {
+                Value::Null => stats.nulls += 1,
+                Value::Boolean(_) => stats.booleans += 1,
+                Value::Number(_) => stats.numbers += 1,
+                Value::String(s) => {
+                    stats.strings += 1;
+                    stats.string_bytes += s.len();
+                }
+                Value::Array(array) => {
+                    stats.arrays += 1;
+                    record_largest(&mut stats.largest_array, pointer, array.len());
+                }
+                Value::Object(map) => {
+                    stats.objects += 1;
+                    stats.key_bytes += map.keys().map(String::len).sum::<usize>();
+                    record_largest(&mut stats.largest_object, pointer, map.len());
+                }
+            }
+        }
+        stats
+    }
+}
+
+impl Value {
+    /// Estimates the memory used by the document in bytes, including the value itself,
+    /// unused capacity, and the buckets and control bytes of object hash tables
+    ///
+    /// Allocator overhead is not counted, so the real usage is somewhat higher.
+    pub fn approx_mem_bytes(&self) -> usize {
+        size_of::<Value>() + self.heap_bytes()
+    }
+
+    fn heap_bytes(&self) -> usize {
+        match self {
+            Value::String(s) => s.capacity(),
+            Value::Array(array) => {
+                let elements = array.capacity() * size_of::<Value>();
+                elements + array.iter().map(Value::heap_bytes).sum::<usize>()
+            }
+            Value::Object(map) => {
+                // the table keeps at most 7/8 of its power of two buckets full, each with a
+                // control byte, plus a group of trailing control bytes
+                let buckets = match map.capacity() {
+                    0 => 0,
+                    capacity => (capacity * 8 / 7).next_power_of_two(),
+                };
+                let table = match buckets {
+                    0 => 0,
+                    buckets => buckets * (size_of::<(String, Value)>() + 1) + 16,
+                };
+                let entries: usize = map
+                    .iter()
+                    .map(|(key, value)| key.capacity() + value.heap_bytes())
+                    .sum();
+                table + entries
+            }
+            _ => 0,
+        }
+    }
+}
+
+fn record_largest(largest: &mut Option<(JsonPointer, usize)>, pointer: JsonPointer, len: usize) {
+    if largest.as_ref().is_none_or(|(_, largest)| len > *largest) {
+        *largest = Some((pointer, len));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::mem::size_of;
+
+    use crate::{JsonPointer, Parser, Value};
+
+    use super::Stats;
+
+    #[test]
+    fn counts_a_document() {
+        let value = Parser::new()
+            .parse_str(
+                r#"{"name": "ab", "tags": ["x", "yz", null], "nested": {"deep": [[true, 1]]}}"#,
+            )
+            .unwrap();
+        let pointer = |input| JsonPointer::parse(input).unwrap();
+        let expected = Stats {
+            nodes: 11,
+            max_depth: 4,
+            string_bytes: 5,
+            key_bytes: 18,
+            nulls: 1,
+            booleans: 1,
+            numbers: 1,
+            strings: 3,
+            arrays: 3,
+            objects: 2,
+            largest_array: Some((pointer("/tags"), 3)),
+            largest_object: Some((pointer(""), 3)),
+        };
+        assert_eq!(value.stats(), expected);
+    }
+
+    #[test]
+    fn memory_estimate() {
+        let base = size_of::<Value>();
+        assert_eq!(Value::Null.approx_mem_bytes(), base);
+        assert_eq!(Value::string("abcd").approx_mem_bytes(), base + 4);
+
+        let array = Value::Array(Vec::with_capacity(4));
+        assert_eq!(array.approx_mem_bytes(), base + 4 * base);
+
+        let empty = Value::Object(HashMap::new());
+        assert_eq!(empty.approx_mem_bytes(), base);
+        let object = Value::object([("key", Value::string("value"))]);
+        let entries = size_of::<(String, Value)>() + 8;
+        assert!(object.approx_mem_bytes() > base + entries);
+    }
+
+    #[test]
+    fn scalar_root() {
+        let stats = Value::Null.stats();
+        assert_eq!((stats.nodes, stats.max_depth, stats.nulls), (1, 0, 1));
+        assert_eq!(stats.largest_array, None);
+    }
+}
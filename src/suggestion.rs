@@ -0,0 +1,131 @@
+use std::fmt;
+
+use crate::ParseError;
+use crate::parse::TokenParseError;
+use crate::tokenize::TokenizeError;
+
+/// A machine-readable hint for fixing a common mistake in the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Suggestion {
+    /// Strings must be wrapped in double quotes `"` rather than single quotes `'`
+    UseDoubleQuotes,
+
+    /// Object keys must be strings, ex. `{"key": 1}` rather than `{key: 1}`
+    QuoteKey(String),
+
+    /// Literals are always lowercase, ex. `true` rather than `True`
+    LowercaseLiteral {
+        found: String,
+        expected: &'static str,
+    },
+
+    /// A comma may not follow the last element of an array or object
+    RemoveTrailingComma,
+}
+
+impl fmt::Display for Suggestion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UseDoubleQuotes => {
+                write!(f, "use double quotes `\"` instead of single quotes `'`")
+            }
+            Self::QuoteKey(key) => {
+                write!(f, "did you mean `\"{key}\"`? object keys must be quoted")
+            }
+            Self::LowercaseLiteral { expected, .. } => write!(f, "did you mean `{expected}`?"),
+            Self::RemoveTrailingComma => write!(f, "remove the trailing comma"),
+        }
+    }
+}
+
+impl TokenizeError {
+    /// A suggested fix for this error, if it is a recognizable mistake
+    pub fn suggestion(&self) -> Option<Suggestion> {
+        match self {
+            Self::CharNotRecognized('\'') => Some(Suggestion::UseDoubleQuotes),
+            Self::UnquotedKey(key) => Some(Suggestion::QuoteKey(key.clone())),
+            Self::MiscasedLiteral(found) => {
+                let expected = match found.to_ascii_lowercase().as_str() {
+                    "null" => "null",
+                    "true" => "true",
+                    _ => "false",
+                };
+                Some(Suggestion::LowercaseLiteral {
+                    found: found.clone(),
+                    expected,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl TokenParseError {
+    /// A suggested fix for this error, if it is a recognizable mistake
+    pub fn suggestion(&self) -> Option<Suggestion> {
+        match self {
+            Self::TrailingComma => Some(Suggestion::RemoveTrailingComma),
+            _ => None,
+        }
+    }
+}
+
+impl ParseError {
+    /// A suggested fix for this error, if it is a recognizable mistake
+    pub fn suggestion(&self) -> Option<Suggestion> {
+        match self {
+            Self::TokenizeError(err) => err.suggestion(),
+            Self::ParseError(err) => err.suggestion(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use crate::parse;
+
+    use super::Suggestion;
+
+    fn check(input: &str, expected: Suggestion) {
+        let actual = parse(String::from(input)).unwrap_err().suggestion();
+        assert_eq!(actual, Some(expected));
+    }
+
+    #[test]
+    fn single_quotes() {
+        check("['a']", Suggestion::UseDoubleQuotes);
+    }
+
+    #[test]
+    fn unquoted_key() {
+        check("{ name: 1 }", Suggestion::QuoteKey(String::from("name")));
+    }
+
+    #[test]
+    fn capitalized_true() {
+        check(
+            "[True]",
+            Suggestion::LowercaseLiteral {
+                found: String::from("True"),
+                expected: "true",
+            },
+        );
+    }
+
+    #[test]
+    fn trailing_comma_in_array() {
+        check("[1, 2,]", Suggestion::RemoveTrailingComma);
+    }
+
+    #[test]
+    fn trailing_comma_in_object() {
+        check(r#"{"a": 1,}"#, Suggestion::RemoveTrailingComma);
+    }
+
+    #[test]
+    fn no_suggestion_for_other_errors() {
+        let err = parse(String::from("]")).unwrap_err();
+        assert_eq!(err.suggestion(), None);
+    }
+}
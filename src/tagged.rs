@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::Value;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaggedError {
+    NotAnObject,
+
+    /// The object has no discriminator field
+    MissingTag,
+
+    /// The discriminator field holds something other than a string
+    TagNotAString,
+
+    /// No handler was given for this discriminator
+    UnknownTag(String),
+}
+
+impl fmt::Display for TaggedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAnObject => f.write_str("the value is not an object"),
+            Self::MissingTag => f.write_str("the object has no discriminator field"),
+            Self::TagNotAString => f.write_str("the discriminator field is not a string"),
+            Self::UnknownTag(tag) => write!(f, "unknown discriminator {tag:?}"),
+        }
+    }
+}
+
+impl std::error::Error for TaggedError {}
+
+impl Value {
+    /// The string in the discriminator `field` of an object, ex. `"user"` for
+    /// `{"type": "user", ...}` and `field` `"type"`
+    pub fn discriminant(&self, field: &str) -> Option<&str> {
+        self.as_object()?.get(field)?.as_str()
+    }
+}
+
+/// Dispatches an object on its discriminator `field` to the first matching [`Tagged::on`]
+///
+/// Each handler receives the object's other fields, without the discriminator.
+pub fn match_tagged<T>(value: Value, field: &str) -> Tagged<T> {
+    let state = match value {
+        Value::Object(mut fields) => match fields.remove(field) {
+            Some(Value::String(tag)) => State::Pending(tag, fields),
+            Some(_) => State::Failed(TaggedError::TagNotAString),
+            None => State::Failed(TaggedError::MissingTag),
+        },
+        _ => State::Failed(TaggedError::NotAnObject),
+    };
+    Tagged { state }
+}
+
+/// An object being dispatched by [`match_tagged`]
+pub struct Tagged<T> {
+    state: State<T>,
+}
+
+enum State<T> {
+    Pending(String, HashMap<String, Value>),
+    Handled(T),
+    Failed(TaggedError),
+}
+
+impl<T> Tagged<T> {
+    /// Calls `handler` with the remaining fields if the discriminator is `tag` and no
+    /// earlier handler matched
+    pub fn on(self, tag: &str, handler: impl FnOnce(HashMap<String, Value>) -> T) -> Self {
+        let state = match self.state {
+            State::Pending(found, fields) if found == tag => State::Handled(handler(fields)),
+            state => state,
+        };
+        Self { state }
+    }
+
+    /// The handler's result, or [`TaggedError::UnknownTag`] if none matched
+    pub fn finish(self) -> Result<T, TaggedError> {
+        match self.state {
+            State::Pending(tag, _) => Err(TaggedError::UnknownTag(tag)),
+            State::Handled(result) => Ok(result),
+            State::Failed(err) => Err(err),
+        }
+    }
+
+    /// Like [`Tagged::finish`], but calls `fallback` with the discriminator and remaining
+    /// fields if no handler matched
+    pub fn otherwise(
+        self,
+        fallback: impl FnOnce(String, HashMap<String, Value>) -> T,
+    ) -> Result<T, TaggedError> {
+        match self.state {
+            State::Pending(tag, fields) => Ok(fallback(tag, fields)),
+            State::Handled(result) => Ok(result),
+            State::Failed(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::{TaggedError, match_tagged};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    #[test]
+    fn discriminant() {
+        let event = value(r#"{"type": "user", "kind": 1}"#);
+        assert_eq!(event.discriminant("type"), Some("user"));
+        assert_eq!(event.discriminant("kind"), None);
+        assert_eq!(event.discriminant("missing"), None);
+        assert_eq!(value(r#"["type"]"#).discriminant("type"), None);
+    }
+
+    #[test]
+    fn dispatches_remaining_fields() {
+        let dispatch = |input: &str| {
+            match_tagged(value(input), "type")
+                .on("user", |fields| fields.len())
+                .on("user", |_| 100)
+                .on("order", |fields| fields.len() * 10)
+                .finish()
+        };
+        assert_eq!(dispatch(r#"{"type": "user", "name": "ada"}"#), Ok(1));
+        assert_eq!(
+            dispatch(r#"{"id": 1, "type": "order", "total": 2}"#),
+            Ok(20)
+        );
+        assert_eq!(
+            dispatch(r#"{"type": "refund"}"#),
+            Err(TaggedError::UnknownTag(String::from("refund")))
+        );
+        assert_eq!(
+            dispatch(r#"{"kind": "user"}"#),
+            Err(TaggedError::MissingTag)
+        );
+        assert_eq!(
+            dispatch(r#"{"type": true}"#),
+            Err(TaggedError::TagNotAString)
+        );
+        assert_eq!(dispatch("[]"), Err(TaggedError::NotAnObject));
+    }
+
+    #[test]
+    fn fallback() {
+        let result = match_tagged(value(r#"{"type": "refund", "id": 3}"#), "type")
+            .on("user", |_| String::new())
+            .otherwise(|tag, fields| format!("{tag} {}", fields.len()));
+        assert_eq!(result, Ok(String::from("refund 1")));
+    }
+}
@@ -0,0 +1,155 @@
+use std::fmt;
+
+use crate::{JsonPointer, Value};
+
+/// A placeholder in a template could not be rendered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateError {
+    /// Location of the string holding the placeholder
+    pub pointer: JsonPointer,
+    pub kind: TemplateErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateErrorKind {
+    /// The dot path in `{{path}}` is not in the context
+    Missing(String),
+
+    /// `{{` without a closing `}}`
+    Unclosed,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            TemplateErrorKind::Missing(path) => {
+                write!(f, "`{path}` is not in the context, at `{}`", self.pointer)
+            }
+            TemplateErrorKind::Unclosed => write!(f, "unclosed `{{{{` at `{}`", self.pointer),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+impl Value {
+    /// A copy of this template with `{{path}}` placeholders in strings filled in from
+    /// `context`, where `path` is a dot path such as `user.emails[0]`
+    ///
+    /// A string that is a single placeholder becomes the context value itself, keeping its
+    /// type, so `"{{port}}"` can render as `8080`. Placeholders inside longer strings are
+    /// replaced by the string's contents, or by compact JSON for any other value. Values
+    /// taken from the context are not rendered again, and object keys are left alone.
+    pub fn render(&self, context: &Value) -> Result<Value, TemplateError> {
+        render_at(self, context, &mut JsonPointer::root())
+    }
+}
+
+fn render_at(
+    template: &Value,
+    context: &Value,
+    pointer: &mut JsonPointer,
+) -> Result<Value, TemplateError> {
+    let error = |kind| TemplateError {
+        pointer: pointer.clone(),
+        kind,
+    };
+    Ok(match template {
+        Value::String(s) => match whole_placeholder(s) {
+            Some(path) => lookup(context, path).map_err(error)?.clone(),
+            None => Value::String(interpolate(s, context).map_err(error)?),
+        },
+        Value::Array(array) => {
+            let mut rendered = Vec::with_capacity(array.len());
+            for (i, item) in array.iter().enumerate() {
+                pointer.push(i.to_string());
+                rendered.push(render_at(item, context, pointer)?);
+                pointer.pop();
+            }
+            Value::Array(rendered)
+        }
+        Value::Object(map) => {
+            let mut rendered = map.clone();
+            for (key, item) in &mut rendered {
+                pointer.push(key.as_str());
+                *item = render_at(item, context, pointer)?;
+                pointer.pop();
+            }
+            Value::Object(rendered)
+        }
+        value => value.clone(),
+    })
+}
+
+/// The path of a string made of exactly one placeholder, ex. `{{ port }}`
+fn whole_placeholder(s: &str) -> Option<&str> {
+    let path = s.strip_prefix("{{")?.strip_suffix("}}")?;
+    (!path.contains("{{") && !path.contains("}}")).then(|| path.trim())
+}
+
+fn lookup<'a>(context: &'a Value, path: &str) -> Result<&'a Value, TemplateErrorKind> {
+    context
+        .get_path(path)
+        .ok_or_else(|| TemplateErrorKind::Missing(String::from(path)))
+}
+
+fn interpolate(template: &str, context: &Value) -> Result<String, TemplateErrorKind> {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find("{{") {
+        output.push_str(&rest[..open]);
+        let after = &rest[open + 2..];
+        let close = after.find("}}").ok_or(TemplateErrorKind::Unclosed)?;
+        match lookup(context, after[..close].trim())? {
+            Value::String(s) => output.push_str(s),
+            value => output.push_str(&value.to_json_string()),
+        }
+        rest = &after[close + 2..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{JsonPointer, Parser, Value};
+
+    use super::{TemplateError, TemplateErrorKind};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    #[test]
+    fn renders_placeholders() {
+        let context = value(
+            r#"{"host": "db", "port": 5432, "tls": true, "tags": ["a", "b"],
+                "raw": "{{host}}"}"#,
+        );
+        let template = value(
+            r#"{"url": "pg://{{host}}:{{ port }}/x", "port": "{{port}}", "tls": "{{tls}}",
+                "first": "{{tags[0]}}", "all": "tags={{tags}}", "raw": "{{raw}}", "n": 1,
+                "{{host}}": "plain"}"#,
+        );
+        let expected = value(
+            r#"{"url": "pg://db:5432/x", "port": 5432, "tls": true, "first": "a",
+                "all": "tags=[\"a\",\"b\"]", "raw": "{{host}}", "n": 1, "{{host}}": "plain"}"#,
+        );
+        assert_eq!(template.render(&context), Ok(expected));
+    }
+
+    #[test]
+    fn errors() {
+        let context = value(r#"{"a": 1}"#);
+        let expected = TemplateError {
+            pointer: JsonPointer::parse("/x/1").unwrap(),
+            kind: TemplateErrorKind::Missing(String::from("b")),
+        };
+        assert_eq!(
+            value(r#"{"x": ["{{a}}", "{{ b }}"]}"#).render(&context),
+            Err(expected)
+        );
+        let err = value(r#"["a {{a"]"#).render(&context).unwrap_err();
+        assert_eq!(err.kind, TemplateErrorKind::Unclosed);
+    }
+}
@@ -1,5 +1,8 @@
 
-use std::num::ParseFloatError;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::num::ParseFloatError;
 
 #[derive(Debug, PartialEq)]
 pub enum Token {
@@ -30,6 +33,35 @@ pub enum TokenizeError {
 
     /// Character is not part of a JSON token
     CharNotRecognized(char),
+
+    /// A literal written with the wrong case, ex. `True` or `NULL`
+    MiscasedLiteral(String),
+
+    /// A bare word followed by a colon, ex. `{key: 1}`
+    UnquotedKey(String),
+}
+
+impl fmt::Display for TokenizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnfinishedLiteralValue => f.write_str("unfinished literal"),
+            Self::ParseNumberError(err) => write!(f, "invalid number: {err}"),
+            Self::UnclosedQuotes => f.write_str("unclosed string"),
+            Self::UnexpectedEof => f.write_str("unexpected end of input"),
+            Self::CharNotRecognized(ch) => write!(f, "unexpected `{}`", ch.escape_default()),
+            Self::MiscasedLiteral(found) => write!(f, "miscased literal `{found}`"),
+            Self::UnquotedKey(key) => write!(f, "unquoted key `{key}`"),
+        }
+    }
+}
+
+impl core::error::Error for TokenizeError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::ParseNumberError(err) => Some(err),
+            _ => None,
+        }
+    }
 }
 
 pub fn tokenize(input: String) -> Result<Vec<Token>, TokenizeError> {
@@ -40,12 +72,12 @@ pub fn tokenize(input: String) -> Result<Vec<Token>, TokenizeError> {
     while index < chars.len() {
         let token = make_token(&chars, &mut index)?;
         tokens.push(token);
-        index = index + 1;
+        index += 1;
     }
     Ok(tokens)
 }
 
-fn make_token(chars: &Vec<char>, index: &mut usize ) -> Result<Token, TokenizeError> {
+fn make_token(chars: &[char], index: &mut usize) -> Result<Token, TokenizeError> {
     let mut ch = chars[*index];
 
     while ch.is_ascii_whitespace() {
@@ -63,52 +95,55 @@ fn make_token(chars: &Vec<char>, index: &mut usize ) -> Result<Token, TokenizeEr
         ']' => Token::RightBracket,
         ',' => Token::Comma,
         ':' => Token::Colon,
-        'n' => tokenize_null(chars, index)?,
-        't' => tokenize_true(chars, index)?,
-        'f' => tokenize_false(chars, index)?,
-       c if c.is_ascii_digit() => tokenize_float(chars, index)?,
-       '"' => tokenize_string(chars, index)?,
-       ch => return Err(TokenizeError::CharNotRecognized(ch)),
-       _ => todo!("Implement other tokens"),
+        c if c.is_ascii_alphabetic() => tokenize_word(chars, index)?,
+        c if c.is_ascii_digit() => tokenize_float(chars, index)?,
+        '"' => tokenize_string(chars, index)?,
+        ch => return Err(TokenizeError::CharNotRecognized(ch)),
     };
 
-   Ok(token)
+    Ok(token)
 }
 
-fn tokenize_null(chars: &Vec<char>, index: &mut usize ) -> Result<Token, TokenizeError> {
-    for expected_char in "null".chars() {
-        if chars[*index] != expected_char {
-            return Err(TokenizeError::UnfinishedLiteralValue);
-        }
+/// Tokenizes `null`, `true` and `false`, diagnosing any other bare word
+fn tokenize_word(chars: &[char], index: &mut usize) -> Result<Token, TokenizeError> {
+    let start = *index;
+    while *index < chars.len() && is_word_char(chars[*index]) {
         *index += 1;
     }
+    let word: String = chars[start..*index].iter().collect();
     *index -= 1;
-    Ok(Token::Null)
-}
 
-fn tokenize_false(chars: &Vec<char>, index: &mut usize) -> Result<Token, TokenizeError> {
-    for expected_char in "false".chars() {
-        if expected_char != chars[*index] {
-            return Err(TokenizeError::UnfinishedLiteralValue);
-        }
-        *index += 1;
+    match word.as_str() {
+        "null" => Ok(Token::Null),
+        "true" => Ok(Token::True),
+        "false" => Ok(Token::False),
+        _ => Err(unrecognized_word(word, chars, *index + 1)),
     }
-    *index -= 1;
-    Ok(Token::False)
 }
 
-fn tokenize_true(chars: &Vec<char>, index: &mut usize) -> Result<Token, TokenizeError> {
-    for expected_char in "true".chars() {
-        if expected_char != chars[*index] {
-            return Err(TokenizeError::UnfinishedLiteralValue);
-        }
-        *index += 1;
+fn is_word_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '_' || ch == '$'
+}
+
+fn unrecognized_word(word: String, chars: &[char], after: usize) -> TokenizeError {
+    let lowercase = word.to_ascii_lowercase();
+    if matches!(lowercase.as_str(), "null" | "true" | "false") {
+        return TokenizeError::MiscasedLiteral(word);
+    }
+
+    let next = chars[after..].iter().find(|ch| !ch.is_ascii_whitespace());
+    if next == Some(&':') {
+        return TokenizeError::UnquotedKey(word);
+    }
+
+    match word.chars().next() {
+        Some('n' | 't' | 'f') => TokenizeError::UnfinishedLiteralValue,
+        Some(first) => TokenizeError::CharNotRecognized(first),
+        None => TokenizeError::UnexpectedEof,
     }
-    *index -= 1;
-    Ok(Token::True)
 }
 
-fn tokenize_float(chars: &Vec<char>, curr_idx: &mut usize) -> Result<Token, TokenizeError> {
+fn tokenize_float(chars: &[char], curr_idx: &mut usize) -> Result<Token, TokenizeError> {
     let mut unparsed_num = String::new();
     let mut has_decimal = false;
 
@@ -124,6 +159,8 @@ fn tokenize_float(chars: &Vec<char>, curr_idx: &mut usize) -> Result<Token, Toke
         }
         *curr_idx += 1;
     }
+    // leave the index on the last digit, like the other multi-character tokens
+    *curr_idx -= 1;
 
     match unparsed_num.parse() {
         Ok(f) => Ok(Token::Number(f)),
@@ -131,7 +168,7 @@ fn tokenize_float(chars: &Vec<char>, curr_idx: &mut usize) -> Result<Token, Toke
     }
 }
 
-fn tokenize_string(chars: &Vec<char>, index: &mut usize) -> Result<Token, TokenizeError> {
+fn tokenize_string(chars: &[char], index: &mut usize) -> Result<Token, TokenizeError> {
     debug_assert!(chars[*index] == '"');
     let mut string = String::new();
     let mut is_escaping = false;
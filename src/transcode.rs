@@ -0,0 +1,311 @@
+use std::{fmt, io};
+
+use crate::{Event, JsonPointer, JsonReader, JsonWriter, PointerError, ReaderError, WriterError};
+
+#[derive(Debug)]
+pub enum TranscodeError {
+    Read(ReaderError),
+    Write(WriterError),
+}
+
+impl fmt::Display for TranscodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read(err) => write!(f, "reading the document failed: {err}"),
+            Self::Write(err) => write!(f, "writing the document failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for TranscodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Read(err) => Some(err),
+            Self::Write(err) => Some(err),
+        }
+    }
+}
+
+impl From<ReaderError> for TranscodeError {
+    fn from(err: ReaderError) -> Self {
+        Self::Read(err)
+    }
+}
+
+impl From<WriterError> for TranscodeError {
+    fn from(err: WriterError) -> Self {
+        Self::Write(err)
+    }
+}
+
+/// A JSON Pointer whose tokens may be wildcards, ex. `/users/*/email` or `/**/password`
+///
+/// A `*` token matches any one key or index, and `**` matches any number of them,
+/// including none.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathPattern {
+    tokens: Vec<String>,
+}
+
+impl PathPattern {
+    pub fn parse(input: &str) -> Result<Self, PointerError> {
+        let tokens = JsonPointer::parse(input)?.tokens().to_vec();
+        Ok(Self { tokens })
+    }
+
+    /// Whether the pattern matches `pointer` or one of its ancestors
+    fn covers(&self, pointer: &JsonPointer) -> bool {
+        covers(&self.tokens, pointer.tokens())
+    }
+
+    /// Whether the pattern could match `pointer` or one of its descendants
+    fn leads_to(&self, pointer: &JsonPointer) -> bool {
+        leads_to(&self.tokens, pointer.tokens())
+    }
+}
+
+fn covers(pattern: &[String], path: &[String]) -> bool {
+    match (pattern.split_first(), path.split_first()) {
+        (None, _) => true,
+        (Some((any, rest)), _) if any == "**" => {
+            covers(rest, path) || (!path.is_empty() && covers(pattern, &path[1..]))
+        }
+        (Some((token, rest)), Some((step, path))) => {
+            (token == "*" || token == step) && covers(rest, path)
+        }
+        (Some(_), None) => false,
+    }
+}
+
+fn leads_to(pattern: &[String], path: &[String]) -> bool {
+    match (pattern.split_first(), path.split_first()) {
+        (_, None) => true,
+        (None, Some(_)) => false,
+        (Some((any, _)), _) if any == "**" => true,
+        (Some((token, rest)), Some((step, path))) => {
+            (token == "*" || token == step) && leads_to(rest, path)
+        }
+    }
+}
+
+/// Copies every event from `reader` to `writer` without building a [`crate::Value`]
+///
+/// Memory use depends only on the nesting depth and the longest string, so this can
+/// minify or pretty-print files of any size depending on how `writer` was created.
+pub fn transcode<R: io::Read, W: io::Write>(
+    reader: &mut JsonReader<R>,
+    writer: &mut JsonWriter<W>,
+) -> Result<(), TranscodeError> {
+    transcode_filtered(reader, writer, |_| true)
+}
+
+/// Copies events from `reader` to `writer`, leaving out values for which `keep` returns
+/// `false`
+///
+/// `keep` is called with the location of every value below the root before it is
+/// written. A dropped array or object is skipped whole, along with its key.
+pub fn transcode_filtered<R: io::Read, W: io::Write>(
+    reader: &mut JsonReader<R>,
+    writer: &mut JsonWriter<W>,
+    mut keep: impl FnMut(&JsonPointer) -> bool,
+) -> Result<(), TranscodeError> {
+    copy_filtered(reader, writer, |pointer, _| keep(pointer))
+}
+
+/// [`transcode_filtered`] where `keep` also sees the first event of each value
+fn copy_filtered<R: io::Read, W: io::Write>(
+    reader: &mut JsonReader<R>,
+    writer: &mut JsonWriter<W>,
+    mut keep: impl FnMut(&JsonPointer, &Event) -> bool,
+) -> Result<(), TranscodeError> {
+    let mut pointer = JsonPointer::root();
+    // the next array index for each open container, `None` for objects
+    let mut indices: Vec<Option<usize>> = Vec::new();
+    let mut pending_key = None;
+
+    while let Some((event, _)) = reader.next_event()? {
+        if let Event::Key(key) = event {
+            pending_key = Some(key);
+            continue;
+        }
+        if let Event::EndObject | Event::EndArray = event {
+            indices.pop();
+            if !indices.is_empty() {
+                pointer.pop();
+            }
+            writer.event(&event)?;
+            continue;
+        }
+
+        // a value, find its location
+        let token = match (indices.last_mut(), pending_key.take()) {
+            (Some(Some(index)), _) => {
+                *index += 1;
+                Some((*index - 1).to_string())
+            }
+            (Some(None), key) => key,
+            (None, _) => None,
+        };
+        if let Some(token) = &token {
+            pointer.push(token.clone());
+            if !keep(&pointer, &event) {
+                pointer.pop();
+                if let Event::StartObject | Event::StartArray = event {
+                    reader.skip_value()?;
+                }
+                continue;
+            }
+            if indices.last() == Some(&None) {
+                writer.key(token)?;
+            }
+        }
+
+        let opens = match event {
+            Event::StartObject => Some(None),
+            Event::StartArray => Some(Some(0)),
+            _ => None,
+        };
+        writer.event(&event)?;
+        match opens {
+            Some(index) => indices.push(index),
+            None if token.is_some() => {
+                pointer.pop();
+            }
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+/// Copies `reader` to `writer`, keeping only the values matched by one of the `keep`
+/// patterns along with the objects and arrays that may lead to them
+///
+/// Containers are decided on before their contents are read, so one on the way to a
+/// pattern stays even when nothing inside it matches, ex. `{}`. The root is always
+/// written. Like [`transcode_filtered`], memory use does not grow with the input.
+pub fn filter_stream<R: io::Read, W: io::Write>(
+    reader: &mut JsonReader<R>,
+    writer: &mut JsonWriter<W>,
+    keep: &[PathPattern],
+) -> Result<(), TranscodeError> {
+    copy_filtered(reader, writer, |pointer, event| {
+        let container = matches!(event, Event::StartObject | Event::StartArray);
+        keep.iter()
+            .any(|pattern| pattern.covers(pointer) || (container && pattern.leads_to(pointer)))
+    })
+}
+
+/// Copies `reader` to `writer`, leaving out the values matched by one of the `drop`
+/// patterns, ex. to strip personal data from an export
+pub fn drop_stream<R: io::Read, W: io::Write>(
+    reader: &mut JsonReader<R>,
+    writer: &mut JsonWriter<W>,
+    drop: &[PathPattern],
+) -> Result<(), TranscodeError> {
+    transcode_filtered(reader, writer, |pointer| {
+        !drop.iter().any(|pattern| pattern.covers(pointer))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{JsonReader, JsonWriter, Parser};
+
+    use super::{PathPattern, drop_stream, filter_stream, transcode, transcode_filtered};
+
+    const INPUT: &str =
+        r#"{ "a" : [ 1, { "secret": true, "b": 2 } ], "secret" : { "x": [1] }, "c": "d" }"#;
+
+    fn run(writer: JsonWriter<Vec<u8>>, keep: impl FnMut(&crate::JsonPointer) -> bool) -> String {
+        let mut reader = JsonReader::new(INPUT.as_bytes());
+        let mut writer = writer;
+        transcode_filtered(&mut reader, &mut writer, keep).unwrap();
+        String::from_utf8(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn minifies() {
+        let mut reader = JsonReader::new(INPUT.as_bytes());
+        let mut writer = JsonWriter::new(Vec::new());
+        transcode(&mut reader, &mut writer).unwrap();
+        let output = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(
+            output,
+            r#"{"a":[1,{"secret":true,"b":2}],"secret":{"x":[1]},"c":"d"}"#
+        );
+    }
+
+    #[test]
+    fn pretty_prints() {
+        let output = run(JsonWriter::pretty(Vec::new()), |_| true);
+        let expected = Parser::new().parse_str(INPUT).unwrap();
+        assert_eq!(Parser::new().parse_str(&output.clone()).unwrap(), expected);
+        assert!(output.starts_with("{\n  \"a\": [\n    1,\n"), "{output}");
+    }
+
+    #[test]
+    fn filters_by_pointer() {
+        let output = run(JsonWriter::new(Vec::new()), |pointer| {
+            pointer
+                .tokens()
+                .last()
+                .is_none_or(|token| token != "secret")
+        });
+        assert_eq!(output, r#"{"a":[1,{"b":2}],"c":"d"}"#);
+
+        let output = run(JsonWriter::new(Vec::new()), |pointer| {
+            pointer.to_string() != "/a/0"
+        });
+        assert_eq!(
+            output,
+            r#"{"a":[{"secret":true,"b":2}],"secret":{"x":[1]},"c":"d"}"#
+        );
+    }
+
+    fn patterns(paths: &[&str]) -> Vec<PathPattern> {
+        paths
+            .iter()
+            .map(|path| PathPattern::parse(path).unwrap())
+            .collect()
+    }
+
+    fn filter(input: &str, keep: &[&str]) -> String {
+        let mut reader = JsonReader::new(input.as_bytes());
+        let mut writer = JsonWriter::new(Vec::new());
+        filter_stream(&mut reader, &mut writer, &patterns(keep)).unwrap();
+        String::from_utf8(writer.finish().unwrap()).unwrap()
+    }
+
+    fn drop(input: &str, paths: &[&str]) -> String {
+        let mut reader = JsonReader::new(input.as_bytes());
+        let mut writer = JsonWriter::new(Vec::new());
+        drop_stream(&mut reader, &mut writer, &patterns(paths)).unwrap();
+        String::from_utf8(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn keeps_matching_paths() {
+        assert_eq!(filter(INPUT, &["/c"]), r#"{"c":"d"}"#);
+        assert_eq!(
+            filter(INPUT, &["/a/*/b", "/secret"]),
+            r#"{"a":[{"b":2}],"secret":{"x":[1]}}"#
+        );
+        assert_eq!(
+            filter(INPUT, &["/**/secret"]),
+            r#"{"a":[{"secret":true}],"secret":{"x":[1]}}"#
+        );
+        assert_eq!(filter(INPUT, &["/missing"]), "{}");
+        assert_eq!(filter(INPUT, &[""]), filter(INPUT, &["/**"]));
+    }
+
+    #[test]
+    fn drops_matching_paths() {
+        assert_eq!(drop(INPUT, &["/**/secret"]), r#"{"a":[1,{"b":2}],"c":"d"}"#);
+        assert_eq!(
+            drop(INPUT, &["/a/*", "/c"]),
+            r#"{"a":[],"secret":{"x":[1]}}"#
+        );
+        assert_eq!(drop("[[1, 2], [3]]", &["/*/0"]), "[[2],[]]");
+        assert!(PathPattern::parse("a/*").is_err());
+    }
+}
@@ -0,0 +1,658 @@
+//! Conversions between [`Value`] and the serde data model, enabled with the `serde` feature
+//!
+//! Integers are stored as `f64`, so integers beyond ±2^53 lose precision.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::de::{self, DeserializeOwned, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{self, Serialize};
+use serde::{Deserialize, Deserializer, Serializer};
+
+use crate::Value;
+
+/// A value could not be converted to or from a Rust type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerdeError(String);
+
+impl fmt::Display for SerdeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for SerdeError {}
+
+impl ser::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+impl de::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+/// Converts any serializable type into a [`Value`]
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value, SerdeError> {
+    value.serialize(ValueSerializer)
+}
+
+/// Converts a [`Value`] into any deserializable type
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, SerdeError> {
+    T::deserialize(value)
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Null => serializer.serialize_unit(),
+            Value::Boolean(b) => serializer.serialize_bool(*b),
+            Value::Number(n) => serializer.serialize_f64(*n),
+            Value::String(s) => serializer.serialize_str(s),
+            Value::Array(array) => serializer.collect_seq(array),
+            Value::Object(map) => serializer.collect_map(map),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_bool<E>(self, b: bool) -> Result<Value, E> {
+        Ok(Value::Boolean(b))
+    }
+
+    fn visit_i64<E>(self, n: i64) -> Result<Value, E> {
+        Ok(Value::Number(n as f64))
+    }
+
+    fn visit_u64<E>(self, n: u64) -> Result<Value, E> {
+        Ok(Value::Number(n as f64))
+    }
+
+    fn visit_f64<E>(self, n: f64) -> Result<Value, E> {
+        Ok(Value::Number(n))
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<Value, E> {
+        Ok(Value::String(String::from(s)))
+    }
+
+    fn visit_string<E>(self, s: String) -> Result<Value, E> {
+        Ok(Value::String(s))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut array = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            array.push(item);
+        }
+        Ok(Value::Array(array))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut object = HashMap::new();
+        while let Some((key, value)) = map.next_entry()? {
+            object.insert(key, value);
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+struct ValueSerializer;
+
+impl Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = SerdeError;
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeArray;
+    type SerializeMap = SerializeObject;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeObject;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, SerdeError> {
+        Ok(Value::Boolean(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, SerdeError> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, SerdeError> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, SerdeError> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, SerdeError> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, SerdeError> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, SerdeError> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, SerdeError> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, SerdeError> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, SerdeError> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, SerdeError> {
+        Ok(Value::Number(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, SerdeError> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, SerdeError> {
+        Ok(Value::String(String::from(v)))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, SerdeError> {
+        Ok(Value::Array(
+            v.iter().map(|b| Value::Number(f64::from(*b))).collect(),
+        ))
+    }
+
+    fn serialize_none(self) -> Result<Value, SerdeError> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, SerdeError> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, SerdeError> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Value, SerdeError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, SerdeError> {
+        let map = HashMap::from([(String::from(variant), to_value(value)?)]);
+        Ok(Value::Object(map))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, SerdeError> {
+        Ok(SerializeArray {
+            variant: None,
+            array: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, SerdeError> {
+        Ok(SerializeArray {
+            variant: Some(variant),
+            array: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeObject, SerdeError> {
+        Ok(SerializeObject {
+            variant: None,
+            map: HashMap::new(),
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeObject, SerdeError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeObject, SerdeError> {
+        Ok(SerializeObject {
+            variant: Some(variant),
+            map: HashMap::new(),
+            next_key: None,
+        })
+    }
+}
+
+/// Wraps an enum variant's contents as `{"Variant": value}`
+fn wrap_variant(variant: Option<&'static str>, value: Value) -> Value {
+    match variant {
+        Some(variant) => Value::Object(HashMap::from([(String::from(variant), value)])),
+        None => value,
+    }
+}
+
+struct SerializeArray {
+    variant: Option<&'static str>,
+    array: Vec<Value>,
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.array.push(to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Ok(wrap_variant(self.variant, Value::Array(self.array)))
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeArray {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+struct SerializeObject {
+    variant: Option<&'static str>,
+    map: HashMap<String, Value>,
+    next_key: Option<String>,
+}
+
+impl ser::SerializeMap for SerializeObject {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeError> {
+        let key = match to_value(key)? {
+            Value::String(s) => s,
+            Value::Number(n) => crate::serialize::format_number(n),
+            Value::Boolean(b) => b.to_string(),
+            other => {
+                return Err(SerdeError(format!(
+                    "object keys must be strings, found {}",
+                    other.type_name()
+                )));
+            }
+        };
+        self.next_key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let key = self
+            .next_key
+            .take()
+            .expect("serialize_key is called before serialize_value");
+        self.map.insert(key, to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Ok(wrap_variant(self.variant, Value::Object(self.map)))
+    }
+}
+
+impl ser::SerializeStruct for SerializeObject {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.map.insert(String::from(key), to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeObject {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+impl<'de> IntoDeserializer<'de, SerdeError> for Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for Value {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            Value::Null => visitor.visit_unit(),
+            Value::Boolean(b) => visitor.visit_bool(b),
+            Value::Number(n) if n.fract() == 0.0 && n >= 0.0 && n < u64::MAX as f64 => {
+                visitor.visit_u64(n as u64)
+            }
+            Value::Number(n) if n.fract() == 0.0 && n >= i64::MIN as f64 && n < 0.0 => {
+                visitor.visit_i64(n as i64)
+            }
+            Value::Number(n) => visitor.visit_f64(n),
+            Value::String(s) => visitor.visit_string(s),
+            Value::Array(array) => {
+                let mut seq = de::value::SeqDeserializer::new(array.into_iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Value::Object(map) => {
+                let mut map = de::value::MapDeserializer::new(map.into_iter());
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            Value::Null => visitor.visit_none(),
+            other => visitor.visit_some(other),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        let (variant, content) = match self {
+            Value::String(variant) => (variant, None),
+            Value::Object(map) if map.len() == 1 => {
+                let (variant, content) = map.into_iter().next().unwrap();
+                (variant, Some(content))
+            }
+            other => {
+                return Err(SerdeError(format!(
+                    "expected a string or single-key object for an enum, found {}",
+                    other.type_name()
+                )));
+            }
+        };
+        visitor.visit_enum(EnumAccess { variant, content })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct EnumAccess {
+    variant: String,
+    content: Option<Value>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess {
+    type Error = SerdeError;
+    type Variant = VariantAccess;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, VariantAccess), SerdeError> {
+        let variant = seed.deserialize(Value::String(self.variant))?;
+        Ok((variant, VariantAccess(self.content)))
+    }
+}
+
+struct VariantAccess(Option<Value>);
+
+impl<'de> de::VariantAccess<'de> for VariantAccess {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        match self.0 {
+            None | Some(Value::Null) => Ok(()),
+            Some(other) => Err(SerdeError(format!(
+                "expected a unit variant, found {}",
+                other.type_name()
+            ))),
+        }
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, SerdeError> {
+        seed.deserialize(self.0.unwrap_or(Value::Null))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.0.unwrap_or(Value::Null).deserialize_any(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.0.unwrap_or(Value::Null).deserialize_any(visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::{Parser, Value};
+
+    use super::{from_value, to_value};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct User {
+        name: String,
+        age: u8,
+        email: Option<String>,
+        roles: Vec<Role>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Role {
+        Admin,
+        Guest(u32),
+        Member { team: String },
+    }
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    #[test]
+    fn struct_round_trip() {
+        let user = User {
+            name: String::from("ken"),
+            age: 35,
+            email: None,
+            roles: vec![
+                Role::Admin,
+                Role::Guest(7),
+                Role::Member {
+                    team: String::from("core"),
+                },
+            ],
+        };
+        let expected = value(
+            r#"{"name": "ken", "age": 35, "email": null,
+                "roles": ["Admin", {"Guest": 7}, {"Member": {"team": "core"}}]}"#,
+        );
+
+        let actual = to_value(&user).unwrap();
+        assert_eq!(actual, expected);
+        assert_eq!(from_value::<User>(actual).unwrap(), user);
+    }
+
+    #[test]
+    fn maps_with_non_string_keys() {
+        let map = BTreeMap::from([(1, true), (2, false)]);
+        assert_eq!(to_value(&map).unwrap(), value(r#"{"1": true, "2": false}"#));
+    }
+
+    #[test]
+    fn type_errors() {
+        let err = from_value::<User>(value(r#"{"name": 1}"#)).unwrap_err();
+        assert!(err.to_string().contains("invalid type"), "{err}");
+        assert!(from_value::<u8>(value("300")).is_err());
+    }
+
+    #[test]
+    fn value_is_serde_compatible() {
+        let original = value(r#"{"a": [1, 2.5, null, "x", {"b": false}]}"#);
+        let through_serde: Value = from_value(to_value(&original).unwrap()).unwrap();
+        assert_eq!(through_serde, original);
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{JsonPointer, Value};
+
+/// Iterator over every value in a document, see [`Value::walk`]
+pub struct Walk<'a> {
+    stack: Vec<(JsonPointer, &'a Value)>,
+}
+
+impl Value {
+    /// Every value in the document along with its pointer, depth-first with each
+    /// container before its children
+    ///
+    /// Object members are visited in sorted key order.
+    pub fn walk(&self) -> Walk<'_> {
+        Walk {
+            stack: vec![(JsonPointer::root(), self)],
+        }
+    }
+
+    /// Every scalar in the document along with its pointer, in the order of [`Value::walk`]
+    ///
+    /// Arrays and objects are left out, including empty ones.
+    pub fn leaves(&self) -> impl Iterator<Item = (JsonPointer, &Value)> {
+        self.walk()
+            .filter(|(_, value)| !matches!(value, Value::Array(_) | Value::Object(_)))
+    }
+
+    /// Every value matching `predicate`, with its pointer, in the order of [`Value::walk`]
+    pub fn find_all(
+        &self,
+        mut predicate: impl FnMut(&JsonPointer, &Value) -> bool,
+    ) -> Vec<(JsonPointer, &Value)> {
+        self.walk()
+            .filter(|(pointer, value)| predicate(pointer, value))
+            .collect()
+    }
+
+    /// The values of every object member named `key`, at any depth, with their pointers
+    pub fn find_key(&self, key: &str) -> Vec<(JsonPointer, &Value)> {
+        let mut found = Vec::new();
+        for (pointer, value) in self.walk() {
+            if let Value::Object(map) = value
+                && let Some(item) = map.get(key)
+            {
+                found.push((pointer.join(key), item));
+            }
+        }
+        found
+    }
+
+    /// Calls `f` on every value in the document, depth-first with each container before
+    /// its children
+    ///
+    /// `f` may replace the value it is given, the walk then continues into the new
+    /// value's children.
+    pub fn walk_mut(&mut self, mut f: impl FnMut(&JsonPointer, &mut Value)) {
+        walk_mut_at(self, &mut JsonPointer::root(), &mut f);
+    }
+
+    /// Replaces every string value with `f` applied to it, object keys are unchanged
+    pub fn map_strings(&mut self, mut f: impl FnMut(&str) -> String) {
+        self.walk_mut(|_, value| {
+            if let Value::String(s) = value {
+                *s = f(s);
+            }
+        });
+    }
+
+    /// Replaces every number with `f` applied to it
+    pub fn map_numbers(&mut self, mut f: impl FnMut(f64) -> f64) {
+        self.walk_mut(|_, value| {
+            if let Value::Number(n) = value {
+                *n = f(*n);
+            }
+        });
+    }
+
+    /// Replaces each value whose pointer matches `predicate` with `f` applied to it
+    ///
+    /// Matching values are transformed before their children are visited.
+    pub fn transform(
+        &mut self,
+        mut predicate: impl FnMut(&JsonPointer) -> bool,
+        mut f: impl FnMut(Value) -> Value,
+    ) {
+        self.walk_mut(|pointer, value| {
+            if predicate(pointer) {
+                *value = f(value.take());
+            }
+        });
+    }
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = (JsonPointer, &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (pointer, value) = self.stack.pop()?;
+        match value {
+            Value::Array(array) => {
+                let children = array
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| (pointer.join(i.to_string()), item));
+                self.stack.extend(children.rev());
+            }
+            Value::Object(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_by_key(|(key, _)| *key);
+                let children = entries
+                    .into_iter()
+                    .map(|(key, item)| (pointer.join(key.as_str()), item));
+                self.stack.extend(children.rev());
+            }
+            _ => {}
+        }
+        Some((pointer, value))
+    }
+}
+
+fn walk_mut_at(
+    value: &mut Value,
+    pointer: &mut JsonPointer,
+    f: &mut impl FnMut(&JsonPointer, &mut Value),
+) {
+    f(pointer, value);
+    match value {
+        Value::Array(array) => {
+            for (i, item) in array.iter_mut().enumerate() {
+                pointer.push(i.to_string());
+                walk_mut_at(item, pointer, f);
+                pointer.pop();
+            }
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter_mut().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            for (key, item) in entries {
+                pointer.push(key.as_str());
+                walk_mut_at(item, pointer, f);
+                pointer.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    fn value(input: &str) -> Value {
+        Parser::new().parse_str(input).unwrap()
+    }
+
+    #[test]
+    fn visits_every_value_in_order() {
+        let document = value(r#"{"b": [1, {"c": null}], "a": true}"#);
+        let pointers: Vec<_> = document
+            .walk()
+            .map(|(pointer, _)| pointer.to_string())
+            .collect();
+        assert_eq!(pointers, ["", "/a", "/b", "/b/0", "/b/1", "/b/1/c"]);
+
+        let (_, found) = document.walk().nth(3).unwrap();
+        assert_eq!(found, &Value::Number(1.0));
+    }
+
+    #[test]
+    fn find_by_predicate_and_key() {
+        let document = value(r#"{"email": "a@x", "users": [{"email": "b@x"}, "email"]}"#);
+        let found = document.find_all(|_, value| value.as_str().is_some_and(|s| s.contains('@')));
+        let pointers: Vec<_> = found
+            .iter()
+            .map(|(pointer, _)| pointer.to_string())
+            .collect();
+        assert_eq!(pointers, ["/email", "/users/0/email"]);
+
+        let found = document.find_key("email");
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[1].0.to_string(), "/users/0/email");
+        assert_eq!(found[1].1, &Value::string("b@x"));
+        assert!(document.find_key("missing").is_empty());
+    }
+
+    #[test]
+    fn leaves_only() {
+        let document = value(r#"{"b": [1, {"c": null}, []], "a": true, "d": {}}"#);
+        let leaves: Vec<_> = document
+            .leaves()
+            .map(|(pointer, value)| (pointer.to_string(), value.clone()))
+            .collect();
+        let expected = [
+            (String::from("/a"), Value::Boolean(true)),
+            (String::from("/b/0"), Value::Number(1.0)),
+            (String::from("/b/1/c"), Value::Null),
+        ];
+        assert_eq!(leaves, expected);
+        assert_eq!(Value::Null.leaves().count(), 1);
+    }
+
+    #[test]
+    fn scalar_root() {
+        let document = Value::Null;
+        assert_eq!(document.walk().count(), 1);
+    }
+
+    #[test]
+    fn walk_mut_edits_in_place() {
+        let mut document = value(r#"{"a": [1, 2], "b": {"secret": "x"}}"#);
+        document.walk_mut(|pointer, value| {
+            if let Value::Number(n) = value {
+                *n *= 10.0;
+            }
+            if pointer.tokens().last().is_some_and(|key| key == "secret") {
+                *value = Value::Null;
+            }
+        });
+        assert_eq!(document, value(r#"{"a": [10, 20], "b": {"secret": null}}"#));
+    }
+
+    #[test]
+    fn map_leaves() {
+        let mut document = value(r#"{" Key ": [" a ", 1.26, {"b": "B "}]}"#);
+        document.map_strings(|s| s.trim().to_lowercase());
+        document.map_numbers(|n| (n * 10.0).round() / 10.0);
+        assert_eq!(document, value(r#"{" Key ": ["a", 1.3, {"b": "b"}]}"#));
+    }
+
+    #[test]
+    fn transform_by_path() {
+        let mut document =
+            value(r#"{"users": [{"email": "A@X.COM", "name": "Ann"}, {"email": null}]}"#);
+        document.transform(
+            |pointer| pointer.tokens().last().is_some_and(|key| key == "email"),
+            |value| match value {
+                Value::String(s) => Value::String(s.to_lowercase()),
+                other => other,
+            },
+        );
+        let expected = r#"{"users": [{"email": "a@x.com", "name": "Ann"}, {"email": null}]}"#;
+        assert_eq!(document, value(expected));
+    }
+
+    #[test]
+    fn walk_mut_descends_into_replacements() {
+        let mut document = value("[0]");
+        let mut visited = Vec::new();
+        document.walk_mut(|pointer, value| {
+            visited.push(pointer.to_string());
+            if *value == Value::Number(0.0) {
+                *value = Value::Array(vec![Value::Null]);
+            }
+        });
+        assert_eq!(visited, ["", "/0", "/0/0"]);
+    }
+}
@@ -0,0 +1,107 @@
+//! A `wasm-bindgen` facade for using the crate from JavaScript
+//!
+//! Documents cross the boundary as JSON text and stay parsed on the Rust side inside a
+//! [`JsonValue`], so repeated queries don't pay for parsing again.
+
+use std::fmt::Debug;
+
+use wasm_bindgen::prelude::*;
+
+use crate::Value;
+use crate::jsonpath::JsonPath;
+use crate::reader::read_document;
+
+/// A parsed document, exported to JavaScript as the `JsonValue` class
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct JsonValue {
+    value: Value,
+}
+
+#[wasm_bindgen]
+impl JsonValue {
+    /// Parses JSON text, throwing an `Error` describing the problem if it is invalid
+    pub fn parse(input: &str) -> Result<JsonValue, JsError> {
+        let value = read_document(input.as_bytes()).map_err(js_error)?;
+        Ok(JsonValue { value })
+    }
+
+    /// Serializes back to JSON text, indented with two spaces when `pretty` is true
+    pub fn stringify(&self, pretty: bool) -> String {
+        if pretty {
+            self.value.to_json_string_pretty()
+        } else {
+            self.value.to_json_string()
+        }
+    }
+
+    /// The values selected by a JSONPath expression, as an array
+    pub fn query(&self, expression: &str) -> Result<JsonValue, JsError> {
+        let path = JsonPath::compile(expression).map_err(js_error)?;
+        let matches = path.query(&self.value);
+        let value = Value::Array(matches.into_iter().map(|m| m.value.clone()).collect());
+        Ok(JsonValue { value })
+    }
+
+    /// The value at a JSON Pointer, or `undefined` if there is none
+    pub fn pointer(&self, pointer: &str) -> Option<JsonValue> {
+        let value = self.value.pointer(pointer)?.clone();
+        Some(JsonValue { value })
+    }
+
+    /// The JSON type of the value, ex. `"object"`
+    #[wasm_bindgen(getter, js_name = typeName)]
+    pub fn type_name(&self) -> String {
+        self.value.type_name().to_string()
+    }
+}
+
+impl From<Value> for JsonValue {
+    fn from(value: Value) -> Self {
+        JsonValue { value }
+    }
+}
+
+impl From<JsonValue> for Value {
+    fn from(value: JsonValue) -> Self {
+        value.value
+    }
+}
+
+/// Reformats JSON text, a shortcut for `JsonValue.parse(input).stringify(pretty)`
+#[wasm_bindgen(js_name = formatJson)]
+pub fn format_json(input: &str, pretty: bool) -> Result<String, JsError> {
+    Ok(JsonValue::parse(input)?.stringify(pretty))
+}
+
+fn js_error(err: impl Debug) -> JsError {
+    JsError::new(&format!("{err:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    // errors can only be built on a wasm target, so only the successful paths run here
+    use crate::Value;
+
+    use super::{JsonValue, format_json};
+
+    #[test]
+    fn parse_query_and_stringify() {
+        let document = JsonValue::parse(r#"{"items": [{"id": -1}, {"id": 2e1}]}"#).unwrap();
+        assert_eq!(document.type_name(), "object");
+        let ids = document.query("$.items[*].id").unwrap();
+        assert_eq!(ids.stringify(false), "[-1,20]");
+        let first = document.pointer("/items/0").unwrap();
+        assert_eq!(
+            Value::from(first),
+            Value::object([("id", Value::Number(-1.0))])
+        );
+        assert!(document.pointer("/missing").is_none());
+    }
+
+    #[test]
+    fn formats_text() {
+        assert_eq!(format_json("[ 1,\n2 ]", false).unwrap(), "[1,2]");
+        assert_eq!(format_json("[1]", true).unwrap(), "[\n  1\n]");
+    }
+}
@@ -0,0 +1,315 @@
+use std::{fmt, io};
+
+use crate::reader::Event;
+use crate::serialize::{write_nested, write_string_to};
+use crate::{SerializeOptions, Value};
+
+/// Writes JSON one event at a time, inserting commas and colons automatically
+pub struct JsonWriter<W> {
+    inner: W,
+    options: SerializeOptions,
+    indent: Option<String>,
+    stack: Vec<Frame>,
+    wrote_root: bool,
+}
+
+enum Frame {
+    Array { first: bool },
+    Object { first: bool, has_key: bool },
+}
+
+#[derive(Debug)]
+pub enum WriterError {
+    /// Writing to the underlying writer failed
+    Io(io::Error),
+
+    /// A value was written inside an object without a key first
+    KeyExpected,
+
+    /// A key was written outside of an object, or twice in a row
+    UnexpectedKey,
+
+    /// An object was ended after a key, before its value
+    ValueExpected,
+
+    /// `end` was called with no open array or object
+    NothingToEnd,
+
+    /// A second value was written after the first complete one
+    MultipleRoots,
+
+    /// `finish` was called before a complete value was written
+    Incomplete,
+}
+
+impl fmt::Display for WriterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "writing output failed: {err}"),
+            Self::KeyExpected => f.write_str("expected a key before the value"),
+            Self::UnexpectedKey => f.write_str("unexpected key outside of an object"),
+            Self::ValueExpected => f.write_str("expected a value after the key"),
+            Self::NothingToEnd => f.write_str("there is no open array or object to end"),
+            Self::MultipleRoots => f.write_str("a complete value was already written"),
+            Self::Incomplete => f.write_str("the value is not complete"),
+        }
+    }
+}
+
+impl std::error::Error for WriterError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for WriterError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl<W: io::Write> JsonWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self::with_options(inner, SerializeOptions::default())
+    }
+
+    /// A writer escaping strings as configured
+    pub fn with_options(inner: W, options: SerializeOptions) -> Self {
+        Self {
+            inner,
+            options,
+            indent: None,
+            stack: Vec::new(),
+            wrote_root: false,
+        }
+    }
+
+    /// A writer that puts each element on its own line, indented by two spaces
+    pub fn pretty(inner: W) -> Self {
+        Self {
+            indent: Some(String::from("  ")),
+            ..Self::new(inner)
+        }
+    }
+
+    /// Writes a reader event, see [`crate::transcode`] for copying a whole stream
+    pub fn event(&mut self, event: &Event) -> Result<(), WriterError> {
+        match event {
+            Event::StartObject => self.begin_object(),
+            Event::StartArray => self.begin_array(),
+            Event::EndObject | Event::EndArray => self.end(),
+            Event::Key(key) => self.key(key),
+            Event::String(s) => self.value(&Value::String(s.clone())),
+            Event::Number(n) => self.value(&Value::Number(*n)),
+            Event::Boolean(b) => self.value(&Value::Boolean(*b)),
+            Event::Null => self.value(&Value::Null),
+        }
+    }
+
+    pub fn begin_object(&mut self) -> Result<(), WriterError> {
+        self.before_value()?;
+        self.inner.write_all(b"{")?;
+        self.stack.push(Frame::Object {
+            first: true,
+            has_key: false,
+        });
+        Ok(())
+    }
+
+    pub fn begin_array(&mut self) -> Result<(), WriterError> {
+        self.before_value()?;
+        self.inner.write_all(b"[")?;
+        self.stack.push(Frame::Array { first: true });
+        Ok(())
+    }
+
+    /// Writes the key for the next value in the current object
+    pub fn key(&mut self, key: &str) -> Result<(), WriterError> {
+        let Some(Frame::Object { first, has_key }) = self.stack.last_mut() else {
+            return Err(WriterError::UnexpectedKey);
+        };
+        if *has_key {
+            return Err(WriterError::UnexpectedKey);
+        }
+        if !*first {
+            self.inner.write_all(b",")?;
+        }
+        *first = false;
+        *has_key = true;
+        self.newline(self.stack.len())?;
+        write_string_to(&mut self.inner, key, &self.options)?;
+        let colon: &[u8] = if self.indent.is_some() { b": " } else { b":" };
+        self.inner.write_all(colon)?;
+        Ok(())
+    }
+
+    /// Writes a complete value, which may itself be an array or object
+    pub fn value(&mut self, value: &Value) -> Result<(), WriterError> {
+        self.before_value()?;
+        let indent = self.indent.as_deref();
+        write_nested(
+            &mut self.inner,
+            value,
+            &self.options,
+            indent,
+            self.stack.len(),
+        )?;
+        self.after_value();
+        Ok(())
+    }
+
+    /// Closes the innermost open array or object
+    pub fn end(&mut self) -> Result<(), WriterError> {
+        match self.stack.pop() {
+            None => Err(WriterError::NothingToEnd),
+            Some(Frame::Object { has_key: true, .. }) => Err(WriterError::ValueExpected),
+            Some(Frame::Object { first, .. }) => {
+                if !first {
+                    self.newline(self.stack.len())?;
+                }
+                self.inner.write_all(b"}")?;
+                self.after_value();
+                Ok(())
+            }
+            Some(Frame::Array { first }) => {
+                if !first {
+                    self.newline(self.stack.len())?;
+                }
+                self.inner.write_all(b"]")?;
+                self.after_value();
+                Ok(())
+            }
+        }
+    }
+
+    /// Checks that a complete value was written and returns the underlying writer
+    pub fn finish(self) -> Result<W, WriterError> {
+        if !self.wrote_root || !self.stack.is_empty() {
+            return Err(WriterError::Incomplete);
+        }
+        Ok(self.inner)
+    }
+
+    fn before_value(&mut self) -> Result<(), WriterError> {
+        match self.stack.last_mut() {
+            None if self.wrote_root => Err(WriterError::MultipleRoots),
+            None => Ok(()),
+            Some(Frame::Object { has_key: false, .. }) => Err(WriterError::KeyExpected),
+            Some(Frame::Object { has_key, .. }) => {
+                *has_key = false;
+                Ok(())
+            }
+            Some(Frame::Array { first }) => {
+                if !*first {
+                    self.inner.write_all(b",")?;
+                }
+                *first = false;
+                self.newline(self.stack.len())
+            }
+        }
+    }
+
+    /// Starts a new line at `depth` when pretty printing
+    fn newline(&mut self, depth: usize) -> Result<(), WriterError> {
+        if let Some(indent) = &self.indent {
+            self.inner.write_all(b"\n")?;
+            for _ in 0..depth {
+                self.inner.write_all(indent.as_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn after_value(&mut self) {
+        if self.stack.is_empty() {
+            self.wrote_root = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parser, Value};
+
+    use super::{JsonWriter, WriterError};
+
+    #[test]
+    fn nested_containers() {
+        let mut writer = JsonWriter::new(Vec::new());
+        writer.begin_array().unwrap();
+        writer.begin_object().unwrap();
+        writer.key("a").unwrap();
+        writer.value(&Value::Number(1.0)).unwrap();
+        writer.key("b\"").unwrap();
+        writer.begin_array().unwrap();
+        writer.end().unwrap();
+        writer.end().unwrap();
+        writer
+            .value(&Parser::new().parse_str(r#"{"c": [true]}"#).unwrap())
+            .unwrap();
+        writer.begin_object().unwrap();
+        writer.end().unwrap();
+        writer.end().unwrap();
+
+        let output = writer.finish().unwrap();
+        let expected = r#"[{"a":1,"b\"":[]},{"c":[true]},{}]"#;
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn pretty_matches_value_output() {
+        let value = Parser::new()
+            .parse_str(r#"{"a": [1, {"b": null}, []], "c": {}}"#)
+            .unwrap();
+        let mut writer = JsonWriter::pretty(Vec::new());
+        writer.begin_object().unwrap();
+        writer.key("a").unwrap();
+        writer.begin_array().unwrap();
+        writer.value(&Value::Number(1.0)).unwrap();
+        writer
+            .value(&Parser::new().parse_str(r#"{"b": null}"#).unwrap())
+            .unwrap();
+        writer.begin_array().unwrap();
+        writer.end().unwrap();
+        writer.end().unwrap();
+        writer.key("c").unwrap();
+        writer.begin_object().unwrap();
+        writer.end().unwrap();
+        writer.end().unwrap();
+
+        let output = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(output, value.to_json_string_pretty());
+    }
+
+    #[test]
+    fn misuse_is_reported() {
+        let mut writer = JsonWriter::new(Vec::new());
+        assert!(matches!(writer.key("a"), Err(WriterError::UnexpectedKey)));
+        assert!(matches!(writer.end(), Err(WriterError::NothingToEnd)));
+        writer.begin_object().unwrap();
+        assert!(matches!(
+            writer.value(&Value::Null),
+            Err(WriterError::KeyExpected)
+        ));
+        writer.key("a").unwrap();
+        assert!(matches!(writer.key("b"), Err(WriterError::UnexpectedKey)));
+        assert!(matches!(writer.end(), Err(WriterError::ValueExpected)));
+    }
+
+    #[test]
+    fn single_root() {
+        let mut writer = JsonWriter::new(Vec::new());
+        writer.value(&Value::Null).unwrap();
+        assert!(matches!(
+            writer.value(&Value::Null),
+            Err(WriterError::MultipleRoots)
+        ));
+
+        let mut writer = JsonWriter::new(Vec::new());
+        writer.begin_array().unwrap();
+        assert!(matches!(writer.finish(), Err(WriterError::Incomplete)));
+    }
+}